@@ -0,0 +1,281 @@
+//! Conformance suite: checks that every sorter keeps the promises of its
+//! [`SorterInfo`] and documentation, and prints a table of how it did.
+//!
+//! ```text
+//! cargo run --release --example conformance
+//! ```
+//!
+//! Each sorter sorts every [`Pattern`] and a random permutation at two lengths eight
+//! times apart. How much its comparisons grow between the lengths gives an exponent,
+//! `e` in `c(n) ~ n^e`, which is held against the exponent of the claimed complexity
+//! between the same lengths, within a tolerance for lower-order terms: the claimed
+//! best case against the pattern it does best on, the average case against the random
+//! input and the worst case against every pattern. Outputs must be sorted, stable
+//! sorters must keep equal keys in order, and the operation counts documented for
+//! individual sorters, such as the n - 1 swaps of selection sort, are checked exactly.
+//! Exits with status 1 if anything fails.
+
+use pangua::generators::{self, Pattern};
+use pangua::*;
+use std::collections::HashMap;
+use std::process;
+
+/// How far a measured exponent may exceed the claimed one.
+const TOLERANCE: f64 = 0.25;
+
+/// A key and its position in the input, to check stability.
+type Item = (usize, usize);
+
+type SortFn = Box<dyn Fn(&mut [Item]) -> SortStats>;
+
+struct Subject {
+    info: SorterInfo,
+    sort: SortFn,
+}
+
+fn subject<S: Sorter + 'static>(sorter: S) -> Subject {
+    Subject {
+        info: sorter.info(),
+        sort: Box::new(move |items| {
+            let counting = Counting::new(&sorter);
+            counting.sort_by_key(items, |item| item.0);
+            counting.stats()
+        }),
+    }
+}
+
+fn subjects() -> Vec<Subject> {
+    vec![
+        subject(DefaultSorter),
+        subject(AutoSort::default()),
+        subject(QuickSort::default()),
+        subject(ThreeWayQuickSort),
+        subject(IntroSort::default()),
+        subject(PdqSort),
+        subject(HeapSort::default()),
+        subject(SmoothSort),
+        subject(MergeSort::default()),
+        subject(BottomUpMergeSort),
+        subject(NaturalMergeSort::default()),
+        subject(BlockSort),
+        subject(PatienceSort),
+        subject(StrandSort),
+        subject(LibrarySort),
+        subject(TreeSort { balanced: true }),
+        subject(ShellSort::default()),
+        subject(CombSort::default()),
+        subject(InsertionSort { smart: true }),
+        subject(InsertionSort { smart: false }),
+        subject(SelectionSort),
+        subject(CycleSort),
+        subject(PancakeSort),
+        subject(BitonicSort),
+        subject(BubbleSort),
+        subject(CocktailShakerSort),
+        subject(GnomeSort),
+        subject(OddEvenSort),
+    ]
+}
+
+/// An input to sort, and whether its keys are distinct.
+struct Input {
+    name: String,
+    keys: Vec<usize>,
+    distinct: bool,
+}
+
+fn inputs(n: usize) -> Vec<Input> {
+    let mut inputs: Vec<Input> = Pattern::ALL
+        .iter()
+        .map(|pattern| Input {
+            name: format!("{:?}", pattern),
+            keys: match pattern {
+                // runs of a fixed length make longer inputs relatively more disordered,
+                // so they are scaled with the input for the growth to mean anything
+                Pattern::RunsWithNoise => generators::runs_with_noise(n, n / 16, 0.05, 1),
+                _ => pattern.generate(n, 1),
+            },
+            distinct: !matches!(pattern, Pattern::OrganPipe | Pattern::FewUnique),
+        })
+        .collect();
+    inputs.push(Input {
+        name: "Random".to_string(),
+        keys: generators::shuffled(n, 1),
+        distinct: true,
+    });
+    inputs
+}
+
+/// The growth function of a complexity class at `n`, up to a constant factor.
+fn growth(complexity: Complexity, n: usize) -> f64 {
+    let n = n as f64;
+    match complexity {
+        Complexity::Linear => n,
+        Complexity::Linearithmic => n * n.log2(),
+        Complexity::LinearLogSquared => n * n.log2().powi(2),
+        Complexity::PowerThreeHalves => n.powf(1.5),
+        Complexity::Quadratic => n * n,
+        Complexity::Factorial => f64::INFINITY,
+    }
+}
+
+/// `e` such that going from `small` to `large` multiplies `n^e` by `ratio`.
+fn exponent(small: usize, large: usize, ratio: f64) -> f64 {
+    ratio.ln() / (large as f64 / small as f64).ln()
+}
+
+/// What a sorter did, and everything it got wrong.
+struct Report {
+    info: SorterInfo,
+    /// The measured exponents of the best pattern, the random input and the worst
+    /// pattern.
+    measured: [f64; 3],
+    failures: Vec<String>,
+}
+
+/// Sorts a copy of `input` with `subject`, checking the output, and returns the stats.
+fn run(subject: &Subject, input: &Input, failures: &mut Vec<String>) -> SortStats {
+    let n = input.keys.len();
+    let mut items: Vec<Item> = input.keys.iter().copied().zip(0..).collect();
+    let stats = (subject.sort)(&mut items);
+    let mut expected = input.keys.clone();
+    expected.sort_unstable();
+    if items.iter().map(|item| item.0).ne(expected) {
+        failures.push(format!("{} of length {}: not sorted", input.name, n));
+    } else if subject.info.stable
+        && items
+            .windows(2)
+            .any(|w| w[0].0 == w[1].0 && w[1].1 < w[0].1)
+    {
+        failures.push(format!("{} of length {}: not stable", input.name, n));
+    }
+    check_documented(subject.info.name, input, stats, failures);
+    stats
+}
+
+/// The operation counts promised in the documentation of particular sorters.
+fn check_documented(name: &str, input: &Input, stats: SortStats, failures: &mut Vec<String>) {
+    let n = input.keys.len();
+    let mut expect = |ok: bool, what: String| {
+        if !ok {
+            failures.push(format!("{} of length {}: {}", input.name, n, what));
+        }
+    };
+    match name {
+        "selection" => expect(
+            stats.swaps < n,
+            format!("{} swaps, more than n - 1", stats.swaps),
+        ),
+        "cycle" if input.distinct => expect(
+            stats.swaps < n,
+            format!("{} swaps, more than n - 1", stats.swaps),
+        ),
+        "pancake" => {
+            let flips = pancake_flips(&mut input.keys.clone()).len();
+            expect(
+                flips <= (2 * n).saturating_sub(3),
+                format!("{} flips, more than 2n - 3", flips),
+            )
+        }
+        "bubble" | "insertion-dumb" if input.name == "Presorted" => expect(
+            stats.comparisons == n - 1,
+            format!(
+                "{} comparisons on sorted input, not n - 1",
+                stats.comparisons
+            ),
+        ),
+        _ => {}
+    }
+}
+
+fn check(subject: &Subject, cache: &mut HashMap<usize, Vec<Input>>) -> Report {
+    let info = subject.info;
+    // quadratic sorters get shorter inputs, to finish in seconds
+    let (small, large) = if info.worst >= Complexity::Quadratic {
+        (256, 2048)
+    } else {
+        (1024, 8192)
+    };
+    let mut failures = Vec::new();
+    let mut exponents = Vec::new();
+    for i in 0..Pattern::ALL.len() + 1 {
+        let small_stats = run(
+            subject,
+            &cache.entry(small).or_insert_with(|| inputs(small))[i],
+            &mut failures,
+        );
+        let large_input = &cache.entry(large).or_insert_with(|| inputs(large))[i];
+        let large_stats = run(subject, large_input, &mut failures);
+        let ratio = large_stats.comparisons as f64 / small_stats.comparisons.max(1) as f64;
+        exponents.push((large_input.name.clone(), exponent(small, large, ratio)));
+    }
+
+    let claimed = |complexity| {
+        exponent(
+            small,
+            large,
+            growth(complexity, large) / growth(complexity, small),
+        )
+    };
+    let best = exponents.iter().map(|e| e.1).fold(f64::INFINITY, f64::min);
+    let random = exponents.last().map_or(0.0, |e| e.1);
+    let worst = exponents
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .expect("there are inputs");
+    let measured = [best, random, worst.1];
+    let checks = [
+        ("best", info.best, best, "its best pattern".to_string()),
+        ("average", info.average, random, "random input".to_string()),
+        ("worst", info.worst, worst.1, worst.0.clone()),
+    ];
+    for (case, complexity, measured, on) in checks.iter() {
+        if *measured > claimed(*complexity) + TOLERANCE {
+            failures.push(format!(
+                "{} case claimed {}, but comparisons grow as n^{:.2} on {}",
+                case, complexity, measured, on
+            ));
+        }
+    }
+    Report {
+        info,
+        measured,
+        failures,
+    }
+}
+
+fn main() {
+    let mut cache = HashMap::new();
+    let reports: Vec<Report> = subjects().iter().map(|s| check(s, &mut cache)).collect();
+
+    println!(
+        "{:16} {:>20} {:>20} {:>20}  result",
+        "algorithm", "best", "average", "worst"
+    );
+    let cell = |complexity: Complexity, measured: f64| format!("{} n^{:.2}", complexity, measured);
+    for report in &reports {
+        let info = report.info;
+        println!(
+            "{:16} {:>20} {:>20} {:>20}  {}",
+            info.name,
+            cell(info.best, report.measured[0]),
+            cell(info.average, report.measured[1]),
+            cell(info.worst, report.measured[2]),
+            if report.failures.is_empty() {
+                "ok"
+            } else {
+                "FAILED"
+            }
+        );
+    }
+
+    let failed: Vec<&Report> = reports.iter().filter(|r| !r.failures.is_empty()).collect();
+    for report in &failed {
+        for failure in &report.failures {
+            eprintln!("{}: {}", report.info.name, failure);
+        }
+    }
+    if !failed.is_empty() {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,34 @@
+//! Times heap sort over heaps of different arities and directions against each other.
+//!
+//! ```text
+//! cargo run --release --features bench --example heap_arity
+//! ```
+//!
+//! Wider heaps are shallower: sifting an element down takes fewer swaps, for more
+//! comparisons at each level. Whether that pays depends on how expensive comparisons
+//! are next to moves and on the cache; for machine integers a 4-ary heap usually wins.
+
+use pangua::bench::{self, Algorithm, Input};
+use pangua::generators::{self, Pattern};
+use pangua::HeapSort;
+
+fn main() {
+    let heap = |d, min_heap| Algorithm::new(HeapSort { d, min_heap });
+    let algorithms = [
+        heap(2, false).named("binary"),
+        heap(2, true).named("binary min"),
+        heap(3, false).named("3-ary"),
+        heap(4, false).named("4-ary"),
+        heap(4, true).named("4-ary min"),
+        heap(8, false).named("8-ary"),
+    ];
+    let mut inputs = Vec::new();
+    for &n in &[10_000, 1_000_000] {
+        // runs of one element are a random permutation
+        let random = generators::runs_with_noise(n, 1, 0.0, 1);
+        inputs.push(Input::new("random", random));
+        inputs.push(Input::pattern(Pattern::FewUnique, n, 1));
+        inputs.push(Input::pattern(Pattern::ReverseSorted, n, 1));
+    }
+    println!("{}", bench::compare(&algorithms, &inputs));
+}
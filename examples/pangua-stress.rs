@@ -0,0 +1,467 @@
+//! Soak test: sorts random inputs with randomly configured sorters, checking every
+//! guarantee each sorter claims, and prints a minimized input for every failure.
+//!
+//! ```text
+//! cargo run --release --example pangua-stress -- [--seed N] [--iterations N] [--max-len N]
+//! ```
+//!
+//! Runs until interrupted unless `--iterations` is given. Iteration `i` is generated
+//! from seed `seed + i`, so a failure is replayed with `--seed <its seed> --iterations 1`.
+//! Every output is checked to be sorted and a permutation of the input; stable sorters
+//! must keep equal keys in order, comparison sorters must stay within a multiple of
+//! their worst-case complexity, and external sorts run under a memory budget small
+//! enough to force merge passes.
+
+use pangua::cmp::ByFn;
+use pangua::external::ExternalSorter;
+use pangua::partition::{Hoare, Lomuto, ThreeWay};
+use pangua::spill::MemorySpillStore;
+use pangua::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+
+/// A key to sort by, tagged with its position in the input to check stability.
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    key: u32,
+    tag: u32,
+}
+
+type SortFn = Box<dyn Fn(&mut [Item]) -> Option<usize>>;
+
+/// A configured sorter under test.
+struct Case {
+    name: String,
+    stable: bool,
+    /// The worst case to bound comparisons by, for sorters that can be counted.
+    worst: Option<Complexity>,
+    /// Sorts by key, returning the comparisons made if they were counted.
+    sort: SortFn,
+}
+
+fn comparison<S: Sorter + 'static>(name: String, sorter: S) -> Case {
+    let info = sorter.info();
+    Case {
+        name,
+        stable: info.stable,
+        worst: Some(info.worst),
+        sort: Box::new(move |items| {
+            let counting = Counting::new(&sorter);
+            counting.sort_by(items, |a, b| a.key.cmp(&b.key));
+            Some(counting.stats().comparisons)
+        }),
+    }
+}
+
+fn uncounted(name: String, stable: bool, sort: impl Fn(&mut [Item]) + 'static) -> Case {
+    Case {
+        name,
+        stable,
+        worst: None,
+        sort: Box::new(move |items| {
+            sort(items);
+            None
+        }),
+    }
+}
+
+fn pick_case(rng: &mut StdRng) -> Case {
+    let small_threshold = *[0, 0, 1, 4, 16, 33].choose(rng).unwrap();
+    match rng.gen_range(0, 37) {
+        0 => comparison("bubble".into(), BubbleSort),
+        1 => comparison("cocktail".into(), CocktailShakerSort),
+        2 => comparison("gnome".into(), GnomeSort),
+        3 => comparison("comb".into(), CombSort::default()),
+        4 => comparison("odd-even".into(), OddEvenSort),
+        5 => {
+            let smart = rng.gen();
+            comparison(
+                format!("insertion smart={}", smart),
+                InsertionSort { smart },
+            )
+        }
+        6 => comparison("selection".into(), SelectionSort),
+        7 => comparison("cycle".into(), CycleSort),
+        8 => comparison("pancake".into(), PancakeSort),
+        9 => comparison("bitonic".into(), BitonicSort),
+        10 => comparison("heap".into(), HeapSort::default()),
+        11 => comparison("smooth".into(), SmoothSort),
+        12 => {
+            let memory = *[
+                MemoryPolicy::Full,
+                MemoryPolicy::Half,
+                MemoryPolicy::InPlace,
+            ]
+            .choose(rng)
+            .unwrap();
+            let min_gallop = rng.gen_range(0, 10);
+            let sorter = MergeSort {
+                memory,
+                small_threshold,
+                min_gallop,
+            };
+            let name = format!(
+                "merge memory={:?} small={} gallop={}",
+                memory, small_threshold, min_gallop
+            );
+            comparison(name, sorter)
+        }
+        13 => comparison("bottom-up".into(), BottomUpMergeSort),
+        14 => comparison("natural".into(), NaturalMergeSort::default()),
+        15 => comparison("block".into(), BlockSort),
+        16 => {
+            let block_bytes = rng.gen_range(1, 4096);
+            let sorter = BlockedSort {
+                block_bytes,
+                inner: MergeSort::default(),
+            };
+            comparison(format!("blocked block_bytes={}", block_bytes), sorter)
+        }
+        17 => comparison("patience".into(), PatienceSort),
+        18 => {
+            let balanced = rng.gen();
+            comparison(format!("tree balanced={}", balanced), TreeSort { balanced })
+        }
+        19 => {
+            let pivot = *[
+                PivotStrategy::First,
+                PivotStrategy::Last,
+                PivotStrategy::Middle,
+                PivotStrategy::MedianOfThree,
+                PivotStrategy::Random(rng.gen()),
+                PivotStrategy::Ninther,
+                PivotStrategy::SampledMedian {
+                    sample_size: rng.gen_range(1, 16),
+                    seed: rng.gen(),
+                },
+            ]
+            .choose(rng)
+            .unwrap();
+            let name = format!("quick pivot={:?} small={}", pivot, small_threshold);
+            let sorter = QuickSort {
+                pivot,
+                partitioner: Hoare,
+                small_threshold,
+            };
+            match rng.gen_range(0, 3) {
+                0 => comparison(name + " hoare", sorter),
+                1 => comparison(name + " lomuto", sorter.with_partitioner(Lomuto)),
+                _ => comparison(name + " three-way", sorter.with_partitioner(ThreeWay)),
+            }
+        }
+        20 => comparison("three-way quick".into(), ThreeWayQuickSort),
+        21 => comparison("intro".into(), IntroSort::default()),
+        22 => {
+            let gaps = [
+                GapSequence::Shell,
+                GapSequence::Knuth,
+                GapSequence::Ciura,
+                GapSequence::Tokuda,
+            ]
+            .choose(rng)
+            .unwrap()
+            .clone();
+            comparison(format!("shell gaps={:?}", gaps), ShellSort { gaps })
+        }
+        23 => comparison("auto".into(), AutoSort::default()),
+        24 => comparison("std".into(), StdSorter),
+        #[cfg(feature = "rayon")]
+        25 => {
+            let sorter = ParallelMergeSort {
+                sequential_cutoff: rng.gen_range(0, 512),
+            };
+            let name = format!("merge-parallel cutoff={}", sorter.sequential_cutoff);
+            uncounted(name, true, move |items| {
+                sorter.sort_by(items, |a, b| a.key.cmp(&b.key))
+            })
+        }
+        #[cfg(feature = "rayon")]
+        26 => {
+            let sorter = ParallelQuickSort {
+                sequential_cutoff: rng.gen_range(0, 512),
+            };
+            let name = format!("quick-parallel cutoff={}", sorter.sequential_cutoff);
+            uncounted(name, false, move |items| {
+                sorter.sort_by(items, |a, b| a.key.cmp(&b.key))
+            })
+        }
+        27 => {
+            let sorter = SampleSort {
+                buckets: rng.gen_range(2, 17),
+                oversampling: rng.gen_range(1, 9),
+                sequential_cutoff: 0,
+            };
+            let name = format!(
+                "sample buckets={} oversampling={}",
+                sorter.buckets, sorter.oversampling
+            );
+            uncounted(name, false, move |items| {
+                sorter.sort_by(items, |a, b| a.key.cmp(&b.key))
+            })
+        }
+        28 => {
+            // a few dozen records per run, and a fan-in of 2, so merges take passes
+            let budget = rng.gen_range(64, 2048);
+            uncounted(format!("external budget={}", budget), true, move |items| {
+                external_sort(items, budget)
+            })
+        }
+        29 => comparison(
+            "descending heap".into(),
+            Descending(Descending(HeapSort::default())),
+        ),
+        30 => comparison(
+            "merge-in-place".into(),
+            MergeSort {
+                memory: MemoryPolicy::InPlace,
+                ..MergeSort::default()
+            },
+        ),
+        31 => comparison("default".into(), DefaultSorter),
+        32 => {
+            let sorter = DefaultSorter;
+            uncounted("default-parallel".into(), true, move |items| {
+                sorter.par_sort_by(items, |a, b| a.key.cmp(&b.key))
+            })
+        }
+        33 => comparison("pdq".into(), PdqSort),
+        34 => comparison("strand".into(), StrandSort),
+        35 => comparison("library".into(), LibrarySort),
+        _ => comparison(
+            "natural timsort".into(),
+            NaturalMergeSort::new(pangua::natural::Timsort),
+        ),
+    }
+}
+
+fn external_sort(items: &mut [Item], budget: usize) {
+    let sorter = ExternalSorter {
+        memory_budget: budget,
+        ..ExternalSorter::new(MergeSort::default())
+    };
+    let records = items.iter().map(|item| (item.key, item.tag));
+    let by_key = ByFn(|a: &(u32, u32), b: &(u32, u32)| a.0.cmp(&b.0));
+    let sorted = sorter
+        .sort_in(records, MemorySpillStore::default(), by_key)
+        .expect("memory store failed");
+    for (item, record) in items.iter_mut().zip(sorted) {
+        let (key, tag) = record.expect("memory store failed");
+        *item = Item { key, tag };
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Pattern {
+    Random,
+    Sorted,
+    Reversed,
+    FewUnique,
+    AllEqual,
+    Sawtooth,
+    OrganPipe,
+    NearlySorted,
+}
+
+fn generate(rng: &mut StdRng, pattern: Pattern, len: usize) -> Vec<u32> {
+    let mut keys: Vec<u32> = match pattern {
+        Pattern::Random | Pattern::Sorted | Pattern::Reversed | Pattern::NearlySorted => {
+            (0..len).map(|_| rng.gen()).collect()
+        }
+        Pattern::FewUnique => {
+            let unique = rng.gen_range(1, 8);
+            (0..len).map(|_| rng.gen_range(0, unique)).collect()
+        }
+        Pattern::AllEqual => vec![rng.gen(); len],
+        Pattern::Sawtooth => {
+            let period = rng.gen_range(1, 64);
+            (0..len as u32).map(|i| i % period).collect()
+        }
+        Pattern::OrganPipe => (0..len as u32).map(|i| i.min(len as u32 - i)).collect(),
+    };
+    match pattern {
+        Pattern::Sorted => keys.sort_unstable(),
+        Pattern::Reversed => keys.sort_unstable_by(|a, b| b.cmp(a)),
+        Pattern::NearlySorted => {
+            keys.sort_unstable();
+            for _ in 0..len / 16 + 1 {
+                if len > 1 {
+                    keys.swap(rng.gen_range(0, len), rng.gen_range(0, len));
+                }
+            }
+        }
+        _ => {}
+    }
+    keys
+}
+
+/// Sorts `keys` with the case and checks the result, returning what went wrong.
+fn check(case: &Case, keys: &[u32]) -> Result<(), String> {
+    let mut items: Vec<Item> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, &key)| Item { key, tag: i as u32 })
+        .collect();
+    let comparisons =
+        panic::catch_unwind(AssertUnwindSafe(|| (case.sort)(&mut items))).map_err(|e| {
+            match e.downcast_ref::<&str>() {
+                Some(msg) => format!("panicked: {}", msg),
+                None => match e.downcast_ref::<String>() {
+                    Some(msg) => format!("panicked: {}", msg),
+                    None => "panicked".to_string(),
+                },
+            }
+        })?;
+
+    let mut seen = vec![false; keys.len()];
+    for item in &items {
+        let tag = item.tag as usize;
+        if tag >= keys.len() || seen[tag] || keys[tag] != item.key {
+            return Err(format!("not a permutation: {:?} out of place", item));
+        }
+        seen[tag] = true;
+    }
+    for (i, w) in items.windows(2).enumerate() {
+        if w[1].key < w[0].key {
+            return Err(format!("out of order at {}", i + 1));
+        }
+        if case.stable && w[1].key == w[0].key && w[1].tag < w[0].tag {
+            return Err(format!("unstable at {}", i + 1));
+        }
+    }
+    if let (Some(worst), Some(comparisons)) = (case.worst, comparisons) {
+        let bound = comparison_bound(worst, keys.len());
+        if comparisons > bound {
+            return Err(format!(
+                "{} comparisons, over the {} bound of {}",
+                comparisons, worst, bound
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A generous multiple of the worst-case comparison count for `n` elements.
+fn comparison_bound(worst: Complexity, n: usize) -> usize {
+    let n = n as f64;
+    let lg = n.max(2.0).log2();
+    let bound = match worst {
+        Complexity::Linear => 8.0 * n,
+        Complexity::Linearithmic => 8.0 * n * lg,
+        Complexity::LinearLogSquared => 8.0 * n * lg * lg,
+        Complexity::PowerThreeHalves => 8.0 * n * n.sqrt(),
+        Complexity::Quadratic => 2.0 * n * n,
+        Complexity::Factorial => f64::INFINITY,
+    };
+    // room for small-slice cutoffs
+    bound.min(usize::MAX as f64) as usize + 1024
+}
+
+/// Shrinks a failing input by removing chunks of it while it keeps failing.
+fn minimize(mut keys: Vec<u32>, fails: impl Fn(&[u32]) -> bool) -> Vec<u32> {
+    let mut chunk = keys.len() / 2;
+    while chunk > 0 {
+        let mut start = 0;
+        let mut removed = false;
+        while start < keys.len() {
+            let end = (start + chunk).min(keys.len());
+            let candidate: Vec<u32> = keys[..start].iter().chain(&keys[end..]).copied().collect();
+            if fails(&candidate) {
+                keys = candidate;
+                removed = true;
+            } else {
+                start = end;
+            }
+        }
+        if !removed {
+            chunk /= 2;
+        }
+    }
+    keys
+}
+
+struct Options {
+    seed: u64,
+    iterations: Option<u64>,
+    max_len: usize,
+}
+
+fn parse_options() -> Options {
+    let mut options = Options {
+        seed: rand::thread_rng().gen(),
+        iterations: None,
+        max_len: 100_000,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args.next().and_then(|v| v.parse::<u64>().ok());
+        match (flag.as_str(), value) {
+            ("--seed", Some(v)) => options.seed = v,
+            ("--iterations", Some(v)) => options.iterations = Some(v),
+            ("--max-len", Some(v)) => options.max_len = v as usize,
+            _ => {
+                eprintln!("usage: pangua-stress [--seed N] [--iterations N] [--max-len N]");
+                process::exit(2);
+            }
+        }
+    }
+    options
+}
+
+fn main() {
+    let options = parse_options();
+    // failures are reported below, with the panic message
+    panic::set_hook(Box::new(|_| {}));
+    println!("seed {}", options.seed);
+
+    let mut failures = 0u64;
+    let mut iteration = 0u64;
+    while options.iterations.is_none_or(|n| iteration < n) {
+        let seed = options.seed.wrapping_add(iteration);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let case = pick_case(&mut rng);
+        let pattern = *[
+            Pattern::Random,
+            Pattern::Sorted,
+            Pattern::Reversed,
+            Pattern::FewUnique,
+            Pattern::AllEqual,
+            Pattern::Sawtooth,
+            Pattern::OrganPipe,
+            Pattern::NearlySorted,
+        ]
+        .choose(&mut rng)
+        .unwrap();
+        // mostly short inputs, where the cutoffs and edge cases are
+        let mut max_len = match rng.gen_range(0, 10) {
+            0..=5 => 64,
+            6..=8 => 2048,
+            _ => options.max_len,
+        };
+        if case.worst.is_some_and(|w| w >= Complexity::Quadratic) {
+            max_len = max_len.min(2048);
+        }
+        let len = rng.gen_range(0, max_len.max(1) + 1);
+        let keys = generate(&mut rng, pattern, len);
+
+        if let Err(error) = check(&case, &keys) {
+            failures += 1;
+            let minimized = minimize(keys, |keys| check(&case, keys).is_err());
+            let error = check(&case, &minimized).err().unwrap_or(error);
+            println!(
+                "FAIL seed {} sorter {} pattern {:?} len {}: {}",
+                seed, case.name, pattern, len, error
+            );
+            println!("  minimized to {} keys: {:?}", minimized.len(), minimized);
+        }
+        iteration += 1;
+        if iteration.is_multiple_of(1000) {
+            println!("{} iterations, {} failures", iteration, failures);
+        }
+    }
+    println!("{} iterations, {} failures", iteration, failures);
+    if failures > 0 {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::cmp::ByFn;
+use crate::{is_trivially_sorted, Complexity, SmallSortPolicy, SorterInfo};
+use alloc::vec;
+
+/// Buckets per pass: one for keys that already ended, then one per byte value.
+const BUCKETS: usize = 257;
+
+/// American Flag Sort
+///
+/// American flag sort is an in-place most significant digit (MSD) radix sort over byte
+/// strings. Each pass counts the elements by their byte at the current depth, then
+/// permutes them into their buckets by following cycles of swaps, and every bucket of
+/// more than one element is sorted again one byte deeper. Keys that end sort before any
+/// that continue, so the order is lexicographic, as for `&str` and `&[u8]`.
+///
+/// Only the bytes needed to tell keys apart are examined, and shared prefixes are
+/// scanned once per element rather than once per comparison, which is where comparison
+/// sorts waste their work on large string datasets. Small buckets are finished by the
+/// [`SmallSortPolicy`], insertion sort under 32 elements by default. The sort is not
+/// stable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AmericanFlagSort;
+
+impl AmericanFlagSort {
+    pub fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "american-flag",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linear,
+            average: Complexity::Linear,
+            worst: Complexity::Linear,
+        }
+    }
+
+    pub fn sort<T: AsRef<[u8]>>(&self, slice: &mut [T]) {
+        self.sort_by_key(slice, T::as_ref)
+    }
+
+    /// Sorts by the bytes of a key borrowed from each element.
+    pub fn sort_by_key<T, F>(&self, slice: &mut [T], key: F)
+    where
+        F: Fn(&T) -> &[u8],
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let small = SmallSortPolicy::resolve(SmallSortPolicy::Insertion { threshold: 32 });
+        // buckets left to sort, with the depth of the byte that tells them apart, kept on
+        // the heap so long shared prefixes cannot overflow the stack
+        let mut work = vec![(0, slice.len(), 0)];
+        while let Some((lo, hi, depth)) = work.pop() {
+            let part = &mut slice[lo..hi];
+            // every key in the bucket is at least `depth` bytes long and shares those bytes
+            let mut by_rest = ByFn(|a: &T, b: &T| key(a)[depth..].cmp(&key(b)[depth..]));
+            if small.sort_small(part, &mut by_rest) {
+                continue;
+            }
+            let digit = |x: &T| key(x).get(depth).map_or(0, |&b| b as usize + 1);
+            let mut counts = [0usize; BUCKETS];
+            for x in part.iter() {
+                counts[digit(x)] += 1;
+            }
+            if counts[0] == part.len() {
+                continue;
+            }
+            if counts.contains(&part.len()) {
+                // one shared byte, nothing to move
+                work.push((lo, hi, depth + 1));
+                continue;
+            }
+            let mut starts = [0usize; BUCKETS];
+            let mut total = 0;
+            for (start, &count) in starts.iter_mut().zip(counts.iter()) {
+                *start = total;
+                total += count;
+            }
+            // next[b] is the first slot of bucket b not yet known to hold a member
+            let mut next = starts;
+            for b in 0..BUCKETS {
+                let end = starts[b] + counts[b];
+                while next[b] < end {
+                    let d = digit(&part[next[b]]);
+                    if d == b {
+                        next[b] += 1;
+                    } else {
+                        part.swap(next[b], next[d]);
+                        next[d] += 1;
+                    }
+                }
+            }
+            // keys that ended are all equal
+            for b in 1..BUCKETS {
+                if counts[b] > 1 {
+                    let start = lo + starts[b];
+                    work.push((start, start + counts[b], depth + 1));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    fn words(n: usize) -> Vec<String> {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        (0..n)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                // short words over a small alphabet share plenty of prefixes
+                let len = (x % 9) as usize;
+                (0..len)
+                    .map(|i| (b'a' + ((x >> (8 + 3 * i)) % 4) as u8) as char)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn american_flag_works() {
+        let mut tings = vec!["banana", "apple", "", "cherry", "app", "b"];
+        AmericanFlagSort.sort(&mut tings);
+        assert_eq!(tings, &["", "app", "apple", "b", "banana", "cherry"]);
+    }
+
+    #[test]
+    fn matches_lexicographic_order() {
+        for &n in &[0, 1, 2, 31, 33, 1000] {
+            let mut tings = words(n);
+            let mut expected = tings.clone();
+            expected.sort();
+            AmericanFlagSort.sort(&mut tings);
+            assert_eq!(tings, expected, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn sorts_bytes_and_keys() {
+        let mut bytes: Vec<Vec<u8>> = vec![vec![255, 0], vec![0, 255], vec![255], vec![]];
+        AmericanFlagSort.sort(&mut bytes);
+        assert_eq!(bytes, vec![vec![], vec![0, 255], vec![255], vec![255, 0]]);
+
+        let mut people: Vec<(String, u32)> = words(200).into_iter().zip(0..).collect();
+        AmericanFlagSort.sort_by_key(&mut people, |p| p.0.as_bytes());
+        assert!(people.windows(2).all(|w| w[0].0 <= w[1].0));
+    }
+
+    #[test]
+    fn long_shared_prefixes_do_not_recurse() {
+        let prefix = "x".repeat(100_000);
+        let mut tings: Vec<String> = (0..100)
+            .map(|i| format!("{}{}", prefix, (i * 7919) % 100))
+            .collect();
+        let mut expected = tings.clone();
+        expected.sort();
+        AmericanFlagSort.sort(&mut tings);
+        assert_eq!(tings, expected);
+    }
+}
@@ -0,0 +1,456 @@
+//! Argsort: computing the permutation that would sort a slice, without moving it.
+
+use crate::cmp::{ByFn, ByOrd, Compare};
+use crate::permutation;
+use crate::{RadixKey, RadixSort, SortableContainer, Sorter};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+#[cfg(feature = "std")]
+use std::thread;
+
+/// Below this many elements, [`par_argsort_by_key`] runs on the calling thread.
+#[cfg(feature = "std")]
+const PARALLEL_CUTOFF: usize = 1 << 14;
+
+/// Returns the indices of the slice's elements in sorted order.
+///
+/// Equal elements keep their relative order, so the result is the same whichever sorter
+/// is used.
+///
+/// ```
+/// use pangua::*;
+///
+/// assert_eq!(argsort(&['c', 'a', 'b', 'a'], HeapSort::default()), &[1, 3, 2, 0]);
+/// ```
+pub fn argsort<T: Ord, S: Sorter>(slice: &[T], sorter: S) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..slice.len()).collect();
+    sorter.sort_by(&mut indices, |&a, &b| {
+        slice[a].cmp(&slice[b]).then(a.cmp(&b))
+    });
+    indices
+}
+
+/// Returns the indices of the slice's elements, sorted by the key `f` computes for each.
+///
+/// Keys are computed once per element and gathered next to their indices, so sorting
+/// compares contiguous memory instead of chasing indices back into the slice.
+pub fn argsort_by_key<T, K, F, S>(slice: &[T], f: F, sorter: S) -> Vec<usize>
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+    S: Sorter,
+{
+    let mut pairs = gather(slice, 0, &f);
+    sorter.sort(&mut pairs);
+    pairs.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Like [`argsort_by_key`], but computes keys and sorts on multiple threads.
+///
+/// The slice is split into one chunk per thread. Each thread gathers the keys of its
+/// chunk and sorts them with `sorter`, then sorted runs are merged pairwise, each merge
+/// on its own thread, until one run is left.
+#[cfg(feature = "std")]
+pub fn par_argsort_by_key<T, K, F, S>(slice: &[T], f: F, sorter: S) -> Vec<usize>
+where
+    T: Sync,
+    K: Ord + Send,
+    F: Fn(&T) -> K + Sync,
+    S: Sorter + Sync,
+{
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    if threads == 1 || slice.len() < PARALLEL_CUTOFF {
+        return argsort_by_key(slice, f, sorter);
+    }
+
+    let chunk = slice.len().div_ceil(threads);
+    let (f, sorter) = (&f, &sorter);
+    let mut runs: Vec<Vec<(K, usize)>> = thread::scope(|scope| {
+        let handles: Vec<_> = slice
+            .chunks(chunk)
+            .enumerate()
+            .map(|(i, part)| {
+                scope.spawn(move || {
+                    let mut pairs = gather(part, i * chunk, f);
+                    sorter.sort(&mut pairs);
+                    pairs
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    while runs.len() > 1 {
+        runs = thread::scope(|scope| {
+            let mut handles = Vec::new();
+            let mut runs = runs.into_iter();
+            while let Some(a) = runs.next() {
+                match runs.next() {
+                    Some(b) => handles.push(scope.spawn(move || merge(a, b))),
+                    None => handles.push(scope.spawn(move || a)),
+                }
+            }
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+    }
+    runs.pop()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(_, i)| i)
+        .collect()
+}
+
+/// Returns the indices of `keys` in sorted order, as `u32` to halve the memory of the
+/// permutation, using a stable LSD radix sort.
+///
+/// Meant for columnar data: to sort rows by several columns, argsort by the least
+/// significant column, then refine the permutation with [`radix_argsort_refine`] by each
+/// more significant column in turn.
+///
+/// Panics if there are more than `u32::MAX` keys.
+///
+/// ```
+/// use pangua::*;
+///
+/// let city = [2u8, 1, 2, 1];
+/// let age = [30u32, 40, 20, 40];
+/// let mut order = radix_argsort(&age);
+/// radix_argsort_refine(&city, &mut order);
+/// assert_eq!(order, &[1, 3, 2, 0]);
+/// ```
+pub fn radix_argsort<K: RadixKey>(keys: &[K]) -> Vec<u32> {
+    assert!(
+        keys.len() <= u32::MAX as usize,
+        "{} keys do not fit u32 indices",
+        keys.len()
+    );
+    let mut order: Vec<u32> = (0..keys.len() as u32).collect();
+    RadixSort::default().lsd(&mut order, |&i| keys[i as usize]);
+    order
+}
+
+/// Stably reorders the permutation `order` of `keys` by the key of each index, so
+/// indices with equal keys keep the order they had.
+///
+/// Panics if `order` is not as long as `keys`.
+pub fn radix_argsort_refine<K: RadixKey>(keys: &[K], order: &mut [u32]) {
+    assert_eq!(
+        order.len(),
+        keys.len(),
+        "permutation and keys differ in length"
+    );
+    RadixSort::default().lsd(order, |&i| keys[i as usize]);
+}
+
+/// Reorders the slice by a permutation, such as one from [`argsort`]: afterwards
+/// `slice[i]` is the element that was at `order[i]`.
+///
+/// Applying the same permutation to several slices sorts parallel columns by one of
+/// them. Elements are swapped along the cycles of the permutation, in place.
+///
+/// Panics if `order` is not a permutation of `0..slice.len()`.
+///
+/// ```
+/// use pangua::*;
+///
+/// let ages = [40, 20, 30];
+/// let mut names = ["ann", "bob", "cat"];
+/// apply_permutation(&mut names, &argsort(&ages, HeapSort::default()));
+/// assert_eq!(names, ["bob", "cat", "ann"]);
+/// ```
+pub fn apply_permutation<T>(slice: &mut [T], order: &[usize]) {
+    assert_eq!(slice.len(), order.len(), "permutation of the wrong length");
+    let mut order = order.to_vec();
+    check_permutation(&order);
+    permutation::apply(slice, &mut order);
+}
+
+/// Returns the inverse of a permutation: where `order` lists the elements in sorted
+/// order, the inverse gives each element's rank.
+///
+/// Panics if `order` is not a permutation of `0..order.len()`.
+///
+/// ```
+/// use pangua::invert_permutation;
+///
+/// assert_eq!(invert_permutation(&[2, 0, 1]), &[1, 2, 0]);
+/// ```
+pub fn invert_permutation(order: &[usize]) -> Vec<usize> {
+    check_permutation(order);
+    let mut inverse = vec![0; order.len()];
+    for (i, &j) in order.iter().enumerate() {
+        inverse[j] = i;
+    }
+    inverse
+}
+
+/// Sorts `keys` with `sorter` and reorders `values` in lockstep, so that each value
+/// stays beside its key, as if the two were zipped into pairs and sorted by key.
+/// Equal keys keep their order.
+///
+/// Only a permutation of indices is allocated; keys and values are swapped into place,
+/// each at most `len - 1` times.
+///
+/// Panics if the slices differ in length.
+///
+/// ```
+/// use pangua::*;
+///
+/// let mut ages = [40, 20, 30];
+/// let mut names = ["ann", "bob", "cat"];
+/// sort_pairs(&mut ages, &mut names, QuickSort::default());
+/// assert_eq!((ages, names), ([20, 30, 40], ["bob", "cat", "ann"]));
+/// ```
+pub fn sort_pairs<K: Ord, V, S: Sorter>(keys: &mut [K], values: &mut [V], sorter: S) {
+    sort_pairs_with(keys, values, sorter, &mut ByOrd)
+}
+
+/// Like [`sort_pairs`], ordering the keys with `compare`.
+pub fn sort_pairs_by<K, V, S, F>(keys: &mut [K], values: &mut [V], sorter: S, compare: F)
+where
+    S: Sorter,
+    F: FnMut(&K, &K) -> Ordering,
+{
+    sort_pairs_with(keys, values, sorter, &mut ByFn(compare))
+}
+
+fn sort_pairs_with<K, V, S, C>(keys: &mut [K], values: &mut [V], sorter: S, cmp: &mut C)
+where
+    S: Sorter,
+    C: Compare<K>,
+{
+    assert_eq!(keys.len(), values.len(), "columns of different lengths");
+    let mut order = argsort_with(keys, sorter, cmp);
+    permutation::apply_by(&mut order, |a, b| {
+        keys.swap(a, b);
+        values.swap(a, b);
+    });
+}
+
+/// A column of a table stored column by column, which [`sort_columns`] reorders.
+///
+/// Implemented for everything that is a [`SortableContainer`], such as slices, vectors
+/// and deques, so columns of any element type can be passed together as
+/// `&mut dyn Column`.
+pub trait Column {
+    fn rows(&self) -> usize;
+
+    fn swap_rows(&mut self, a: usize, b: usize);
+}
+
+impl<C: SortableContainer + ?Sized> Column for C {
+    fn rows(&self) -> usize {
+        self.len()
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        self.swap(a, b)
+    }
+}
+
+/// Sorts `keys` with `sorter` and reorders every one of `columns` in lockstep, as
+/// [`sort_pairs`] does with one.
+///
+/// Panics if any column differs in length from the keys.
+///
+/// ```
+/// use pangua::*;
+///
+/// let mut ids = vec![3, 1, 2];
+/// let mut names = vec!["cat", "ann", "bob"];
+/// let mut scores = [0.5, 0.9, 0.1];
+/// sort_columns(&mut ids, MergeSort::default(), &mut [&mut names, &mut scores]);
+/// assert_eq!(ids, [1, 2, 3]);
+/// assert_eq!(names, ["ann", "bob", "cat"]);
+/// assert_eq!(scores, [0.9, 0.1, 0.5]);
+/// ```
+pub fn sort_columns<K: Ord, S: Sorter>(keys: &mut [K], sorter: S, columns: &mut [&mut dyn Column]) {
+    let mut order = argsort_with(keys, sorter, &mut ByOrd);
+    apply_to_columns(&mut order, columns, |a, b| keys.swap(a, b));
+}
+
+/// Reorders every one of `columns` by the permutation `order`, as
+/// [`apply_permutation`] does a single slice.
+///
+/// Panics if `order` is not a permutation, or a column is not as long as it.
+pub fn apply_permutation_to_columns(order: &[usize], columns: &mut [&mut dyn Column]) {
+    check_permutation(order);
+    apply_to_columns(&mut order.to_vec(), columns, |_, _| {});
+}
+
+/// Applies `order` to the columns, calling `also` with every swap made.
+fn apply_to_columns<F>(order: &mut [usize], columns: &mut [&mut dyn Column], mut also: F)
+where
+    F: FnMut(usize, usize),
+{
+    for column in columns.iter() {
+        assert_eq!(column.rows(), order.len(), "columns of different lengths");
+    }
+    permutation::apply_by(order, |a, b| {
+        also(a, b);
+        for column in columns.iter_mut() {
+            column.swap_rows(a, b);
+        }
+    });
+}
+
+/// The stable order of `keys` under `cmp`: ties are broken by index, so any sorter
+/// gives the same one.
+fn argsort_with<K, S: Sorter, C: Compare<K>>(keys: &[K], sorter: S, cmp: &mut C) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    sorter.sort_by(&mut order, |&a, &b| {
+        cmp.compare(&keys[a], &keys[b]).then(a.cmp(&b))
+    });
+    order
+}
+
+fn check_permutation(order: &[usize]) {
+    let mut seen = vec![false; order.len()];
+    for &i in order {
+        assert!(
+            i < order.len() && !seen[i],
+            "not a permutation: {} out of range or repeated",
+            i
+        );
+        seen[i] = true;
+    }
+}
+
+/// Pairs each element's key with its index, counting indices from `offset`.
+fn gather<T, K, F: Fn(&T) -> K>(slice: &[T], offset: usize, f: F) -> Vec<(K, usize)> {
+    slice
+        .iter()
+        .enumerate()
+        .map(|(i, x)| (f(x), offset + i))
+        .collect()
+}
+
+/// Merges two sorted runs.
+#[cfg(feature = "std")]
+fn merge<K: Ord>(a: Vec<(K, usize)>, b: Vec<(K, usize)>) -> Vec<(K, usize)> {
+    let mut out = Vec::with_capacity(a.len() + b.len());
+    let (mut a, mut b) = (a.into_iter().peekable(), b.into_iter().peekable());
+    loop {
+        let next = match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) if y < x => b.next(),
+            (Some(_), _) => a.next(),
+            (None, Some(_)) => b.next(),
+            (None, None) => break,
+        };
+        out.extend(next);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn argsort_works() {
+        let tings = vec![30, 10, 20, 10];
+        assert_eq!(argsort(&tings, QuickSort::default()), &[1, 3, 2, 0]);
+        assert_eq!(
+            argsort_by_key(&tings, |&x| core::cmp::Reverse(x), HeapSort::default()),
+            &[0, 2, 1, 3]
+        );
+    }
+
+    #[test]
+    fn permutations_apply_and_invert() {
+        let keys = vec![3, 1, 2, 1, 0];
+        let order = argsort(&keys, MergeSort::default());
+        let mut sorted = keys.clone();
+        apply_permutation(&mut sorted, &order);
+        assert_eq!(sorted, &[0, 1, 1, 2, 3]);
+        let ranks = invert_permutation(&order);
+        assert_eq!(ranks, &[4, 1, 3, 2, 0]);
+        assert_eq!(invert_permutation(&ranks), order);
+        let mut empty: [u8; 0] = [];
+        apply_permutation(&mut empty, &[]);
+    }
+
+    #[test]
+    fn columns_move_in_lockstep() {
+        let mut keys = vec![3, 1, 2, 1, 0];
+        let mut tags: Vec<char> = "abcde".chars().collect();
+        sort_pairs(&mut keys, &mut tags, HeapSort::default());
+        assert_eq!(keys, [0, 1, 1, 2, 3]);
+        assert_eq!(tags, ['e', 'b', 'd', 'c', 'a']);
+        sort_pairs_by(&mut keys, &mut tags, QuickSort::default(), |a, b| b.cmp(a));
+        assert_eq!(tags, ['a', 'c', 'b', 'd', 'e']);
+
+        let mut ids = vec![2u32, 0, 1];
+        let mut names = alloc::collections::VecDeque::from(vec!["c", "a", "b"]);
+        let mut flags = [true, false, true];
+        sort_columns(
+            &mut ids,
+            MergeSort::default(),
+            &mut [&mut names, &mut flags],
+        );
+        assert_eq!(ids, [0, 1, 2]);
+        assert_eq!(names, ["a", "b", "c"]);
+        assert_eq!(flags, [false, true, true]);
+        apply_permutation_to_columns(&[2, 0, 1], &mut [&mut ids, &mut flags]);
+        assert_eq!((ids, flags), (vec![2, 0, 1], [true, false, true]));
+    }
+
+    #[test]
+    #[should_panic(expected = "columns of different lengths")]
+    fn columns_must_match_in_length() {
+        sort_pairs(&mut [2, 1], &mut [1, 2, 3], HeapSort::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "not a permutation")]
+    fn apply_permutation_rejects_repeats() {
+        apply_permutation(&mut [1, 2, 3], &[0, 1, 1]);
+    }
+
+    #[test]
+    fn radix_argsort_matches_argsort() {
+        let tings: Vec<i32> = (0..1000).map(|i| (i * 7919) % 201 - 100).collect();
+        let expected: Vec<u32> = argsort(&tings, MergeSort::default())
+            .into_iter()
+            .map(|i| i as u32)
+            .collect();
+        assert_eq!(radix_argsort(&tings), expected);
+        assert!(radix_argsort::<u8>(&[]).is_empty());
+    }
+
+    #[test]
+    fn refining_composes_columns() {
+        let n = 2000u64;
+        let a: Vec<u8> = (0..n).map(|i| (i * 7919 % 7) as u8).collect();
+        let b: Vec<u64> = (0..n).map(|i| i * 2654435761 % 13).collect();
+        let c: Vec<i16> = (0..n).map(|i| (i * 31 % 5) as i16 - 2).collect();
+        let mut order = radix_argsort(&c);
+        radix_argsort_refine(&b, &mut order);
+        radix_argsort_refine(&a, &mut order);
+        let rows: Vec<_> = (0..n as usize).map(|i| (a[i], b[i], c[i], i)).collect();
+        let expected: Vec<u32> = argsort(&rows, QuickSort::default())
+            .into_iter()
+            .map(|i| i as u32)
+            .collect();
+        assert_eq!(order, expected);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn par_argsort_matches_sequential() {
+        let n = 100_000u32;
+        let tings: Vec<u32> = (0..n).map(|i| i.wrapping_mul(2654435761) % 1000).collect();
+        let expected = argsort(&tings, MergeSort::default());
+        assert_eq!(
+            par_argsort_by_key(&tings, |&x| x, IntroSort::default()),
+            expected
+        );
+        let small = &tings[..100];
+        assert_eq!(
+            par_argsort_by_key(small, |&x| x, IntroSort::default()),
+            argsort(small, MergeSort::default())
+        );
+    }
+}
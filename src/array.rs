@@ -0,0 +1,166 @@
+//! Sorting fixed-size arrays, at run time or at compile time.
+//!
+//! [`sort_array`] picks its algorithm from the length of the array, which is known at
+//! compile time: up to [`MAX_OPTIMAL`](crate::networks::MAX_OPTIMAL) elements it runs
+//! the smallest known sorting network, whose compare-exchanges the compiler can unroll
+//! with no branching on the length.
+//!
+//! Trait methods cannot be called in a `const fn`, so the compile-time sorts come one
+//! per primitive type: `insertion_sorted_u32`, `network_sorted_i64` and so on, taking
+//! the array by value and returning it sorted. They are meant for lookup tables:
+//!
+//! ```
+//! use pangua::array;
+//!
+//! const PRIMES: [u16; 6] = array::network_sorted_u16([13, 2, 11, 7, 3, 5]);
+//! assert_eq!(PRIMES, [2, 3, 5, 7, 11, 13]);
+//! assert!(PRIMES.binary_search(&7).is_ok());
+//! ```
+
+use crate::cmp::{self, ByFn, ByOrd, Compare};
+use crate::networks::{self, Batcher, MAX_OPTIMAL};
+use crate::{IntroSort, Sorter};
+use core::cmp::Ordering;
+
+/// Sorts the array: with a sorting network up to
+/// [`MAX_OPTIMAL`](crate::networks::MAX_OPTIMAL) elements, with [`IntroSort`] beyond.
+/// Not stable.
+///
+/// ```
+/// let mut tings = [5, 1, 4, 2, 3];
+/// pangua::sort_array(&mut tings);
+/// assert_eq!(tings, [1, 2, 3, 4, 5]);
+/// ```
+pub fn sort_array<T: Ord, const N: usize>(array: &mut [T; N]) {
+    sort_array_with(array, &mut ByOrd)
+}
+
+/// Like [`sort_array`], ordering with `compare`.
+pub fn sort_array_by<T, F, const N: usize>(array: &mut [T; N], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    sort_array_with(array, &mut ByFn(compare))
+}
+
+/// Like [`sort_array`], ordering by the keys `f` extracts.
+pub fn sort_array_by_key<T, K, F, const N: usize>(array: &mut [T; N], f: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    sort_array_with(array, &mut cmp::by_key(f))
+}
+
+fn sort_array_with<T, C: Compare<T>, const N: usize>(array: &mut [T; N], cmp: &mut C) {
+    if N <= MAX_OPTIMAL {
+        networks::network_sort(array, cmp)
+    } else {
+        IntroSort::default().sort_with(array, cmp)
+    }
+}
+
+macro_rules! const_sorts {
+    ($($t:ty => $insertion:ident, $network:ident;)*) => {$(
+        #[doc = concat!("Insertion sort on an array of `", stringify!($t), "`, usable in constants.")]
+        pub const fn $insertion<const N: usize>(mut array: [$t; N]) -> [$t; N] {
+            let mut unsorted = 1;
+            while unsorted < N {
+                let mut i = unsorted;
+                while i > 0 && array[i] < array[i - 1] {
+                    array.swap(i - 1, i);
+                    i -= 1;
+                }
+                unsorted += 1;
+            }
+            array
+        }
+
+        #[doc = concat!("Sorts an array of `", stringify!($t), "` with a sorting network, \
+                         as [`sorting_network`](crate::networks::sorting_network) does, \
+                         usable in constants.")]
+        pub const fn $network<const N: usize>(mut array: [$t; N]) -> [$t; N] {
+            if let Some(network) = networks::optimal(N) {
+                let mut k = 0;
+                while k < network.len() {
+                    let (a, b) = (network[k].0 as usize, network[k].1 as usize);
+                    if array[b] < array[a] {
+                        array.swap(a, b);
+                    }
+                    k += 1;
+                }
+            } else {
+                let mut pairs = Batcher::new(N);
+                while let Some((a, b)) = pairs.next_pair() {
+                    if array[b] < array[a] {
+                        array.swap(a, b);
+                    }
+                }
+            }
+            array
+        }
+    )*};
+}
+
+const_sorts! {
+    u8 => insertion_sorted_u8, network_sorted_u8;
+    u16 => insertion_sorted_u16, network_sorted_u16;
+    u32 => insertion_sorted_u32, network_sorted_u32;
+    u64 => insertion_sorted_u64, network_sorted_u64;
+    u128 => insertion_sorted_u128, network_sorted_u128;
+    usize => insertion_sorted_usize, network_sorted_usize;
+    i8 => insertion_sorted_i8, network_sorted_i8;
+    i16 => insertion_sorted_i16, network_sorted_i16;
+    i32 => insertion_sorted_i32, network_sorted_i32;
+    i64 => insertion_sorted_i64, network_sorted_i64;
+    i128 => insertion_sorted_i128, network_sorted_i128;
+    isize => insertion_sorted_isize, network_sorted_isize;
+    char => insertion_sorted_char, network_sorted_char;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: [i32; 20] = network_sorted_i32([
+        7, -3, 12, 0, 5, 5, -8, 19, 2, 11, 3, -1, 14, 6, 9, 1, 4, -5, 8, 10,
+    ]);
+
+    fn check<const N: usize>() {
+        let mut tings = [0u64; N];
+        for (i, x) in tings.iter_mut().enumerate() {
+            *x = (i as u64 * 7919) % 23;
+        }
+        let mut expected = tings;
+        expected.sort_unstable();
+        assert_eq!(insertion_sorted_u64(tings), expected, "N = {}", N);
+        assert_eq!(network_sorted_u64(tings), expected, "N = {}", N);
+    }
+
+    #[test]
+    fn const_sorts_sort() {
+        assert!(TABLE.windows(2).all(|w| w[0] <= w[1]));
+        check::<0>();
+        check::<1>();
+        check::<7>();
+        check::<16>();
+        check::<17>();
+        check::<40>();
+        assert_eq!(insertion_sorted_char(['c', 'a', 'b']), ['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn sorts_arrays_of_any_length() {
+        let mut small = [(3, 'a'), (1, 'b'), (2, 'c')];
+        sort_array_by_key(&mut small, |t| t.0);
+        assert_eq!(small, [(1, 'b'), (2, 'c'), (3, 'a')]);
+        let mut big = [0u32; 100];
+        for (i, x) in big.iter_mut().enumerate() {
+            *x = (i as u32 * 31) % 100;
+        }
+        sort_array_by(&mut big, |a, b| b.cmp(a));
+        assert!(big.windows(2).all(|w| w[0] >= w[1]));
+        sort_array(&mut big);
+        assert!(big.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::cmp::{ByFn, Compare};
+use crate::partition::ThreeWay;
+use crate::runs;
+use crate::{
+    is_trivially_sorted, Complexity, InsertionSort, IntroSort, NaturalMergeSort, Sorter, SorterInfo,
+};
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::cmp::Ordering;
+
+/// Slices this short are insertion sorted outright.
+const INSERTION_MAX: usize = 24;
+/// Natural merging is picked when the runs average at least this many elements.
+const MIN_RUN_LEN: usize = 32;
+/// Elements sampled to estimate the density of duplicates.
+const SAMPLE: usize = 64;
+/// Sampled fraction of duplicates above which introsort partitions three ways.
+const DUPLICATE_RATIO: f64 = 0.5;
+
+/// The algorithm [`AutoSort`] dispatched to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoChoice {
+    /// Insertion sort, for short slices.
+    Insertion,
+    /// [`NaturalMergeSort`], for slices made of a few long presorted runs.
+    NaturalMerge,
+    /// [`IntroSort`] with Hoare partitioning.
+    Intro,
+    /// [`IntroSort`] with three-way partitioning, for slices dense in duplicates.
+    IntroThreeWay,
+}
+
+/// What [`AutoSort`] found out about a slice, and the algorithm it chose accordingly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoReport {
+    pub len: usize,
+    /// Presorted runs found, counted up to the most that would still favour merging.
+    pub runs: usize,
+    /// Fraction of a sample of the elements equal to another sampled element.
+    pub duplicate_ratio: f64,
+    pub choice: AutoChoice,
+}
+
+/// Auto Sort
+///
+/// Auto sort inspects the input before sorting it and dispatches to the algorithm that
+/// suits it: insertion sort below 25 elements, natural merge sort when the slice is made
+/// of presorted runs averaging 32 elements or more, and introsort otherwise, with
+/// three-way partitioning when at least half of an evenly spaced sample of 64 elements
+/// are duplicates. Counting runs stops as soon as there are too many to merge, so
+/// shuffled inputs pay for a handful of comparisons, and the sample costs O(1).
+///
+/// The choice of the last sort is kept for inspection, see [`AutoSort::report`]. The
+/// sort is not stable, since introsort is not.
+#[derive(Debug, Clone, Default)]
+pub struct AutoSort {
+    report: Cell<Option<AutoReport>>,
+}
+
+impl AutoSort {
+    /// The analysis behind the last sort performed by this sorter, if any.
+    pub fn report(&self) -> Option<AutoReport> {
+        self.report.get()
+    }
+
+    /// Inspects the slice and picks an algorithm, without sorting.
+    pub fn analyze<T, C: Compare<T>>(slice: &[T], cmp: &mut C) -> AutoReport {
+        let len = slice.len();
+        let mut report = AutoReport {
+            len,
+            runs: 0,
+            duplicate_ratio: 0.0,
+            choice: AutoChoice::Insertion,
+        };
+        if len <= INSERTION_MAX {
+            return report;
+        }
+        report.runs = count_runs(slice, len / MIN_RUN_LEN, cmp);
+        if report.runs <= len / MIN_RUN_LEN {
+            report.choice = AutoChoice::NaturalMerge;
+            return report;
+        }
+        report.duplicate_ratio = duplicate_ratio(slice, cmp);
+        report.choice = if report.duplicate_ratio >= DUPLICATE_RATIO {
+            AutoChoice::IntroThreeWay
+        } else {
+            AutoChoice::Intro
+        };
+        report
+    }
+}
+
+impl Sorter for AutoSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "auto",
+            stable: false,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let report = AutoSort::analyze(slice, cmp);
+        self.report.set(Some(report));
+        match report.choice {
+            AutoChoice::Insertion => InsertionSort { smart: false }.sort_with(slice, cmp),
+            AutoChoice::NaturalMerge => NaturalMergeSort::default().sort_with(slice, cmp),
+            AutoChoice::Intro => IntroSort::default().sort_with(slice, cmp),
+            AutoChoice::IntroThreeWay => IntroSort {
+                partitioner: ThreeWay,
+            }
+            .sort_with(slice, cmp),
+        }
+    }
+}
+
+/// Counts the slice's runs as natural merge sort finds them, non-descending or strictly
+/// descending, giving up once there are more than `limit`.
+fn count_runs<T, C: Compare<T>>(slice: &[T], limit: usize, cmp: &mut C) -> usize {
+    let mut runs = 0;
+    let mut start = 0;
+    while start < slice.len() && runs <= limit {
+        start += runs::run_at(&slice[start..], cmp).0;
+        runs += 1;
+    }
+    runs
+}
+
+/// The fraction of an evenly spaced sample of the slice equal to another sampled element.
+fn duplicate_ratio<T, C: Compare<T>>(slice: &[T], cmp: &mut C) -> f64 {
+    let step = slice.len().div_ceil(SAMPLE);
+    let mut sample: Vec<&T> = slice.iter().step_by(step).collect();
+    let mut by_cmp = ByFn(|a: &&T, b: &&T| cmp.compare(a, b));
+    InsertionSort { smart: false }.sort_with(&mut sample, &mut by_cmp);
+    let duplicates = sample
+        .windows(2)
+        .filter(|w| by_cmp.compare(&w[0], &w[1]) == Ordering::Equal)
+        .count();
+    duplicates as f64 / sample.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmp::ByOrd;
+    use alloc::vec;
+
+    fn choice(tings: &[u32]) -> AutoChoice {
+        AutoSort::analyze(tings, &mut ByOrd).choice
+    }
+
+    #[test]
+    fn auto_works() {
+        let sorter = AutoSort::default();
+        assert_eq!(sorter.report(), None);
+        let mut tings = vec![5, 1, 4, 2, 3];
+        sorter.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        assert_eq!(sorter.report().unwrap().choice, AutoChoice::Insertion);
+
+        let mut tings: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        sorter.sort(&mut tings);
+        assert_eq!(tings, (0..1000).collect::<Vec<_>>());
+        let report = sorter.report().unwrap();
+        assert_eq!((report.len, report.choice), (1000, AutoChoice::Intro));
+    }
+
+    #[test]
+    fn chooses_by_input_shape() {
+        let ascending: Vec<u32> = (0..1000).collect();
+        assert_eq!(choice(&ascending), AutoChoice::NaturalMerge);
+        let mut descending = ascending.clone();
+        descending.reverse();
+        assert_eq!(choice(&descending), AutoChoice::NaturalMerge);
+        // ten ascending runs of 100
+        let sawtooth: Vec<u32> = (0..1000).map(|i| i % 100).collect();
+        assert_eq!(choice(&sawtooth), AutoChoice::NaturalMerge);
+        let few_keys: Vec<u32> = (0..1000).map(|i| (i * 7919) % 7).collect();
+        assert_eq!(choice(&few_keys), AutoChoice::IntroThreeWay);
+        let shuffled: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        assert_eq!(choice(&shuffled), AutoChoice::Intro);
+        assert_eq!(choice(&shuffled[..INSERTION_MAX]), AutoChoice::Insertion);
+    }
+
+    #[test]
+    fn run_counting_gives_up_early() {
+        let shuffled: Vec<u32> = (0..100_000).map(|i| (i * 7919) % 100_000).collect();
+        let mut counter = crate::Counter::new(ByOrd);
+        let report = AutoSort::analyze(&shuffled, &mut counter);
+        assert_eq!(report.runs, 100_000 / MIN_RUN_LEN + 1);
+        assert!(counter.stats().comparisons < 100_000);
+    }
+}
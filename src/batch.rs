@@ -0,0 +1,114 @@
+//! Sorting many independent slices at once.
+
+use crate::cmp::ByOrd;
+use crate::Sorter;
+use std::mem;
+use std::thread;
+
+/// Below this many elements in total, the batch is sorted on the calling thread.
+const PARALLEL_CUTOFF: usize = 1 << 14;
+
+/// Sorts each of the slices independently.
+///
+/// The slices are divided among threads, each sorting its share one slice at a time, so
+/// parallelism comes from the number of slices rather than from splitting any of them.
+/// Shares are runs of slices holding about the same number of elements, and each thread
+/// merges through one scratch buffer for all of its slices, through
+/// [`Sorter::sort_with_scratch`], instead of allocating one per slice. This suits many
+/// small or medium slices (e.g. rows of a table); for one huge slice use a parallel
+/// sorter instead.
+pub fn sort_batch<T, S>(slices: &mut [&mut [T]], sorter: S)
+where
+    T: Ord + Send,
+    S: Sorter + Sync,
+{
+    let total: usize = slices.iter().map(|s| s.len()).sum();
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    if threads == 1 || slices.len() < 2 || total < PARALLEL_CUTOFF {
+        sort_each(slices, &sorter);
+        return;
+    }
+
+    let per_thread = total.div_ceil(threads);
+    let sorter = &sorter;
+    thread::scope(|scope| {
+        let mut rest = slices;
+        while !rest.is_empty() {
+            let count = share(rest, per_thread);
+            let (group, tail) = mem::take(&mut rest).split_at_mut(count);
+            rest = tail;
+            scope.spawn(move || sort_each(group, sorter));
+        }
+    });
+}
+
+/// How many of the leading slices make up a share of at least `per_thread` elements, or
+/// all of them if they hold fewer.
+fn share<T>(slices: &[&mut [T]], per_thread: usize) -> usize {
+    let mut len = 0;
+    let mut count = 0;
+    while count < slices.len() && len < per_thread {
+        len += slices[count].len();
+        count += 1;
+    }
+    count
+}
+
+/// Sorts the slices one after another, merging through a single scratch buffer.
+fn sort_each<T: Ord, S: Sorter>(slices: &mut [&mut [T]], sorter: &S) {
+    let mut scratch = Vec::new();
+    for slice in slices.iter_mut() {
+        sorter.sort_with_scratch(slice, &mut scratch, &mut ByOrd);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn sorts_every_slice() {
+        let mut data: Vec<Vec<u32>> = (0..500u32)
+            .map(|row| (0..100).map(|i| (i * 7919 + row * 31) % 101).collect())
+            .collect();
+        let mut slices: Vec<&mut [u32]> = data.iter_mut().map(|v| &mut v[..]).collect();
+        sort_batch(&mut slices, QuickSort::default());
+        for row in &data {
+            assert!(row.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn shares_are_balanced_by_elements() {
+        let mut big = vec![0u8; 600];
+        let mut small: Vec<Vec<u8>> = vec![vec![0; 100]; 9];
+        let mut slices: Vec<&mut [u8]> = vec![&mut big];
+        slices.extend(small.iter_mut().map(|v| &mut v[..]));
+        // 1500 elements over three threads
+        assert_eq!(share(&slices, 500), 1);
+        assert_eq!(share(&slices[1..], 500), 5);
+        assert_eq!(share(&slices[6..], 500), 4);
+    }
+
+    #[test]
+    fn scratch_is_reused_between_slices() {
+        let mut scratch = Vec::new();
+        let mut tings: Vec<u32> = (0..1000).rev().collect();
+        MergeSort::default().sort_with_scratch(&mut tings, &mut scratch, &mut cmp::ByOrd);
+        let buffer = scratch.as_ptr();
+        let mut tings: Vec<u32> = (0..500).rev().collect();
+        DefaultSorter.sort_with_scratch(&mut tings, &mut scratch, &mut cmp::ByOrd);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(scratch.as_ptr(), buffer);
+    }
+
+    #[test]
+    fn small_batches_work() {
+        let mut a = vec![3, 1, 2];
+        let mut b: Vec<i32> = vec![];
+        let mut slices: Vec<&mut [i32]> = vec![&mut a, &mut b];
+        sort_batch(&mut slices, HeapSort::default());
+        assert_eq!(a, &[1, 2, 3]);
+    }
+}
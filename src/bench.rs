@@ -0,0 +1,305 @@
+//! A small benchmark harness: which sorter is fastest on which input.
+//!
+//! [`compare`] sorts every input with every [`Algorithm`], several times each, and
+//! collects the median wall time along with the comparisons and swaps of a counted run
+//! into a [`BenchReport`], whose `Display` is a table with the fastest sorter for each
+//! input marked. Inputs are typically made from the [`generators`](crate::generators)
+//! patterns. Build with `--release` for meaningful times.
+//!
+//! ```
+//! use pangua::bench::{self, Algorithm, Input};
+//! use pangua::generators::Pattern;
+//! use pangua::{DefaultSorter, IntroSort, MergeSort};
+//!
+//! let algorithms = [
+//!     Algorithm::new(IntroSort::default()),
+//!     Algorithm::new(MergeSort::default()),
+//!     Algorithm::new(DefaultSorter),
+//! ];
+//! let inputs = [
+//!     Input::pattern(Pattern::Presorted, 1000, 1),
+//!     Input::pattern(Pattern::FewUnique, 1000, 1),
+//! ];
+//! let report = bench::compare(&algorithms, &inputs);
+//! println!("{}", report);
+//! let fastest = report.fastest("presorted").unwrap();
+//! assert_eq!(fastest.len, 1000);
+//! ```
+
+use crate::generators::Pattern;
+use crate::{Counting, SortStats, Sorter};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Timed runs of each sorter on each input, in [`compare`].
+pub const DEFAULT_REPEATS: usize = 5;
+
+/// A sorter, type-erased so sorters of different types can be compared on slices of
+/// `T`.
+pub struct Algorithm<'a, T> {
+    name: &'static str,
+    sorter: Box<dyn ErasedSorter<T> + 'a>,
+}
+
+/// The part of [`Sorter`] the harness calls, for one element type.
+trait ErasedSorter<T> {
+    fn sort(&self, slice: &mut [T]);
+    fn count(&self, slice: &mut [T]) -> SortStats;
+}
+
+impl<T: Ord, S: Sorter> ErasedSorter<T> for S {
+    fn sort(&self, slice: &mut [T]) {
+        Sorter::sort(self, slice)
+    }
+
+    fn count(&self, slice: &mut [T]) -> SortStats {
+        let counting = Counting::new(self);
+        Sorter::sort(&counting, slice);
+        counting.stats()
+    }
+}
+
+impl<'a, T: Ord> Algorithm<'a, T> {
+    pub fn new<S: Sorter + 'a>(sorter: S) -> Self {
+        Algorithm {
+            name: sorter.info().name,
+            sorter: Box::new(sorter),
+        }
+    }
+
+    /// The name the results are reported under, the sorter's own by default.
+    pub fn named(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// A named input to sort.
+#[derive(Debug, Clone)]
+pub struct Input<T> {
+    pub name: String,
+    pub data: Vec<T>,
+}
+
+impl<T> Input<T> {
+    pub fn new(name: impl Into<String>, data: Vec<T>) -> Self {
+        Input {
+            name: name.into(),
+            data,
+        }
+    }
+}
+
+impl Input<usize> {
+    /// `n` values of a generated pattern, named after it in snake case.
+    pub fn pattern(pattern: Pattern, n: usize, seed: u64) -> Self {
+        Input::new(
+            snake_case(&format!("{:?}", pattern)),
+            pattern.generate(n, seed),
+        )
+    }
+}
+
+fn snake_case(camel: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in camel.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+/// How one sorter did on one input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchResult {
+    pub algorithm: &'static str,
+    pub input: String,
+    pub len: usize,
+    /// The median wall time of the timed runs.
+    pub median: Duration,
+    pub repeats: usize,
+    /// Comparisons, swaps and moves of a separate counted run, so that counting does
+    /// not slow down the timed ones.
+    pub stats: SortStats,
+}
+
+/// The results of [`compare`], input by input, in the order the algorithms were given.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BenchReport {
+    pub results: Vec<BenchResult>,
+}
+
+impl BenchReport {
+    /// The results on the input named `input`.
+    pub fn for_input<'r>(&'r self, input: &'r str) -> impl Iterator<Item = &'r BenchResult> {
+        self.results.iter().filter(move |r| r.input == input)
+    }
+
+    /// The result with the lowest median time on the input named `input`, the first
+    /// listed among ties.
+    pub fn fastest(&self, input: &str) -> Option<&BenchResult> {
+        self.results.iter().filter(|r| r.input == input).fold(
+            None,
+            |best: Option<&BenchResult>, r| match best {
+                Some(b) if b.median <= r.median => Some(b),
+                _ => Some(r),
+            },
+        )
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let input_width = self
+            .results
+            .iter()
+            .map(|r| r.input.len())
+            .chain(Some("input".len()))
+            .max()
+            .unwrap_or(0);
+        let algorithm_width = self
+            .results
+            .iter()
+            .map(|r| r.algorithm.len())
+            .chain(Some("algorithm".len()))
+            .max()
+            .unwrap_or(0);
+        writeln!(
+            f,
+            "{:iw$}  {:>9}  {:aw$}  {:>12}  {:>12}  {:>12}",
+            "input",
+            "n",
+            "algorithm",
+            "median",
+            "comparisons",
+            "swaps",
+            iw = input_width,
+            aw = algorithm_width,
+        )?;
+        for r in &self.results {
+            let fastest = self.fastest(&r.input).is_some_and(|b| std::ptr::eq(b, r));
+            writeln!(
+                f,
+                "{:iw$}  {:>9}  {:aw$}  {:>12}  {:>12}  {:>12}{}",
+                r.input,
+                r.len,
+                r.algorithm,
+                format!("{:.1?}", r.median),
+                r.stats.comparisons,
+                r.stats.swaps,
+                if fastest { "  *" } else { "" },
+                iw = input_width,
+                aw = algorithm_width,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Sorts every input with every algorithm, [`DEFAULT_REPEATS`] timed runs each.
+pub fn compare<T: Ord + Clone>(
+    algorithms: &[Algorithm<'_, T>],
+    inputs: &[Input<T>],
+) -> BenchReport {
+    compare_repeated(algorithms, inputs, DEFAULT_REPEATS)
+}
+
+/// Like [`compare`], with `repeats` timed runs of each sorter on each input, at least
+/// one.
+///
+/// # Panics
+///
+/// If an algorithm leaves an input unsorted.
+pub fn compare_repeated<T: Ord + Clone>(
+    algorithms: &[Algorithm<'_, T>],
+    inputs: &[Input<T>],
+    repeats: usize,
+) -> BenchReport {
+    let repeats = repeats.max(1);
+    let mut results = Vec::with_capacity(algorithms.len() * inputs.len());
+    for input in inputs {
+        for algorithm in algorithms {
+            let mut tings = input.data.clone();
+            let stats = algorithm.sorter.count(&mut tings);
+            assert!(
+                tings.windows(2).all(|w| w[0] <= w[1]),
+                "{} left {} unsorted",
+                algorithm.name,
+                input.name
+            );
+            let mut times: Vec<Duration> = (0..repeats)
+                .map(|_| {
+                    let mut tings = input.data.clone();
+                    let start = Instant::now();
+                    algorithm.sorter.sort(&mut tings);
+                    start.elapsed()
+                })
+                .collect();
+            times.sort_unstable();
+            results.push(BenchResult {
+                algorithm: algorithm.name,
+                input: input.name.clone(),
+                len: input.data.len(),
+                median: times[times.len() / 2],
+                repeats,
+                stats,
+            });
+        }
+    }
+    BenchReport { results }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BubbleSort, InsertionSort, MergeSort};
+
+    #[test]
+    fn reports_every_pair() {
+        let algorithms = [
+            Algorithm::new(BubbleSort),
+            Algorithm::new(MergeSort::default()),
+            Algorithm::new(InsertionSort { smart: false }).named("insertion-linear"),
+        ];
+        let inputs = [
+            Input::pattern(Pattern::ReverseSorted, 300, 0),
+            Input::new("tiny", vec![2, 1]),
+        ];
+        let report = compare_repeated(&algorithms, &inputs, 3);
+        assert_eq!(report.results.len(), 6);
+        let reversed: Vec<_> = report.for_input("reverse_sorted").collect();
+        assert_eq!(reversed[2].algorithm, "insertion-linear");
+        assert_eq!(reversed[0].stats.swaps, 300 * 299 / 2);
+        assert_eq!(reversed[0].repeats, 3);
+        assert!(report.fastest("tiny").is_some());
+        assert!(report.fastest("missing").is_none());
+        let table = report.to_string();
+        assert_eq!(table.lines().count(), 7);
+        assert_eq!(table.matches("  *").count(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "unsorted")]
+    fn rejects_broken_sorters() {
+        use crate::cmp::Compare;
+        use crate::SorterInfo;
+
+        struct Noop;
+
+        impl Sorter for Noop {
+            fn info(&self) -> SorterInfo {
+                BubbleSort.info()
+            }
+
+            fn sort_with<T, C: Compare<T>>(&self, _: &mut [T], _: &mut C) {}
+        }
+
+        compare(&[Algorithm::new(Noop)], &[Input::new("pair", vec![2, 1])]);
+    }
+}
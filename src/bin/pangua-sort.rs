@@ -0,0 +1,424 @@
+//! `sort(1)` with a choice of algorithm: sorts the lines of a file or stdin.
+//!
+//! ```text
+//! cargo run --release --features cli --bin pangua-sort -- \
+//!     [--algorithm NAME] [--column N [--delimiter C] [--header]] \
+//!     [--numeric] [--reverse] [--stable] [--unique] [--stats] [--list] [FILE]
+//! cargo run --release --features cli --bin pangua-sort -- merge \
+//!     [--column N [--delimiter C]] [--numeric] [--reverse] [--unique] \
+//!     [--binary] [--memory BYTES] [--output FILE] FILE...
+//! ```
+//!
+//! Lines are ordered by their text, or with `--column` by a field of a CSV record,
+//! counting from 1; double-quoted fields may hold the delimiter. `--numeric` orders keys
+//! as floating-point numbers, keys that are not numbers before all numbers and by their
+//! text. Lines with equal keys are ordered by their whole text, as `sort(1)` does, or
+//! with `--stable` kept in their input order, so every algorithm gives the same output.
+//! `--unique` keeps only the first of the lines with equal keys. `--stats` prints the
+//! comparisons, swaps and time of the sort to stderr.
+//!
+//! `merge` merges files that are each sorted by the same options into one, with a
+//! [`FileMerger`](pangua::external::FileMerger) reading them in about `--memory` bytes,
+//! 64 MiB by default. Each line's key is parsed once, as it is read, and lines with
+//! equal keys are ordered by their whole text as in a sort; with `--unique` the first
+//! of them, in the order of the files, is kept. `--binary` files hold records as a
+//! little-endian `u64` length followed by the bytes, rather than lines. The options
+//! only a sort has, `--algorithm`, `--header`, `--stable` and `--list`, are errors.
+
+use pangua::external::{FileMerger, Format};
+use pangua::*;
+use std::cmp::Ordering;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::process;
+use std::time::Instant;
+
+const USAGE: &str = "usage: pangua-sort [--algorithm NAME] [--column N [--delimiter C] \
+                     [--header]] [--numeric] [--reverse] [--stable] [--unique] [--stats] \
+                     [--list] [FILE]
+       pangua-sort merge [--column N [--delimiter C]] [--numeric] [--reverse] \
+                     [--unique] [--binary] [--memory BYTES] [--output FILE] FILE...";
+
+/// A line of input with the key it is sorted by.
+struct Line {
+    text: String,
+    key: Key,
+    index: usize,
+}
+
+/// What a line is ordered by: its text or a field of it, and the number that is, if
+/// ordering numerically.
+struct Key {
+    text: String,
+    number: Option<Total<f64>>,
+}
+
+type Compare<'a> = &'a mut dyn FnMut(&Line, &Line) -> Ordering;
+type SortFn = Box<dyn Fn(&mut [Line], Compare<'_>) -> SortStats>;
+
+/// A sorter the lines can be sorted with.
+struct Algorithm {
+    info: SorterInfo,
+    sort: SortFn,
+}
+
+fn algorithm<S: Sorter + 'static>(sorter: S) -> Algorithm {
+    Algorithm {
+        info: sorter.info(),
+        sort: Box::new(move |lines, compare| {
+            let counting = Counting::new(&sorter);
+            counting.sort_by(lines, |a, b| compare(a, b));
+            counting.stats()
+        }),
+    }
+}
+
+fn algorithms() -> Vec<Algorithm> {
+    vec![
+        algorithm(DefaultSorter),
+        algorithm(StdSorter),
+        algorithm(AutoSort::default()),
+        algorithm(QuickSort::default()),
+        algorithm(ThreeWayQuickSort),
+        algorithm(IntroSort::default()),
+        algorithm(PdqSort),
+        algorithm(HeapSort::default()),
+        algorithm(SmoothSort),
+        algorithm(MergeSort::default()),
+        algorithm(BottomUpMergeSort),
+        algorithm(NaturalMergeSort::default()),
+        algorithm(BlockSort),
+        algorithm(PatienceSort),
+        algorithm(StrandSort),
+        algorithm(LibrarySort),
+        algorithm(TreeSort { balanced: false }),
+        algorithm(TreeSort { balanced: true }),
+        algorithm(ShellSort::default()),
+        algorithm(CombSort::default()),
+        algorithm(InsertionSort { smart: true }),
+        algorithm(InsertionSort { smart: false }),
+        algorithm(SelectionSort),
+        algorithm(CycleSort),
+        algorithm(PancakeSort),
+        algorithm(BitonicSort),
+        algorithm(BubbleSort),
+        algorithm(CocktailShakerSort),
+        algorithm(GnomeSort),
+        algorithm(OddEvenSort),
+    ]
+}
+
+struct Options {
+    merge: bool,
+    algorithm: String,
+    column: Option<usize>,
+    delimiter: char,
+    header: bool,
+    numeric: bool,
+    reverse: bool,
+    stable: bool,
+    unique: bool,
+    stats: bool,
+    list: bool,
+    binary: bool,
+    memory: Option<usize>,
+    output: Option<String>,
+    paths: Vec<String>,
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+fn parse_options() -> Options {
+    let mut options = Options {
+        merge: false,
+        algorithm: "default".to_string(),
+        column: None,
+        delimiter: ',',
+        header: false,
+        numeric: false,
+        reverse: false,
+        stable: false,
+        unique: false,
+        stats: false,
+        list: false,
+        binary: false,
+        memory: None,
+        output: None,
+        paths: Vec::new(),
+    };
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("merge") {
+        args.next();
+        options.merge = true;
+    }
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--algorithm" | "--header" | "--stable" | "--list" if options.merge => {
+                eprintln!("pangua-sort: {} cannot be used with merge", arg);
+                process::exit(2);
+            }
+            "--algorithm" => options.algorithm = args.next().unwrap_or_else(|| usage()),
+            "--column" => match args.next().and_then(|v| v.parse::<usize>().ok()) {
+                Some(n) if n > 0 => options.column = Some(n - 1),
+                _ => usage(),
+            },
+            "--delimiter" => {
+                let value = args.next().unwrap_or_else(|| usage());
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => options.delimiter = c,
+                    _ => usage(),
+                }
+            }
+            "--header" => options.header = true,
+            "--numeric" => options.numeric = true,
+            "--reverse" => options.reverse = true,
+            "--stable" => options.stable = true,
+            "--unique" => options.unique = true,
+            "--stats" => options.stats = true,
+            "--list" => options.list = true,
+            "--binary" if options.merge => options.binary = true,
+            "--memory" if options.merge => {
+                match args.next().and_then(|v| v.parse::<usize>().ok()) {
+                    Some(bytes) => options.memory = Some(bytes),
+                    None => usage(),
+                }
+            }
+            "--output" if options.merge => {
+                options.output = Some(args.next().unwrap_or_else(|| usage()))
+            }
+            _ if arg.starts_with("--") => usage(),
+            _ => options.paths.push(arg),
+        }
+    }
+    let paths_allowed = if options.merge {
+        !options.paths.is_empty()
+    } else {
+        options.paths.len() <= 1
+    };
+    if !paths_allowed {
+        usage();
+    }
+    options
+}
+
+/// Field `column` of a CSV record, unquoted, or `""` if the record is shorter.
+fn field(record: &str, column: usize, delimiter: char) -> String {
+    let mut field = String::new();
+    let mut current = 0;
+    let mut quoted = false;
+    let mut chars = record.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                if current == column {
+                    field.push('"');
+                }
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => {
+                if current == column {
+                    break;
+                }
+                current += 1;
+            }
+            c if current == column => field.push(c),
+            _ => {}
+        }
+    }
+    field
+}
+
+fn key(text: &str, options: &Options) -> Key {
+    let text = match options.column {
+        Some(column) => field(text, column, options.delimiter),
+        None => text.to_string(),
+    };
+    let number = if options.numeric {
+        text.trim().parse::<f64>().ok().map(Total)
+    } else {
+        None
+    };
+    Key { text, number }
+}
+
+/// Orders keys, in reverse with `--reverse`.
+fn compare_keys(a: &Key, b: &Key, options: &Options) -> Ordering {
+    let order = if options.numeric {
+        a.number.cmp(&b.number).then_with(|| a.text.cmp(&b.text))
+    } else {
+        a.text.cmp(&b.text)
+    };
+    if options.reverse {
+        order.reverse()
+    } else {
+        order
+    }
+}
+
+/// Orders lines by key, then by input position with `--stable` and by their whole text
+/// otherwise, in reverse too with `--reverse`.
+fn compare_lines(a: &Line, b: &Line, options: &Options) -> Ordering {
+    compare_keys(&a.key, &b.key, options).then_with(|| {
+        if options.stable {
+            a.index.cmp(&b.index)
+        } else if options.reverse {
+            b.text.cmp(&a.text)
+        } else {
+            a.text.cmp(&b.text)
+        }
+    })
+}
+
+fn main() {
+    let options = parse_options();
+    if options.merge {
+        return merge(&options);
+    }
+    let algorithms = algorithms();
+    if options.list {
+        let listing: Vec<String> = algorithms
+            .iter()
+            .map(|a| {
+                let stable = if a.info.stable { "stable" } else { "unstable" };
+                format!(
+                    "{:16} {:8} average {:?}",
+                    a.info.name, stable, a.info.average
+                )
+            })
+            .collect();
+        print_lines(listing.iter().map(String::as_str));
+        return;
+    }
+    let algorithm = match algorithms.iter().find(|a| a.info.name == options.algorithm) {
+        Some(algorithm) => algorithm,
+        None => {
+            eprintln!(
+                "pangua-sort: unknown algorithm {:?}, see --list",
+                options.algorithm
+            );
+            process::exit(2);
+        }
+    };
+
+    let mut input = String::new();
+    let read = match options.paths.first() {
+        Some(path) => fs::File::open(path).and_then(|mut f| f.read_to_string(&mut input)),
+        None => io::stdin().read_to_string(&mut input),
+    };
+    if let Err(error) = read {
+        fail(error);
+    }
+
+    let mut texts = input.lines();
+    let header = if options.header { texts.next() } else { None };
+    let mut lines: Vec<Line> = texts
+        .enumerate()
+        .map(|(index, text)| Line {
+            text: text.to_string(),
+            key: key(text, &options),
+            index,
+        })
+        .collect();
+
+    let start = Instant::now();
+    let stats = (algorithm.sort)(&mut lines, &mut |a, b| compare_lines(a, b, &options));
+    let elapsed = start.elapsed();
+    if options.unique {
+        lines.dedup_by(|b, a| compare_keys(&a.key, &b.key, &options) == Ordering::Equal);
+    }
+    if options.stats {
+        eprintln!(
+            "{}: {} lines, {} comparisons, {} swaps, {} moves in {:.1?}",
+            algorithm.info.name,
+            lines.len(),
+            stats.comparisons,
+            stats.swaps,
+            stats.moves,
+            elapsed
+        );
+    }
+
+    print_lines(
+        header
+            .into_iter()
+            .chain(lines.iter().map(|line| line.text.as_str())),
+    );
+}
+
+/// Merges the files named on the command line, each sorted already.
+fn merge(options: &Options) {
+    let inputs = options
+        .paths
+        .iter()
+        .map(File::open)
+        .collect::<io::Result<Vec<_>>>();
+    let inputs = inputs.unwrap_or_else(|error| fail(error));
+    let output: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(File::create(path).unwrap_or_else(|error| fail(error))),
+        None => Box::new(io::stdout()),
+    };
+    let format = if options.binary {
+        Format::LengthPrefixed
+    } else {
+        Format::Lines
+    };
+    let mut merger = FileMerger {
+        unique: options.unique,
+        ..FileMerger::new(format)
+    };
+    if let Some(bytes) = options.memory {
+        merger.memory_budget = bytes;
+    }
+    let start = Instant::now();
+    let line = |record: &[u8]| {
+        let text = String::from_utf8_lossy(record).into_owned();
+        Line {
+            key: key(&text, options),
+            text,
+            index: 0,
+        }
+    };
+    let merged = merger.merge_by_cached_key(inputs, output, line, |a, b| {
+        if options.unique {
+            compare_keys(&a.key, &b.key, options)
+        } else {
+            compare_lines(a, b, options)
+        }
+    });
+    match merged {
+        Ok(records) if options.stats => eprintln!(
+            "merge: {} records from {} files in {:.1?}",
+            records,
+            options.paths.len(),
+            start.elapsed()
+        ),
+        Ok(_) => {}
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(error) => fail(error),
+    }
+}
+
+fn fail(error: io::Error) -> ! {
+    eprintln!("pangua-sort: {}", error);
+    process::exit(1);
+}
+
+/// Writes `lines` to stdout, stopping quietly if the reader goes away.
+fn print_lines<'a>(lines: impl IntoIterator<Item = &'a str>) {
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let written = lines
+        .into_iter()
+        .try_for_each(|line| writeln!(out, "{}", line))
+        .and_then(|()| out.flush());
+    if let Err(error) = written {
+        if error.kind() != io::ErrorKind::BrokenPipe {
+            fail(error);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::cmp::{self, Compare};
+use crate::{is_trivially_sorted, Complexity, Sorter, SorterInfo};
+
+/// Bitonic Sort
+///
+/// Bitonic sort sorts the two halves of the slice in opposite directions, which makes the
+/// whole slice bitonic (rising then falling), then merges it by compare-exchanging
+/// elements a fixed distance apart and recursing into both halves. Which elements get
+/// compared never depends on the data, so every compare-exchange at one level can run
+/// at once; that makes it the usual choice for GPU and SIMD sorting, at the cost of
+/// O(n log² n) comparisons whatever the input.
+///
+/// Lengths that are not a power of two are handled as if padded with elements greater
+/// than any other: such virtual sentinels would never move, so the compare-exchanges
+/// touching them are simply skipped, and no padding is allocated.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BitonicSort;
+
+impl BitonicSort {
+    /// Sorts the slice, ascending or descending.
+    fn sort<T, C: Compare<T>>(slice: &mut [T], ascending: bool, cmp: &mut C) {
+        if slice.len() < 2 {
+            return;
+        }
+        let mid = slice.len() / 2;
+        cmp.on_enter();
+        // halves sorted in opposite directions make the slice bitonic
+        Self::sort(&mut slice[..mid], !ascending, cmp);
+        Self::sort(&mut slice[mid..], ascending, cmp);
+        Self::merge(slice, ascending, cmp);
+        cmp.on_leave();
+    }
+
+    /// Sorts a bitonic slice, ascending or descending.
+    fn merge<T, C: Compare<T>>(slice: &mut [T], ascending: bool, cmp: &mut C) {
+        let len = slice.len();
+        if len < 2 {
+            return;
+        }
+        // the largest power of two below the length: the distance of the compare-exchanges
+        // in the slice padded up to twice that
+        let gap = 1 << (usize::BITS - 1 - (len - 1).leading_zeros());
+        for i in 0..len - gap {
+            let out_of_order = if ascending {
+                cmp.is_less(&slice[i + gap], &slice[i])
+            } else {
+                cmp.is_less(&slice[i], &slice[i + gap])
+            };
+            if out_of_order {
+                cmp::swap(cmp, slice, i, i + gap);
+            }
+        }
+        let (low, high) = slice.split_at_mut(gap);
+        Self::merge(low, ascending, cmp);
+        Self::merge(high, ascending, cmp);
+    }
+}
+
+impl Sorter for BitonicSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "bitonic",
+            stable: false,
+            in_place: true,
+            best: Complexity::LinearLogSquared,
+            average: Complexity::LinearLogSquared,
+            worst: Complexity::LinearLogSquared,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        Self::sort(slice, true, cmp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counting;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn bitonic_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        BitonicSort.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn sorts_every_binary_input() {
+        // by the 0-1 principle, sorting all 0-1 inputs proves the network for each length
+        for n in 0..=13 {
+            for bits in 0u32..1 << n {
+                let mut tings: Vec<_> = (0..n).map(|i| (bits >> i) & 1).collect();
+                BitonicSort.sort(&mut tings);
+                assert!(tings.windows(2).all(|w| w[0] <= w[1]), "n = {}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn comparisons_ignore_the_input() {
+        for &n in &[64u32, 100, 1000] {
+            let count = |tings: &mut Vec<u32>| {
+                let sorter = Counting::new(BitonicSort);
+                sorter.sort(tings);
+                sorter.stats().comparisons
+            };
+            let mut shuffled: Vec<_> = (0..n).map(|i| (i * 7919) % n).collect();
+            let mut reversed: Vec<_> = (0..n).rev().collect();
+            assert_eq!(count(&mut shuffled), count(&mut reversed));
+            assert_eq!(shuffled, reversed);
+        }
+        // a power of two makes the classic network, with n/4 · log n · (log n + 1) comparators
+        let sorter = Counting::new(BitonicSort);
+        sorter.sort(&mut (0..64).rev().collect::<Vec<_>>());
+        assert_eq!(sorter.stats().comparisons, 16 * 6 * 7);
+    }
+}
@@ -0,0 +1,398 @@
+use crate::cmp::{self, Compare};
+use crate::ops;
+use crate::{
+    is_trivially_sorted, Complexity, HeapSort, InsertionSort, MemoryPolicy, MergeSort, Sorter,
+    SorterInfo, StableSorter,
+};
+
+/// Runs this short are built with insertion sort before any merging.
+const RUN: usize = 8;
+
+/// Block Sort
+///
+/// Block sort (block merge sort) is a stable merge sort that needs only constant extra
+/// memory. It extracts about 2√n distinct elements from the input: half of them serve as
+/// an internal buffer that merges swap elements through, and half as tags recording
+/// which run each block of √n elements came from. Long runs are merged by ordering their
+/// blocks by first element and then merging neighbouring blocks locally through the
+/// buffer. Finally the extracted elements are sorted and merged back in.
+///
+/// This implementation follows the structure of GrailSort. It runs in O(n log n) when the
+/// input holds at least 2√n distinct values; with fewer, no buffer can be formed and
+/// merges fall back to rotations, for O(n log² n).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlockSort;
+
+impl Sorter for BlockSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "block",
+            stable: true,
+            in_place: true,
+            best: Complexity::Linearithmic,
+            average: Complexity::Linearithmic,
+            // without enough distinct values for the buffer
+            worst: Complexity::LinearLogSquared,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let n = slice.len();
+        if n <= 2 * RUN {
+            InsertionSort { smart: false }.sort_with(slice, cmp);
+            return;
+        }
+        // block length and buffer size: the smallest power of two with s * s >= n, so a
+        // merge never has more blocks than there are tags
+        let mut s = RUN;
+        while s * s < n {
+            s *= 2;
+        }
+        if collect_keys(slice, 2 * s, cmp) < 2 * s {
+            MergeSort {
+                memory: MemoryPolicy::InPlace,
+                ..MergeSort::default()
+            }
+            .sort_with(slice, cmp);
+            return;
+        }
+
+        // [ tags | buffer | data ]
+        let data = 2 * s;
+        for run in slice[data..].chunks_mut(RUN) {
+            InsertionSort { smart: false }.sort_with(run, cmp);
+        }
+        let mut width = RUN;
+        while width < n - data {
+            let mut buf = s;
+            while buf + s < n {
+                let start = buf + s;
+                let la = width.min(n - start);
+                let lb = width.min(n - start - la);
+                if lb == 0 {
+                    // a lone run only needs to move past the buffer
+                    shift_left(slice, start, start + la, s, cmp);
+                } else if width < s {
+                    merge_buffered(slice, buf, start, start + la, start + la + lb, cmp);
+                } else {
+                    merge_blocks(slice, s, buf, la, lb, cmp);
+                }
+                buf += la + lb;
+            }
+            // the buffer travelled to the end; bring it back in front of the data
+            rotate(slice, s, n, n - 2 * s, cmp);
+            width *= 2;
+        }
+
+        // the keys are distinct, so any sort restores their order; they are the first
+        // occurrences of their values, so they go before equal elements of the data
+        HeapSort::default().sort_with(&mut slice[..data], cmp);
+        merge_short_left(slice, data, cmp);
+    }
+}
+
+impl StableSorter for BlockSort {}
+
+/// Moves up to `want` distinct elements to the front of the slice, in sorted order,
+/// returning how many were found.
+///
+/// The first occurrence of each value is taken and the rest of the slice keeps its order,
+/// so equal elements stay in their original order.
+fn collect_keys<T, C: Compare<T>>(slice: &mut [T], want: usize, cmp: &mut C) -> usize {
+    // the keys are kept sorted in slice[h..h + k], rolled along behind the scan
+    let (mut h, mut k) = (0, 1);
+    for i in 1..slice.len() {
+        if k == want {
+            break;
+        }
+        let pos = lower_bound(&slice[h..h + k], &slice[i], cmp);
+        if pos < k && !cmp.is_less(&slice[i], &slice[h + pos]) {
+            continue;
+        }
+        rotate(slice, h, i, k, cmp);
+        h = i - k;
+        cmp::rotate_right(cmp, &mut slice[h + pos..=i], 1);
+        k += 1;
+    }
+    rotate(slice, 0, h + k, h, cmp);
+    k
+}
+
+/// Merges the runs `slice[q..x]` and `slice[x..e]` into `slice[buf..]`, where the buffer
+/// `slice[buf..q]` is at least as long as the second run. The buffer ends up at the end.
+fn merge_buffered<T, C: Compare<T>>(
+    slice: &mut [T],
+    buf: usize,
+    q: usize,
+    x: usize,
+    e: usize,
+    cmp: &mut C,
+) {
+    let s = q - buf;
+    let (i, j) = merge_left(slice, buf, q, x, e, true, cmp);
+    if i == x {
+        shift_left(slice, j, e, s, cmp);
+    } else {
+        shift_left(slice, i, x, s - (e - x), cmp);
+    }
+}
+
+/// Merges `slice[q..x]` and `slice[x..e]` through the buffer `slice[buf..q]`, writing from
+/// `buf` onwards, until either run is exhausted. The second run must not be longer than
+/// the buffer. Ties go to the first run if `first_wins`, else to the second.
+///
+/// Returns the positions reached in both runs. If the first run ran out, the buffer now
+/// sits right before the rest of the second; otherwise it is split around the rest of
+/// the first, with its tail in the space of the second run.
+fn merge_left<T, C: Compare<T>>(
+    slice: &mut [T],
+    buf: usize,
+    q: usize,
+    x: usize,
+    e: usize,
+    first_wins: bool,
+    cmp: &mut C,
+) -> (usize, usize) {
+    let (mut w, mut i, mut j) = (buf, q, x);
+    while i < x && j < e {
+        let take_first = if first_wins {
+            !cmp.is_less(&slice[j], &slice[i])
+        } else {
+            cmp.is_less(&slice[i], &slice[j])
+        };
+        if take_first {
+            cmp::swap(cmp, slice, w, i);
+            i += 1;
+        } else {
+            cmp::swap(cmp, slice, w, j);
+            j += 1;
+        }
+        w += 1;
+    }
+    (i, j)
+}
+
+/// Merges the runs `slice[start..start + la]` and the following `lb` elements, where the
+/// buffer of length `s` is `slice[buf..start]`. The buffer ends up at the end.
+///
+/// The runs are cut into blocks of length `s`, which are ordered by their first element
+/// (ties broken by the tags in `slice[..s]`, which also record which run each block came
+/// from), so every element is at most one block away from its final position. Blocks
+/// are then merged pairwise from left to right. A last block shorter than `s` is merged
+/// in separately.
+fn merge_blocks<T, C: Compare<T>>(
+    slice: &mut [T],
+    s: usize,
+    buf: usize,
+    la: usize,
+    lb: usize,
+    cmp: &mut C,
+) {
+    let start = buf + s;
+    let (p, q) = (la / s, lb / s);
+    let blocks = p + q;
+    let tail = lb - q * s;
+    let block = |k: usize| start + k * s;
+
+    // selection sort the blocks, tracking the tag of the first block of the second run
+    let mut mid = p;
+    for i in 0..blocks {
+        let mut min = i;
+        for j in i + 1..blocks {
+            let (fj, fm) = (block(j), block(min));
+            if cmp.is_less(&slice[fj], &slice[fm])
+                || (!cmp.is_less(&slice[fm], &slice[fj]) && cmp.is_less(&slice[j], &slice[min]))
+            {
+                min = j;
+            }
+        }
+        if min != i {
+            for k in 0..s {
+                cmp::swap(cmp, slice, block(i) + k, block(min) + k);
+            }
+            cmp::swap(cmp, slice, i, min);
+            if mid == i {
+                mid = min;
+            } else if mid == min {
+                mid = i;
+            }
+        }
+    }
+    let from_first =
+        |slice: &[T], k: usize, cmp: &mut C| q == 0 || cmp.is_less(&slice[k], &slice[mid]);
+
+    // [ merged | buffer | pending | next block | ... ]
+    let mut pending = start;
+    let mut pending_first = from_first(slice, 0, cmp);
+    for k in 1..blocks {
+        let (x, e) = (block(k), block(k) + s);
+        let next_first = from_first(slice, k, cmp);
+        if next_first == pending_first {
+            // nothing later belongs before the pending elements
+            shift_left(slice, pending, x, s, cmp);
+            pending = x;
+        } else {
+            let (i, j) = merge_left(slice, pending - s, pending, x, e, pending_first, cmp);
+            if i == x {
+                pending = j;
+                pending_first = next_first;
+            } else {
+                shift_right(slice, i, x, s, cmp);
+                pending = i + s;
+            }
+        }
+    }
+    let end = block(blocks);
+    shift_left(slice, pending, end, s, cmp);
+    if tail > 0 {
+        merge_tail(slice, buf, end - s, end, end + tail, cmp);
+    }
+
+    InsertionSort { smart: false }.sort_with(&mut slice[..blocks], cmp);
+}
+
+/// Merges the run `slice[lo..hole]` with the shorter run `slice[tail..end]`, where the
+/// buffer `slice[hole..tail]` is longer than the second run. The buffer ends up at the
+/// end.
+fn merge_tail<T, C: Compare<T>>(
+    slice: &mut [T],
+    lo: usize,
+    hole: usize,
+    tail: usize,
+    end: usize,
+    cmp: &mut C,
+) {
+    // fill [lo, hole + len) from the right; ties go to the tail, which came later
+    let (mut w, mut i, mut j) = (hole + (end - tail), hole, end);
+    while j > tail {
+        if i > lo && cmp.is_less(&slice[j - 1], &slice[i - 1]) {
+            cmp::swap(cmp, slice, w - 1, i - 1);
+            i -= 1;
+        } else {
+            cmp::swap(cmp, slice, w - 1, j - 1);
+            j -= 1;
+        }
+        w -= 1;
+    }
+}
+
+/// Merges the short sorted run `slice[..k]` into the sorted rest, placing each of its
+/// elements before equal elements of the rest.
+fn merge_short_left<T, C: Compare<T>>(slice: &mut [T], k: usize, cmp: &mut C) {
+    let (mut a, mut mid) = (0, k);
+    while a < mid && mid < slice.len() {
+        let p = lower_bound(&slice[mid..], &slice[a], cmp);
+        if p > 0 {
+            rotate(slice, a, mid + p, mid - a, cmp);
+            a += p;
+            mid += p;
+        }
+        a += 1;
+    }
+}
+
+/// Moves `slice[a..z]` left by `d` through swaps with the buffer elements before it.
+fn shift_left<T, C: Compare<T>>(slice: &mut [T], a: usize, z: usize, d: usize, cmp: &mut C) {
+    if d > 0 {
+        for k in a..z {
+            cmp::swap(cmp, slice, k - d, k);
+        }
+    }
+}
+
+/// Moves `slice[a..z]` right by `d` through swaps with the buffer elements after it.
+fn shift_right<T, C: Compare<T>>(slice: &mut [T], a: usize, z: usize, d: usize, cmp: &mut C) {
+    if d > 0 {
+        for k in (a..z).rev() {
+            cmp::swap(cmp, slice, k, k + d);
+        }
+    }
+}
+
+/// Rotates `slice[lo..hi]` so that the element at `lo + mid` comes first.
+fn rotate<T, C: Compare<T>>(slice: &mut [T], lo: usize, hi: usize, mid: usize, cmp: &mut C) {
+    if mid > 0 && lo + mid < hi {
+        let region = &mut slice[lo..hi];
+        ops::rotate(region, mid);
+        cmp.on_move(region);
+    }
+}
+
+/// Number of elements in the sorted `slice` that are less than `x`.
+fn lower_bound<T, C: Compare<T>>(slice: &[T], x: &T, cmp: &mut C) -> usize {
+    let (mut lo, mut hi) = (0, slice.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if cmp.is_less(&slice[mid], x) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crate::Counting;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn check(values: Vec<u32>) {
+        let mut tings: Vec<_> = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| (v, i))
+            .collect();
+        let mut expected = tings.clone();
+        expected.sort_by_key(|t| t.0);
+        BlockSort.sort_by_key(&mut tings, |t| t.0);
+        assert_eq!(tings, expected);
+    }
+
+    #[test]
+    fn block_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        BlockSort.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn is_stable_on_random_input() {
+        let mut rng = Rng::new(42);
+        for n in (0..300).chain([1000, 1023, 1025, 4097, 10_000]) {
+            for &distinct in &[2, 30, 1000, u32::MAX] {
+                check((0..n).map(|_| rng.next_u64() as u32 % distinct).collect());
+            }
+        }
+    }
+
+    #[test]
+    fn sorts_patterns() {
+        let n = 5000;
+        check((0..n).collect());
+        check((0..n).rev().collect());
+        check((0..n).map(|i| i % 100).collect());
+        check((0..n).map(|i| if i % 2 == 0 { i } else { n - i }).collect());
+    }
+
+    #[test]
+    fn is_linearithmic_with_enough_keys() {
+        let n = 1 << 14;
+        let sorter = Counting::new(BlockSort);
+        let mut rng = Rng::new(7);
+        let mut tings: Vec<_> = (0..n).map(|_| rng.next_u64()).collect();
+        sorter.sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        assert!(sorter.stats().comparisons < 2 * n * 14);
+        assert!(sorter.stats().swaps + sorter.stats().moves < 8 * n * 14);
+    }
+}
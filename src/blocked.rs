@@ -0,0 +1,160 @@
+use crate::cmp::Compare;
+use crate::ops;
+use crate::{
+    is_trivially_sorted, Complexity, IntroSort, MergeSort, Sorter, SorterInfo, StableSorter,
+};
+use alloc::vec::Vec;
+use core::mem;
+
+/// Bytes of elements in a block by default, about the size of a core's L2 cache.
+const BLOCK_BYTES: usize = 256 * 1024;
+
+/// Blocked Sort
+///
+/// Blocked sort is meant for slices far larger than the caches, such as memory-mapped
+/// files, where a quicksort's partitions keep sweeping the whole slice and thrash the
+/// TLB and page cache. It sorts blocks of `block_bytes` bytes one at a time with the
+/// `inner` sorter, each fitting in cache, then merges neighbouring runs bottom-up.
+/// Merges scan their runs sequentially, and runs too long to merge within a block's
+/// worth of scratch space are split into smaller merges by rotations first, so the
+/// working set stays bounded by the block size whatever the length of the slice.
+///
+/// The rotations cost an extra log factor on long merges. The sort is stable when the
+/// inner sorter is.
+#[derive(Debug, Clone, Copy)]
+pub struct BlockedSort<S = IntroSort> {
+    pub block_bytes: usize,
+    pub inner: S,
+}
+
+impl Default for BlockedSort {
+    fn default() -> Self {
+        BlockedSort::new(IntroSort::default())
+    }
+}
+
+impl<S: Sorter> BlockedSort<S> {
+    /// Creates a blocked sort over blocks of 256 KiB.
+    pub fn new(inner: S) -> Self {
+        BlockedSort {
+            block_bytes: BLOCK_BYTES,
+            inner,
+        }
+    }
+
+    /// Elements in a block of `T`, at least 2.
+    pub fn block_len<T>(&self) -> usize {
+        (self.block_bytes / mem::size_of::<T>().max(1)).max(2)
+    }
+}
+
+impl<S: Sorter> Sorter for BlockedSort<S> {
+    fn info(&self) -> SorterInfo {
+        let inner = self.inner.info();
+        SorterInfo {
+            name: "blocked",
+            stable: inner.stable,
+            in_place: inner.in_place,
+            best: inner.best,
+            average: Complexity::LinearLogSquared,
+            worst: Complexity::LinearLogSquared,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let block = self.block_len::<T>();
+        for chunk in slice.chunks_mut(block) {
+            self.inner.sort_with(chunk, cmp);
+        }
+        let mut scratch = Vec::with_capacity(block.min(slice.len()));
+        let mut width = block;
+        while width < slice.len() {
+            for run in slice.chunks_mut(2 * width) {
+                if run.len() > width {
+                    cmp.on_enter();
+                    merge(run, width, block, &mut scratch, cmp);
+                    cmp.on_leave();
+                }
+            }
+            width *= 2;
+        }
+    }
+}
+
+impl<S: StableSorter> StableSorter for BlockedSort<S> {}
+
+/// Merges `slice[..mid]` and `slice[mid..]` through at most `block` indices of scratch,
+/// splitting longer merges with rotations.
+fn merge<T, C: Compare<T>>(
+    slice: &mut [T],
+    mid: usize,
+    block: usize,
+    scratch: &mut Vec<usize>,
+    cmp: &mut C,
+) {
+    if mid == 0 || mid == slice.len() || !cmp.is_less(&slice[mid], &slice[mid - 1]) {
+        return;
+    }
+    if slice.len() <= block {
+        MergeSort::merge_buffered(slice, mid, scratch, cmp);
+        return;
+    }
+    let (split, left_mid, right_mid) = ops::split_merge(slice, mid, cmp);
+    let (left, right) = slice.split_at_mut(split);
+    merge(left, left_mid, block, scratch, cmp);
+    merge(right, right_mid, block, scratch, cmp);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn small_blocks<S: Sorter>(inner: S) -> BlockedSort<S> {
+        BlockedSort {
+            block_bytes: 64,
+            inner,
+        }
+    }
+
+    #[test]
+    fn blocked_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        BlockedSort::default().sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for n in [0u64, 1, 7, 8, 9, 100, 1000, 4097] {
+            let mut tings: Vec<u64> = (0..n).map(|i| (i * 7919) % (n / 3 + 1)).collect();
+            let mut expected = tings.clone();
+            expected.sort();
+            small_blocks(IntroSort::default()).sort(&mut tings);
+            assert_eq!(tings, expected, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn stable_with_a_stable_inner_sorter() {
+        let sorter = small_blocks(MergeSort::default());
+        assert!(sorter.info().stable);
+        let mut tings: Vec<(u8, u16)> = (0..2000u16).map(|i| ((i * 31 % 7) as u8, i)).collect();
+        sorter.sort_by_key(&mut tings, |t| t.0);
+        assert!(tings.windows(2).all(|w| w[0] < w[1]));
+        assert!(!small_blocks(IntroSort::default()).info().stable);
+    }
+
+    #[test]
+    fn merges_within_a_block_of_scratch() {
+        assert_eq!(small_blocks(IntroSort::default()).block_len::<u64>(), 8);
+        assert_eq!(small_blocks(IntroSort::default()).block_len::<()>(), 64);
+        let mut tings: Vec<u32> = (0..500).map(|i| 2 * i).chain(0..700).collect();
+        let mut scratch = Vec::new();
+        merge(&mut tings, 500, 16, &mut scratch, &mut crate::cmp::ByOrd);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        assert!(scratch.capacity() <= 16);
+    }
+}
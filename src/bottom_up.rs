@@ -0,0 +1,92 @@
+use crate::cmp::Compare;
+use crate::{is_trivially_sorted, Complexity, MergeSort, Sorter, SorterInfo, StableSorter};
+use alloc::vec::Vec;
+
+/// Bottom-Up Merge Sort
+///
+/// Bottom-up merge sort treats the list as n sublists of width 1, then iteratively merges
+/// neighbouring sublists of width 1, 2, 4, ... until a single sorted list remains. It
+/// performs the same merges as top-down merge sort, in a different order, without any
+/// recursion.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BottomUpMergeSort;
+
+impl Sorter for BottomUpMergeSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "merge-bottom-up",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        self.sort_with_scratch(slice, &mut Vec::new(), cmp)
+    }
+
+    fn sort_with_scratch<T, C>(&self, slice: &mut [T], scratch: &mut Vec<usize>, cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let n = slice.len();
+        scratch.clear();
+        scratch.reserve(n);
+        let mut width = 1;
+        while width < n {
+            // [ run | run ] [ run | run ] ... [ run | partial run ]
+            for start in (0..n - width).step_by(2 * width) {
+                let end = (start + 2 * width).min(n);
+                MergeSort::merge_buffered(&mut slice[start..end], width, scratch, cmp);
+            }
+            width *= 2;
+        }
+    }
+}
+
+impl StableSorter for BottomUpMergeSort {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counting;
+    use alloc::vec;
+
+    #[test]
+    fn bottom_up_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        BottomUpMergeSort.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for n in 0..40 {
+            let mut tings: Vec<_> = (0..n).map(|i| (i * 7919) % 17).collect();
+            BottomUpMergeSort.sort(&mut tings);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn bottom_up_is_stable() {
+        let mut tings: Vec<_> = (0..200).map(|i| ((i * 37) % 11, i)).collect();
+        BottomUpMergeSort.sort_by_key(&mut tings, |t| t.0);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn does_not_recurse() {
+        let sorter = Counting::new(BottomUpMergeSort);
+        let mut tings: Vec<_> = (0..1000).rev().collect();
+        sorter.sort(&mut tings);
+        assert_eq!(sorter.stats().max_depth, 0);
+        let sorter = Counting::new(BottomUpMergeSort);
+        sorter.sort(&mut tings);
+        assert!(sorter.stats().comparisons < 1000);
+    }
+}
@@ -0,0 +1,365 @@
+//! Cancellation: sorts that give up at a deadline or when asked to, or that hand over
+//! to heapsort once they take too long.
+
+use crate::cmp::{ByFn, ByOrd, Compare};
+use crate::{Error, HeapSort, Sorter, SorterInfo};
+use std::cmp::Ordering;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Comparisons between two checks of the deadline and the cancel flag.
+const CHECK_INTERVAL: usize = 256;
+
+/// Wraps a sorter so its sorts can be aborted, at a deadline or by setting a flag.
+///
+/// The deadline and the flag are checked before the sort starts and then every few
+/// hundred comparisons from inside the inner sorter, which is aborted by unwinding out
+/// of it; this needs the default `panic = "unwind"`. Sorters only ever swap and move
+/// the elements of the slice, so an aborted sort leaves it a permutation of what it
+/// held, partly sorted. Sorters that do not compare, such as radix sort, are only
+/// checked before they start.
+///
+/// ```
+/// use pangua::{BoundedSorter, Error, IntroSort};
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+///
+/// let cancel = Arc::new(AtomicBool::new(false));
+/// let sorter = BoundedSorter {
+///     cancel: Some(Arc::clone(&cancel)),
+///     ..BoundedSorter::new(IntroSort::default())
+/// };
+/// let mut tings = vec![3, 1, 2];
+/// assert_eq!(sorter.try_sort(&mut tings), Ok(()));
+/// cancel.store(true, Ordering::Relaxed);
+/// assert_eq!(sorter.try_sort(&mut tings), Err(Error::Cancelled));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BoundedSorter<S> {
+    pub inner: S,
+    /// Sorts still running at this instant are aborted with [`Error::DeadlineExceeded`].
+    pub deadline: Option<Instant>,
+    /// Sorts are aborted with [`Error::Cancelled`] once this is set.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+/// The payload unwound out of an aborted sort.
+struct Abort(Error);
+
+impl<S: Sorter> BoundedSorter<S> {
+    /// A sorter with neither a deadline nor a cancel flag, which never aborts.
+    pub fn new(inner: S) -> Self {
+        BoundedSorter {
+            inner,
+            deadline: None,
+            cancel: None,
+        }
+    }
+
+    pub fn try_sort<T: Ord>(&self, slice: &mut [T]) -> Result<(), Error> {
+        self.try_sort_with(slice, &mut ByOrd)
+    }
+
+    pub fn try_sort_by<T, F>(&self, slice: &mut [T], compare: F) -> Result<(), Error>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.try_sort_with(slice, &mut ByFn(compare))
+    }
+
+    /// Sorts with the inner sorter unless aborted first, returning why if it was.
+    pub fn try_sort_with<T, C: Compare<T>>(
+        &self,
+        slice: &mut [T],
+        cmp: &mut C,
+    ) -> Result<(), Error> {
+        self.check()?;
+        let mut watch = Watch {
+            inner: cmp,
+            sorter: self,
+            until_check: CHECK_INTERVAL,
+        };
+        let sorted =
+            panic::catch_unwind(AssertUnwindSafe(|| self.inner.sort_with(slice, &mut watch)));
+        match sorted {
+            Ok(()) => Ok(()),
+            Err(payload) => match payload.downcast::<Abort>() {
+                Ok(abort) => Err(abort.0),
+                Err(payload) => panic::resume_unwind(payload),
+            },
+        }
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if let Some(cancel) = &self.cancel {
+            if cancel.load(atomic::Ordering::Relaxed) {
+                return Err(Error::Cancelled);
+            }
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(Error::DeadlineExceeded),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Forwards to `inner`, aborting the sort once the sorter's limits are reached.
+struct Watch<'s, S, C> {
+    inner: C,
+    sorter: &'s BoundedSorter<S>,
+    until_check: usize,
+}
+
+impl<S: Sorter, C> Watch<'_, S, C> {
+    fn tick(&mut self) {
+        self.until_check -= 1;
+        if self.until_check == 0 {
+            self.until_check = CHECK_INTERVAL;
+            if let Err(e) = self.sorter.check() {
+                // resume_unwind skips the panic hook: an abort is not a crash to report
+                panic::resume_unwind(Box::new(Abort(e)));
+            }
+        }
+    }
+}
+
+impl<T, S: Sorter, C: Compare<T>> Compare<T> for Watch<'_, S, C> {
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        self.tick();
+        self.inner.compare(a, b)
+    }
+    fn is_less(&mut self, a: &T, b: &T) -> bool {
+        self.tick();
+        self.inner.is_less(a, b)
+    }
+    fn on_swap(&mut self, a: &T, b: &T) {
+        self.inner.on_swap(a, b)
+    }
+    fn on_move(&mut self, region: &[T]) {
+        self.inner.on_move(region)
+    }
+    fn on_pivot(&mut self, pivot: &T) {
+        self.inner.on_pivot(pivot)
+    }
+    fn on_partition(&mut self, region: &[T], mid: usize) {
+        self.inner.on_partition(region, mid)
+    }
+    fn on_enter(&mut self) {
+        self.inner.on_enter()
+    }
+    fn on_leave(&mut self) {
+        self.inner.on_leave()
+    }
+    fn on_fallback(&mut self, to: &'static str) {
+        self.inner.on_fallback(to)
+    }
+}
+
+/// Comparisons allowed per element and per level of a balanced sort by default.
+const GUARD_FACTOR: usize = 4;
+
+/// Wraps a sorter so that a sort taking more than `factor * n * log2(n)` comparisons is
+/// aborted and redone with [`HeapSort`], guarding against quadratic behaviour on
+/// adversarial input.
+///
+/// The inner sorter is aborted by unwinding out of it, as [`BoundedSorter`] aborts
+/// sorts, and heapsort then sorts the permutation it left. This needs the default
+/// `panic = "unwind"`: with `panic = "abort"`, a sort over budget takes the process
+/// down with it instead of falling back. The fallback is reported
+/// through [`Compare::on_fallback`], so it shows in [`SortStats::fallbacks`] and in
+/// [`telemetry`](crate::telemetry). The comparisons of the abandoned attempt are
+/// wasted: at most the budget, so a guarded sort is O(n log n) whatever the inner
+/// sorter.
+///
+/// [`SortStats::fallbacks`]: crate::SortStats::fallbacks
+///
+/// ```
+/// use pangua::{BubbleSort, Counting, Guarded, Sorter};
+///
+/// let sorter = Counting::new(Guarded::new(BubbleSort));
+/// let mut tings: Vec<u32> = (0..1000).rev().collect();
+/// sorter.sort(&mut tings);
+/// assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+/// assert_eq!(sorter.stats().fallbacks, 1);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Guarded<S> {
+    pub inner: S,
+    /// The budget, in comparisons per element and level; 4 by default.
+    pub factor: usize,
+}
+
+/// The payload unwound out of a sort over its budget.
+struct OverBudget;
+
+impl<S: Sorter> Guarded<S> {
+    pub fn new(inner: S) -> Self {
+        Guarded {
+            inner,
+            factor: GUARD_FACTOR,
+        }
+    }
+
+    /// The comparisons allowed for sorting `n` elements before falling back.
+    pub fn budget(&self, n: usize) -> usize {
+        let log2 = (usize::BITS - n.leading_zeros()) as usize;
+        self.factor.saturating_mul(n).saturating_mul(log2.max(1))
+    }
+}
+
+impl<S: Sorter> Sorter for Guarded<S> {
+    fn info(&self) -> SorterInfo {
+        let inner = self.inner.info();
+        SorterInfo {
+            name: "guarded",
+            // the heapsort fallback is not stable
+            stable: false,
+            worst: inner.worst.min(HeapSort::default().info().worst),
+            ..inner
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        let mut guard = Guard {
+            inner: &mut *cmp,
+            left: self.budget(slice.len()),
+        };
+        let sorted =
+            panic::catch_unwind(AssertUnwindSafe(|| self.inner.sort_with(slice, &mut guard)));
+        if let Err(payload) = sorted {
+            if !payload.is::<OverBudget>() {
+                panic::resume_unwind(payload);
+            }
+            let heap = HeapSort::default();
+            cmp.on_fallback(heap.info().name);
+            heap.sort_with(slice, cmp);
+        }
+    }
+}
+
+/// Forwards to `inner`, aborting the sort once `left` comparisons have been made.
+struct Guard<C> {
+    inner: C,
+    left: usize,
+}
+
+impl<C> Guard<C> {
+    fn tick(&mut self) {
+        match self.left.checked_sub(1) {
+            Some(left) => self.left = left,
+            None => panic::resume_unwind(Box::new(OverBudget)),
+        }
+    }
+}
+
+impl<T, C: Compare<T>> Compare<T> for Guard<C> {
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        self.tick();
+        self.inner.compare(a, b)
+    }
+    fn is_less(&mut self, a: &T, b: &T) -> bool {
+        self.tick();
+        self.inner.is_less(a, b)
+    }
+    fn on_swap(&mut self, a: &T, b: &T) {
+        self.inner.on_swap(a, b)
+    }
+    fn on_move(&mut self, region: &[T]) {
+        self.inner.on_move(region)
+    }
+    fn on_pivot(&mut self, pivot: &T) {
+        self.inner.on_pivot(pivot)
+    }
+    fn on_partition(&mut self, region: &[T], mid: usize) {
+        self.inner.on_partition(region, mid)
+    }
+    fn on_enter(&mut self) {
+        self.inner.on_enter()
+    }
+    fn on_leave(&mut self) {
+        self.inner.on_leave()
+    }
+    fn on_fallback(&mut self, to: &'static str) {
+        self.inner.on_fallback(to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use std::time::Duration;
+
+    #[test]
+    fn aborts_when_cancelled_mid_sort() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let sorter = BoundedSorter {
+            cancel: Some(Arc::clone(&cancel)),
+            ..BoundedSorter::new(InsertionSort { smart: false })
+        };
+        let mut tings: Vec<u32> = (0..2000).rev().collect();
+        let mut comparisons = 0;
+        let result = sorter.try_sort_by(&mut tings, |a, b| {
+            comparisons += 1;
+            if comparisons == 10_000 {
+                cancel.store(true, atomic::Ordering::Relaxed);
+            }
+            a.cmp(b)
+        });
+        assert_eq!(result, Err(Error::Cancelled));
+        assert!(comparisons < 10_000 + CHECK_INTERVAL);
+        let mut permutation = tings.clone();
+        permutation.sort_unstable();
+        assert_eq!(permutation, (0..2000).collect::<Vec<_>>());
+        assert_ne!(tings, permutation);
+    }
+
+    #[test]
+    fn checks_the_deadline() {
+        let mut tings = vec![2, 1];
+        let past = BoundedSorter {
+            deadline: Some(Instant::now()),
+            ..BoundedSorter::new(MergeSort::default())
+        };
+        assert_eq!(past.try_sort(&mut tings), Err(Error::DeadlineExceeded));
+        assert_eq!(tings, [2, 1]);
+        let future = BoundedSorter {
+            deadline: Some(Instant::now() + Duration::from_secs(3600)),
+            ..past
+        };
+        assert_eq!(future.try_sort(&mut tings), Ok(()));
+        assert_eq!(tings, [1, 2]);
+    }
+
+    #[test]
+    fn guard_falls_back_on_quadratic_work() {
+        let n = 2000;
+        let input = generators::median_of_three_killer(n);
+        let guarded = Counting::new(Guarded::new(QuickSort::default()));
+        let mut tings = input.clone();
+        guarded.sort(&mut tings);
+        assert_eq!(tings, (0..n).collect::<Vec<_>>());
+        let stats = guarded.stats();
+        assert_eq!(stats.fallbacks, 1);
+        // the abandoned attempt, then heapsort
+        assert!(stats.comparisons < 2 * guarded.into_inner().budget(n));
+
+        // within budget, the inner sorter runs alone
+        let guarded = Counting::new(Guarded::new(QuickSort::default()));
+        guarded.sort(&mut generators::shuffled(n, 1));
+        assert_eq!(guarded.stats().fallbacks, 0);
+        assert_eq!(guarded.info().worst, Complexity::Linearithmic);
+    }
+
+    #[test]
+    #[should_panic(expected = "comparator broke")]
+    fn other_panics_propagate() {
+        let sorter = BoundedSorter::new(HeapSort::default());
+        let _ = sorter.try_sort_by(&mut [2, 1], |_, _| panic!("comparator broke"));
+    }
+}
@@ -0,0 +1,296 @@
+use crate::cmp::{ByFn, ByOrd, Compare};
+use crate::counting::counting_order;
+use crate::permutation;
+use crate::rng::Rng;
+use crate::{is_trivially_sorted, Complexity, Error, Sorter, SorterInfo};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Elements sampled per bucket to place the splitters of [`BucketSort::sort_by_quantiles`].
+const SAMPLE_PER_BUCKET: usize = 16;
+
+/// Elements with a numeric position on the number line, consistent with their order:
+/// `a < b` implies `a.to_ordinal() <= b.to_ordinal()`.
+pub trait ToOrdinal {
+    fn to_ordinal(&self) -> f64;
+}
+
+macro_rules! impl_to_ordinal {
+    ($($t:ty),*) => {
+        $(
+            impl ToOrdinal for $t {
+                #[inline]
+                fn to_ordinal(&self) -> f64 {
+                    *self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_to_ordinal!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl ToOrdinal for char {
+    fn to_ordinal(&self) -> f64 {
+        *self as u32 as f64
+    }
+}
+
+/// Bucket Sort
+///
+/// Bucket sort distributes the elements into a number of buckets according to a numeric
+/// projection of each element, sorts every bucket individually with another sorter, and
+/// concatenates the buckets. When the projected values are spread evenly, buckets stay
+/// small and the whole sort runs in linear time on average.
+///
+/// The projection must be monotone: `a < b` implies `project(a) <= project(b)`.
+/// Buckets span equal ranges between the smallest and largest projected value.
+pub struct BucketSort<S> {
+    pub buckets: usize,
+    pub inner: S,
+}
+
+impl<S: Sorter> BucketSort<S> {
+    /// Creates a bucket sorter with `buckets` buckets, which must be positive, each
+    /// sorted by `inner`.
+    pub fn new(buckets: usize, inner: S) -> Result<Self, Error> {
+        let sorter = BucketSort { buckets, inner };
+        sorter.validate()?;
+        Ok(sorter)
+    }
+
+    /// Checks the configuration, as the sort itself would before starting.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.buckets == 0 {
+            return Err(Error::ZeroBuckets);
+        }
+        Ok(())
+    }
+
+    pub fn info(&self) -> SorterInfo {
+        let inner = self.inner.info();
+        SorterInfo {
+            name: "bucket",
+            // distribution keeps equal elements in order
+            stable: inner.stable,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linear,
+            worst: inner.worst,
+        }
+    }
+
+    pub fn sort<T, P>(&self, slice: &mut [T], project: P)
+    where
+        T: Ord,
+        P: FnMut(&T) -> f64,
+    {
+        self.sort_with(slice, project, &mut ByOrd)
+    }
+
+    pub fn sort_by<T, P, F>(&self, slice: &mut [T], project: P, compare: F)
+    where
+        P: FnMut(&T) -> f64,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.sort_with(slice, project, &mut ByFn(compare))
+    }
+
+    pub fn sort_with<T, P, C>(&self, slice: &mut [T], project: P, cmp: &mut C)
+    where
+        P: FnMut(&T) -> f64,
+        C: Compare<T>,
+    {
+        if let Err(e) = self.validate() {
+            panic!("{}", e)
+        }
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let values: Vec<f64> = slice.iter().map(project).collect();
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let scale = if max > min {
+            self.buckets as f64 / (max - min)
+        } else {
+            0.0
+        };
+        let keys: Vec<usize> = values
+            .iter()
+            .map(|v| (((v - min) * scale) as usize).min(self.buckets - 1))
+            .collect();
+
+        let mut sizes = vec![0usize; self.buckets];
+        for &k in &keys {
+            sizes[k] += 1;
+        }
+        let mut order = counting_order(&keys);
+        permutation::apply(slice, &mut order);
+
+        let mut start = 0;
+        for size in sizes {
+            self.inner.sort_with(&mut slice[start..start + size], cmp);
+            start += size;
+        }
+    }
+
+    /// Sorts without a user projection, choosing bucket boundaries from the data.
+    ///
+    /// Ordinals of a random sample of the elements are sorted, and each element is
+    /// projected to its interpolated rank within the sample, an estimate of the
+    /// fraction of elements below it. Buckets of equal ranges of that estimate hold
+    /// about as many elements each, however skewed the values are.
+    pub fn sort_by_quantiles<T: Ord + ToOrdinal>(&self, slice: &mut [T]) {
+        if let Err(e) = self.validate() {
+            panic!("{}", e)
+        }
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut rng = Rng::new(slice.len() as u64);
+        let samples = (self.buckets * SAMPLE_PER_BUCKET).min(slice.len());
+        let mut sample: Vec<f64> = (0..samples)
+            .map(|_| slice[rng.below(slice.len())].to_ordinal())
+            .collect();
+        sample.sort_by(f64::total_cmp);
+        self.sort(slice, |x| interpolated_rank(&sample, x.to_ordinal()))
+    }
+}
+
+/// The rank of `x` among the sorted `sample`, interpolated linearly between the sampled
+/// values around it. Monotone in `x`.
+fn interpolated_rank(sample: &[f64], x: f64) -> f64 {
+    let above = sample.partition_point(|&s| s <= x);
+    if above == 0 || above == sample.len() {
+        return above as f64;
+    }
+    let (lo, hi) = (sample[above - 1], sample[above]);
+    (above - 1) as f64 + (x - lo) / (hi - lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn bucket_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        BucketSort {
+            buckets: 3,
+            inner: InsertionSort { smart: false },
+        }
+        .sort(&mut tings, |&x| x as f64);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn sorts_floats() {
+        let mut tings: Vec<f64> = (0..1000)
+            .map(|i| ((i * 7919) % 1000) as f64 / 1000.0)
+            .collect();
+        let sorter = BucketSort {
+            buckets: 100,
+            inner: InsertionSort { smart: false },
+        };
+        sorter.sort_by(&mut tings, |&x| x, |a, b| a.partial_cmp(b).unwrap());
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn handles_equal_projections() {
+        let mut tings = vec![3, 2, 1];
+        BucketSort {
+            buckets: 8,
+            inner: MergeSort::default(),
+        }
+        .sort(&mut tings, |_| 1.0);
+        assert_eq!(tings, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn is_stable_with_stable_inner() {
+        let mut tings = vec![(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e')];
+        let sorter = BucketSort {
+            buckets: 2,
+            inner: MergeSort::default(),
+        };
+        assert!(sorter.info().stable);
+        sorter.sort_by(&mut tings, |t| t.0 as f64, |a, b| a.0.cmp(&b.0));
+        assert_eq!(tings, &[(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c')]);
+    }
+
+    #[test]
+    fn quantiles_sort_skewed_data() {
+        // exponentially spread values would crowd into the first equal-range bucket
+        let mut tings: Vec<u64> = (0..5000u64).map(|i| 1 << ((i * 7919) % 60)).collect();
+        let mut expected = tings.clone();
+        expected.sort();
+        let sorter = BucketSort {
+            buckets: 64,
+            inner: Counting::new(InsertionSort { smart: false }),
+        };
+        sorter.sort_by_quantiles(&mut tings);
+        assert_eq!(tings, expected);
+
+        let mut tings: Vec<i64> = (0..2000).map(|i| (i * i * 7919) % 1000 - 500).collect();
+        let mut expected = tings.clone();
+        expected.sort();
+        BucketSort::new(10, IntroSort::default())
+            .unwrap()
+            .sort_by_quantiles(&mut tings);
+        assert_eq!(tings, expected);
+    }
+
+    #[test]
+    fn quantile_buckets_are_balanced() {
+        /// Records the longest slice it was asked to sort.
+        struct Longest(core::cell::Cell<usize>);
+
+        impl Sorter for Longest {
+            fn info(&self) -> SorterInfo {
+                HeapSort::default().info()
+            }
+
+            fn sort_with<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) {
+                self.0.set(self.0.get().max(slice.len()));
+                HeapSort::default().sort_with(slice, cmp)
+            }
+        }
+
+        // squares crowd into the low end of their range
+        let mut tings: Vec<u64> = (0..4096u64).map(|i| ((i * 7919) % 4096).pow(2)).collect();
+        let sorter = BucketSort::new(64, Longest(Default::default())).unwrap();
+        sorter.sort_by_quantiles(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        // 64 elements per bucket on average
+        assert!(sorter.inner.0.get() < 4 * 64);
+
+        let sorter = BucketSort::new(64, Longest(Default::default())).unwrap();
+        sorter.sort(&mut tings, |&x| x as f64);
+        assert!(sorter.inner.0.get() >= 8 * 64);
+    }
+
+    #[test]
+    fn interpolated_rank_is_monotone() {
+        let sample = [1.0, 2.0, 2.0, 4.0];
+        assert_eq!(interpolated_rank(&sample, 0.0), 0.0);
+        assert_eq!(interpolated_rank(&sample, 1.0), 0.0);
+        assert_eq!(interpolated_rank(&sample, 2.0), 2.0);
+        assert_eq!(interpolated_rank(&sample, 3.0), 2.5);
+        assert_eq!(interpolated_rank(&sample, 9.0), 4.0);
+    }
+
+    #[test]
+    fn new_rejects_zero_buckets() {
+        assert_eq!(
+            BucketSort::new(0, IntroSort::default())
+                .err()
+                .map(|e| e.to_string()),
+            Some("bucket count must be positive".to_string())
+        );
+        assert!(BucketSort::new(4, IntroSort::default()).is_ok());
+    }
+}
@@ -0,0 +1,504 @@
+//! Comparison utilities shared by the sorters.
+//!
+//! Every sorter compares elements through a [`Compare`] implementation rather than
+//! calling `Ord` directly. Besides choosing the ordering, a comparator is told about the
+//! work the sorter performs (swaps, moves, recursion), which is how the instrumented
+//! wrappers observe an algorithm without changing it.
+
+use core::cmp::Ordering;
+use core::ops::Deref;
+
+pub mod branchless;
+pub mod natural;
+
+/// An ordering over `T`, plus hooks observing the operations of a sort.
+///
+/// The hooks default to doing nothing, so a plain comparator only needs `compare`.
+pub trait Compare<T: ?Sized> {
+    fn compare(&mut self, a: &T, b: &T) -> Ordering;
+
+    fn is_less(&mut self, a: &T, b: &T) -> bool {
+        self.compare(a, b) == Ordering::Less
+    }
+
+    /// Called right before `a` and `b` are swapped.
+    fn on_swap(&mut self, _a: &T, _b: &T) {}
+
+    /// Called after the elements of `region` were moved into their new positions.
+    fn on_move(&mut self, _region: &[T])
+    where
+        T: Sized,
+    {
+    }
+
+    /// Called when `pivot` is chosen to partition around.
+    fn on_pivot(&mut self, _pivot: &T) {}
+
+    /// Called after `region` was partitioned, with the pivot ending up at `region[mid]`.
+    fn on_partition(&mut self, _region: &[T], _mid: usize)
+    where
+        T: Sized,
+    {
+    }
+
+    /// Called when the sorter descends one level of recursion.
+    fn on_enter(&mut self) {}
+
+    /// Called when the sorter returns from one level of recursion.
+    fn on_leave(&mut self) {}
+
+    /// Called when the sorter gives up on its own strategy and hands what is left to the
+    /// algorithm named `to`, as introsort does to heapsort once quicksort recurses too deep.
+    fn on_fallback(&mut self, _to: &'static str) {}
+}
+
+impl<T: ?Sized, C: Compare<T> + ?Sized> Compare<T> for &mut C {
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        (**self).compare(a, b)
+    }
+    fn is_less(&mut self, a: &T, b: &T) -> bool {
+        (**self).is_less(a, b)
+    }
+    fn on_swap(&mut self, a: &T, b: &T) {
+        (**self).on_swap(a, b)
+    }
+    fn on_move(&mut self, region: &[T])
+    where
+        T: Sized,
+    {
+        (**self).on_move(region)
+    }
+    fn on_pivot(&mut self, pivot: &T) {
+        (**self).on_pivot(pivot)
+    }
+    fn on_partition(&mut self, region: &[T], mid: usize)
+    where
+        T: Sized,
+    {
+        (**self).on_partition(region, mid)
+    }
+    fn on_enter(&mut self) {
+        (**self).on_enter()
+    }
+    fn on_leave(&mut self) {
+        (**self).on_leave()
+    }
+    fn on_fallback(&mut self, to: &'static str) {
+        (**self).on_fallback(to)
+    }
+}
+
+/// Compares elements by their `Ord` implementation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByOrd;
+
+impl<T: Ord + ?Sized> Compare<T> for ByOrd {
+    #[inline]
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+    #[inline]
+    fn is_less(&mut self, a: &T, b: &T) -> bool {
+        a < b
+    }
+}
+
+/// Compares elements with a closure.
+#[derive(Debug, Clone, Copy)]
+pub struct ByFn<F>(pub F);
+
+impl<T: ?Sized, F> Compare<T> for ByFn<F>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    #[inline]
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        (self.0)(a, b)
+    }
+}
+
+/// Reverses the ordering of the inner comparator, forwarding its hooks.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reversed<C>(pub C);
+
+impl<T: ?Sized, C: Compare<T>> Compare<T> for Reversed<C> {
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        self.0.compare(b, a)
+    }
+    fn is_less(&mut self, a: &T, b: &T) -> bool {
+        self.0.is_less(b, a)
+    }
+    fn on_swap(&mut self, a: &T, b: &T) {
+        self.0.on_swap(a, b)
+    }
+    fn on_move(&mut self, region: &[T])
+    where
+        T: Sized,
+    {
+        self.0.on_move(region)
+    }
+    fn on_pivot(&mut self, pivot: &T) {
+        self.0.on_pivot(pivot)
+    }
+    fn on_partition(&mut self, region: &[T], mid: usize)
+    where
+        T: Sized,
+    {
+        self.0.on_partition(region, mid)
+    }
+    fn on_enter(&mut self) {
+        self.0.on_enter()
+    }
+    fn on_leave(&mut self) {
+        self.0.on_leave()
+    }
+    fn on_fallback(&mut self, to: &'static str) {
+        self.0.on_fallback(to)
+    }
+}
+
+/// Compares pointers — `Box<T>`, `Rc<T>`, `Arc<T>`, `&T` or any other [`Deref`] — by
+/// their targets, with the inner comparator.
+///
+/// The targets may be unsized, such as trait objects or `str`. The per-element hooks
+/// are forwarded with the targets; `on_move` and `on_partition` are not, as the targets
+/// of a region of pointers are not a slice.
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use pangua::cmp::{by_key, ByDeref, CompareExt};
+/// use pangua::{MergeSort, Sorter};
+///
+/// let mut tings: Vec<Box<str>> = vec!["pear".into(), "fig".into(), "apple".into()];
+/// let mut order = ByDeref(by_key(str::len).reversed());
+/// MergeSort::default().sort_with(&mut tings, &mut order);
+/// assert_eq!(tings, ["apple".into(), "pear".into(), "fig".into()]);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ByDeref<C = ByOrd>(pub C);
+
+impl<P, C> Compare<P> for ByDeref<C>
+where
+    P: Deref,
+    C: Compare<P::Target>,
+{
+    #[inline]
+    fn compare(&mut self, a: &P, b: &P) -> Ordering {
+        self.0.compare(a, b)
+    }
+    #[inline]
+    fn is_less(&mut self, a: &P, b: &P) -> bool {
+        self.0.is_less(a, b)
+    }
+    fn on_swap(&mut self, a: &P, b: &P) {
+        self.0.on_swap(a, b)
+    }
+    fn on_pivot(&mut self, pivot: &P) {
+        self.0.on_pivot(pivot)
+    }
+    fn on_enter(&mut self) {
+        self.0.on_enter()
+    }
+    fn on_leave(&mut self) {
+        self.0.on_leave()
+    }
+    fn on_fallback(&mut self, to: &'static str) {
+        self.0.on_fallback(to)
+    }
+}
+
+/// Compares elements by a key extracted with `key`, ordering keys with `cmp`.
+///
+/// Built by [`by_key`] and [`by_key_desc`], and chained into a multi-field ordering
+/// with [`CompareExt`].
+#[derive(Debug, Clone, Copy)]
+pub struct ByKey<F, C = ByOrd> {
+    key: F,
+    cmp: C,
+}
+
+/// Orders elements by the key `key` extracts, ascending.
+///
+/// Together with [`CompareExt`] this builds an `ORDER BY` of several fields:
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use pangua::cmp::{by_key, CompareExt};
+/// use pangua::{MergeSort, Sorter};
+///
+/// struct Player {
+///     team: &'static str,
+///     score: u32,
+///     rank: Option<u8>,
+/// }
+///
+/// let mut tings = vec![
+///     Player { team: "red", score: 3, rank: None },
+///     Player { team: "blue", score: 5, rank: Some(2) },
+///     Player { team: "red", score: 7, rank: Some(1) },
+///     Player { team: "blue", score: 5, rank: None },
+/// ];
+/// // ORDER BY team, score DESC, rank NULLS LAST
+/// let mut order = by_key(|p: &Player| p.team)
+///     .then_by_desc(|p| p.score)
+///     .then(by_key(|p: &Player| p.rank).nulls_last());
+/// MergeSort::default().sort_with(&mut tings, &mut order);
+/// let sorted: Vec<_> = tings.iter().map(|p| (p.team, p.score, p.rank)).collect();
+/// assert_eq!(
+///     sorted,
+///     [
+///         ("blue", 5, Some(2)),
+///         ("blue", 5, None),
+///         ("red", 7, Some(1)),
+///         ("red", 3, None),
+///     ]
+/// );
+/// # }
+/// ```
+pub fn by_key<T: ?Sized, K, F>(key: F) -> ByKey<F>
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    ByKey { key, cmp: ByOrd }
+}
+
+/// Orders elements by the key `key` extracts, descending.
+pub fn by_key_desc<T: ?Sized, K, F>(key: F) -> ByKey<F, Reversed<ByOrd>>
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    ByKey {
+        key,
+        cmp: Reversed(ByOrd),
+    }
+}
+
+impl<F, C> ByKey<F, C> {
+    /// Orders elements whose key is `None` after all the others, in either direction.
+    pub fn nulls_last(self) -> ByKey<F, NullsLast<C>> {
+        ByKey {
+            key: self.key,
+            cmp: NullsLast(self.cmp),
+        }
+    }
+
+    /// Orders elements whose key is `None` before all the others, in either direction.
+    pub fn nulls_first(self) -> ByKey<F, NullsFirst<C>> {
+        ByKey {
+            key: self.key,
+            cmp: NullsFirst(self.cmp),
+        }
+    }
+}
+
+impl<T: ?Sized, K, F, C> Compare<T> for ByKey<F, C>
+where
+    F: FnMut(&T) -> K,
+    C: Compare<K>,
+{
+    #[inline]
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        let (a, b) = ((self.key)(a), (self.key)(b));
+        self.cmp.compare(&a, &b)
+    }
+}
+
+/// Orders `None` after every `Some`, which are ordered by the inner comparator.
+#[derive(Debug, Clone, Copy)]
+pub struct NullsLast<C>(pub C);
+
+impl<T, C: Compare<T>> Compare<Option<T>> for NullsLast<C> {
+    fn compare(&mut self, a: &Option<T>, b: &Option<T>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => self.0.compare(a, b),
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        }
+    }
+}
+
+/// Orders `None` before every `Some`, which are ordered by the inner comparator.
+#[derive(Debug, Clone, Copy)]
+pub struct NullsFirst<C>(pub C);
+
+impl<T, C: Compare<T>> Compare<Option<T>> for NullsFirst<C> {
+    fn compare(&mut self, a: &Option<T>, b: &Option<T>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => self.0.compare(a, b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        }
+    }
+}
+
+/// Orders by the first comparator, breaking its ties with the second.
+///
+/// The hooks are forwarded to the first comparator only, so each operation of a sort
+/// is observed once.
+#[derive(Debug, Clone, Copy)]
+pub struct Then<A, B>(pub A, pub B);
+
+impl<T: ?Sized, A: Compare<T>, B: Compare<T>> Compare<T> for Then<A, B> {
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        match self.0.compare(a, b) {
+            Ordering::Equal => self.1.compare(a, b),
+            order => order,
+        }
+    }
+    fn on_swap(&mut self, a: &T, b: &T) {
+        self.0.on_swap(a, b)
+    }
+    fn on_move(&mut self, region: &[T])
+    where
+        T: Sized,
+    {
+        self.0.on_move(region)
+    }
+    fn on_pivot(&mut self, pivot: &T) {
+        self.0.on_pivot(pivot)
+    }
+    fn on_partition(&mut self, region: &[T], mid: usize)
+    where
+        T: Sized,
+    {
+        self.0.on_partition(region, mid)
+    }
+    fn on_enter(&mut self) {
+        self.0.on_enter()
+    }
+    fn on_leave(&mut self) {
+        self.0.on_leave()
+    }
+    fn on_fallback(&mut self, to: &'static str) {
+        self.0.on_fallback(to)
+    }
+}
+
+/// Combinators chaining comparators into lexicographic orderings.
+pub trait CompareExt<T: ?Sized>: Compare<T> + Sized {
+    /// Breaks ties with `next`.
+    fn then<D: Compare<T>>(self, next: D) -> Then<Self, D> {
+        Then(self, next)
+    }
+
+    /// Breaks ties by the key `key` extracts, ascending.
+    fn then_by<K: Ord, F: FnMut(&T) -> K>(self, key: F) -> Then<Self, ByKey<F>> {
+        Then(self, by_key(key))
+    }
+
+    /// Breaks ties by the key `key` extracts, descending.
+    fn then_by_desc<K, F>(self, key: F) -> Then<Self, ByKey<F, Reversed<ByOrd>>>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        Then(self, by_key_desc(key))
+    }
+
+    fn reversed(self) -> Reversed<Self> {
+        Reversed(self)
+    }
+}
+
+impl<T: ?Sized, C: Compare<T>> CompareExt<T> for C {}
+
+/// Swaps two elements, reporting the swap to the comparator.
+#[inline]
+pub(crate) fn swap<T, C: Compare<T>>(cmp: &mut C, slice: &mut [T], a: usize, b: usize) {
+    cmp.on_swap(&slice[a], &slice[b]);
+    slice.swap(a, b);
+}
+
+/// Rotates the slice right by `k`, reporting the moved elements to the comparator.
+#[inline]
+pub(crate) fn rotate_right<T, C: Compare<T>>(cmp: &mut C, slice: &mut [T], k: usize) {
+    slice.rotate_right(k);
+    cmp.on_move(slice);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn reversed_flips_ordering() {
+        let mut cmp = Reversed(ByOrd);
+        assert_eq!(cmp.compare(&1, &2), Ordering::Greater);
+        assert!(cmp.is_less(&2, &1));
+        assert!(!cmp.is_less(&1, &1));
+    }
+
+    #[test]
+    fn by_fn_uses_closure() {
+        let mut cmp = ByFn(|a: &(i32, i32), b: &(i32, i32)| a.1.cmp(&b.1));
+        assert!(cmp.is_less(&(9, 1), &(0, 2)));
+    }
+
+    #[test]
+    fn chained_keys_order_lexicographically() {
+        let mut tings = vec![
+            (1, 'b', None),
+            (0, 'a', Some(2)),
+            (1, 'b', Some(1)),
+            (1, 'c', None),
+        ];
+        let mut order = by_key(|t: &(u8, char, Option<u8>)| t.0)
+            .then_by_desc(|t| t.1)
+            .then(by_key(|t: &(u8, char, Option<u8>)| t.2).nulls_last());
+        tings.sort_by(|a, b| order.compare(a, b));
+        assert_eq!(
+            tings,
+            [
+                (0, 'a', Some(2)),
+                (1, 'c', None),
+                (1, 'b', Some(1)),
+                (1, 'b', None)
+            ]
+        );
+        let mut reversed = order.reversed();
+        tings.sort_by(|a, b| reversed.compare(a, b));
+        assert_eq!(tings[0], (1, 'b', None));
+    }
+
+    #[test]
+    fn nulls_stay_put_when_reversed() {
+        let mut last = by_key_desc(|x: &Option<u8>| *x).nulls_last();
+        assert_eq!(last.compare(&Some(1), &Some(2)), Ordering::Greater);
+        assert_eq!(last.compare(&None, &Some(2)), Ordering::Greater);
+        let mut first = by_key(|x: &Option<u8>| *x).nulls_first();
+        assert_eq!(first.compare(&None, &Some(0)), Ordering::Less);
+        assert_eq!(first.compare(&None, &None), Ordering::Equal);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn by_deref_compares_targets() {
+        use crate::{Counter, HeapSort, MergeSort, Sorter};
+        use alloc::boxed::Box;
+        use alloc::rc::Rc;
+        use alloc::string::String;
+        use alloc::sync::Arc;
+        use alloc::vec::Vec;
+
+        let mut boxes: Vec<Box<u32>> = vec![Box::new(3), Box::new(1), Box::new(2)];
+        let mut counter = ByDeref(Counter::new(ByOrd));
+        HeapSort::default().sort_with(&mut boxes, &mut counter);
+        assert_eq!(boxes, [Box::new(1), Box::new(2), Box::new(3)]);
+        assert!(counter.0.stats().swaps > 0);
+
+        let (a, b) = (String::from("b"), String::from("a"));
+        let mut refs = vec![&a, &b];
+        MergeSort::default().sort_with(&mut refs, &mut ByDeref(ByOrd));
+        assert_eq!(refs, [&b, &a]);
+
+        let mut rcs: Vec<Rc<[u8]>> = vec![Rc::from(&[2u8, 0][..]), Rc::from(&[1u8][..])];
+        MergeSort::default().sort_by_deref(&mut rcs, |a, b| b.len().cmp(&a.len()));
+        assert_eq!(&*rcs[1], [1]);
+
+        let mut arcs: Vec<Arc<str>> = vec![Arc::from("y"), Arc::from("x")];
+        HeapSort::default().sort_with(&mut arcs, &mut ByDeref(Reversed(ByOrd)));
+        assert_eq!(&*arcs[0], "y");
+    }
+}
@@ -0,0 +1,141 @@
+//! Branch-free comparison primitives for primitive integers.
+//!
+//! The comparisons here produce a flag instead of a jump, which the compiler lowers to
+//! conditional moves. Inside small sorting kernels, where the outcome of each comparison
+//! is essentially random, this avoids the cost of mispredicted branches. The sorts'
+//! base cases use them where the keys are integers, as radix sort's are.
+
+use crate::networks;
+
+/// Integer types supporting branch-free selection.
+pub trait Branchless: Copy + Ord {
+    /// Returns `a` if `cond` is true, `b` otherwise, without branching.
+    fn select(cond: bool, a: Self, b: Self) -> Self;
+}
+
+macro_rules! impl_branchless {
+    ($($t:ty),*) => {
+        $(
+            impl Branchless for $t {
+                #[inline(always)]
+                fn select(cond: bool, a: Self, b: Self) -> Self {
+                    // all ones if `cond`, all zeros otherwise
+                    let mask = (cond as $t).wrapping_neg();
+                    (a & mask) | (b & !mask)
+                }
+            }
+        )*
+    };
+}
+
+impl_branchless!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Returns `a` if `cond` is true, `b` otherwise.
+#[inline(always)]
+pub fn select<T: Branchless>(cond: bool, a: T, b: T) -> T {
+    T::select(cond, a, b)
+}
+
+/// Returns the smaller of two values.
+#[inline(always)]
+pub fn min<T: Branchless>(a: T, b: T) -> T {
+    select(a < b, a, b)
+}
+
+/// Returns the larger of two values.
+#[inline(always)]
+pub fn max<T: Branchless>(a: T, b: T) -> T {
+    select(a < b, b, a)
+}
+
+/// Orders two values so that `*a <= *b` afterwards.
+#[inline(always)]
+pub fn cmp_swap<T: Branchless>(a: &mut T, b: &mut T) {
+    let (x, y) = (*a, *b);
+    let less = y < x;
+    *a = select(less, y, x);
+    *b = select(less, x, y);
+}
+
+/// Insertion sort built from [`cmp_swap`].
+///
+/// Every element is bubbled down to the front without an early exit, so the running
+/// time is always quadratic; in exchange the inner loop has no data-dependent branches.
+/// Intended as a base case for slices of a few dozen elements at most.
+pub fn insertion_sort<T: Branchless>(slice: &mut [T]) {
+    for unsorted in 1..slice.len() {
+        for i in (1..=unsorted).rev() {
+            cmp_swap_at(slice, i - 1, i);
+        }
+    }
+}
+
+/// Sorting network built from [`cmp_swap`]: the smallest known one up to
+/// [`MAX_OPTIMAL`](networks::MAX_OPTIMAL) elements, Batcher's beyond.
+pub fn network_sort<T: Branchless>(slice: &mut [T]) {
+    match networks::optimal(slice.len()) {
+        Some(network) => {
+            for &(a, b) in network {
+                cmp_swap_at(slice, a as usize, b as usize);
+            }
+        }
+        None => networks::for_each_batcher(slice.len(), |a, b| cmp_swap_at(slice, a, b)),
+    }
+}
+
+/// [`cmp_swap`] of the elements at `a` and `b`, where `a < b`.
+#[inline(always)]
+fn cmp_swap_at<T: Branchless>(slice: &mut [T], a: usize, b: usize) {
+    let (left, right) = slice.split_at_mut(b);
+    cmp_swap(&mut left[a], &mut right[0]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn select_picks_operand() {
+        assert_eq!(select(true, 3u8, 7), 3);
+        assert_eq!(select(false, 3u8, 7), 7);
+        assert_eq!(select(true, -3i64, 7), -3);
+        assert_eq!(select(false, -3i64, i64::MIN), i64::MIN);
+    }
+
+    #[test]
+    fn min_max_work() {
+        assert_eq!(min(-1i32, 1), -1);
+        assert_eq!(max(-1i32, 1), 1);
+        assert_eq!(min(u64::MAX, 0), 0);
+        assert_eq!(max(5usize, 5), 5);
+    }
+
+    #[test]
+    fn cmp_swap_orders() {
+        let (mut a, mut b) = (9i16, -4);
+        cmp_swap(&mut a, &mut b);
+        assert_eq!((a, b), (-4, 9));
+        cmp_swap(&mut a, &mut b);
+        assert_eq!((a, b), (-4, 9));
+    }
+
+    #[test]
+    fn insertion_sort_works() {
+        let mut tings = vec![5, 1, 4, 2, 3, -7, 0, 4];
+        insertion_sort(&mut tings);
+        assert_eq!(tings, &[-7, 0, 1, 2, 3, 4, 4, 5]);
+    }
+
+    #[test]
+    fn network_sort_works() {
+        for n in 0..40u64 {
+            let mut tings: Vec<u64> = (0..n).map(|i| (i * 7919) % 13).collect();
+            let mut expected = tings.clone();
+            expected.sort();
+            network_sort(&mut tings);
+            assert_eq!(tings, expected, "n = {}", n);
+        }
+    }
+}
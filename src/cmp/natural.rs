@@ -0,0 +1,327 @@
+//! Natural string ordering, the order people expect of file names and versions.
+//!
+//! Runs of ASCII digits compare by their numeric value, so "file2" sorts before
+//! "file10", and everything else compares character by character, optionally ignoring
+//! case.
+//!
+//! Ignoring case does not depend on the locale: letters compare by their Unicode
+//! lowercase forms. Latin-1 characters, which most text is made of, are folded through
+//! a table, and stretches of ASCII are compared eight bytes at a time, so the full
+//! Unicode case mapping is only consulted for the other characters.
+//!
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! use pangua::cmp::natural::NaturalOrder;
+//! use pangua::{MergeSort, Sorter};
+//!
+//! let mut tings = vec!["v1.10", "File2", "v1.9", "file10", "file1"];
+//! MergeSort::default().sort_with(&mut tings, &mut NaturalOrder { ignore_case: true });
+//! assert_eq!(tings, ["file1", "File2", "file10", "v1.9", "v1.10"]);
+//! # }
+//! ```
+
+use super::Compare;
+use core::cmp::Ordering;
+use core::convert::TryInto;
+
+/// Compares strings in natural order, see [`natural_cmp`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NaturalOrder {
+    /// Compare letters by their lowercase forms.
+    pub ignore_case: bool,
+}
+
+impl<T: AsRef<str>> Compare<T> for NaturalOrder {
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        natural(a.as_ref(), b.as_ref(), self.ignore_case)
+    }
+}
+
+/// Orders strings with runs of digits compared as numbers.
+///
+/// Numbers of any length compare by value, so only the lengths and digits of the runs
+/// are looked at. Strings equal by that measure, such as "a01" and "a1", are ordered
+/// by the leading zeros of their first differing number, fewer first, and then as
+/// plain strings, which keeps the order total.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    natural(a, b, false)
+}
+
+/// Like [`natural_cmp`], comparing letters by their lowercase forms. Strings differing
+/// only in case are then ordered as plain strings.
+pub fn natural_cmp_ignore_case(a: &str, b: &str) -> Ordering {
+    natural(a, b, true)
+}
+
+fn natural(a: &str, b: &str, ignore_case: bool) -> Ordering {
+    let (mut x, mut y) = (a, b);
+    // the first difference in leading zeros, in case nothing else tells them apart
+    let mut zeros = Ordering::Equal;
+    loop {
+        let equal = equal_prefix(x.as_bytes(), y.as_bytes(), ignore_case);
+        x = &x[equal..];
+        y = &y[equal..];
+        let (c, d) = match (x.chars().next(), y.chars().next()) {
+            (None, None) => return zeros.then_with(|| a.cmp(b)),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(c), Some(d)) => (c, d),
+        };
+        if c.is_ascii_digit() && d.is_ascii_digit() {
+            let (m, rest_x) = split_number(x);
+            let (n, rest_y) = split_number(y);
+            let (m_value, n_value) = (m.trim_start_matches('0'), n.trim_start_matches('0'));
+            let order = m_value
+                .len()
+                .cmp(&n_value.len())
+                .then_with(|| m_value.cmp(n_value));
+            if order != Ordering::Equal {
+                return order;
+            }
+            if zeros == Ordering::Equal {
+                zeros = m.len().cmp(&n.len());
+            }
+            x = rest_x;
+            y = rest_y;
+        } else {
+            let order = if ignore_case {
+                cmp_lowercase(c, d)
+            } else {
+                c.cmp(&d)
+            };
+            if order != Ordering::Equal {
+                return order;
+            }
+            x = &x[c.len_utf8()..];
+            y = &y[d.len_utf8()..];
+        }
+    }
+}
+
+/// The lowercase forms of the Latin-1 characters, U+0000 to U+00FF, all of which are
+/// Latin-1 characters themselves.
+static LATIN1_LOWERCASE: [u8; 256] = latin1_lowercase();
+
+const fn latin1_lowercase() -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let c = i as u8;
+        // A-Z, and À-Þ but for the multiplication sign
+        let upper = c.is_ascii_uppercase() || (c >= 0xC0 && c <= 0xDE && c != 0xD7);
+        table[i] = if upper { c + 0x20 } else { c };
+        i += 1;
+    }
+    table
+}
+
+/// Compares two characters by their lowercase forms.
+fn cmp_lowercase(c: char, d: char) -> Ordering {
+    if (c as u32) < 256 && (d as u32) < 256 {
+        LATIN1_LOWERCASE[c as usize].cmp(&LATIN1_LOWERCASE[d as usize])
+    } else {
+        c.to_lowercase().cmp(d.to_lowercase())
+    }
+}
+
+/// Broadcasts a byte to every byte of a word.
+const fn splat(byte: u8) -> u64 {
+    u64::from_ne_bytes([byte; 8])
+}
+
+const HIGH_BITS: u64 = splat(0x80);
+
+/// The high bit of each byte of an ASCII `word` that lies in `lo..=hi`.
+///
+/// Adding `0x80 - lo` sets a byte's high bit if it is at least `lo`, without carrying
+/// into the next byte since ASCII bytes are below `0x80`.
+const fn in_range(word: u64, lo: u8, hi: u8) -> u64 {
+    let at_least_lo = word + splat(0x80 - lo);
+    let above_hi = word + splat(0x80 - hi - 1);
+    at_least_lo & !above_hi & HIGH_BITS
+}
+
+/// The length of the longest prefix of `x` and `y`, in whole words of eight bytes,
+/// made of ASCII characters other than digits and equal in both, in lowercase if
+/// `ignore_case`.
+///
+/// Digits are left out because a run of them is compared as a whole; everything
+/// skipped over compares equal character by character, so the comparison can pick up
+/// after it.
+fn equal_prefix(x: &[u8], y: &[u8], ignore_case: bool) -> usize {
+    let mut equal = 0;
+    for (u, v) in x.chunks_exact(8).zip(y.chunks_exact(8)) {
+        let u = u64::from_ne_bytes(u.try_into().unwrap());
+        let v = u64::from_ne_bytes(v.try_into().unwrap());
+        if (u | v) & HIGH_BITS != 0 || (in_range(u, b'0', b'9') | in_range(v, b'0', b'9')) != 0 {
+            break;
+        }
+        let (u, v) = if ignore_case {
+            // an uppercase letter's high bit, shifted down, is the bit that lowercases it
+            (
+                u | in_range(u, b'A', b'Z') >> 2,
+                v | in_range(v, b'A', b'Z') >> 2,
+            )
+        } else {
+            (u, v)
+        };
+        if u != v {
+            break;
+        }
+        equal += 8;
+    }
+    equal
+}
+
+/// Splits the run of ASCII digits off the start of `s`.
+fn split_number(s: &str) -> (&str, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec;
+
+    #[test]
+    fn numbers_compare_by_value() {
+        let mut tings = vec![
+            "img12.png",
+            "img10.png",
+            "img2.png",
+            "img1.png",
+            "img",
+            "img02.png",
+            "img99999999999999999999999.png",
+        ];
+        tings.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            tings,
+            [
+                "img",
+                "img1.png",
+                "img2.png",
+                "img02.png",
+                "img10.png",
+                "img12.png",
+                "img99999999999999999999999.png"
+            ]
+        );
+        assert_eq!(natural_cmp("1.2.10", "1.2.9"), Ordering::Greater);
+        assert_eq!(natural_cmp("a0", "a00"), Ordering::Less);
+        assert_eq!(natural_cmp("x7", "x7"), Ordering::Equal);
+    }
+
+    #[test]
+    fn case_can_be_ignored() {
+        assert_eq!(natural_cmp("B1", "a2"), Ordering::Less);
+        assert_eq!(natural_cmp_ignore_case("B1", "a2"), Ordering::Greater);
+        assert_eq!(natural_cmp_ignore_case("Ä3", "ä10"), Ordering::Less);
+        // equal but for case, so ordered as plain strings
+        assert_eq!(natural_cmp_ignore_case("ABC", "abc"), Ordering::Less);
+        let mut cmp = NaturalOrder { ignore_case: true };
+        assert!(cmp.is_less(&String::from("readme"), &String::from("TODO")));
+    }
+
+    #[test]
+    fn fast_paths_agree_with_unicode_folding() {
+        use rand::prelude::*;
+
+        /// The comparison without the folding table or the word-at-a-time prefix.
+        fn reference(a: &str, b: &str, ignore_case: bool) -> Ordering {
+            let (mut x, mut y) = (a, b);
+            let mut zeros = Ordering::Equal;
+            loop {
+                let (c, d) = match (x.chars().next(), y.chars().next()) {
+                    (None, None) => return zeros.then_with(|| a.cmp(b)),
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(c), Some(d)) => (c, d),
+                };
+                if c.is_ascii_digit() && d.is_ascii_digit() {
+                    let (m, rest_x) = split_number(x);
+                    let (n, rest_y) = split_number(y);
+                    let (m_value, n_value) = (m.trim_start_matches('0'), n.trim_start_matches('0'));
+                    let order = m_value
+                        .len()
+                        .cmp(&n_value.len())
+                        .then_with(|| m_value.cmp(n_value));
+                    if order != Ordering::Equal {
+                        return order;
+                    }
+                    if zeros == Ordering::Equal {
+                        zeros = m.len().cmp(&n.len());
+                    }
+                    x = rest_x;
+                    y = rest_y;
+                } else {
+                    let order = if ignore_case {
+                        c.to_lowercase().cmp(d.to_lowercase())
+                    } else {
+                        c.cmp(&d)
+                    };
+                    if order != Ordering::Equal {
+                        return order;
+                    }
+                    x = &x[c.len_utf8()..];
+                    y = &y[d.len_utf8()..];
+                }
+            }
+        }
+
+        for i in 0..=255u8 {
+            let c = i as char;
+            assert_eq!(c.to_lowercase().count(), 1);
+            assert_eq!(
+                c.to_lowercase().next(),
+                Some(LATIN1_LOWERCASE[i as usize] as char)
+            );
+        }
+        assert_eq!(
+            equal_prefix(b"abcdefgh@ZYX[`{|x", b"ABCDEFGH@zyx[`{|y", true),
+            16
+        );
+        assert_eq!(
+            equal_prefix(b"abcdefgh@ZYX[`{|x", b"ABCDEFGH@zyx[`{|y", false),
+            0
+        );
+        assert_eq!(equal_prefix(b"abcdefgh1", b"abcdefgh2", false), 8);
+        assert_eq!(equal_prefix(b"abcdefg1", b"abcdefg1", false), 0);
+        assert_eq!(
+            equal_prefix(
+                "abcdefg\u{e9}".as_bytes(),
+                "abcdefg\u{e9}".as_bytes(),
+                false
+            ),
+            0
+        );
+        // mostly the same long ASCII prefix, so the word-at-a-time path does the work
+        let pieces = [
+            "abcdefgh", "ABCDEFGH", "abcDEFgh", "a", "Z", "0", "07", "9", "@", "[", "`", "{",
+            "\u{e9}", "\u{c9}", "\u{d7}", "\u{f7}", "\u{ff}", "\u{178}", "\u{212a}", "k",
+            "\u{3a3}", "\u{3c3}", "\u{130}",
+        ];
+        let mut rng = StdRng::seed_from_u64(8);
+        let word = |rng: &mut StdRng| -> String {
+            (0..rng.gen_range(0, 8))
+                .map(|_| *pieces.choose(rng).unwrap())
+                .collect()
+        };
+        for _ in 0..20_000 {
+            let prefix = word(&mut rng);
+            let a = prefix.clone() + &word(&mut rng);
+            let b = prefix + &word(&mut rng);
+            assert_eq!(
+                natural_cmp(&a, &b),
+                reference(&a, &b, false),
+                "{:?} {:?}",
+                a,
+                b
+            );
+            let folded = natural_cmp_ignore_case(&a, &b);
+            assert_eq!(folded, reference(&a, &b, true), "{:?} {:?}", a, b);
+        }
+    }
+}
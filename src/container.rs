@@ -0,0 +1,188 @@
+//! Sorting containers other than slices, through [`SortableContainer`].
+
+use crate::cmp::Compare;
+use crate::indirect::ByIndex;
+use crate::{permutation, Sorter};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+/// A random-access container that can be sorted by [`Sorter::sort_container`]: read by
+/// index and rearranged by swaps.
+///
+/// Containers holding their elements in one slice should return it from
+/// [`as_mut_slice`](SortableContainer::as_mut_slice), to be sorted in place like any
+/// slice. The others are sorted indirectly: an array of indices is sorted by the
+/// elements they point at, and the elements are then swapped into place, at most
+/// `len - 1` swaps for the whole sort.
+///
+/// ```
+/// use pangua::{MergeSort, Sorter};
+/// use std::collections::VecDeque;
+///
+/// let mut tings: VecDeque<_> = vec![3, 4, 5].into();
+/// tings.push_front(2);
+/// tings.push_front(6);
+/// MergeSort::default().sort_container(&mut tings);
+/// assert_eq!(tings, [2, 3, 4, 5, 6]);
+/// ```
+pub trait SortableContainer {
+    type Item;
+
+    fn len(&self) -> usize;
+
+    /// The element at `index`, which is less than [`len`](SortableContainer::len).
+    fn get(&self, index: usize) -> &Self::Item;
+
+    fn swap(&mut self, a: usize, b: usize);
+
+    /// The elements as a single slice, if they are stored as one; `None` by default.
+    fn as_mut_slice(&mut self) -> Option<&mut [Self::Item]> {
+        None
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> SortableContainer for [T] {
+    type Item = T;
+
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+    fn get(&self, index: usize) -> &T {
+        &self[index]
+    }
+    fn swap(&mut self, a: usize, b: usize) {
+        <[T]>::swap(self, a, b)
+    }
+    fn as_mut_slice(&mut self) -> Option<&mut [T]> {
+        Some(self)
+    }
+}
+
+impl<T> SortableContainer for Vec<T> {
+    type Item = T;
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+    fn get(&self, index: usize) -> &T {
+        &self[index]
+    }
+    fn swap(&mut self, a: usize, b: usize) {
+        <[T]>::swap(self, a, b)
+    }
+    fn as_mut_slice(&mut self) -> Option<&mut [T]> {
+        Some(self)
+    }
+}
+
+impl<T, const N: usize> SortableContainer for [T; N] {
+    type Item = T;
+
+    fn len(&self) -> usize {
+        N
+    }
+    fn get(&self, index: usize) -> &T {
+        &self[index]
+    }
+    fn swap(&mut self, a: usize, b: usize) {
+        <[T]>::swap(self, a, b)
+    }
+    fn as_mut_slice(&mut self) -> Option<&mut [T]> {
+        Some(self)
+    }
+}
+
+/// A deque may wrap around its buffer, in which case it is sorted through indices
+/// rather than made contiguous first.
+impl<T> SortableContainer for VecDeque<T> {
+    type Item = T;
+
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+    fn get(&self, index: usize) -> &T {
+        &self[index]
+    }
+    fn swap(&mut self, a: usize, b: usize) {
+        VecDeque::swap(self, a, b)
+    }
+    fn as_mut_slice(&mut self) -> Option<&mut [T]> {
+        match self.as_mut_slices() {
+            (front, []) => Some(front),
+            _ => None,
+        }
+    }
+}
+
+/// Sorts the container with `sorter`, in place if it is one slice.
+pub(crate) fn sort_container_with<S, K, C>(sorter: &S, container: &mut K, cmp: &mut C)
+where
+    S: Sorter + ?Sized,
+    K: SortableContainer + ?Sized,
+    C: Compare<K::Item>,
+{
+    if let Some(slice) = container.as_mut_slice() {
+        return crate::sort_reported(sorter, slice, cmp);
+    }
+    let mut order: Vec<usize> = (0..container.len()).collect();
+    sorter.sort_with(
+        &mut order,
+        &mut ByIndex {
+            items: &*container,
+            inner: &mut *cmp,
+        },
+    );
+    permutation::apply_by(&mut order, |a, b| {
+        cmp.on_swap(container.get(a), container.get(b));
+        container.swap(a, b);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use alloc::vec;
+
+    #[test]
+    fn sorts_wrapped_deques_through_indices() {
+        for front in 1..20 {
+            let mut tings: VecDeque<(u32, u32)> = VecDeque::with_capacity(40);
+            for i in 0..20 {
+                tings.push_back(((i * 7) % 5, i));
+            }
+            for i in 0..front {
+                tings.push_front(((i * 3) % 5, 100 + i));
+            }
+            assert!(tings.as_mut_slice().is_none());
+            let mut expected: Vec<_> = tings.iter().copied().collect();
+            expected.sort_by_key(|t| t.0);
+            // only the final swaps move elements of the deque
+            let mut counter = Counter::new(cmp::by_key(|t: &(u32, u32)| t.0));
+            sort_container_with(&MergeSort::default(), &mut tings, &mut counter);
+            assert!(tings.iter().eq(expected.iter()), "front = {}", front);
+            assert!(counter.stats().swaps < tings.len());
+            assert_eq!(counter.stats().moves, 0);
+        }
+    }
+
+    #[test]
+    fn sorts_slices_in_place() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        InsertionSort { smart: false }.sort_container_by(&mut tings, |a, b| b.cmp(a));
+        assert_eq!(tings, [5, 4, 3, 2, 1]);
+        InsertionSort { smart: false }.sort_container(&mut tings);
+        assert_eq!(tings, [1, 2, 3, 4, 5]);
+        let mut deque: VecDeque<_> = vec![3, 1, 2].into();
+        assert!(deque.as_mut_slice().is_some());
+        HeapSort::default().sort_container(&mut deque);
+        assert_eq!(deque, [1, 2, 3]);
+        let mut array = [2, 3, 1];
+        HeapSort::default().sort_container(&mut array);
+        assert_eq!(array, [1, 2, 3]);
+    }
+}
@@ -0,0 +1,245 @@
+//! Sorting inside async tasks without blocking the executor.
+//!
+//! An executor runs many tasks on few threads, each task running until it awaits, so a
+//! sort of millions of elements in one task holds its thread for the whole sort.
+//! [`AsyncSorter`] breaks the sort into bounded pieces of work and yields to the
+//! executor between them, with [`yield_now`], letting other tasks run. It needs no
+//! particular runtime: yielding only wakes the task again at once, as `tokio`'s and
+//! `async-std`'s `yield_now` do.
+//!
+//! ```
+//! use pangua::{async_sort, QuickSort};
+//!
+//! async fn handle(mut tings: Vec<u64>) -> Vec<u64> {
+//!     async_sort(&mut tings, QuickSort::default()).await;
+//!     tings
+//! }
+//! ```
+
+use crate::cmp::{self, ByFn, ByOrd, Compare};
+use crate::{is_trivially_sorted, Sorter};
+use core::cmp::Ordering;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Elements sorted at a time, and operations between yields, by default.
+const CHUNK: usize = 1 << 12;
+
+/// Sorts the slice with `sorter`, yielding to the executor every few thousand
+/// operations, see [`AsyncSorter`].
+pub async fn async_sort<T: Ord, S: Sorter>(slice: &mut [T], sorter: S) {
+    AsyncSorter::new(sorter).sort(slice).await
+}
+
+/// A sorter for async tasks, yielding to the executor as it goes.
+///
+/// The slice is cut into chunks of `chunk` elements, each sorted in one go by `inner`
+/// and followed by a yield. The sorted chunks are then merged bottom-up, pairs of runs
+/// at a time, yielding every `chunk` comparisons or swaps: each merge records the merged
+/// order of its runs in a buffer of indices, then moves the elements into place by
+/// following the cycles of that order. Other tasks so wait for at most one chunk's
+/// sort or `chunk` merge operations, at the cost of O(n) extra memory and
+/// O(n log(n / chunk)) merging after the chunks are sorted. The sort is stable when
+/// `inner` is.
+///
+/// A [`Stepper`](crate::Stepper) would not do: it runs the whole sort when it is
+/// created, recording the steps to replay later.
+#[derive(Debug, Clone, Copy)]
+pub struct AsyncSorter<S> {
+    pub inner: S,
+    pub chunk: usize,
+}
+
+impl<S: Sorter> AsyncSorter<S> {
+    /// A sorter of chunks of 4096 elements, yielding every 4096 merge operations.
+    pub fn new(inner: S) -> Self {
+        AsyncSorter {
+            inner,
+            chunk: CHUNK,
+        }
+    }
+
+    pub async fn sort<T: Ord>(&self, slice: &mut [T]) {
+        self.sort_with(slice, &mut ByOrd).await
+    }
+
+    /// Like [`sort`](AsyncSorter::sort), ordering with `compare`.
+    pub async fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.sort_with(slice, &mut ByFn(compare)).await
+    }
+
+    /// Like [`sort`](AsyncSorter::sort), ordering by the keys `f` extracts.
+    pub async fn sort_by_key<T, K, F>(&self, slice: &mut [T], f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_with(slice, &mut cmp::by_key(f)).await
+    }
+
+    /// Like [`sort`](AsyncSorter::sort), ordering with `cmp`.
+    pub async fn sort_with<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let chunk = self.chunk.max(1);
+        for part in slice.chunks_mut(chunk) {
+            self.inner.sort_with(part, cmp);
+            yield_now().await;
+        }
+        let mut budget = Budget {
+            left: chunk,
+            every: chunk,
+        };
+        let mut order = Vec::new();
+        let mut width = chunk;
+        while width < slice.len() {
+            for run in slice.chunks_mut(2 * width) {
+                if run.len() > width {
+                    merge(run, width, &mut order, &mut budget, cmp).await;
+                }
+            }
+            width *= 2;
+        }
+    }
+}
+
+/// Operations left before the next yield.
+struct Budget {
+    left: usize,
+    every: usize,
+}
+
+impl Budget {
+    async fn spend(&mut self) {
+        self.left -= 1;
+        if self.left == 0 {
+            self.left = self.every;
+            yield_now().await;
+        }
+    }
+}
+
+/// Merges the sorted runs `slice[..mid]` and `slice[mid..]` through `order`, spending
+/// one unit of `budget` per comparison and swap.
+async fn merge<T, C: Compare<T>>(
+    slice: &mut [T],
+    mid: usize,
+    order: &mut Vec<usize>,
+    budget: &mut Budget,
+    cmp: &mut C,
+) {
+    if !cmp.is_less(&slice[mid], &slice[mid - 1]) {
+        return;
+    }
+    order.clear();
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < slice.len() {
+        // ties go to the left run, keeping the merge stable
+        if cmp.is_less(&slice[j], &slice[i]) {
+            order.push(j);
+            j += 1;
+        } else {
+            order.push(i);
+            i += 1;
+        }
+        budget.spend().await;
+    }
+    order.extend(i..mid);
+    order.extend(j..slice.len());
+    // as permutation::apply_by, with a yield wherever the budget runs out
+    for start in 0..order.len() {
+        let mut cur = start;
+        loop {
+            let next = order[cur];
+            order[cur] = cur;
+            if next == start {
+                break;
+            }
+            cmp::swap(cmp, slice, cur, next);
+            cur = next;
+            budget.spend().await;
+        }
+    }
+}
+
+/// Returns a future that is pending once, waking its task at once, so the executor can
+/// run others before coming back to it.
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+/// The future returned by [`yield_now`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless awaited"]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use std::task::Waker;
+
+    /// Runs the future to completion, returning its output and how often it yielded.
+    fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+        let mut future = Box::pin(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut yields = 0;
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return (output, yields),
+                Poll::Pending => yields += 1,
+            }
+        }
+    }
+
+    #[test]
+    fn sorts_stably_in_chunks() {
+        for n in [0u64, 1, 2, 63, 64, 65, 1000] {
+            let mut tings: Vec<_> = (0..n).map(|i| ((i * 7919) % 13, i)).collect();
+            let mut expected = tings.clone();
+            expected.sort_by_key(|t| t.0);
+            let sorter = AsyncSorter {
+                inner: MergeSort::default(),
+                chunk: 16,
+            };
+            block_on(sorter.sort_by_key(&mut tings, |t| t.0));
+            assert_eq!(tings, expected, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn yields_between_bounded_pieces_of_work() {
+        let n = 1 << 16;
+        let mut tings: Vec<u64> = (0..n).map(|i| (i * 2_654_435_761) % 1_000_003).collect();
+        let sorter = AsyncSorter::new(HeapSort::default());
+        let ((), yields) = block_on(sorter.sort(&mut tings));
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        // once per chunk, then at least once per chunk's worth of merging
+        let chunks = n as usize / sorter.chunk;
+        assert!(yields >= 2 * chunks);
+
+        // sorted chunks need no merging
+        let ((), yields) = block_on(async_sort(&mut tings, QuickSort::default()));
+        assert_eq!(yields, chunks);
+    }
+}
@@ -0,0 +1,209 @@
+//! Estimating what a sort will cost from its metadata, without running it.
+
+use crate::parallel;
+use crate::{Complexity, SorterInfo};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Constant factors of one algorithm, multiplying the growth of its average complexity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostConstants {
+    pub comparisons: f64,
+    pub moves: f64,
+}
+
+impl Default for CostConstants {
+    fn default() -> Self {
+        CostConstants {
+            comparisons: 1.0,
+            moves: 1.0,
+        }
+    }
+}
+
+/// Machine costs and per-algorithm constants used by [`estimate_cost`].
+///
+/// The defaults are the textbook constants of the crate's algorithms (quicksort's 1.39
+/// n log2 n comparisons, insertion sort's n²/4, ...) and costs typical of a modern CPU
+/// on small keys. Replace them with measurements to tune the estimates to a machine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningProfile {
+    pub comparison_nanos: f64,
+    pub move_nanos_per_byte: f64,
+    /// Bytes [`ParallelRadixSort`](crate::ParallelRadixSort) stages per bucket before
+    /// writing them out, best a few cache lines.
+    pub radix_write_buffer_bytes: usize,
+    /// Constants by [`SorterInfo::name`]; algorithms not listed get 1 for both.
+    pub constants: HashMap<&'static str, CostConstants>,
+}
+
+impl Default for TuningProfile {
+    fn default() -> Self {
+        let constants = [
+            ("bubble", 0.5, 0.25),
+            ("cocktail-shaker", 0.5, 0.25),
+            ("gnome", 0.25, 0.25),
+            ("odd-even", 0.5, 0.25),
+            ("insertion-dumb", 0.25, 0.25),
+            ("insertion-smart", 0.25, 0.25),
+            ("selection", 0.5, 0.0),
+            ("cycle", 0.5, 0.0),
+            ("pancake", 0.5, 0.5),
+            ("quick", 1.39, 0.35),
+            ("quick-3way", 1.39, 0.5),
+            ("intro", 1.2, 0.35),
+            ("pdq", 1.1, 0.3),
+            ("heap", 2.0, 1.0),
+            ("merge", 1.0, 1.0),
+            ("merge-bottom-up", 1.0, 1.0),
+            ("merge-natural", 1.0, 1.0),
+            ("bitonic", 0.25, 0.125),
+            ("std", 1.0, 1.0),
+        ];
+        TuningProfile {
+            comparison_nanos: 2.0,
+            move_nanos_per_byte: 0.25,
+            radix_write_buffer_bytes: parallel::WRITE_BUFFER_BYTES,
+            constants: constants
+                .iter()
+                .map(|&(name, comparisons, moves)| (name, CostConstants { comparisons, moves }))
+                .collect(),
+        }
+    }
+}
+
+/// The predicted cost of sorting, as returned by [`estimate_cost`].
+///
+/// For sorts that do not compare elements, such as radix sort, `comparisons` counts the
+/// key inspections instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostEstimate {
+    pub comparisons: f64,
+    pub moves: f64,
+    /// Extra memory allocated by the sort, beyond the slice itself.
+    pub aux_bytes: usize,
+    pub est_time: Duration,
+}
+
+/// Predicts the cost of sorting `len` elements of `element_size` bytes with the sorter
+/// described by `info`.
+///
+/// The operation counts follow the sorter's average complexity scaled by the profile's
+/// constants, and the time weighs them by the profile's machine costs. Sorts that are
+/// not in place are assumed to need a buffer as large as the input. The results are
+/// meant for ranking candidate algorithms, not as exact predictions.
+///
+/// ```
+/// use pangua::*;
+///
+/// let profile = TuningProfile::default();
+/// let quick = estimate_cost(QuickSort::default().info(), 100_000, 8, &profile);
+/// let bubble = estimate_cost(BubbleSort.info(), 100_000, 8, &profile);
+/// assert!(quick.est_time < bubble.est_time);
+/// ```
+pub fn estimate_cost(
+    info: SorterInfo,
+    len: usize,
+    element_size: usize,
+    profile: &TuningProfile,
+) -> CostEstimate {
+    let constants = profile
+        .constants
+        .get(info.name)
+        .copied()
+        .unwrap_or_default();
+    let work = if len < 2 {
+        0.0
+    } else {
+        growth(info.average, len)
+    };
+    let comparisons = constants.comparisons * work;
+    let moves = constants.moves * work;
+    let aux_bytes = if info.in_place || len < 2 {
+        0
+    } else {
+        len.saturating_mul(element_size)
+    };
+    let nanos = comparisons * profile.comparison_nanos
+        + moves * element_size as f64 * profile.move_nanos_per_byte;
+    CostEstimate {
+        comparisons,
+        moves,
+        aux_bytes,
+        est_time: Duration::try_from_secs_f64(nanos / 1e9).unwrap_or(Duration::MAX),
+    }
+}
+
+/// The growth function of a complexity class at `n`, with logarithms in base 2.
+fn growth(complexity: Complexity, n: usize) -> f64 {
+    let n = n as f64;
+    let log = n.log2();
+    match complexity {
+        Complexity::Linear => n,
+        Complexity::Linearithmic => n * log,
+        Complexity::LinearLogSquared => n * log * log,
+        Complexity::PowerThreeHalves => n.powf(1.5),
+        Complexity::Quadratic => n * n,
+        // overflows to infinity past about 170 elements
+        Complexity::Factorial => (1..=n as u64).fold(n, |acc, k| acc * k as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn measured<S: Sorter>(sorter: S, n: u64) -> f64 {
+        let sorter = Counting::new(sorter);
+        sorter.sort(&mut (0..n).map(|i| (i * 7919) % n).collect::<Vec<_>>());
+        sorter.stats().comparisons as f64
+    }
+
+    fn estimated<S: Sorter>(sorter: S, n: u64) -> f64 {
+        estimate_cost(sorter.info(), n as usize, 8, &TuningProfile::default()).comparisons
+    }
+
+    #[test]
+    fn comparisons_are_close_to_measured() {
+        fn check<S: Sorter>(sorter: S) {
+            let ratio = estimated(&sorter, 5000) / measured(&sorter, 5000);
+            assert!(
+                (0.5..2.0).contains(&ratio),
+                "{}: {}",
+                sorter.info().name,
+                ratio
+            );
+        }
+        check(QuickSort::default());
+        check(MergeSort::default());
+        check(HeapSort::default());
+        check(InsertionSort { smart: false });
+        check(BubbleSort);
+        check(BitonicSort);
+    }
+
+    #[test]
+    fn buffers_count_only_for_out_of_place_sorts() {
+        let profile = TuningProfile::default();
+        let merge = estimate_cost(MergeSort::default().info(), 1000, 16, &profile);
+        assert_eq!(merge.aux_bytes, 16_000);
+        let heap = estimate_cost(HeapSort::default().info(), 1000, 16, &profile);
+        assert_eq!(heap.aux_bytes, 0);
+        assert!(heap.est_time > Duration::ZERO);
+    }
+
+    #[test]
+    fn degenerate_inputs_cost_nothing_or_saturate() {
+        let profile = TuningProfile::default();
+        let one = estimate_cost(QuickSort::default().info(), 1, 8, &profile);
+        assert_eq!(one.comparisons, 0.0);
+        assert_eq!(one.est_time, Duration::ZERO);
+        let factorial = SorterInfo {
+            average: Complexity::Factorial,
+            ..HeapSort::default().info()
+        };
+        let hopeless = estimate_cost(factorial, 1000, 8, &profile);
+        assert_eq!(hopeless.est_time, Duration::MAX);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::permutation;
+use crate::{is_trivially_sorted, Complexity, SorterInfo};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Elements that map to a small, non-negative integer key.
+pub trait CountKey {
+    fn count_key(&self) -> usize;
+}
+
+macro_rules! impl_count_key {
+    ($($t:ty),*) => {
+        $(
+            impl CountKey for $t {
+                #[inline]
+                fn count_key(&self) -> usize {
+                    *self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_count_key!(u8, u16, bool);
+
+/// Counting Sort
+///
+/// Counting sort counts the number of elements having each distinct key value, then
+/// uses prefix sums of those counts to determine the position of each key in the output.
+/// It runs in O(n + k) time and space for keys in `0..k`, so it only suits small key
+/// universes such as bytes or enum discriminants. The sort is stable.
+pub struct CountingSort;
+
+impl CountingSort {
+    pub fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "counting",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linear,
+            worst: Complexity::Linear,
+        }
+    }
+
+    pub fn sort<T: CountKey>(&self, slice: &mut [T]) {
+        self.sort_by_key(slice, T::count_key)
+    }
+
+    /// Sorts by a key computed once per element.
+    ///
+    /// A count array as large as the largest key is allocated.
+    pub fn sort_by_key<T, F>(&self, slice: &mut [T], key: F)
+    where
+        F: FnMut(&T) -> usize,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let keys: Vec<usize> = slice.iter().map(key).collect();
+        let mut order = counting_order(&keys);
+        permutation::apply(slice, &mut order);
+    }
+}
+
+/// The stable order that sorts `keys`: `order[destination] = source`.
+pub(crate) fn counting_order(keys: &[usize]) -> Vec<usize> {
+    let max = keys.iter().copied().max().unwrap_or(0);
+    let mut counts = vec![0usize; max + 1];
+    for &k in keys {
+        counts[k] += 1;
+    }
+    let mut total = 0;
+    for c in counts.iter_mut() {
+        let n = *c;
+        *c = total;
+        total += n;
+    }
+    let mut order = vec![0; keys.len()];
+    for (i, &k) in keys.iter().enumerate() {
+        order[counts[k]] = i;
+        counts[k] += 1;
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counting_works() {
+        let mut tings = vec![5u8, 1, 4, 2, 3, 1, 0, 255];
+        CountingSort.sort(&mut tings);
+        assert_eq!(tings, &[0, 1, 1, 2, 3, 4, 5, 255]);
+    }
+
+    #[test]
+    fn sorts_bools() {
+        let mut tings = vec![true, false, true, false];
+        CountingSort.sort(&mut tings);
+        assert_eq!(tings, &[false, false, true, true]);
+    }
+
+    #[test]
+    fn sort_by_key_is_stable() {
+        let mut tings = vec!["bb", "a", "cc", "d", "eee", ""];
+        CountingSort.sort_by_key(&mut tings, |s| s.len());
+        assert_eq!(tings, &["", "a", "d", "bb", "cc", "eee"]);
+    }
+}
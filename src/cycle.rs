@@ -0,0 +1,105 @@
+use crate::cmp::{self, Compare};
+use crate::{is_trivially_sorted, Complexity, Sorter, SorterInfo};
+
+/// Cycle Sort
+///
+/// Cycle sort decomposes the permutation that sorts the slice into cycles and rotates
+/// each cycle into place. The final position of an element is found by counting the
+/// elements smaller than it, and the element is swapped straight there, bringing back
+/// the element to place next. Every swap puts one element into its final position for
+/// good and an element already in place is never moved, so distinct elements forming
+/// c cycles take n - c swaps: the fewest possible, no more than even
+/// [`SelectionSort`](crate::SelectionSort) makes. This suits storage where writes are
+/// far costlier than reads, such as flash memory, at the price of quadratic comparisons.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CycleSort;
+
+impl Sorter for CycleSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "cycle",
+            stable: false,
+            in_place: true,
+            best: Complexity::Quadratic,
+            average: Complexity::Quadratic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        for start in 0..slice.len() - 1 {
+            // `slice[..start]` is final; send `slice[start]` home until it belongs here
+            loop {
+                let mut pos = start;
+                for i in start + 1..slice.len() {
+                    if cmp.is_less(&slice[i], &slice[start]) {
+                        pos += 1;
+                    }
+                }
+                if pos == start {
+                    break;
+                }
+                // equal elements already sent home come first
+                while !cmp.is_less(&slice[start], &slice[pos])
+                    && !cmp.is_less(&slice[pos], &slice[start])
+                {
+                    pos += 1;
+                }
+                cmp::swap(cmp, slice, start, pos);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Counting, SelectionSort};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn cycle_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        CycleSort.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for n in 0..80u64 {
+            let mut tings: Vec<_> = (0..n).map(|i| (i * 7919) % 7).collect();
+            CycleSort.sort(&mut tings);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn swaps_once_per_misplaced_element_and_cycle() {
+        // cycles (0 1), (2 3 4), (5)
+        let mut tings = vec![1, 0, 3, 4, 2, 5];
+        let sorter = Counting::new(CycleSort);
+        sorter.sort(&mut tings);
+        assert_eq!(tings, &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(sorter.stats().swaps, 6 - 3);
+
+        let sorter = Counting::new(CycleSort);
+        sorter.sort(&mut tings);
+        assert_eq!(sorter.stats().swaps, 0);
+    }
+
+    #[test]
+    fn never_swaps_more_than_selection_sort() {
+        for n in 0..60u64 {
+            // distinct, as 61 is prime
+            let tings: Vec<_> = (0..n).map(|i| (i * 7919) % 61).collect();
+            let cycle = Counting::new(CycleSort);
+            cycle.sort(&mut tings.clone());
+            let selection = Counting::new(SelectionSort);
+            selection.sort(&mut tings.clone());
+            assert!(cycle.stats().swaps <= selection.stats().swaps, "n = {}", n);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use crate::cmp::Compare;
+use crate::natural::{self, Powersort, MIN_GALLOP};
+#[cfg(feature = "std")]
+use crate::{cmp::ByFn, parallel, MergeSort};
+use crate::{is_trivially_sorted, Complexity, Sorter, SorterInfo, StableSorter};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::{cmp::Ordering, thread};
+/// Runs shorter than this are extended by binary insertion before merging.
+const MIN_RUN: usize = 24;
+/// Slices shorter than this are not worth splitting across threads.
+#[cfg(feature = "std")]
+const PARALLEL_CUTOFF: usize = 1 << 14;
+
+/// The sorter to reach for when no other is called for.
+///
+/// A stable, adaptive merge sort built from the crate's parts:
+///
+/// - the presorted runs of the input are found, strictly descending ones reversed, as
+///   in [`NaturalMergeSort`](crate::NaturalMergeSort);
+/// - runs shorter than 24 elements are extended to that length by binary insertion, so
+///   short slices and random input start from sorted blocks rather than single
+///   elements;
+/// - the runs are merged as [`Powersort`] decides, which stays within a constant of the
+///   optimal merge cost for the runs found, galloping through stretches where one run
+///   keeps winning.
+///
+/// Sorted or reversed input takes a single pass, input made of r runs takes
+/// O(n log r), and everything else O(n log n), with a scratch buffer of up to n
+/// indices. [`DefaultSorter::par_sort_by`] sorts on every core instead.
+///
+/// ```
+/// use pangua::{default_sorter, Sorter};
+///
+/// let mut tings = vec![5, 1, 4, 2, 3];
+/// default_sorter().sort(&mut tings);
+/// assert_eq!(tings, &[1, 2, 3, 4, 5]);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultSorter;
+
+/// The crate's recommended sorter, see [`DefaultSorter`].
+pub fn default_sorter() -> DefaultSorter {
+    DefaultSorter
+}
+
+impl Sorter for DefaultSorter {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "default",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        self.sort_with_scratch(slice, &mut Vec::new(), cmp)
+    }
+
+    fn sort_with_scratch<T, C>(&self, slice: &mut [T], scratch: &mut Vec<usize>, cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        natural::merge_runs(slice, &Powersort, MIN_RUN, MIN_GALLOP, scratch, cmp)
+    }
+}
+
+impl StableSorter for DefaultSorter {}
+
+impl DefaultSorter {
+    /// Sorts the slice on every core, as [`par_sort_by`](DefaultSorter::par_sort_by) does.
+    #[cfg(feature = "std")]
+    pub fn par_sort<T: Ord + Send>(&self, slice: &mut [T]) {
+        self.par_sort_by(slice, T::cmp)
+    }
+
+    /// Sorts equal parts of the slice on every core, then merges neighbouring parts in
+    /// parallel until one is left. Stable, like the sequential sort.
+    #[cfg(feature = "std")]
+    pub fn par_sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        let threads = parallel::threads();
+        if threads == 1 || slice.len() < PARALLEL_CUTOFF {
+            self.sort_by(slice, compare);
+            return;
+        }
+        let part = slice.len().div_ceil(threads);
+        let compare = &compare;
+        thread::scope(|scope| {
+            for chunk in slice.chunks_mut(part) {
+                scope.spawn(move || self.sort_by(chunk, compare));
+            }
+        });
+        par_merge(slice, part, compare);
+    }
+
+    /// Like [`par_sort_by`](DefaultSorter::par_sort_by), ordering by the keys `key` extracts.
+    #[cfg(feature = "std")]
+    pub fn par_sort_by_key<T, K, F>(&self, slice: &mut [T], key: F)
+    where
+        T: Send,
+        K: Ord,
+        F: Fn(&T) -> K + Sync,
+    {
+        self.par_sort_by(slice, |a, b| key(a).cmp(&key(b)))
+    }
+}
+
+/// Merges the sorted parts of `part` elements each, neighbouring pairs in parallel, until
+/// one is left. The merges gallop, as [`natural::merge_runs`] does, so parts that barely
+/// overlap take few comparisons.
+#[cfg(feature = "std")]
+fn par_merge<T, F>(slice: &mut [T], part: usize, compare: &F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let mut width = part;
+    while width < slice.len() {
+        thread::scope(|scope| {
+            for pair in slice
+                .chunks_mut(2 * width)
+                .filter(|pair| pair.len() > width)
+            {
+                scope.spawn(move || {
+                    let mut scratch = Vec::new();
+                    let cmp = &mut ByFn(compare);
+                    MergeSort::merge_galloping(pair, width, &mut scratch, MIN_GALLOP, cmp)
+                });
+            }
+        });
+        width *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counting;
+    use alloc::vec::Vec;
+    use rand::prelude::*;
+
+    #[test]
+    fn sorts_stably() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for &n in &[0, 1, 2, 23, 24, 25, 100, 1000] {
+            let mut tings: Vec<(u8, usize)> = (0..n).map(|i| (rng.gen_range(0, 8), i)).collect();
+            let mut expected = tings.clone();
+            expected.sort_by_key(|t| t.0);
+            DefaultSorter.sort_by_key(&mut tings, |t| t.0);
+            assert_eq!(tings, expected);
+        }
+    }
+
+    #[test]
+    fn adapts_to_runs() {
+        let n = 1 << 12;
+        let sorter = Counting::new(DefaultSorter);
+        let mut tings: Vec<usize> = (0..n).rev().collect();
+        sorter.sort(&mut tings);
+        assert!(sorter.stats().comparisons < n);
+        let mut tings: Vec<usize> = (0..n).map(|i| (i * 7919) % n).collect();
+        let sorter = Counting::new(DefaultSorter);
+        sorter.sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        assert!(sorter.stats().comparisons < n * 12);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn sorts_in_parallel() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut tings: Vec<(u16, usize)> = (0..100_000).map(|i| (rng.gen(), i)).collect();
+        let mut expected = tings.clone();
+        expected.sort_by_key(|t| t.0);
+        DefaultSorter.par_sort_by_key(&mut tings, |t| t.0);
+        assert_eq!(tings, expected);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn parallel_merges_gallop() {
+        use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+        // four sorted parts, each a few long blocks barely overlapping the others'
+        let part = 1 << 12;
+        let mut tings: Vec<usize> = (0..4 * part)
+            .map(|i| (i % part / 1024) * 4096 + (i / part) * 1024 + i % 1024)
+            .collect();
+        let mut expected = tings.clone();
+        expected.sort();
+        let comparisons = AtomicUsize::new(0);
+        par_merge(&mut tings, part, &|a: &usize, b: &usize| {
+            comparisons.fetch_add(1, Relaxed);
+            a.cmp(b)
+        });
+        assert_eq!(tings, expected);
+        assert!(comparisons.into_inner() < tings.len() / 4);
+    }
+}
@@ -0,0 +1,246 @@
+//! Order-preserving byte encodings of keys.
+//!
+//! [`KeyEncode`] turns a key into bytes that compare, as byte strings, the way the key
+//! compares. Any record can then be sorted by a plain `&[u8]`, with a byte-wise sort
+//! such as [`AmericanFlagSort`](crate::AmericanFlagSort), or have its key written out
+//! where only bytes fit. Encodings are also prefix-free, no encoding being the start of
+//! another of the same type, so the encodings of a tuple's fields can simply be
+//! concatenated.
+//!
+//! ```
+//! use pangua::encode::encode;
+//! use pangua::AmericanFlagSort;
+//! use std::cmp::Reverse;
+//!
+//! let mut tings = vec![("pear", -2i32), ("fig", 7), ("pear", 5), ("fig", -1)];
+//! // ORDER BY name, value DESC
+//! let mut keyed: Vec<_> = tings.iter().map(|t| (encode(&(t.0, Reverse(t.1))), *t)).collect();
+//! AmericanFlagSort.sort_by_key(&mut keyed, |(key, _)| key);
+//! let sorted: Vec<_> = keyed.into_iter().map(|(_, t)| t).collect();
+//! assert_eq!(sorted, [("fig", 7), ("fig", -1), ("pear", 5), ("pear", -2)]);
+//! ```
+
+use crate::Total;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+/// Keys with a byte encoding that orders as they do.
+///
+/// For any `a` and `b` of the type, the bytes `encode_key` appends for `a` must compare
+/// to those for `b` as `a` compares to `b`, and neither may be a proper prefix of the
+/// other.
+pub trait KeyEncode {
+    fn encode_key(&self, out: &mut Vec<u8>);
+}
+
+/// Encodes a key into a new vector.
+pub fn encode<K: KeyEncode + ?Sized>(key: &K) -> Vec<u8> {
+    let mut out = Vec::new();
+    key.encode_key(&mut out);
+    out
+}
+
+macro_rules! impl_key_encode_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl KeyEncode for $t {
+                #[inline]
+                fn encode_key(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_key_encode_signed {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl KeyEncode for $t {
+                #[inline]
+                fn encode_key(&self, out: &mut Vec<u8>) {
+                    // flipping the sign bit moves the negatives below the positives
+                    let flipped = (*self as $u) ^ (1 << (<$u>::BITS - 1));
+                    out.extend_from_slice(&flipped.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_key_encode_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_key_encode_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+macro_rules! impl_key_encode_float {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            /// Encodes the float's total order, as [`Total`] has it.
+            impl KeyEncode for Total<$t> {
+                #[inline]
+                fn encode_key(&self, out: &mut Vec<u8>) {
+                    let bits = self.0.to_bits();
+                    let sign = 1 << (<$u>::BITS - 1);
+                    // negatives order backwards by their bits, and below the positives
+                    let ordered = if bits & sign != 0 { !bits } else { bits | sign };
+                    out.extend_from_slice(&ordered.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_key_encode_float!(f32 => u32, f64 => u64);
+
+impl KeyEncode for bool {
+    #[inline]
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+}
+
+impl KeyEncode for char {
+    #[inline]
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        (*self as u32).encode_key(out)
+    }
+}
+
+/// Byte strings are terminated by `00 00`, with every zero byte inside escaped as
+/// `00 FF`, so that a string ending sorts before any longer one it begins.
+impl KeyEncode for [u8] {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        for &byte in self {
+            out.push(byte);
+            if byte == 0 {
+                out.push(0xFF);
+            }
+        }
+        out.extend_from_slice(&[0, 0]);
+    }
+}
+
+/// Encoded as its UTF-8 bytes, whose order is that of `str`.
+impl KeyEncode for str {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        self.as_bytes().encode_key(out)
+    }
+}
+
+impl KeyEncode for String {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        self.as_str().encode_key(out)
+    }
+}
+
+impl KeyEncode for Vec<u8> {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        self.as_slice().encode_key(out)
+    }
+}
+
+/// `None` first, as `Option`'s own order has it.
+impl<T: KeyEncode> KeyEncode for Option<T> {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(value) => {
+                out.push(1);
+                value.encode_key(out);
+            }
+        }
+    }
+}
+
+/// The inner encoding with every byte inverted, which reverses the order of encodings
+/// none of which is a prefix of another.
+impl<T: KeyEncode> KeyEncode for Reverse<T> {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        let start = out.len();
+        self.0.encode_key(out);
+        for byte in &mut out[start..] {
+            *byte = !*byte;
+        }
+    }
+}
+
+impl<T: KeyEncode + ?Sized> KeyEncode for &T {
+    fn encode_key(&self, out: &mut Vec<u8>) {
+        (**self).encode_key(out)
+    }
+}
+
+macro_rules! impl_key_encode_tuple {
+    ($($name:ident)+) => {
+        /// The fields' encodings one after the other, ordering lexicographically.
+        impl<$($name: KeyEncode),+> KeyEncode for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn encode_key(&self, out: &mut Vec<u8>) {
+                let ($($name,)+) = self;
+                $($name.encode_key(out);)+
+            }
+        }
+    };
+}
+
+impl_key_encode_tuple!(A);
+impl_key_encode_tuple!(A B);
+impl_key_encode_tuple!(A B C);
+impl_key_encode_tuple!(A B C D);
+impl_key_encode_tuple!(A B C D E);
+impl_key_encode_tuple!(A B C D E F);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use rand::prelude::*;
+
+    fn check_order<K: KeyEncode + Ord + core::fmt::Debug>(mut keys: Vec<K>) {
+        keys.sort();
+        let encoded: Vec<Vec<u8>> = keys.iter().map(encode).collect();
+        for (k, w) in keys.windows(2).zip(encoded.windows(2)) {
+            assert_eq!(k[0].cmp(&k[1]), w[0].cmp(&w[1]), "{:?}", k);
+            assert!(!w[1].starts_with(&w[0]) || w[0] == w[1], "{:?}", k);
+        }
+    }
+
+    #[test]
+    fn encodings_order_as_keys() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let words = ["", "a", "a\0", "a\0b", "ab", "b", "\0", "\u{e9}"];
+        let tings: Vec<(i16, String, Option<u8>, Reverse<i64>)> = (0..500)
+            .map(|_| {
+                (
+                    rng.gen_range(-3, 3),
+                    words.choose(&mut rng).unwrap().to_string(),
+                    if rng.gen() { Some(rng.gen()) } else { None },
+                    Reverse(rng.gen_range(-2, 2) << 40),
+                )
+            })
+            .collect();
+        check_order(tings);
+        check_order(vec![i8::MIN, -1, 0, 1, i8::MAX]);
+        check_order(vec![Reverse(b"ab".to_vec()), Reverse(b"a".to_vec())]);
+        check_order(vec![('z', true), ('a', false), ('a', true)]);
+    }
+
+    #[test]
+    fn floats_encode_their_total_order() {
+        let floats = [
+            -f64::NAN,
+            f64::NEG_INFINITY,
+            -2.5,
+            -0.0,
+            0.0,
+            1e-300,
+            7.0,
+            f64::INFINITY,
+            f64::NAN,
+        ];
+        let keys: Vec<Total<f64>> = floats.iter().copied().map(Total).collect();
+        check_order(keys);
+        assert!(encode(&Total(-1.0f32)) < encode(&Total(1.0f32)));
+    }
+}
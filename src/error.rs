@@ -0,0 +1,73 @@
+//! The error type shared by the crate's fallible operations.
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
+
+/// Errors reported by misconfigured sorters and by checked sorts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// A radix sorter was configured with digits of zero bits.
+    ZeroRadixBits,
+    /// A bucket sorter was configured with no buckets.
+    ZeroBuckets,
+    /// A bounded structure was configured to hold no elements.
+    ZeroCapacity,
+    /// A comb sorter was configured with a gap shrink factor not greater than 1.
+    InvalidShrink,
+    /// A flashsort was configured with a class ratio that is not positive and finite.
+    InvalidClassRatio,
+    /// A k-way merge was configured to merge fewer than two inputs at a time.
+    FanInTooSmall { fan_in: usize },
+    /// A heap sorter was configured with fewer than two children per node.
+    ArityTooSmall { d: usize },
+    /// A pigeonhole sort would need `range` holes for only `len` elements.
+    RangeTooLarge { range: u128, len: usize },
+    /// A randomized sort was still unsorted after its allowed number of attempts.
+    GaveUp { attempts: usize },
+    /// `slice[index]` is ordered before `slice[index - 1]` after sorting.
+    NotSorted { index: usize },
+    /// The elements after sorting are not the elements before it.
+    ChecksumMismatch { before: u64, after: u64 },
+    /// A bounded sort was aborted through its cancel flag.
+    Cancelled,
+    /// A bounded sort was still running at its deadline.
+    DeadlineExceeded,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ZeroRadixBits => f.write_str("radix width must be positive"),
+            Error::ZeroBuckets => f.write_str("bucket count must be positive"),
+            Error::ZeroCapacity => f.write_str("capacity must be positive"),
+            Error::InvalidShrink => f.write_str("shrink factor must be greater than 1"),
+            Error::InvalidClassRatio => f.write_str("class ratio must be positive and finite"),
+            Error::FanInTooSmall { fan_in } => {
+                write!(f, "fan-in must be at least 2, got {}", fan_in)
+            }
+            Error::ArityTooSmall { d } => {
+                write!(f, "heap arity must be at least 2, got {}", d)
+            }
+            Error::RangeTooLarge { range, len } => write!(
+                f,
+                "key range of {} is too wide to pigeonhole {} elements",
+                range, len
+            ),
+            Error::GaveUp { attempts } => write!(f, "gave up after {} attempts", attempts),
+            Error::NotSorted { index } => {
+                write!(f, "output is out of order at index {}", index)
+            }
+            Error::ChecksumMismatch { before, after } => write!(
+                f,
+                "output is not a permutation of the input (checksum {:#x}, expected {:#x})",
+                after, before
+            ),
+            Error::Cancelled => f.write_str("sort was cancelled"),
+            Error::DeadlineExceeded => f.write_str("sort did not finish before its deadline"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for Error {}
@@ -0,0 +1,308 @@
+//! The exchange sorts closest to bubble sort, mostly of interest for teaching.
+
+use crate::cmp::{self, Compare};
+use crate::{is_trivially_sorted, Complexity, Error, Sorter, SorterInfo, StableSorter};
+
+/// Cocktail Shaker Sort
+///
+/// Cocktail shaker sort is a bubble sort that alternates direction: a forward pass
+/// carries the largest element to the end, a backward pass carries the smallest to the
+/// front. Small elements near the end, which bubble sort moves one step per pass, get
+/// to the front in a single backward pass.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CocktailShakerSort;
+
+impl Sorter for CocktailShakerSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "cocktail-shaker",
+            stable: true,
+            in_place: true,
+            best: Complexity::Linear,
+            average: Complexity::Quadratic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        // everything outside [lo, hi] is already in its final place
+        let (mut lo, mut hi) = (0, slice.len() - 1);
+        while lo < hi {
+            let mut last_swap = lo;
+            for i in lo..hi {
+                if cmp.is_less(&slice[i + 1], &slice[i]) {
+                    cmp::swap(cmp, slice, i, i + 1);
+                    last_swap = i;
+                }
+            }
+            hi = last_swap;
+            for i in (lo..hi).rev() {
+                if cmp.is_less(&slice[i + 1], &slice[i]) {
+                    cmp::swap(cmp, slice, i, i + 1);
+                    last_swap = i + 1;
+                }
+            }
+            lo = last_swap;
+        }
+    }
+}
+
+impl StableSorter for CocktailShakerSort {}
+
+/// Gnome Sort
+///
+/// Gnome sort walks the slice like a garden gnome sorting flower pots: if the pot in
+/// front is in order it steps forward, otherwise it swaps the two pots and steps back.
+/// This is insertion sort where the sorted prefix is regrown after each insertion.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GnomeSort;
+
+impl Sorter for GnomeSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "gnome",
+            stable: true,
+            in_place: true,
+            best: Complexity::Linear,
+            average: Complexity::Quadratic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut pos = 1;
+        while pos < slice.len() {
+            if cmp.is_less(&slice[pos], &slice[pos - 1]) {
+                cmp::swap(cmp, slice, pos - 1, pos);
+                pos = (pos - 1).max(1);
+            } else {
+                pos += 1;
+            }
+        }
+    }
+}
+
+impl StableSorter for GnomeSort {}
+
+/// Comb Sort
+///
+/// Comb sort is a bubble sort comparing elements `gap` apart, the gap shrinking by
+/// `shrink` after every pass until it reaches 1, where passes repeat until nothing is
+/// swapped. The early, wide passes move small elements near the end ("turtles") far
+/// towards the front, which is what makes bubble sort slow.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CombSort {
+    /// Factor the gap is divided by after each pass, greater than 1. About 1.3 works best.
+    pub shrink: f64,
+}
+
+impl CombSort {
+    /// Creates a comb sorter dividing the gap by `shrink`, which must be greater than 1.
+    pub fn new(shrink: f64) -> Result<Self, Error> {
+        let sorter = CombSort { shrink };
+        sorter.validate()?;
+        Ok(sorter)
+    }
+
+    /// Checks the configuration, as the sort itself would before starting.
+    pub fn validate(&self) -> Result<(), Error> {
+        // also rejects NaN
+        if !(self.shrink > 1.0 && self.shrink.is_finite()) {
+            return Err(Error::InvalidShrink);
+        }
+        Ok(())
+    }
+}
+
+impl Default for CombSort {
+    fn default() -> Self {
+        CombSort { shrink: 1.3 }
+    }
+}
+
+impl Sorter for CombSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "comb",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linearithmic,
+            average: Complexity::Quadratic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if let Err(e) = self.validate() {
+            panic!("{}", e)
+        }
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut gap = slice.len();
+        loop {
+            gap = ((gap as f64 / self.shrink) as usize).max(1);
+            let mut swapped = false;
+            for i in 0..slice.len().saturating_sub(gap) {
+                if cmp.is_less(&slice[i + gap], &slice[i]) {
+                    cmp::swap(cmp, slice, i, i + gap);
+                    swapped = true;
+                }
+            }
+            if gap == 1 && !swapped {
+                return;
+            }
+        }
+    }
+}
+
+/// Odd-Even Sort
+///
+/// Odd-even transposition sort alternates between comparing every pair at an odd index
+/// with its right neighbour, and every pair at an even index. The comparisons within a
+/// pass are independent of each other, so on a parallel machine each pass takes one
+/// step, and n passes always suffice.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OddEvenSort;
+
+impl Sorter for OddEvenSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "odd-even",
+            stable: true,
+            in_place: true,
+            best: Complexity::Linear,
+            average: Complexity::Quadratic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut sorted = false;
+        while !sorted {
+            sorted = true;
+            for start in [1, 0] {
+                for i in (start..slice.len() - 1).step_by(2) {
+                    if cmp.is_less(&slice[i + 1], &slice[i]) {
+                        cmp::swap(cmp, slice, i, i + 1);
+                        sorted = false;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl StableSorter for OddEvenSort {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BubbleSort, Counting};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn check<S: Sorter>(sorter: S) {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        sorter.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for n in 0..60u64 {
+            let mut tings: Vec<_> = (0..n).map(|i| (i * 7919) % 13).collect();
+            sorter.sort(&mut tings);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]), "n = {}", n);
+        }
+    }
+
+    fn check_stable<S: Sorter>(sorter: S) {
+        let mut tings: Vec<_> = (0..200).map(|i| ((i * 37) % 11, i)).collect();
+        sorter.sort_by_key(&mut tings, |t| t.0);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn cocktail_shaker_works() {
+        check(CocktailShakerSort);
+        check_stable(CocktailShakerSort);
+    }
+
+    #[test]
+    fn cocktail_shaker_moves_turtles_in_one_pass() {
+        let mut tings: Vec<_> = (1..100).chain(Some(0)).collect();
+        let sorter = Counting::new(CocktailShakerSort);
+        sorter.sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        // one forward pass, one backward pass, and a final check
+        assert!(sorter.stats().comparisons < 3 * 100);
+
+        let bubble = Counting::new(BubbleSort);
+        let mut tings: Vec<_> = (1..100).chain(Some(0)).collect();
+        bubble.sort(&mut tings);
+        assert!(bubble.stats().comparisons > 99 * 99);
+    }
+
+    #[test]
+    fn gnome_works() {
+        check(GnomeSort);
+        check_stable(GnomeSort);
+    }
+
+    #[test]
+    fn comb_works() {
+        check(CombSort::default());
+        check(CombSort::new(2.0).unwrap());
+        check(CombSort::new(1.01).unwrap());
+    }
+
+    #[test]
+    fn comb_rejects_bad_shrink() {
+        for &shrink in &[1.0, 0.5, -2.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(CombSort::new(shrink), Err(Error::InvalidShrink));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "shrink factor")]
+    fn comb_panics_on_bad_shrink() {
+        CombSort { shrink: 1.0 }.sort(&mut [2, 1]);
+    }
+
+    #[test]
+    fn odd_even_works() {
+        check(OddEvenSort);
+        check_stable(OddEvenSort);
+    }
+
+    #[test]
+    fn adapt_to_sorted_input() {
+        fn check_linear<S: Sorter>(sorter: S) {
+            let sorter = Counting::new(sorter);
+            sorter.sort(&mut (0..1000).collect::<Vec<_>>());
+            assert!(sorter.stats().comparisons < 1000, "{}", sorter.info().name);
+            assert_eq!(sorter.stats().swaps, 0);
+        }
+        check_linear(CocktailShakerSort);
+        check_linear(GnomeSort);
+        check_linear(OddEvenSort);
+    }
+}
@@ -0,0 +1,1377 @@
+//! Sorting more records than fit in memory.
+//!
+//! [`ExternalSorter`] reads records into a buffer of a bounded size, sorts each full
+//! buffer with an in-memory [`Sorter`] and spills it as a sorted run to a
+//! [`SpillStore`], temporary files by default. The runs are then merged back with a
+//! [`LoserTree`], in several passes if there are too many to merge at once, and the
+//! last merge is handed back as an iterator over the sorted records. Input that fits in
+//! the budget never touches the store.
+//!
+//! Records are written to runs through [`Record`], implemented here for integers,
+//! strings, byte vectors and pairs of records.
+//!
+//! [`FileMerger`] is the merge half on its own, for files already sorted by an earlier
+//! run or another process.
+//!
+//! ```
+//! use pangua::external::ExternalSorter;
+//! use pangua::IntroSort;
+//!
+//! let mut sorter = ExternalSorter::new(IntroSort::default());
+//! sorter.memory_budget = 4096;
+//! let sorted: Vec<u64> = sorter
+//!     .sort((0..10_000u64).rev())
+//!     .unwrap()
+//!     .collect::<Result<_, _>>()
+//!     .unwrap();
+//! assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+//! ```
+
+use crate::cmp::{ByFn, ByOrd, Compare};
+use crate::spill::{read_header, write_run, FileSpillStore, RunHeader, RunId, SpillStore};
+use crate::{parallel, IntroSort, KWayMerge, LoserTree, Sorter};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::marker::PhantomData;
+use std::mem;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::mpsc::{self, Receiver, RecvError, TrySendError};
+use std::sync::{Mutex, PoisonError};
+use std::thread::{self, Scope, Thread};
+use std::vec;
+
+/// Bytes of memory records are buffered in by default.
+const MEMORY_BUDGET: usize = 64 * 1024 * 1024;
+/// Bytes of buffering in front of every run being merged.
+const READ_BUFFER: usize = 64 * 1024;
+
+/// A record that can be written to a run and read back.
+pub trait Record: Sized {
+    /// Appends the encoding of the record to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Reads back a record written by [`Record::encode`].
+    fn decode<R: Read>(input: &mut R) -> io::Result<Self>;
+
+    /// Bytes the record takes in memory, including what it owns on the heap.
+    fn memory_size(&self) -> usize {
+        mem::size_of::<Self>()
+    }
+}
+
+macro_rules! impl_record_for_int {
+    ($($t:ty)*) => {$(
+        impl Record for $t {
+            fn encode(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn decode<R: Read>(input: &mut R) -> io::Result<Self> {
+                let mut bytes = [0; mem::size_of::<$t>()];
+                input.read_exact(&mut bytes)?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+
+impl_record_for_int!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+impl Record for Vec<u8> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u64).encode(out);
+        out.extend_from_slice(self);
+    }
+
+    fn decode<R: Read>(input: &mut R) -> io::Result<Self> {
+        let len = u64::decode(input)?;
+        let mut bytes = Vec::new();
+        input.take(len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != len {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        Ok(bytes)
+    }
+
+    fn memory_size(&self) -> usize {
+        mem::size_of::<Self>() + self.capacity()
+    }
+}
+
+impl Record for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u64).encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode<R: Read>(input: &mut R) -> io::Result<Self> {
+        String::from_utf8(Vec::decode(input)?)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+    }
+
+    fn memory_size(&self) -> usize {
+        mem::size_of::<Self>() + self.capacity()
+    }
+}
+
+impl<A: Record, B: Record> Record for (A, B) {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+        self.1.encode(out);
+    }
+
+    fn decode<R: Read>(input: &mut R) -> io::Result<Self> {
+        Ok((A::decode(input)?, B::decode(input)?))
+    }
+
+    fn memory_size(&self) -> usize {
+        // both halves count their own inline size already
+        self.0.memory_size() + self.1.memory_size()
+    }
+}
+
+/// External Merge Sort
+///
+/// Sorts an iterator of records in about `memory_budget` bytes: half of it holds
+/// records, as measured by [`Record::memory_size`], and the other half their encoding
+/// while a run is spilled. The merge reads every run through a buffer of 64 KiB, so it
+/// merges as many runs at once as the budget has room for, and at least two.
+///
+/// The merge keeps equal records in the order of their runs, so the sort is stable
+/// when `sorter` is.
+///
+/// [`ExternalSorter::par_sort_in`] merges the groups of runs of every intermediate pass
+/// on `merge_workers` threads at once, each reading its runs ahead and writing its
+/// output behind on threads of their own, so that disks and cores are kept busy
+/// together.
+#[derive(Debug, Clone)]
+pub struct ExternalSorter<S = IntroSort> {
+    pub memory_budget: usize,
+    /// Where [`ExternalSorter::sort`] creates the directory for its runs.
+    pub temp_dir: PathBuf,
+    /// Sorts the runs in memory.
+    pub sorter: S,
+    /// Groups of runs merged at once by [`ExternalSorter::par_sort_in`], or 0 for one
+    /// per core.
+    pub merge_workers: usize,
+}
+
+impl Default for ExternalSorter {
+    fn default() -> Self {
+        ExternalSorter::new(IntroSort::default())
+    }
+}
+
+impl<S: Sorter> ExternalSorter<S> {
+    /// Creates an external sorter with a budget of 64 MiB, spilling to the system's
+    /// temporary directory.
+    pub fn new(sorter: S) -> Self {
+        ExternalSorter {
+            memory_budget: MEMORY_BUDGET,
+            temp_dir: env::temp_dir(),
+            sorter,
+            merge_workers: 0,
+        }
+    }
+
+    /// Runs merged together in one pass.
+    pub fn fan_in<T: Record, R: Read>(&self) -> usize {
+        KWayMerge::from_memory_budget::<RunReader<T, R>>(self.memory_budget, READ_BUFFER)
+            .map_or(2, |merge| merge.fan_in)
+    }
+
+    /// Sorts the records, spilling runs to temporary files.
+    pub fn sort<T, I>(&self, input: I) -> io::Result<Sorted<T, FileSpillStore, ByOrd>>
+    where
+        T: Record + Ord,
+        I: IntoIterator<Item = T>,
+    {
+        self.sort_in(input, FileSpillStore::new(&self.temp_dir)?, ByOrd)
+    }
+
+    /// Sorts the records in the order defined by `compare`, spilling runs to temporary
+    /// files.
+    pub fn sort_by<T, I, F>(
+        &self,
+        input: I,
+        compare: F,
+    ) -> io::Result<Sorted<T, FileSpillStore, ByFn<F>>>
+    where
+        T: Record,
+        I: IntoIterator<Item = T>,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.sort_in(input, FileSpillStore::new(&self.temp_dir)?, ByFn(compare))
+    }
+
+    /// Sorts the records in the order defined by `cmp`, spilling runs to `store`.
+    ///
+    /// Runs already in the store are left alone.
+    pub fn sort_in<T, I, St, C>(&self, input: I, store: St, cmp: C) -> io::Result<Sorted<T, St, C>>
+    where
+        T: Record,
+        I: IntoIterator<Item = T>,
+        St: SpillStore,
+        C: Compare<T>,
+    {
+        self.sort_after(input, Vec::new(), store, cmp)
+    }
+
+    /// Resumes a sort into `store` that failed after committing some of its runs, as
+    /// one whose disk filled up may, without sorting their records again.
+    ///
+    /// `input` must yield the records the failed sort was given, in the same order.
+    /// Runs are spilled in input order, so as many records as the store's runs hold are
+    /// skipped, and the rest are sorted and merged with those runs. A run being written
+    /// when the sort failed was never committed and is not counted. A sort that failed
+    /// while removing runs it had merged cannot be resumed, as their records are then in
+    /// two runs.
+    pub fn resume_in<T, I, St, C>(
+        &self,
+        input: I,
+        store: St,
+        cmp: C,
+    ) -> io::Result<Sorted<T, St, C>>
+    where
+        T: Record,
+        I: IntoIterator<Item = T>,
+        St: SpillStore,
+        C: Compare<T>,
+    {
+        let runs = store.runs();
+        let mut spilled = 0;
+        for &id in &runs {
+            spilled += RunReader::<T, _>::open(&store, id)?.header.elements;
+        }
+        let rest = input.into_iter().skip(spilled as usize);
+        self.sort_after(rest, runs, store, cmp)
+    }
+
+    /// Sorts the records into `store`, merging them with the sorted `runs` already in it.
+    fn sort_after<T, I, St, C>(
+        &self,
+        input: I,
+        mut runs: Vec<RunId>,
+        mut store: St,
+        mut cmp: C,
+    ) -> io::Result<Sorted<T, St, C>>
+    where
+        T: Record,
+        I: IntoIterator<Item = T>,
+        St: SpillStore,
+        C: Compare<T>,
+    {
+        let buffer = self.spill_all(input, &mut runs, &mut store, &mut cmp)?;
+        if runs.is_empty() {
+            return Ok(Sorted {
+                output: Output::Memory(buffer.into_iter()),
+                store,
+            });
+        }
+        let fan_in = self.fan_in::<T, St::Reader>();
+        while runs.len() > fan_in {
+            let mut next = Vec::with_capacity(runs.len().div_ceil(fan_in));
+            for group in runs.chunks(fan_in) {
+                next.push(merge_runs(&mut store, group, &mut cmp)?);
+            }
+            runs = next;
+        }
+        Sorted::merge(&runs, store, cmp)
+    }
+
+    /// Like [`ExternalSorter::sort_in`], merging the groups of runs of each intermediate
+    /// pass on up to `merge_workers` threads at once.
+    ///
+    /// A worker reads its runs ahead of the merge on one thread, a block of each at a
+    /// time, and writes the merged run on another, handing over each block as it fills,
+    /// so reading, merging and writing overlap. The runs come out of a pass in the order
+    /// of their groups, so the sort is as stable as [`ExternalSorter::sort_in`]. The
+    /// last pass is the returned iterator, as it is there.
+    pub fn par_sort_in<T, I, St, F>(
+        &self,
+        input: I,
+        mut store: St,
+        compare: F,
+    ) -> io::Result<Sorted<T, St, ByFn<F>>>
+    where
+        T: Record + Send,
+        I: IntoIterator<Item = T>,
+        St: SpillStore + Send,
+        St::Reader: Send,
+        St::Writer: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        let mut runs = Vec::new();
+        let buffer = self.spill_all(input, &mut runs, &mut store, &mut ByFn(&compare))?;
+        if runs.is_empty() {
+            return Ok(Sorted {
+                output: Output::Memory(buffer.into_iter()),
+                store,
+            });
+        }
+        let workers = match self.merge_workers {
+            0 => parallel::threads(),
+            workers => workers,
+        };
+        let fan_in = self.fan_in::<T, St::Reader>();
+        while runs.len() > fan_in {
+            runs = par_merge_pass(&mut store, &runs, fan_in, workers, &compare)?;
+        }
+        Sorted::merge(&runs, store, ByFn(compare))
+    }
+
+    /// Sorts the records and spills them as runs to `store`, appended to `runs`.
+    ///
+    /// Returns the records instead, sorted, when they fit in the budget and there were
+    /// no runs to begin with.
+    fn spill_all<T, I, St, C>(
+        &self,
+        input: I,
+        runs: &mut Vec<RunId>,
+        store: &mut St,
+        cmp: &mut C,
+    ) -> io::Result<Vec<T>>
+    where
+        T: Record,
+        I: IntoIterator<Item = T>,
+        St: SpillStore,
+        C: Compare<T>,
+    {
+        let limit = self.memory_budget / 2;
+        let mut buffer = Vec::new();
+        let mut used = 0;
+        let mut encoded = Vec::new();
+        for record in input {
+            used += record.memory_size();
+            buffer.push(record);
+            if used >= limit {
+                runs.push(self.spill(store, &mut buffer, &mut encoded, cmp)?);
+                used = 0;
+            }
+        }
+        if runs.is_empty() {
+            self.sorter.sort_with(&mut buffer, cmp);
+        } else if !buffer.is_empty() {
+            runs.push(self.spill(store, &mut buffer, &mut encoded, cmp)?);
+        }
+        Ok(buffer)
+    }
+
+    /// Sorts the buffered records and writes them to the store as a run.
+    fn spill<T, St, C>(
+        &self,
+        store: &mut St,
+        buffer: &mut Vec<T>,
+        encoded: &mut Vec<u8>,
+        cmp: &mut C,
+    ) -> io::Result<RunId>
+    where
+        T: Record,
+        St: SpillStore,
+        C: Compare<T>,
+    {
+        self.sorter.sort_with(buffer, cmp);
+        encoded.clear();
+        for record in buffer.iter() {
+            record.encode(encoded);
+        }
+        let id = write_run(store, buffer.len() as u64, encoded)?;
+        buffer.clear();
+        Ok(id)
+    }
+}
+
+/// Merges the runs `ids` into a new run, removing them once it is committed.
+fn merge_runs<T, St, C>(store: &mut St, ids: &[RunId], cmp: &mut C) -> io::Result<RunId>
+where
+    T: Record,
+    St: SpillStore,
+    C: Compare<T>,
+{
+    let mut readers = Vec::with_capacity(ids.len());
+    // records encode the same wherever they are, so the counts just add up
+    let mut header = RunHeader {
+        elements: 0,
+        bytes: 0,
+    };
+    for &id in ids {
+        let reader = RunReader::<T, _>::open(store, id)?;
+        header.elements += reader.header.elements;
+        header.bytes += reader.header.bytes;
+        readers.push(reader);
+    }
+    let mut out = BufWriter::new(store.create()?);
+    out.write_all(&header.encode())?;
+    let mut encoded = Vec::new();
+    for record in LoserTree::with_compare(readers, ErrorsFirst(&mut *cmp)) {
+        encoded.clear();
+        record?.encode(&mut encoded);
+        out.write_all(&encoded)?;
+    }
+    let run = out.into_inner().map_err(|e| e.into_error())?;
+    let merged = store.commit(run)?;
+    for &id in ids {
+        store.remove(id)?;
+    }
+    Ok(merged)
+}
+
+/// Merges each group of `fan_in` runs into a new run, on up to `workers` threads at
+/// once, and returns the new runs in the order of their groups.
+fn par_merge_pass<T, St, F>(
+    store: &mut St,
+    runs: &[RunId],
+    fan_in: usize,
+    workers: usize,
+    compare: &F,
+) -> io::Result<Vec<RunId>>
+where
+    T: Record + Send,
+    St: SpillStore + Send,
+    St::Reader: Send,
+    St::Writer: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let groups: Vec<&[RunId]> = runs.chunks(fan_in).collect();
+    let store = Mutex::new(store);
+    let next = AtomicUsize::new(0);
+    let mut merged: Vec<(usize, io::Result<RunId>)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..workers.clamp(1, groups.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut merged = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, AtomicOrdering::Relaxed);
+                        let group = match groups.get(i) {
+                            Some(&group) => group,
+                            None => return merged,
+                        };
+                        let run = par_merge_runs::<T, _, _>(&store, group, compare);
+                        let failed = run.is_err();
+                        merged.push((i, run));
+                        if failed {
+                            // let the other workers stop at their next group
+                            next.store(groups.len(), AtomicOrdering::Relaxed);
+                            return merged;
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("merge worker panicked"))
+            .collect()
+    });
+    merged.sort_by_key(|&(i, _)| i);
+    merged.into_iter().map(|(_, run)| run).collect()
+}
+
+/// Merges the runs `ids` into a new run as [`merge_runs`] does, reading the runs ahead
+/// of the merge on one thread and writing the new run behind it on another.
+fn par_merge_runs<T, St, F>(store: &Mutex<&mut St>, ids: &[RunId], compare: &F) -> io::Result<RunId>
+where
+    T: Record,
+    St: SpillStore,
+    St::Reader: Send,
+    St::Writer: Send,
+    F: Fn(&T, &T) -> Ordering,
+{
+    let lock = || store.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut inputs = Vec::with_capacity(ids.len());
+    let mut headers = Vec::with_capacity(ids.len());
+    let mut header = RunHeader {
+        elements: 0,
+        bytes: 0,
+    };
+    for &id in ids {
+        let mut input = lock().open(id)?;
+        let run = read_header(&mut input)?;
+        header.elements += run.elements;
+        header.bytes += run.bytes;
+        inputs.push(input);
+        headers.push(run);
+    }
+    let mut out = lock().create()?;
+    let run = thread::scope(|scope| {
+        let (blocks, written) = mpsc::sync_channel::<Vec<u8>>(1);
+        let writer = scope.spawn(move || {
+            out.write_all(&header.encode())?;
+            for block in written {
+                out.write_all(&block)?;
+            }
+            out.flush()?;
+            Ok::<_, io::Error>(out)
+        });
+        // the prefetched blocks are the buffer
+        let readers = prefetch(scope, inputs)
+            .into_iter()
+            .zip(headers)
+            .map(|(input, header)| {
+                RunReader::<T, _>::new(BufReader::with_capacity(0, input), header)
+            });
+        let mut block = Vec::with_capacity(BLOCK);
+        for record in LoserTree::with_compare(readers, ErrorsFirst(ByFn(compare))) {
+            record?.encode(&mut block);
+            if block.len() >= BLOCK && blocks.send(mem::take(&mut block)).is_err() {
+                // the writer failed, and says why below
+                break;
+            }
+        }
+        if !block.is_empty() {
+            // a failed writer reports its error when joined
+            let _ = blocks.send(block);
+        }
+        drop(blocks);
+        writer.join().expect("run writer panicked")
+    })?;
+    let merged = lock().commit(run)?;
+    for &id in ids {
+        lock().remove(id)?;
+    }
+    Ok(merged)
+}
+
+/// Bytes read or written at a time by [`par_merge_runs`]: a run's block being merged,
+/// the next one queued and one more being read fit in the run's read buffer.
+const BLOCK: usize = READ_BUFFER / 4;
+
+/// Reads the inputs ahead on one thread of `scope`, keeping the next block of each
+/// queued while the current one is consumed.
+fn prefetch<'scope, R>(scope: &'scope Scope<'scope, '_>, inputs: Vec<R>) -> Vec<Prefetched>
+where
+    R: Read + Send + 'scope,
+{
+    let (senders, receivers): (Vec<_>, Vec<_>) =
+        inputs.iter().map(|_| mpsc::sync_channel(1)).unzip();
+    let reader = scope.spawn(move || {
+        let mut inputs: Vec<_> = inputs
+            .into_iter()
+            .zip(senders)
+            .map(|(input, blocks)| Some((input, blocks, None)))
+            .collect();
+        while inputs.iter().any(Option::is_some) {
+            let mut progress = false;
+            for slot in inputs.iter_mut() {
+                let (input, blocks, pending) = match slot {
+                    Some(slot) => slot,
+                    None => continue,
+                };
+                let block = pending.take().unwrap_or_else(|| read_block(input));
+                let last = !matches!(&block, Ok(block) if !block.is_empty());
+                match blocks.try_send(block) {
+                    Ok(()) if !last => {}
+                    Err(TrySendError::Full(block)) => {
+                        *pending = Some(block);
+                        continue;
+                    }
+                    // sent the end of the input, or its reader went away
+                    _ => *slot = None,
+                }
+                progress = true;
+            }
+            if !progress {
+                // woken once a block is taken, or a reader goes away
+                thread::park();
+            }
+        }
+    });
+    let reader = reader.thread().clone();
+    receivers
+        .into_iter()
+        .map(|blocks| Prefetched {
+            blocks,
+            block: Vec::new(),
+            pos: 0,
+            reader: reader.clone(),
+        })
+        .collect()
+}
+
+/// Reads up to a block from `input`, empty at its end.
+fn read_block<R: Read>(input: &mut R) -> io::Result<Vec<u8>> {
+    let mut block = Vec::with_capacity(BLOCK);
+    input.take(BLOCK as u64).read_to_end(&mut block)?;
+    Ok(block)
+}
+
+/// An input read ahead by [`prefetch`], a block at a time.
+struct Prefetched {
+    blocks: Receiver<io::Result<Vec<u8>>>,
+    block: Vec<u8>,
+    pos: usize,
+    reader: Thread,
+}
+
+impl Read for Prefetched {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.block.len() {
+            let next = self.blocks.recv();
+            self.reader.unpark();
+            match next {
+                Ok(block) => {
+                    self.block = block?;
+                    self.pos = 0;
+                }
+                Err(RecvError) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.block.len() - self.pos);
+        buf[..n].copy_from_slice(&self.block[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl Drop for Prefetched {
+    fn drop(&mut self) {
+        self.reader.unpark();
+    }
+}
+
+/// Reads the records of a run one by one.
+struct RunReader<T, R> {
+    input: BufReader<R>,
+    header: RunHeader,
+    remaining: u64,
+    _records: PhantomData<T>,
+}
+
+impl<T: Record, R: Read> RunReader<T, R> {
+    fn open<St: SpillStore<Reader = R>>(store: &St, id: RunId) -> io::Result<Self> {
+        let mut input = BufReader::with_capacity(READ_BUFFER, store.open(id)?);
+        let header = read_header(&mut input)?;
+        Ok(RunReader::new(input, header))
+    }
+
+    /// Reads the records of a run whose header has been read already.
+    fn new(input: BufReader<R>, header: RunHeader) -> Self {
+        RunReader {
+            input,
+            header,
+            remaining: header.elements,
+            _records: PhantomData,
+        }
+    }
+}
+
+impl<T: Record, R: Read> Iterator for RunReader<T, R> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<io::Result<T>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let record = T::decode(&mut self.input);
+        // a run that failed to read is over
+        self.remaining = if record.is_ok() {
+            self.remaining - 1
+        } else {
+            0
+        };
+        Some(record)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Orders read errors before any record, so the merge reports them right away.
+struct ErrorsFirst<C>(C);
+
+impl<T, C: Compare<T>> Compare<io::Result<T>> for ErrorsFirst<C> {
+    fn compare(&mut self, a: &io::Result<T>, b: &io::Result<T>) -> Ordering {
+        match (a, b) {
+            (Ok(a), Ok(b)) => self.0.compare(a, b),
+            (Err(_), Err(_)) => Ordering::Equal,
+            (Err(_), Ok(_)) => Ordering::Less,
+            (Ok(_), Err(_)) => Ordering::Greater,
+        }
+    }
+}
+
+enum Output<T: Record, R: Read, C> {
+    Memory(vec::IntoIter<T>),
+    Merge(LoserTree<RunReader<T, R>, ErrorsFirst<C>>),
+}
+
+/// The sorted records of an [`ExternalSorter`], read back from the store they were
+/// spilled to.
+///
+/// Yields an error and then ends if a run cannot be read. The store, and with it any
+/// temporary files, lives as long as the iterator.
+pub struct Sorted<T: Record, S: SpillStore, C> {
+    output: Output<T, S::Reader, C>,
+    store: S,
+}
+
+impl<T: Record, S: SpillStore, C> Sorted<T, S, C> {
+    /// The store the runs were spilled to.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Merges the runs `runs` of `store` as the records are asked for.
+    fn merge(runs: &[RunId], store: S, cmp: C) -> io::Result<Self>
+    where
+        C: Compare<T>,
+    {
+        let readers = runs
+            .iter()
+            .map(|&id| RunReader::open(&store, id))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Sorted {
+            output: Output::Merge(LoserTree::with_compare(readers, ErrorsFirst(cmp))),
+            store,
+        })
+    }
+}
+
+impl<T: Record, S: SpillStore, C: Compare<T>> Iterator for Sorted<T, S, C> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<io::Result<T>> {
+        match &mut self.output {
+            Output::Memory(records) => records.next().map(Ok),
+            Output::Merge(merge) => {
+                let record = merge.next();
+                if let Some(Err(_)) = record {
+                    self.output = Output::Memory(Vec::new().into_iter());
+                }
+                record
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.output {
+            Output::Memory(records) => records.size_hint(),
+            Output::Merge(merge) => merge.size_hint(),
+        }
+    }
+}
+
+/// How records are laid out in the files a [`FileMerger`] reads and writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Lines ending in `\n`, compared without it. A last line missing its newline gets
+    /// one in the output.
+    Lines,
+    /// Byte strings as [`Record`] encodes a `Vec<u8>`: the length as a little-endian
+    /// `u64`, then the bytes.
+    LengthPrefixed,
+}
+
+impl Format {
+    /// Reads the next record, or `None` at the end of the input.
+    fn read<R: BufRead>(self, input: &mut R) -> io::Result<Option<Vec<u8>>> {
+        match self {
+            Format::Lines => {
+                let mut line = Vec::new();
+                if input.read_until(b'\n', &mut line)? == 0 {
+                    return Ok(None);
+                }
+                if line.last() == Some(&b'\n') {
+                    line.pop();
+                }
+                Ok(Some(line))
+            }
+            Format::LengthPrefixed => {
+                if input.fill_buf()?.is_empty() {
+                    return Ok(None);
+                }
+                Vec::decode(input).map(Some)
+            }
+        }
+    }
+
+    fn write<W: Write>(self, record: &[u8], out: &mut W) -> io::Result<()> {
+        match self {
+            Format::Lines => {
+                out.write_all(record)?;
+                out.write_all(b"\n")
+            }
+            Format::LengthPrefixed => {
+                out.write_all(&(record.len() as u64).to_le_bytes())?;
+                out.write_all(record)
+            }
+        }
+    }
+}
+
+/// Merges inputs that are each sorted into one sorted output, in bounded memory.
+///
+/// Every input is read through a buffer of 64 KiB, and as many inputs are merged at
+/// once as `memory_budget` has room for, and at least two. With more inputs than that,
+/// groups of them are first merged into intermediate runs in a spill store, pass after
+/// pass. Records comparing equal come out in the order of their inputs, and with
+/// `unique` only the first of them is written. Inputs that are not sorted make for an
+/// output that is not sorted either, rather than an error.
+///
+/// ```
+/// use pangua::external::{FileMerger, Format};
+///
+/// let inputs: Vec<&[u8]> = vec![b"apple\npear\n", b"fig\npear\nplum\n"];
+/// let mut output = Vec::new();
+/// let merger = FileMerger {
+///     unique: true,
+///     ..FileMerger::new(Format::Lines)
+/// };
+/// assert_eq!(merger.merge(inputs, &mut output).unwrap(), 4);
+/// assert_eq!(output, b"apple\nfig\npear\nplum\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileMerger {
+    pub format: Format,
+    pub memory_budget: usize,
+    /// Whether to write only the first of the records comparing equal.
+    pub unique: bool,
+    /// Where [`FileMerger::merge`] creates the directory for intermediate runs.
+    pub temp_dir: PathBuf,
+}
+
+/// An input of a merge pass: one of the merger's inputs, or the output of an earlier
+/// pass, stored as a sequence of runs.
+enum Source<R> {
+    Input(R),
+    Chunks(Vec<RunId>),
+}
+
+/// Reads the records of a [`Source`], opening its runs one at a time.
+enum SourceReader<'s, R, St: SpillStore> {
+    Input {
+        input: BufReader<R>,
+        format: Format,
+    },
+    Chunks {
+        store: &'s RefCell<St>,
+        chunks: vec::IntoIter<RunId>,
+        current: Option<RunReader<Vec<u8>, St::Reader>>,
+    },
+}
+
+impl<R: Read, St: SpillStore> Iterator for SourceReader<'_, R, St> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<io::Result<Vec<u8>>> {
+        match self {
+            SourceReader::Input { input, format } => format.read(input).transpose(),
+            SourceReader::Chunks {
+                store,
+                chunks,
+                current,
+            } => loop {
+                if let Some(record) = current.as_mut().and_then(Iterator::next) {
+                    return Some(record);
+                }
+                *current = None;
+                match RunReader::open(&*store.borrow(), chunks.next()?) {
+                    Ok(reader) => *current = Some(reader),
+                    Err(e) => return Some(Err(e)),
+                }
+            },
+        }
+    }
+}
+
+/// A record being merged, with the key it is ordered by.
+struct Keyed<K> {
+    key: K,
+    record: Vec<u8>,
+}
+
+/// Orders [`Keyed`] records by the records themselves, with the inner comparator.
+struct ByRecord<C>(C);
+
+impl<K, C: Compare<Vec<u8>>> Compare<Keyed<K>> for ByRecord<C> {
+    fn compare(&mut self, a: &Keyed<K>, b: &Keyed<K>) -> Ordering {
+        self.0.compare(&a.record, &b.record)
+    }
+}
+
+impl FileMerger {
+    /// Creates a merger with a budget of 64 MiB, spilling to the system's temporary
+    /// directory.
+    pub fn new(format: Format) -> Self {
+        FileMerger {
+            format,
+            memory_budget: MEMORY_BUDGET,
+            unique: false,
+            temp_dir: env::temp_dir(),
+        }
+    }
+
+    /// Inputs merged together in one pass.
+    pub fn fan_in(&self) -> usize {
+        KWayMerge::from_memory_budget::<SourceReader<'static, File, FileSpillStore>>(
+            self.memory_budget,
+            READ_BUFFER,
+        )
+        .map_or(2, |merge| merge.fan_in)
+    }
+
+    /// Merges inputs sorted byte-wise into `output`, returning the records written.
+    pub fn merge<R: Read, W: Write>(&self, inputs: Vec<R>, output: W) -> io::Result<u64> {
+        let store = FileSpillStore::new(&self.temp_dir)?;
+        self.merge_in(inputs, output, store, ByOrd)
+    }
+
+    /// Merges inputs sorted in the order defined by `compare` into `output`, returning
+    /// the records written.
+    pub fn merge_by<R, W, F>(&self, inputs: Vec<R>, output: W, mut compare: F) -> io::Result<u64>
+    where
+        R: Read,
+        W: Write,
+        F: FnMut(&[u8], &[u8]) -> Ordering,
+    {
+        let store = FileSpillStore::new(&self.temp_dir)?;
+        let cmp = ByFn(|a: &Vec<u8>, b: &Vec<u8>| compare(a, b));
+        self.merge_in(inputs, output, store, cmp)
+    }
+
+    /// Merges inputs sorted in the order `compare` defines on the keys `key` extracts
+    /// into `output`, returning the records written.
+    ///
+    /// Each record's key is extracted once per pass, as the record is read, rather than
+    /// on every comparison, which pays off when parsing the key costs more than
+    /// comparing it. Records with equal keys come out in the order of their inputs, and
+    /// with `unique` only the first of them is written.
+    pub fn merge_by_cached_key<R, W, K, G, F>(
+        &self,
+        inputs: Vec<R>,
+        output: W,
+        key: G,
+        mut compare: F,
+    ) -> io::Result<u64>
+    where
+        R: Read,
+        W: Write,
+        G: Fn(&[u8]) -> K,
+        F: FnMut(&K, &K) -> Ordering,
+    {
+        let store = FileSpillStore::new(&self.temp_dir)?;
+        let cmp = ByFn(|a: &Keyed<K>, b: &Keyed<K>| compare(&a.key, &b.key));
+        self.merge_keyed(inputs, output, store, &key, cmp)
+    }
+
+    /// Merges inputs sorted in the order defined by `cmp` into `output`, keeping any
+    /// intermediate runs in `store`, and returns the records written.
+    pub fn merge_in<R, W, St, C>(
+        &self,
+        inputs: Vec<R>,
+        output: W,
+        store: St,
+        cmp: C,
+    ) -> io::Result<u64>
+    where
+        R: Read,
+        W: Write,
+        St: SpillStore,
+        C: Compare<Vec<u8>>,
+    {
+        self.merge_keyed(inputs, output, store, &|_: &[u8]| (), ByRecord(cmp))
+    }
+
+    /// Merges inputs sorted in the order `cmp` defines on records keyed by `key`.
+    fn merge_keyed<R, W, St, K, G, C>(
+        &self,
+        inputs: Vec<R>,
+        output: W,
+        store: St,
+        key: &G,
+        mut cmp: C,
+    ) -> io::Result<u64>
+    where
+        R: Read,
+        W: Write,
+        St: SpillStore,
+        G: Fn(&[u8]) -> K,
+        C: Compare<Keyed<K>>,
+    {
+        let store = RefCell::new(store);
+        let fan_in = self.fan_in();
+        let mut sources: Vec<Source<R>> = inputs.into_iter().map(Source::Input).collect();
+        while sources.len() > fan_in {
+            let mut next = Vec::with_capacity(sources.len().div_ceil(fan_in));
+            let mut rest = sources.into_iter().peekable();
+            while rest.peek().is_some() {
+                let group = rest.by_ref().take(fan_in).collect();
+                next.push(self.spill_group(group, &store, key, &mut cmp)?);
+            }
+            sources = next;
+        }
+        let mut out = BufWriter::new(output);
+        let format = self.format;
+        let written = self.merge_group(sources, &store, key, &mut cmp, |record| {
+            format.write(record, &mut out)
+        })?;
+        out.flush()?;
+        Ok(written)
+    }
+
+    /// Merges a group of sources into runs of about the size of a read buffer,
+    /// removing the runs of the group once they are merged.
+    fn spill_group<R, St, K, G, C>(
+        &self,
+        group: Vec<Source<R>>,
+        store: &RefCell<St>,
+        key: &G,
+        cmp: &mut C,
+    ) -> io::Result<Source<R>>
+    where
+        R: Read,
+        St: SpillStore,
+        G: Fn(&[u8]) -> K,
+        C: Compare<Keyed<K>>,
+    {
+        let merged: Vec<RunId> = group
+            .iter()
+            .flat_map(|source| match source {
+                Source::Input(_) => &[][..],
+                Source::Chunks(ids) => ids,
+            })
+            .copied()
+            .collect();
+        let mut chunks = Vec::new();
+        let mut chunk = Vec::new();
+        let mut elements = 0;
+        self.merge_group(group, store, key, cmp, |record| {
+            (record.len() as u64).encode(&mut chunk);
+            chunk.extend_from_slice(record);
+            elements += 1;
+            if chunk.len() >= READ_BUFFER {
+                chunks.push(write_run(&mut *store.borrow_mut(), elements, &chunk)?);
+                chunk.clear();
+                elements = 0;
+            }
+            Ok(())
+        })?;
+        if elements > 0 {
+            chunks.push(write_run(&mut *store.borrow_mut(), elements, &chunk)?);
+        }
+        for id in merged {
+            store.borrow_mut().remove(id)?;
+        }
+        Ok(Source::Chunks(chunks))
+    }
+
+    /// Merges a group of sources, handing each record to `emit`, and returns the
+    /// records emitted.
+    fn merge_group<R, St, K, G, C, E>(
+        &self,
+        group: Vec<Source<R>>,
+        store: &RefCell<St>,
+        key: &G,
+        cmp: &mut C,
+        mut emit: E,
+    ) -> io::Result<u64>
+    where
+        R: Read,
+        St: SpillStore,
+        G: Fn(&[u8]) -> K,
+        C: Compare<Keyed<K>>,
+        E: FnMut(&[u8]) -> io::Result<()>,
+    {
+        let readers = group.into_iter().map(|source| {
+            let reader = match source {
+                Source::Input(input) => SourceReader::Input {
+                    input: BufReader::with_capacity(READ_BUFFER, input),
+                    format: self.format,
+                },
+                Source::Chunks(ids) => SourceReader::Chunks {
+                    store,
+                    chunks: ids.into_iter(),
+                    current: None,
+                },
+            };
+            reader.map(move |record| {
+                record.map(|record| Keyed {
+                    key: key(&record),
+                    record,
+                })
+            })
+        });
+        let mut merge = LoserTree::with_compare(readers, ErrorsFirst(cmp));
+        let mut last: Option<Keyed<K>> = None;
+        let mut emitted = 0;
+        while let Some(record) = merge.next() {
+            let record = record?;
+            if let Some(last) = &last {
+                if self.unique && merge.compare_mut().0.compare(last, &record) == Ordering::Equal {
+                    continue;
+                }
+            }
+            emit(&record.record)?;
+            emitted += 1;
+            last = Some(record);
+        }
+        Ok(emitted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spill::{FaultyStore, MemorySpillStore};
+    use crate::MergeSort;
+
+    fn small(budget: usize) -> ExternalSorter<MergeSort> {
+        ExternalSorter {
+            memory_budget: budget,
+            ..ExternalSorter::new(MergeSort::default())
+        }
+    }
+
+    fn scrambled(n: u64) -> impl Iterator<Item = u64> {
+        (0..n).map(move |i| (i * 7919) % n)
+    }
+
+    #[test]
+    fn external_works() {
+        let sorted: Vec<u64> = small(1024)
+            .sort(scrambled(5000))
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(sorted, (0..5000).collect::<Vec<_>>());
+
+        let tings = ["pear", "fig", "apple", "kiwi", "banana"];
+        let sorted: Vec<String> = small(64)
+            .sort_by(tings.iter().map(|s| s.to_string()), |a, b| {
+                b.len().cmp(&a.len())
+            })
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(sorted, &["banana", "apple", "pear", "kiwi", "fig"]);
+    }
+
+    #[test]
+    fn small_inputs_stay_in_memory() {
+        let sorted = small(1 << 20)
+            .sort_in(scrambled(100), MemorySpillStore::default(), ByOrd)
+            .unwrap();
+        assert_eq!(sorted.store().bytes(), 0);
+        assert!(sorted.map(Result::unwrap).eq(0..100));
+    }
+
+    #[test]
+    fn merges_in_passes_and_removes_merged_runs() {
+        let sorter = small(1024);
+        assert_eq!(sorter.fan_in::<u64, &[u8]>(), 2);
+        let tings: Vec<(u64, u64)> = (0..3000).map(|i| (i * 31 % 7, i)).collect();
+        let sorted = sorter
+            .sort_in(
+                tings,
+                MemorySpillStore::default(),
+                ByFn(|a: &(u64, u64), b: &(u64, u64)| a.0.cmp(&b.0)),
+            )
+            .unwrap();
+        // 94 runs of 32 records merged pairwise down to two
+        assert_eq!(sorted.store().runs().len(), 2);
+        let sorted: Vec<(u64, u64)> = sorted.map(Result::unwrap).collect();
+        assert_eq!(sorted.len(), 3000);
+        // stable, since the runs were sorted by a stable sorter
+        assert!(sorted.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn parallel_merge_matches_sequential() {
+        let sorter = ExternalSorter {
+            merge_workers: 4,
+            ..small(1024)
+        };
+        let tings: Vec<(u64, u64)> = (0..3000).map(|i| (i * 31 % 7, i)).collect();
+        let by_key = |a: &(u64, u64), b: &(u64, u64)| a.0.cmp(&b.0);
+        let sequential = sorter
+            .sort_in(tings.clone(), MemorySpillStore::default(), ByFn(by_key))
+            .unwrap();
+        let parallel = sorter
+            .par_sort_in(tings, MemorySpillStore::default(), by_key)
+            .unwrap();
+        assert_eq!(
+            parallel.store().runs().len(),
+            sequential.store().runs().len()
+        );
+        let sequential: Vec<(u64, u64)> = sequential.map(Result::unwrap).collect();
+        let parallel: Vec<(u64, u64)> = parallel.map(Result::unwrap).collect();
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn parallel_merge_failures_are_reported() {
+        let sorter = ExternalSorter {
+            merge_workers: 4,
+            ..small(256)
+        };
+        let mut clean = FaultyStore::new(MemorySpillStore::default());
+        sorter
+            .par_sort_in(scrambled(1000), &mut clean, u64::cmp)
+            .unwrap();
+        // the last write is made by a worker merging a group
+        let mut store = FaultyStore::new(MemorySpillStore::default());
+        store.fail_write(clean.writes());
+        let err = sorter
+            .par_sort_in(scrambled(1000), &mut store, u64::cmp)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::Other);
+        assert_eq!(store.writes(), clean.writes());
+    }
+
+    #[test]
+    fn store_failures_are_reported() {
+        let mut store = FaultyStore::new(MemorySpillStore::default());
+        store.fail_write(3);
+        let err = small(256)
+            .sort_in(scrambled(1000), store, ByOrd)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::Other);
+
+        let mut store = FaultyStore::new(MemorySpillStore::default());
+        store.corrupt_header(1);
+        let err = small(256)
+            .sort_in(scrambled(1000), store, ByOrd)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn failed_sorts_resume_from_committed_runs() {
+        let mut clean = FaultyStore::new(MemorySpillStore::default());
+        small(256)
+            .sort_in(scrambled(1000), &mut clean, ByOrd)
+            .unwrap();
+        let writes = clean.writes();
+        // fail while spilling the first runs, later ones, and while merging them
+        for fail in [3, writes / 3, 2 * writes / 3, writes] {
+            let mut store = FaultyStore::new(MemorySpillStore::default());
+            store.fail_write(fail);
+            let err = small(256)
+                .sort_in(scrambled(1000), &mut store, ByOrd)
+                .err()
+                .unwrap();
+            assert_eq!(err.kind(), ErrorKind::Other);
+            let committed = store.runs().len();
+            let resumed = small(256)
+                .resume_in(scrambled(1000), &mut store, ByOrd)
+                .unwrap();
+            assert!(resumed.map(Result::unwrap).eq(0..1000));
+            // the committed runs are not written again
+            assert!(committed > 0);
+            assert!(store.writes() - fail < writes);
+        }
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let mut out = Vec::new();
+        (-5i32, String::from("héllo")).encode(&mut out);
+        vec![1u8, 2, 3].encode(&mut out);
+        let mut input = &out[..];
+        assert_eq!(
+            <(i32, String)>::decode(&mut input).unwrap(),
+            (-5, "héllo".to_string())
+        );
+        assert_eq!(Vec::<u8>::decode(&mut input).unwrap(), &[1, 2, 3]);
+        assert!(input.is_empty());
+        let err = String::decode(&mut &[1, 0, 0, 0, 0, 0, 0, 0, 0xff][..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            u64::decode(&mut &[1][..]).unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+    }
+
+    fn lines<T: ToString>(tings: impl IntoIterator<Item = T>) -> Vec<u8> {
+        tings
+            .into_iter()
+            .flat_map(|t| (t.to_string() + "\n").into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn file_merger_merges_in_passes() {
+        let merger = FileMerger {
+            memory_budget: 1,
+            ..FileMerger::new(Format::Lines)
+        };
+        assert_eq!(merger.fan_in(), 2);
+        // five inputs take three passes, and runs of over 64 KiB several chunks
+        let padded = |i: u32| format!("{:0100}", i);
+        let inputs: Vec<Vec<u8>> = (0..5)
+            .map(|r| lines((0..2000).filter(|i| i % 5 == r).map(padded)))
+            .collect();
+        let mut output = Vec::new();
+        let written = merger
+            .merge_in(
+                inputs.iter().map(Vec::as_slice).collect(),
+                &mut output,
+                MemorySpillStore::default(),
+                ByOrd,
+            )
+            .unwrap();
+        assert_eq!(written, 2000);
+        assert_eq!(output, lines((0..2000).map(padded)));
+    }
+
+    #[test]
+    fn file_merger_extracts_each_key_once() {
+        let inputs = [lines([2, 10, 30]), lines([1, 9, 20, 100])];
+        let extracted = std::cell::Cell::new(0);
+        let number = |record: &[u8]| {
+            extracted.set(extracted.get() + 1);
+            std::str::from_utf8(record).unwrap().parse::<u32>().unwrap()
+        };
+        let mut output = Vec::new();
+        let written = FileMerger::new(Format::Lines)
+            .merge_by_cached_key(
+                inputs.iter().map(Vec::as_slice).collect(),
+                &mut output,
+                number,
+                u32::cmp,
+            )
+            .unwrap();
+        assert_eq!(written, 7);
+        assert_eq!(extracted.get(), 7);
+        assert_eq!(output, lines([1, 2, 9, 10, 20, 30, 100]));
+    }
+
+    #[test]
+    fn file_merger_dedups_and_reads_length_prefixed() {
+        let mut first = Vec::new();
+        let mut second = Vec::new();
+        for (input, tings) in [(&mut first, ["a,3", "b,1"]), (&mut second, ["a,1", "c,2"])] {
+            for t in tings.iter() {
+                t.as_bytes().to_vec().encode(input);
+            }
+        }
+        let merger = FileMerger {
+            unique: true,
+            ..FileMerger::new(Format::LengthPrefixed)
+        };
+        let mut output = Vec::new();
+        let written = merger
+            .merge_by(vec![&first[..], &second[..]], &mut output, |a, b| {
+                a[0].cmp(&b[0])
+            })
+            .unwrap();
+        assert_eq!(written, 3);
+        let mut merged = &output[..];
+        let records: Vec<Vec<u8>> = (0..3).map(|_| Vec::decode(&mut merged).unwrap()).collect();
+        assert_eq!(records, [&b"a,3"[..], b"b,1", b"c,2"]);
+        assert!(merged.is_empty());
+
+        let truncated = &first[..first.len() - 1];
+        let err = merger.merge(vec![truncated], io::sink()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+}
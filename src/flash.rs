@@ -0,0 +1,184 @@
+use crate::cmp::{self, ByFn, ByOrd, Compare};
+use crate::{is_trivially_sorted, Complexity, Error, InsertionSort, Sorter, SorterInfo, ToOrdinal};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Flash Sort
+///
+/// Flashsort classifies the elements into m classes by interpolating a numeric projection
+/// of each between the smallest and largest, counts the classes, and permutes the
+/// elements into them in place by following cycles, so that only a count per class is
+/// allocated. The slice is then nearly sorted, every element within its class, and one
+/// pass of insertion sort finishes it. With `class_ratio` classes per element (Neubert's
+/// 0.43 by default), uniformly distributed values sort in linear time; values bunched
+/// into a few classes degrade it towards insertion sort.
+///
+/// The projection must be monotone: `a < b` implies `project(a) <= project(b)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlashSort {
+    pub class_ratio: f64,
+}
+
+impl Default for FlashSort {
+    fn default() -> Self {
+        FlashSort { class_ratio: 0.43 }
+    }
+}
+
+impl FlashSort {
+    /// Creates a flashsort with `class_ratio` classes per element, which must be
+    /// positive and finite.
+    pub fn new(class_ratio: f64) -> Result<Self, Error> {
+        let sorter = FlashSort { class_ratio };
+        sorter.validate()?;
+        Ok(sorter)
+    }
+
+    /// Checks the configuration, as the sort itself would before starting.
+    pub fn validate(&self) -> Result<(), Error> {
+        if !(self.class_ratio > 0.0 && self.class_ratio.is_finite()) {
+            return Err(Error::InvalidClassRatio);
+        }
+        Ok(())
+    }
+
+    pub fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "flash",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linear,
+            average: Complexity::Linear,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    /// Sorts elements by their ordinals.
+    pub fn sort<T: Ord + ToOrdinal>(&self, slice: &mut [T]) {
+        self.sort_with(slice, T::to_ordinal, &mut ByOrd)
+    }
+
+    /// Like [`FlashSort::sort`], classifying by `project` and ordering with `compare`.
+    pub fn sort_by<T, P, F>(&self, slice: &mut [T], project: P, compare: F)
+    where
+        P: FnMut(&T) -> f64,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.sort_with(slice, project, &mut ByFn(compare))
+    }
+
+    pub fn sort_with<T, P, C>(&self, slice: &mut [T], mut project: P, cmp: &mut C)
+    where
+        P: FnMut(&T) -> f64,
+        C: Compare<T>,
+    {
+        if let Err(e) = self.validate() {
+            panic!("{}", e)
+        }
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let n = slice.len();
+        let classes = ((self.class_ratio * n as f64) as usize).clamp(1, n);
+        let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+        for x in slice.iter() {
+            let v = project(x);
+            min = min.min(v);
+            max = max.max(v);
+        }
+        let scale = if max > min {
+            (classes - 1) as f64 / (max - min)
+        } else {
+            0.0
+        };
+        let mut class = |x: &T| (((project(x) - min) * scale) as usize).min(classes - 1);
+
+        let mut counts = vec![0usize; classes];
+        for x in slice.iter() {
+            counts[class(x)] += 1;
+        }
+        let mut starts = Vec::with_capacity(classes);
+        let mut total = 0;
+        for &count in &counts {
+            starts.push(total);
+            total += count;
+        }
+        // next[k] is the first slot of class k not yet known to hold a member
+        let mut next = starts.clone();
+        for k in 0..classes {
+            let end = starts[k] + counts[k];
+            while next[k] < end {
+                let c = class(&slice[next[k]]);
+                if c == k {
+                    next[k] += 1;
+                } else {
+                    cmp::swap(cmp, slice, next[k], next[c]);
+                    next[c] += 1;
+                }
+            }
+        }
+        InsertionSort { smart: false }.sort_with(slice, cmp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counter;
+
+    fn uniform(n: usize) -> Vec<u64> {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        (0..n)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x % 1_000_000
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flash_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        FlashSort::default().sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for n in 0..200 {
+            let mut tings: Vec<i64> = (0..n).map(|i| (i * 7919) % 37 - 18).collect();
+            FlashSort::default().sort(&mut tings);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn linear_on_uniform_data() {
+        let n = 20_000;
+        let mut tings = uniform(n);
+        let mut counter = Counter::new(ByOrd);
+        FlashSort::default().sort_with(&mut tings, |&x| x as f64, &mut counter);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        assert!(counter.stats().comparisons < 4 * n);
+    }
+
+    #[test]
+    fn sorts_by_projection() {
+        let mut tings: Vec<(f64, usize)> = uniform(500)
+            .into_iter()
+            .map(|x| x as f64 / 7.0)
+            .zip(0..)
+            .collect();
+        FlashSort::default().sort_by(&mut tings, |t| t.0, |a, b| a.0.total_cmp(&b.0));
+        assert!(tings.windows(2).all(|w| w[0].0 <= w[1].0));
+        let mut equal = vec![3; 100];
+        FlashSort::default().sort(&mut equal);
+        assert_eq!(equal, vec![3; 100]);
+    }
+
+    #[test]
+    fn rejects_bad_class_ratios() {
+        assert_eq!(FlashSort::new(0.0), Err(Error::InvalidClassRatio));
+        assert_eq!(FlashSort::new(f64::NAN), Err(Error::InvalidClassRatio));
+        assert!(FlashSort::new(2.0).is_ok());
+    }
+}
@@ -0,0 +1,187 @@
+//! Input distributions for benchmarking sorters, from the friendly to the adversarial.
+//!
+//! Random input only shows the average case. Real data is often partly sorted or full
+//! of duplicates, and some inputs are built to defeat a particular algorithm, so a fair
+//! comparison of sorters runs them on each [`Pattern`]. Every pattern is made of `n`
+//! values below `n`, and the randomized ones take a seed so runs are reproducible.
+//!
+//! ```
+//! use pangua::generators::Pattern;
+//! use pangua::{Counting, IntroSort, Sorter};
+//!
+//! for pattern in Pattern::ALL.iter() {
+//!     let mut tings = pattern.generate(1000, 42);
+//!     let sorter = Counting::new(IntroSort::default());
+//!     sorter.sort(&mut tings);
+//!     println!("{:?}: {} comparisons", pattern, sorter.stats().comparisons);
+//! }
+//! ```
+
+use crate::rng::{self, Rng};
+use crate::{worst_case, QuickSort};
+use alloc::vec::Vec;
+
+/// The inputs [`Pattern::generate`] produces, with the parameters it picks for them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pattern {
+    /// See [`presorted`].
+    Presorted,
+    /// See [`reverse_sorted`].
+    ReverseSorted,
+    /// See [`organ_pipe`].
+    OrganPipe,
+    /// [`few_unique`] with 16 distinct values, or `n` if fewer.
+    FewUnique,
+    /// See [`median_of_three_killer`].
+    MedianOfThreeKiller,
+    /// See [`heap_sort_killer`].
+    HeapSortKiller,
+    /// [`runs_with_noise`] with runs of 64 elements and 1 element in 20 out of place.
+    RunsWithNoise,
+}
+
+impl Pattern {
+    /// Every pattern.
+    pub const ALL: [Pattern; 7] = [
+        Pattern::Presorted,
+        Pattern::ReverseSorted,
+        Pattern::OrganPipe,
+        Pattern::FewUnique,
+        Pattern::MedianOfThreeKiller,
+        Pattern::HeapSortKiller,
+        Pattern::RunsWithNoise,
+    ];
+
+    /// `n` values of the pattern; `seed` only matters to the randomized ones.
+    pub fn generate(self, n: usize, seed: u64) -> Vec<usize> {
+        match self {
+            Pattern::Presorted => presorted(n),
+            Pattern::ReverseSorted => reverse_sorted(n),
+            Pattern::OrganPipe => organ_pipe(n),
+            Pattern::FewUnique => few_unique(n, n.min(16), seed),
+            Pattern::MedianOfThreeKiller => median_of_three_killer(n),
+            Pattern::HeapSortKiller => heap_sort_killer(n),
+            Pattern::RunsWithNoise => runs_with_noise(n, 64, 0.05, seed),
+        }
+    }
+}
+
+/// `0..n` in order, the best case of adaptive sorts and the worst of a quicksort
+/// pivoting on an end.
+pub fn presorted(n: usize) -> Vec<usize> {
+    (0..n).collect()
+}
+
+/// `0..n` backwards.
+pub fn reverse_sorted(n: usize) -> Vec<usize> {
+    (0..n).rev().collect()
+}
+
+/// Values rising to the middle and falling back, each but the largest appearing twice:
+/// `0, 1, 2, 2, 1, 0`. Two runs, and a trap for pivots taken from the ends and middle.
+pub fn organ_pipe(n: usize) -> Vec<usize> {
+    (0..n).map(|i| i.min(n - 1 - i)).collect()
+}
+
+/// A uniformly random permutation of `0..n`, the average case.
+pub fn shuffled(n: usize, seed: u64) -> Vec<usize> {
+    let mut tings = presorted(n);
+    rng::shuffle(&mut tings, seed);
+    tings
+}
+
+/// `n` values drawn uniformly from `0..distinct`, which sorts must handle without
+/// degrading on the long stretches of equal elements.
+pub fn few_unique(n: usize, distinct: usize, seed: u64) -> Vec<usize> {
+    let mut rng = Rng::new(seed);
+    (0..n).map(|_| rng.below(distinct.max(1))).collect()
+}
+
+/// A permutation of `0..n` on which [`QuickSort`] with its default median-of-three
+/// pivot makes a quadratic number of comparisons, found with
+/// [`worst_case::adversary`].
+pub fn median_of_three_killer(n: usize) -> Vec<usize> {
+    worst_case::adversary(&QuickSort::default(), n)
+}
+
+/// A permutation of `0..n` on which every sift of heapsort goes to the bottom of the
+/// heap, see [`worst_case::heap_sort`].
+pub fn heap_sort_killer(n: usize) -> Vec<usize> {
+    worst_case::heap_sort(n)
+}
+
+/// `0..n` split into ascending runs of `run_len` shuffled among themselves, with a
+/// `noise` fraction of the elements then swapped with random others. Logs and merged
+/// batches look like this: mostly ordered, never quite.
+pub fn runs_with_noise(n: usize, run_len: usize, noise: f64, seed: u64) -> Vec<usize> {
+    let mut rng = Rng::new(seed);
+    let mut runs: Vec<Vec<usize>> = presorted(n)
+        .chunks(run_len.max(1))
+        .map(<[usize]>::to_vec)
+        .collect();
+    rng.shuffle(&mut runs);
+    let mut tings = runs.concat();
+    if n > 0 {
+        let swaps = (n as f64 * noise.clamp(0.0, 1.0)) as usize;
+        for _ in 0..swaps {
+            let (a, b) = (rng.below(n), rng.below(n));
+            tings.swap(a, b);
+        }
+    }
+    tings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Counting, HeapSort, Sorter};
+
+    fn comparisons<S: Sorter>(sorter: S, input: &[usize]) -> usize {
+        let sorter = Counting::new(sorter);
+        sorter.sort(&mut input.to_vec());
+        sorter.stats().comparisons
+    }
+
+    #[test]
+    fn patterns_are_reproducible_and_in_range() {
+        for &pattern in Pattern::ALL.iter() {
+            for n in [0, 1, 2, 7, 100] {
+                let tings = pattern.generate(n, 3);
+                assert_eq!(tings.len(), n, "{:?}", pattern);
+                assert!(tings.iter().all(|&x| x < n), "{:?}", pattern);
+                assert_eq!(tings, pattern.generate(n, 3), "{:?}", pattern);
+            }
+        }
+        assert_eq!(organ_pipe(6), [0, 1, 2, 2, 1, 0]);
+        assert_eq!(organ_pipe(5), [0, 1, 2, 1, 0]);
+        assert_ne!(few_unique(100, 16, 1), few_unique(100, 16, 2));
+    }
+
+    #[test]
+    fn runs_with_noise_is_mostly_ordered() {
+        let n = 1 << 12;
+        let tings = runs_with_noise(n, 64, 0.05, 9);
+        let descents = tings.windows(2).filter(|w| w[1] < w[0]).count();
+        // the run boundaries, and at most two more for each of the n / 20 swaps
+        assert!(
+            descents > n / 64 && descents <= n / 64 + 2 * (n / 20),
+            "{}",
+            descents
+        );
+        let mut sorted = tings.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, presorted(n));
+    }
+
+    #[test]
+    fn killers_are_worse_than_random() {
+        let n = 1 << 11;
+        let random = runs_with_noise(n, 1, 0.0, 4);
+        let quick = QuickSort::default();
+        assert!(comparisons(quick, &median_of_three_killer(n)) > 10 * comparisons(quick, &random));
+        assert!(
+            comparisons(HeapSort::default(), &heap_sort_killer(n))
+                > comparisons(HeapSort::default(), &random)
+        );
+    }
+}
@@ -0,0 +1,337 @@
+//! Binary heaps: the machinery behind [`HeapSort`](crate::HeapSort), on slices and as
+//! a priority queue.
+//!
+//! A slice is a heap when every element orders at least as late as its children, at
+//! `2i + 1` and `2i + 2`, so its root is the largest element. [`heapify`],
+//! [`sift_down`] and [`sift_up`] maintain that on any slice, in the order of any
+//! [`Compare`], and report their swaps to it. [`Heap`] owns its elements in a `Vec`,
+//! with [`MaxHeap`] and [`MinHeap`] for the natural order and its reverse.
+//! [`HeapSort`](crate::HeapSort) generalises the slice heaps to `d` children per node.
+//!
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! use pangua::heap::MinHeap;
+//!
+//! let mut heap = MinHeap::from_slice(&[5, 1, 4]);
+//! heap.push(2);
+//! assert_eq!(heap.peek(), Some(&1));
+//! assert_eq!(heap.pop(), Some(1));
+//! assert_eq!(heap.pop(), Some(2));
+//! assert_eq!(heap.len(), 2);
+//! # }
+//! ```
+
+#[cfg(feature = "alloc")]
+use crate::cmp::ByOrd;
+use crate::cmp::{self, Compare, Reversed};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::iter::FromIterator;
+
+/// Rearranges the slice into a heap, in O(n).
+pub fn heapify<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) {
+    for root in (0..slice.len() / 2).rev() {
+        sift_down(slice, root, cmp);
+    }
+}
+
+/// Moves the element at `root` down until it orders no earlier than its children,
+/// restoring the heap below `root` when only that element was out of place.
+pub fn sift_down<T, C: Compare<T>>(heap: &mut [T], root: usize, cmp: &mut C) {
+    Shape::BINARY.sift_down(heap, root, cmp)
+}
+
+/// Moves the element at `index` up until it orders no later than its parent, restoring
+/// the heap when only that element was out of place.
+pub fn sift_up<T, C: Compare<T>>(heap: &mut [T], mut index: usize, cmp: &mut C) {
+    while index > 0 {
+        let parent = (index - 1) / 2;
+        if !cmp.is_less(&heap[parent], &heap[index]) {
+            return;
+        }
+        cmp::swap(cmp, heap, parent, index);
+        index = parent;
+    }
+}
+
+/// Sorts a heap in place by moving its root behind the rest, one at a time.
+#[cfg(feature = "alloc")]
+pub(crate) fn sort_heap<T, C: Compare<T>>(heap: &mut [T], cmp: &mut C) {
+    for end in (1..heap.len()).rev() {
+        cmp::swap(cmp, heap, 0, end);
+        sift_down(&mut heap[..end], 0, cmp);
+    }
+}
+
+/// Heap sort over a `d`-ary heap; with `min_heap`, over a min-heap rooted at the end of
+/// the slice, which moves the minima to the front.
+pub(crate) fn sort_d_ary<T, C: Compare<T>>(slice: &mut [T], d: usize, min_heap: bool, cmp: &mut C) {
+    let shape = Shape {
+        d,
+        mirrored: min_heap,
+    };
+    if min_heap {
+        shape.sort(slice, &mut Reversed(cmp))
+    } else {
+        shape.sort(slice, cmp)
+    }
+}
+
+/// How a heap is laid over a slice: node `i` has children `d * i + 1` to `d * i + d`,
+/// and lives at index `i` of the slice, or at `len - 1 - i` when the heap is mirrored
+/// to be rooted at the end.
+#[derive(Clone, Copy)]
+struct Shape {
+    d: usize,
+    mirrored: bool,
+}
+
+impl Shape {
+    const BINARY: Shape = Shape {
+        d: 2,
+        mirrored: false,
+    };
+
+    fn at(self, len: usize, node: usize) -> usize {
+        if self.mirrored {
+            len - 1 - node
+        } else {
+            node
+        }
+    }
+
+    fn sift_down<T, C: Compare<T>>(self, heap: &mut [T], mut root: usize, cmp: &mut C) {
+        let len = heap.len();
+        loop {
+            let first = match self.d.checked_mul(root) {
+                Some(first) if first + 1 < len => first + 1,
+                _ => return,
+            };
+            let mut largest = root;
+            for child in first..len.min(first + self.d) {
+                if cmp.is_less(&heap[self.at(len, largest)], &heap[self.at(len, child)]) {
+                    largest = child;
+                }
+            }
+            if largest == root {
+                return;
+            }
+            cmp::swap(cmp, heap, self.at(len, root), self.at(len, largest));
+            root = largest;
+        }
+    }
+
+    fn sort<T, C: Compare<T>>(self, slice: &mut [T], cmp: &mut C) {
+        let len = slice.len();
+        // the nodes with children, the last one being the parent of node len - 1
+        for root in (0..(len + self.d - 2) / self.d).rev() {
+            self.sift_down(slice, root, cmp);
+        }
+        for end in (1..len).rev() {
+            cmp::swap(cmp, slice, self.at(len, 0), self.at(len, end));
+            let heap = if self.mirrored {
+                &mut slice[len - end..]
+            } else {
+                &mut slice[..end]
+            };
+            self.sift_down(heap, 0, cmp);
+        }
+    }
+}
+
+/// A priority queue popping the element that orders last under `C` first.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct Heap<T, C = ByOrd> {
+    data: Vec<T>,
+    cmp: C,
+}
+
+/// A heap popping its largest element first.
+#[cfg(feature = "alloc")]
+pub type MaxHeap<T> = Heap<T, ByOrd>;
+
+/// A heap popping its smallest element first.
+#[cfg(feature = "alloc")]
+pub type MinHeap<T> = Heap<T, Reversed<ByOrd>>;
+
+#[cfg(feature = "alloc")]
+impl<T, C: Compare<T> + Default> Heap<T, C> {
+    pub fn new() -> Self {
+        Heap::with_compare(C::default())
+    }
+
+    /// A heap of clones of the slice's elements, built in O(n).
+    pub fn from_slice(slice: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        Heap::from_vec(slice.to_vec(), C::default())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C: Compare<T> + Default> Default for Heap<T, C> {
+    fn default() -> Self {
+        Heap::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C: Compare<T>> Heap<T, C> {
+    /// An empty heap ordered by `cmp`.
+    pub fn with_compare(cmp: C) -> Self {
+        Heap {
+            data: Vec::new(),
+            cmp,
+        }
+    }
+
+    /// A heap of the vector's elements ordered by `cmp`, built in place in O(n).
+    pub fn from_vec(mut data: Vec<T>, mut cmp: C) -> Self {
+        heapify(&mut data, &mut cmp);
+        Heap { data, cmp }
+    }
+
+    /// Adds an element, in O(log n).
+    pub fn push(&mut self, x: T) {
+        let index = self.data.len();
+        self.data.push(x);
+        sift_up(&mut self.data, index, &mut self.cmp);
+    }
+
+    /// Removes the element that orders last, in O(log n).
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.data.len().checked_sub(1)?;
+        self.data.swap(0, last);
+        let top = self.data.pop();
+        sift_down(&mut self.data, 0, &mut self.cmp);
+        top
+    }
+
+    /// The element that orders last, the next one [`pop`](Heap::pop) returns.
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Replaces the top element with `x` and returns the old top, in a single sift:
+    /// cheaper than a pop followed by a push.
+    pub fn replace_top(&mut self, x: T) -> Option<T> {
+        if self.data.is_empty() {
+            self.data.push(x);
+            return None;
+        }
+        let top = core::mem::replace(&mut self.data[0], x);
+        sift_down(&mut self.data, 0, &mut self.cmp);
+        Some(top)
+    }
+
+    /// Pushes `x` and pops the element that orders last, in at most one sift: `x`
+    /// itself when nothing in the heap orders after it. Cheaper than a push followed by
+    /// a pop.
+    pub fn push_pop(&mut self, x: T) -> T {
+        match self.data.first() {
+            Some(top) if self.cmp.is_less(&x, top) => {
+                let top = core::mem::replace(&mut self.data[0], x);
+                sift_down(&mut self.data, 0, &mut self.cmp);
+                top
+            }
+            _ => x,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The elements, in heap order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// The elements, in heap order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// The elements in ascending order under `C`, sorted in place.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        sort_heap(&mut self.data, &mut self.cmp);
+        self.data
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C: Compare<T>> Extend<T> for Heap<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C: Compare<T> + Default> FromIterator<T> for Heap<T, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Heap::from_vec(iter.into_iter().collect(), C::default())
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::cmp::by_key;
+
+    fn is_heap<T: Ord>(slice: &[T]) -> bool {
+        (1..slice.len()).all(|i| slice[(i - 1) / 2] >= slice[i])
+    }
+
+    #[test]
+    fn heap_functions_keep_the_heap() {
+        for n in 0..40 {
+            let mut tings: Vec<u32> = (0..n).map(|i| (i * 7919) % 13).collect();
+            heapify(&mut tings, &mut ByOrd);
+            assert!(is_heap(&tings), "n = {}", n);
+            if n > 0 {
+                tings[0] = 0;
+                sift_down(&mut tings, 0, &mut ByOrd);
+                assert!(is_heap(&tings), "n = {}", n);
+                tings.push(20);
+                let last = tings.len() - 1;
+                sift_up(&mut tings, last, &mut ByOrd);
+                assert!(is_heap(&tings) && tings[0] == 20, "n = {}", n);
+            }
+            sort_heap(&mut tings, &mut ByOrd);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn heaps_pop_in_order() {
+        let tings = [5, 3, 9, 1, 7, 3, 0];
+        let mut max = MaxHeap::from_slice(&tings);
+        let mut popped = Vec::new();
+        while let Some(x) = max.pop() {
+            popped.push(x);
+        }
+        assert_eq!(popped, [9, 7, 5, 3, 3, 1, 0]);
+        assert_eq!(max.peek(), None);
+
+        let mut min: MinHeap<_> = tings.iter().copied().collect();
+        assert_eq!(min.replace_top(4), Some(0));
+        assert_eq!(min.push_pop(2), 1);
+        assert_eq!(min.push_pop(0), 0);
+        assert_eq!(min.len(), 7);
+        assert_eq!(min.into_sorted_vec(), [9, 7, 5, 4, 3, 3, 2]);
+
+        let mut by_len = Heap::with_compare(by_key(|s: &&str| s.len()));
+        by_len.extend(["ccc", "a", "bb"]);
+        assert_eq!(by_len.pop(), Some("ccc"));
+        assert_eq!(by_len.into_vec().len(), 2);
+    }
+}
@@ -0,0 +1,221 @@
+//! Memory access patterns of a sort, recorded from its trace and rendered as an image.
+
+use crate::SortEvent;
+use std::io;
+
+/// A read or a write of one slot of the slice being sorted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Access {
+    Read { index: usize },
+    Write { index: usize },
+}
+
+impl Access {
+    pub fn index(&self) -> usize {
+        match *self {
+            Access::Read { index } | Access::Write { index } => index,
+        }
+    }
+}
+
+impl SortEvent {
+    /// The slots of the slice this step reads and writes, in order.
+    ///
+    /// A comparison reads both elements, a swap reads then writes both, and a move
+    /// reads then writes its whole range. Pivots, partitions and recursion are
+    /// bookkeeping and access nothing by themselves.
+    pub fn accesses(&self) -> Vec<Access> {
+        let read = |index| Access::Read { index };
+        let write = |index| Access::Write { index };
+        match self {
+            SortEvent::Compare { a, b } => a.iter().chain(b).map(|&i| read(i)).collect(),
+            &SortEvent::Swap { a, b } => vec![read(a), read(b), write(a), write(b)],
+            SortEvent::Move { range } => range
+                .clone()
+                .map(read)
+                .chain(range.clone().map(write))
+                .collect(),
+            SortEvent::Pivot { .. }
+            | SortEvent::Partition { .. }
+            | SortEvent::Enter
+            | SortEvent::Leave => Vec::new(),
+        }
+    }
+}
+
+/// Records the accesses a sort makes to a slice of `len` elements, for rendering as a
+/// heatmap of index against time.
+///
+/// Feed it the events of a [`TracedSorter`](crate::TracedSorter):
+///
+/// ```
+/// use pangua::*;
+///
+/// let mut heatmap = AccessHeatmap::new(64);
+/// let mut tings: Vec<u32> = (0..64).rev().collect();
+/// TracedSorter::new(HeapSort::default(), |e| heatmap.record(&e)).sort(&mut tings);
+/// let mut image = Vec::new();
+/// heatmap.write_ppm(&mut image, 64, 32).unwrap();
+/// assert!(image.starts_with(b"P6\n64 32\n255\n"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AccessHeatmap {
+    len: usize,
+    accesses: Vec<Access>,
+}
+
+impl AccessHeatmap {
+    pub fn new(len: usize) -> Self {
+        AccessHeatmap {
+            len,
+            accesses: Vec::new(),
+        }
+    }
+
+    /// Records the accesses of one step of the sort.
+    pub fn record(&mut self, event: &SortEvent) {
+        self.accesses.extend(event.accesses());
+    }
+
+    /// Every access recorded so far, in order.
+    pub fn accesses(&self) -> &[Access] {
+        &self.accesses
+    }
+
+    /// The average distance between the slots of consecutive accesses.
+    ///
+    /// Sorts that hop around the slice, like heapsort, have a long stride and make poor
+    /// use of caches; sorts that sweep it, like merge sort, have a short one.
+    pub fn mean_stride(&self) -> f64 {
+        if self.accesses.len() < 2 {
+            return 0.0;
+        }
+        let total: usize = self
+            .accesses
+            .windows(2)
+            .map(|w| {
+                let (a, b) = (w[0].index(), w[1].index());
+                a.max(b) - a.min(b)
+            })
+            .sum();
+        total as f64 / (self.accesses.len() - 1) as f64
+    }
+
+    /// Counts reads and writes per cell of a `width` by `height` grid, columns spanning
+    /// slice indices and rows spanning time, first access at the top.
+    ///
+    /// Returns `(reads, writes)` per cell, row by row.
+    pub fn grid(&self, width: usize, height: usize) -> Vec<(u32, u32)> {
+        let mut cells = vec![(0, 0); width * height];
+        if width == 0 || height == 0 || self.len == 0 {
+            return cells;
+        }
+        let steps = self.accesses.len();
+        for (t, access) in self.accesses.iter().enumerate() {
+            let row = t * height / steps;
+            let col = (access.index() * width / self.len).min(width - 1);
+            let cell = &mut cells[row * width + col];
+            match access {
+                Access::Read { .. } => cell.0 += 1,
+                Access::Write { .. } => cell.1 += 1,
+            }
+        }
+        cells
+    }
+
+    /// Writes the heatmap as a binary PPM image, `width` by `height` pixels.
+    ///
+    /// Reads show in green and writes in red, each scaled to the busiest cell, so
+    /// cells both read and written a lot appear yellow.
+    pub fn write_ppm<W: io::Write>(
+        &self,
+        mut out: W,
+        width: usize,
+        height: usize,
+    ) -> io::Result<()> {
+        let cells = self.grid(width, height);
+        let max_reads = cells.iter().map(|c| c.0).max().unwrap_or(0).max(1);
+        let max_writes = cells.iter().map(|c| c.1).max().unwrap_or(0).max(1);
+        let scale = |count: u32, max: u32| (count as u64 * 255 / max as u64) as u8;
+        write!(out, "P6\n{} {}\n255\n", width, height)?;
+        let pixels: Vec<u8> = cells
+            .iter()
+            .flat_map(|&(reads, writes)| [scale(writes, max_writes), scale(reads, max_reads), 0])
+            .collect();
+        out.write_all(&pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    fn heatmap<S: Sorter>(sorter: S, n: u32) -> AccessHeatmap {
+        let mut heatmap = AccessHeatmap::new(n as usize);
+        let mut tings: Vec<_> = (0..n).map(|i| (i * 7919) % n).collect();
+        TracedSorter::new(sorter, |e| heatmap.record(&e)).sort(&mut tings);
+        heatmap
+    }
+
+    #[test]
+    fn events_map_to_accesses() {
+        let compare = SortEvent::Compare {
+            a: Some(3),
+            b: None,
+        };
+        assert_eq!(compare.accesses(), &[Access::Read { index: 3 }]);
+        let swap = SortEvent::Swap { a: 1, b: 2 };
+        assert_eq!(
+            swap.accesses(),
+            &[
+                Access::Read { index: 1 },
+                Access::Read { index: 2 },
+                Access::Write { index: 1 },
+                Access::Write { index: 2 },
+            ]
+        );
+        assert_eq!(SortEvent::Move { range: 4..6 }.accesses().len(), 4);
+        assert!(SortEvent::Enter.accesses().is_empty());
+    }
+
+    #[test]
+    fn heapsort_strides_further_than_mergesort() {
+        let heap = heatmap(HeapSort::default(), 1024);
+        let merge = heatmap(
+            MergeSort {
+                memory: MemoryPolicy::InPlace,
+                ..MergeSort::default()
+            },
+            1024,
+        );
+        assert!(heap.mean_stride() > 10.0 * merge.mean_stride());
+    }
+
+    #[test]
+    fn grid_counts_every_access() {
+        let heatmap = heatmap(InsertionSort { smart: false }, 50);
+        let cells = heatmap.grid(10, 7);
+        assert_eq!(cells.len(), 70);
+        let (reads, writes) = cells
+            .iter()
+            .fold((0, 0), |(r, w), c| (r + c.0 as usize, w + c.1 as usize));
+        assert_eq!(reads + writes, heatmap.accesses().len());
+        assert!(writes > 0);
+    }
+
+    #[test]
+    fn writes_ppm() {
+        let mut image = Vec::new();
+        heatmap(HeapSort::default(), 100)
+            .write_ppm(&mut image, 20, 10)
+            .unwrap();
+        let header = b"P6\n20 10\n255\n";
+        assert!(image.starts_with(header));
+        assert_eq!(image.len(), header.len() + 20 * 10 * 3);
+
+        let mut image = Vec::new();
+        AccessHeatmap::new(0).write_ppm(&mut image, 2, 2).unwrap();
+        assert!(image.ends_with(&[0; 12]));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::cmp::{self, Compare};
+use crate::permutation;
+use crate::SortableContainer;
+use crate::{is_trivially_sorted, IntroSort, Sorter, SorterInfo, StableSorter};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem;
+
+/// Elements at least this many bytes large are sorted indirectly by default.
+const MIN_BYTES: usize = 128;
+
+/// Indirect Sort
+///
+/// Indirect sort keeps large elements still while the `inner` sorter works: it sorts
+/// an array of indices into the slice, comparing the elements they point at, and then
+/// moves every element into place at once by following the cycles of the resulting
+/// permutation, in at most n - 1 swaps. Sorting a slice of large structs directly
+/// moves whole elements at every swap, so it pays once elements are `min_bytes` or
+/// larger; smaller ones are handed to the inner sorter as they are.
+///
+/// The indices take O(n) extra memory. The sort is stable when the inner sorter is.
+///
+/// ```
+/// use pangua::{IndirectSort, MergeSort, Sorter};
+///
+/// let mut tings: Vec<(u8, [u64; 32])> = vec![(3, [0; 32]), (1, [1; 32]), (2, [2; 32])];
+/// IndirectSort::new(MergeSort::default()).sort_by_key(&mut tings, |t| t.0);
+/// assert_eq!(tings[0].1, [1; 32]);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct IndirectSort<S = IntroSort> {
+    pub min_bytes: usize,
+    pub inner: S,
+}
+
+impl Default for IndirectSort {
+    fn default() -> Self {
+        IndirectSort::new(IntroSort::default())
+    }
+}
+
+impl<S: Sorter> IndirectSort<S> {
+    /// Creates an indirect sort of elements of 128 bytes or more.
+    pub fn new(inner: S) -> Self {
+        IndirectSort {
+            min_bytes: MIN_BYTES,
+            inner,
+        }
+    }
+
+    /// Whether slices of `T` are sorted through indices.
+    pub fn is_indirect<T>(&self) -> bool {
+        mem::size_of::<T>() >= self.min_bytes
+    }
+}
+
+impl<S: Sorter> Sorter for IndirectSort<S> {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "indirect",
+            in_place: false,
+            ..self.inner.info()
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if !self.is_indirect::<T>() {
+            return self.inner.sort_with(slice, cmp);
+        }
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut order: Vec<usize> = (0..slice.len()).collect();
+        self.inner.sort_with(
+            &mut order,
+            &mut ByIndex {
+                items: &*slice,
+                inner: &mut *cmp,
+            },
+        );
+        permutation::apply_by(&mut order, |a, b| cmp::swap(cmp, slice, a, b));
+    }
+}
+
+impl<S: StableSorter> StableSorter for IndirectSort<S> {}
+
+/// Compares indices by the elements of `items` they point at.
+///
+/// Swaps and moves of the indices leave the items as they are, so they are not reported.
+pub(crate) struct ByIndex<'a, K: ?Sized, C> {
+    pub(crate) items: &'a K,
+    pub(crate) inner: C,
+}
+
+impl<K, C> Compare<usize> for ByIndex<'_, K, C>
+where
+    K: SortableContainer + ?Sized,
+    C: Compare<K::Item>,
+{
+    fn compare(&mut self, a: &usize, b: &usize) -> Ordering {
+        self.inner.compare(self.items.get(*a), self.items.get(*b))
+    }
+    fn is_less(&mut self, a: &usize, b: &usize) -> bool {
+        self.inner.is_less(self.items.get(*a), self.items.get(*b))
+    }
+    fn on_pivot(&mut self, pivot: &usize) {
+        self.inner.on_pivot(self.items.get(*pivot))
+    }
+    fn on_enter(&mut self) {
+        self.inner.on_enter()
+    }
+    fn on_leave(&mut self) {
+        self.inner.on_leave()
+    }
+    fn on_fallback(&mut self, to: &'static str) {
+        self.inner.on_fallback(to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use alloc::vec;
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Big {
+        key: u32,
+        payload: [u64; 32],
+    }
+
+    fn bigs(n: u32) -> Vec<Big> {
+        (0..n)
+            .map(|i| {
+                let key = (i * 7919) % 101;
+                Big {
+                    key,
+                    payload: [i as u64; 32],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn large_elements_take_fewer_swaps() {
+        let n = 1000;
+        let direct = Counting::new(IntroSort::default());
+        let mut expected = bigs(n);
+        direct.sort_by_key(&mut expected, |b| b.key);
+
+        let indirect = Counting::new(IndirectSort::default());
+        let mut tings = bigs(n);
+        indirect.sort_by_key(&mut tings, |b| b.key);
+        assert!(tings.windows(2).all(|w| w[0].key <= w[1].key));
+        assert!(indirect.stats().swaps < n as usize);
+        assert!(direct.stats().swaps > n as usize);
+
+        // a stable inner sorter gives the one stable order
+        let mut stable = bigs(n);
+        IndirectSort::new(MergeSort::default()).sort_by_key(&mut stable, |b| b.key);
+        let mut std_sorted = bigs(n);
+        std_sorted.sort_by_key(|b| b.key);
+        assert_eq!(stable, std_sorted);
+    }
+
+    #[test]
+    fn small_elements_are_sorted_directly() {
+        let sorter = IndirectSort::new(InsertionSort { smart: false });
+        assert!(!sorter.is_indirect::<u64>());
+        let counting = Counting::new(sorter);
+        let mut tings = vec![3u64, 1, 2];
+        counting.sort(&mut tings);
+        assert_eq!(tings, [1, 2, 3]);
+        assert_eq!(counting.stats().swaps, 2);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::cmp::Compare;
+use crate::partition::{Hoare, Partitioner};
+use crate::quick;
+use crate::{is_trivially_sorted, Complexity, HeapSort, SmallSortPolicy, Sorter, SorterInfo};
+
+/// Partitions this small are finished with branchless insertion sort, unless overridden globally.
+const SMALL_SORT: SmallSortPolicy = SmallSortPolicy::Branchless { threshold: 16 };
+
+/// Intro Sort
+///
+/// Introsort (introspective sort) begins with quicksort and switches to heapsort when the
+/// recursion depth exceeds a level based on the logarithm of the number of elements being
+/// sorted, so it keeps quicksort's practical speed with heapsort's O(n log n) worst case.
+/// Small partitions are finished with a branchless insertion sort. Partitions are split
+/// by a [`Partitioner`], Hoare's scheme by default.
+#[derive(Debug, Clone, Copy)]
+pub struct IntroSort<P = Hoare> {
+    pub partitioner: P,
+}
+
+impl Default for IntroSort {
+    fn default() -> Self {
+        IntroSort { partitioner: Hoare }
+    }
+}
+
+impl<P: Partitioner> IntroSort<P> {
+    fn introsort<T, C: Compare<T>>(
+        &self,
+        slice: &mut [T],
+        depth_limit: usize,
+        small: SmallSortPolicy,
+        cmp: &mut C,
+    ) {
+        if small.sort_small(slice, cmp) || slice.len() < 2 {
+            return;
+        }
+        if depth_limit == 0 {
+            cmp.on_fallback(HeapSort::default().info().name);
+            HeapSort::default().sort_with(slice, cmp);
+            return;
+        }
+        quick::median_of_three(slice, cmp);
+        let equal = self.partitioner.partition(slice, cmp);
+        let (left, right) = slice.split_at_mut(equal.start);
+        cmp.on_enter();
+        self.introsort(left, depth_limit - 1, small, cmp);
+        self.introsort(&mut right[equal.len()..], depth_limit - 1, small, cmp);
+        cmp.on_leave();
+    }
+}
+
+impl<P: Partitioner> Sorter for IntroSort<P> {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "intro",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linearithmic,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let log2 = (usize::BITS - 1 - slice.len().leading_zeros()) as usize;
+        self.introsort(slice, 2 * log2, SmallSortPolicy::resolve(SMALL_SORT), cmp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counting;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn is_sorted<T: Ord>(slice: &[T]) -> bool {
+        slice.windows(2).all(|w| w[0] <= w[1])
+    }
+
+    #[test]
+    fn intro_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        IntroSort::default().sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn sorts_patterns() {
+        let n = 1000;
+        let mut sorted: Vec<_> = (0..n).collect();
+        let mut reversed: Vec<_> = (0..n).rev().collect();
+        let mut equal = vec![7; n];
+        let mut sawtooth: Vec<_> = (0..n).map(|i| (i * 7919) % 97).collect();
+        for v in [&mut sorted, &mut reversed, &mut equal, &mut sawtooth] {
+            IntroSort::default().sort(v);
+            assert!(is_sorted(v));
+        }
+    }
+
+    #[test]
+    fn bounds_work_on_adversarial_input() {
+        // all-equal input makes this partition scheme degenerate
+        let n = 1 << 12;
+        let sorter = Counting::new(IntroSort::default());
+        sorter.sort(&mut vec![0; n]);
+        assert!(sorter.stats().comparisons < 8 * n * 12);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::cmp::{self, ByFn, ByOrd, Compare};
+use crate::Sorter;
+use alloc::vec::{self, Vec};
+use core::cmp::Ordering;
+
+/// Sorting adapters for iterators: collect the items into a `Vec`, sort it with the
+/// given [`Sorter`] and iterate over it in order, as itertools' `sorted` does with the
+/// standard library's sort.
+///
+/// ```
+/// use pangua::{IteratorSortExt, MergeSort, QuickSort};
+///
+/// let tings = vec!["ccc", "a", "bb", "b"];
+/// let sorted: Vec<_> = tings.iter().sorted_with(QuickSort::default()).collect();
+/// assert_eq!(sorted, [&"a", &"b", &"bb", &"ccc"]);
+/// let by_len = tings.into_iter().sorted_by_key_with(MergeSort::default(), |s| s.len());
+/// assert!(by_len.eq(["a", "b", "bb", "ccc"]));
+/// ```
+pub trait IteratorSortExt: Iterator + Sized {
+    fn sorted_with<S: Sorter>(self, sorter: S) -> vec::IntoIter<Self::Item>
+    where
+        Self::Item: Ord,
+    {
+        self.sorted_cmp_with(sorter, &mut ByOrd)
+    }
+
+    /// Like [`sorted_with`](IteratorSortExt::sorted_with), ordering with `compare`.
+    fn sorted_by_with<S, F>(self, sorter: S, compare: F) -> vec::IntoIter<Self::Item>
+    where
+        S: Sorter,
+        F: FnMut(&Self::Item, &Self::Item) -> Ordering,
+    {
+        self.sorted_cmp_with(sorter, &mut ByFn(compare))
+    }
+
+    /// Like [`sorted_with`](IteratorSortExt::sorted_with), ordering by the keys `key`
+    /// extracts.
+    fn sorted_by_key_with<S, K, F>(self, sorter: S, key: F) -> vec::IntoIter<Self::Item>
+    where
+        S: Sorter,
+        K: Ord,
+        F: FnMut(&Self::Item) -> K,
+    {
+        self.sorted_cmp_with(sorter, &mut cmp::by_key(key))
+    }
+
+    /// Like [`sorted_with`](IteratorSortExt::sorted_with), ordering with `cmp`.
+    fn sorted_cmp_with<S, C>(self, sorter: S, cmp: &mut C) -> vec::IntoIter<Self::Item>
+    where
+        S: Sorter,
+        C: Compare<Self::Item>,
+    {
+        let mut items: Vec<Self::Item> = self.collect();
+        crate::sort_reported(&sorter, &mut items, cmp);
+        items.into_iter()
+    }
+}
+
+impl<I: Iterator> IteratorSortExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn iterates_in_sorted_order() {
+        let tings = [(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd')];
+        let sorted: Vec<_> = tings
+            .iter()
+            .copied()
+            .sorted_by_key_with(MergeSort::default(), |t| t.0)
+            .collect();
+        assert_eq!(sorted, [(0, 'd'), (1, 'b'), (2, 'a'), (2, 'c')]);
+
+        let descending = (0..50).sorted_by_with(HeapSort::default(), |a, b| b.cmp(a));
+        assert_eq!(descending.len(), 50);
+        assert!(descending.eq((0..50).rev()));
+        assert_eq!(
+            core::iter::empty::<u8>()
+                .sorted_with(QuickSort::default())
+                .next(),
+            None
+        );
+    }
+}
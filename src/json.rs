@@ -0,0 +1,397 @@
+//! Sorting JSON-like documents by the values at JSON pointers.
+//!
+//! Documents are read through [`JsonValue`], which takes a few lines to implement for
+//! the value type of any JSON library. A schema is a list of [`SortKey`]s, each a JSON
+//! pointer (RFC 6901, e.g. `/user/age`) and a direction; documents are ordered by the
+//! first key, ties broken by the next, and so on.
+//!
+//! Values are compared by type first: booleans, then numbers, then strings, then arrays
+//! and objects, which are all equal to each other. Nulls and missing values come last
+//! in either direction.
+//!
+//! `serde_json::Value` implements [`JsonValue`], and fields can be named by dotted paths
+//! as well, such as `user.age`. The same comparison applies to the string fields of
+//! CSV-like records, see [`sort_records_by_column`].
+//!
+//! ```
+//! use pangua::json::sort_by_path;
+//! use pangua::MergeSort;
+//! use serde_json::json;
+//!
+//! let mut users = vec![
+//!     json!({"name": "ann", "info": {"age": 40}}),
+//!     json!({"name": "bob", "info": {"age": null}}),
+//!     json!({"name": "cat", "info": {"age": 7}}),
+//! ];
+//! sort_by_path(&mut users, "info.age", false, MergeSort::default());
+//! assert_eq!(users[0]["name"], "cat");
+//! assert_eq!(users[2]["name"], "bob");
+//! ```
+
+use crate::Sorter;
+use alloc::string::String;
+use core::cmp::Ordering;
+
+/// The part of a JSON value that sorting looks at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scalar<'a> {
+    Null,
+    Bool(bool),
+    Number(f64),
+    /// An integer, kept exact where an `f64` could not hold it, past 2^53.
+    Integer(i128),
+    String(&'a str),
+    /// An array or an object.
+    Composite,
+}
+
+impl Scalar<'_> {
+    /// Position of the type in the order, nulls last.
+    fn rank(&self) -> u8 {
+        match self {
+            Scalar::Bool(_) => 0,
+            Scalar::Number(_) | Scalar::Integer(_) => 1,
+            Scalar::String(_) => 2,
+            Scalar::Composite => 3,
+            Scalar::Null => 4,
+        }
+    }
+
+    fn cmp_same_type(&self, other: &Scalar<'_>) -> Ordering {
+        match (self, other) {
+            (Scalar::Bool(a), Scalar::Bool(b)) => a.cmp(b),
+            (Scalar::Number(a), Scalar::Number(b)) => a.total_cmp(b),
+            (Scalar::Integer(a), Scalar::Integer(b)) => a.cmp(b),
+            (Scalar::Integer(a), Scalar::Number(b)) => cmp_integer_float(*a, *b),
+            (Scalar::Number(a), Scalar::Integer(b)) => cmp_integer_float(*b, *a).reverse(),
+            (Scalar::String(a), Scalar::String(b)) => a.cmp(b),
+            _ => Ordering::Equal,
+        }
+    }
+
+    /// Compares by type, then by value in the given direction, nulls last either way.
+    fn cmp_directed(&self, other: &Scalar<'_>, descending: bool) -> Ordering {
+        self.rank().cmp(&other.rank()).then_with(|| {
+            let order = self.cmp_same_type(other);
+            if descending {
+                order.reverse()
+            } else {
+                order
+            }
+        })
+    }
+
+    /// A text field read as a value, without surrounding whitespace: empty is null,
+    /// anything that parses as a finite number is one, and the rest, "NaN" and "inf"
+    /// included, is text.
+    pub fn from_field(field: &str) -> Scalar<'_> {
+        let trimmed = field.trim();
+        if trimmed.is_empty() {
+            return Scalar::Null;
+        }
+        match trimmed.parse().ok().filter(|n: &f64| n.is_finite()) {
+            Some(number) => Scalar::Number(number),
+            None => Scalar::String(trimmed),
+        }
+    }
+}
+
+/// Compares an integer with a float exactly: by the float's total order, then, where the
+/// integer rounds to the float, by the integer the float holds.
+fn cmp_integer_float(integer: i128, float: f64) -> Ordering {
+    (integer as f64).total_cmp(&float).then_with(|| {
+        // only integral floats get here, which the cast holds exactly, saturating at 2^127
+        integer.cmp(&(float as i128))
+    })
+}
+
+/// A JSON document that can be searched by pointer.
+pub trait JsonValue {
+    /// The value at the JSON pointer, `""` being the document itself.
+    fn pointer(&self, pointer: &str) -> Option<&Self>;
+
+    fn scalar(&self) -> Scalar<'_>;
+}
+
+/// One level of a sorting schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    pub pointer: String,
+    pub descending: bool,
+}
+
+impl SortKey {
+    pub fn ascending(pointer: impl Into<String>) -> Self {
+        SortKey {
+            pointer: pointer.into(),
+            descending: false,
+        }
+    }
+
+    pub fn descending(pointer: impl Into<String>) -> Self {
+        SortKey {
+            pointer: pointer.into(),
+            descending: true,
+        }
+    }
+}
+
+/// The JSON pointer to the field at a dotted path: `user.tags.0` is `/user/tags/0`, and
+/// the empty path is the document itself.
+pub fn pointer_from_path(path: &str) -> String {
+    let mut pointer = String::new();
+    if path.is_empty() {
+        return pointer;
+    }
+    for token in path.split('.') {
+        pointer.push('/');
+        for c in token.chars() {
+            match c {
+                '~' => pointer.push_str("~0"),
+                '/' => pointer.push_str("~1"),
+                c => pointer.push(c),
+            }
+        }
+    }
+    pointer
+}
+
+/// Compares two documents by the schema `keys`.
+pub fn compare_by_schema<V: JsonValue>(a: &V, b: &V, keys: &[SortKey]) -> Ordering {
+    for key in keys {
+        let x = a.pointer(&key.pointer).map_or(Scalar::Null, V::scalar);
+        let y = b.pointer(&key.pointer).map_or(Scalar::Null, V::scalar);
+        let order = x.cmp_directed(&y, key.descending);
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Sorts the documents by the schema `keys` with `sorter`.
+///
+/// Use a stable sorter to keep documents equal under the schema in their original order.
+pub fn sort_by_schema<V: JsonValue, S: Sorter>(values: &mut [V], keys: &[SortKey], sorter: S) {
+    sorter.sort_by(values, |a, b| compare_by_schema(a, b, keys))
+}
+
+/// Sorts the documents by the field at the dotted `path` with `sorter`, see
+/// [`pointer_from_path`].
+pub fn sort_by_path<V: JsonValue, S: Sorter>(
+    values: &mut [V],
+    path: &str,
+    descending: bool,
+    sorter: S,
+) {
+    let key = SortKey {
+        pointer: pointer_from_path(path),
+        descending,
+    };
+    sort_by_schema(values, &[key], sorter)
+}
+
+/// Compares two text fields as [`Scalar::from_field`] reads them: numbers numerically
+/// and before strings, strings lexically, empty fields last.
+pub fn compare_fields(a: &str, b: &str, descending: bool) -> Ordering {
+    Scalar::from_field(a).cmp_directed(&Scalar::from_field(b), descending)
+}
+
+/// Sorts records of text fields, such as parsed CSV rows, by the field in `column`
+/// with `sorter`, comparing as [`compare_fields`] does. Records too short to have the
+/// column come last.
+pub fn sort_records_by_column<R, F, S>(
+    records: &mut [R],
+    column: usize,
+    descending: bool,
+    sorter: S,
+) where
+    R: AsRef<[F]>,
+    F: AsRef<str>,
+    S: Sorter,
+{
+    sorter.sort_by(records, |a, b| {
+        let x = a.as_ref().get(column).map_or("", F::as_ref);
+        let y = b.as_ref().get(column).map_or("", F::as_ref);
+        compare_fields(x, y, descending)
+    })
+}
+
+impl JsonValue for serde_json::Value {
+    fn pointer(&self, pointer: &str) -> Option<&Self> {
+        serde_json::Value::pointer(self, pointer)
+    }
+
+    fn scalar(&self) -> Scalar<'_> {
+        match self {
+            serde_json::Value::Null => Scalar::Null,
+            serde_json::Value::Bool(b) => Scalar::Bool(*b),
+            serde_json::Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+                (Some(i), _) => Scalar::Integer(i.into()),
+                (_, Some(u)) => Scalar::Integer(u.into()),
+                _ => n.as_f64().map_or(Scalar::Null, Scalar::Number),
+            },
+            serde_json::Value::String(s) => Scalar::String(s),
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => Scalar::Composite,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MergeSort;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Doc {
+        Null,
+        Num(f64),
+        Str(&'static str),
+        Obj(Vec<(&'static str, Doc)>),
+    }
+
+    impl JsonValue for Doc {
+        fn pointer(&self, pointer: &str) -> Option<&Doc> {
+            let mut doc = self;
+            for token in pointer.split('/').skip(1) {
+                match doc {
+                    Doc::Obj(fields) => doc = &fields.iter().find(|f| f.0 == token)?.1,
+                    _ => return None,
+                }
+            }
+            Some(doc)
+        }
+
+        fn scalar(&self) -> Scalar<'_> {
+            match self {
+                Doc::Null => Scalar::Null,
+                Doc::Num(n) => Scalar::Number(*n),
+                Doc::Str(s) => Scalar::String(s),
+                Doc::Obj(_) => Scalar::Composite,
+            }
+        }
+    }
+
+    fn person(name: &'static str, age: Doc) -> Doc {
+        Doc::Obj(vec![
+            ("name", Doc::Str(name)),
+            ("info", Doc::Obj(vec![("age", age)])),
+        ])
+    }
+
+    fn names(docs: &[Doc]) -> Vec<&'static str> {
+        docs.iter()
+            .map(|d| match d.pointer("/name") {
+                Some(Doc::Str(s)) => *s,
+                _ => "?",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sorts_by_pointer_with_nulls_last() {
+        let mut docs = vec![
+            person("ann", Doc::Num(40.0)),
+            person("bob", Doc::Null),
+            person("cat", Doc::Str("unknown")),
+            person("dan", Doc::Num(7.0)),
+            Doc::Obj(vec![("name", Doc::Str("eve"))]),
+        ];
+        sort_by_schema(
+            &mut docs,
+            &[SortKey::ascending("/info/age")],
+            MergeSort::default(),
+        );
+        assert_eq!(names(&docs), &["dan", "ann", "cat", "bob", "eve"]);
+        sort_by_schema(
+            &mut docs,
+            &[SortKey::descending("/info/age")],
+            MergeSort::default(),
+        );
+        assert_eq!(names(&docs), &["ann", "dan", "cat", "bob", "eve"]);
+    }
+
+    #[test]
+    fn later_keys_break_ties() {
+        let mut docs = vec![
+            person("cat", Doc::Num(30.0)),
+            person("ann", Doc::Num(30.0)),
+            person("bob", Doc::Num(20.0)),
+        ];
+        let schema = [
+            SortKey::descending("/info/age"),
+            SortKey::ascending("/name"),
+        ];
+        sort_by_schema(&mut docs, &schema, MergeSort::default());
+        assert_eq!(names(&docs), &["ann", "cat", "bob"]);
+        assert_eq!(
+            compare_by_schema(&docs[0], &docs[0], &schema),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn records_sort_by_typed_fields() {
+        assert_eq!(pointer_from_path("info.age"), "/info/age");
+        assert_eq!(pointer_from_path("a/b.~c"), "/a~1b/~0c");
+        assert_eq!(pointer_from_path(""), "");
+
+        let mut records = vec![
+            vec!["ann", "10"],
+            vec!["bob", ""],
+            vec!["cat", "9"],
+            vec!["dan", "n/a"],
+            vec!["eve"],
+            vec!["fay", "-2.5"],
+        ];
+        sort_records_by_column(&mut records, 1, false, MergeSort::default());
+        let names: Vec<_> = records.iter().map(|r| r[0]).collect();
+        assert_eq!(names, ["fay", "cat", "ann", "dan", "bob", "eve"]);
+        sort_records_by_column(&mut records, 1, true, MergeSort::default());
+        let names: Vec<_> = records.iter().map(|r| r[0]).collect();
+        assert_eq!(names, ["ann", "cat", "fay", "dan", "bob", "eve"]);
+    }
+
+    #[test]
+    fn fields_are_trimmed_and_only_finite_numbers_are_numbers() {
+        for &field in ["NaN", "inf", "-Infinity"].iter() {
+            assert_eq!(Scalar::from_field(field), Scalar::String(field));
+        }
+        assert_eq!(Scalar::from_field("  b "), Scalar::String("b"));
+        assert_eq!(Scalar::from_field(" 1e3 "), Scalar::Number(1000.0));
+        assert_eq!(compare_fields(" b", "b", false), Ordering::Equal);
+        assert_eq!(compare_fields("NaN", "1e308", false), Ordering::Greater);
+        assert_eq!(compare_fields("inf", "a", false), Ordering::Greater);
+    }
+
+    #[test]
+    fn sorts_serde_values_by_path() {
+        use serde_json::json;
+
+        let mut docs = vec![
+            json!({"id": 1, "user": {"tags": ["b"]}}),
+            json!({"id": 2, "user": {"tags": []}}),
+            json!({"id": 3, "user": {"tags": ["a"]}}),
+            json!({"id": 4, "user": {"tags": [1.5]}}),
+        ];
+        sort_by_path(&mut docs, "user.tags.0", false, MergeSort::default());
+        let ids: Vec<_> = docs.iter().map(|d| d["id"].as_u64().unwrap()).collect();
+        assert_eq!(ids, [4, 3, 1, 2]);
+
+        // past 2^53, where neighbouring integers share an f64
+        let big = 1u64 << 60;
+        let mut docs = vec![
+            json!({"id": 1, "n": big + 1}),
+            json!({"id": 2, "n": big as f64}),
+            json!({"id": 3, "n": big}),
+            json!({"id": 4, "n": -(big as i64) - 1}),
+            json!({"id": 5, "n": u64::MAX}),
+            json!({"id": 6, "n": 0.5}),
+        ];
+        sort_by_path(&mut docs, "n", false, MergeSort::default());
+        let ids: Vec<_> = docs.iter().map(|d| d["id"].as_u64().unwrap()).collect();
+        assert_eq!(ids, [4, 6, 2, 3, 1, 5]);
+    }
+}
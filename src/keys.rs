@@ -0,0 +1,128 @@
+//! Sorting by keys that are expensive to compute.
+
+use crate::permutation;
+use crate::Sorter;
+use alloc::vec::Vec;
+
+/// Reusable buffers for [`sort_by_expensive_key`].
+///
+/// Keys are computed once per element into the arena, then `(key, index)` pairs are
+/// sorted and the resulting permutation is applied to the slice. The keys are dropped
+/// after every sort, but the allocations are kept so repeated sorts don't reallocate.
+pub struct KeyArena<K> {
+    keys: Vec<K>,
+    indices: Vec<usize>,
+}
+
+impl<K: Ord> KeyArena<K> {
+    pub fn new() -> Self {
+        KeyArena {
+            keys: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    pub fn sort_by_expensive_key<T, F, S>(&mut self, slice: &mut [T], f: F, sorter: S)
+    where
+        F: FnMut(&T) -> K,
+        S: Sorter,
+    {
+        self.keys.clear();
+        self.keys.extend(slice.iter().map(f));
+        self.indices.clear();
+        self.indices.extend(0..slice.len());
+
+        let keys = &self.keys;
+        // ties are broken by position, so the result is stable whatever the sorter
+        sorter.sort_by(&mut self.indices, |&a, &b| {
+            keys[a].cmp(&keys[b]).then(a.cmp(&b))
+        });
+        permutation::apply(slice, &mut self.indices);
+        self.keys.clear();
+    }
+}
+
+impl<K: Ord> Default for KeyArena<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sorts the slice by a key computed once per element.
+///
+/// Unlike `sort_by_key`, which calls `f` on every comparison, this calls `f` exactly
+/// `slice.len()` times, which pays off when keys are costly (allocating, parsing, ...).
+/// Use a [`KeyArena`] directly to reuse its buffers across many sorts.
+pub fn sort_by_expensive_key<T, K, F, S>(slice: &mut [T], f: F, sorter: S)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+    S: Sorter,
+{
+    KeyArena::new().sort_by_expensive_key(slice, f, sorter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use alloc::string::String;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn sorts_by_computed_key() {
+        let mut tings = vec!["Delta", "alpha", "Charlie", "bravo"];
+        sort_by_expensive_key(&mut tings, |s| s.to_lowercase(), QuickSort::default());
+        assert_eq!(tings, &["alpha", "bravo", "Charlie", "Delta"]);
+    }
+
+    #[test]
+    fn sorters_cache_keys() {
+        let mut calls = 0;
+        let mut tings: Vec<String> = ["10", "9", "100", "1"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        IntroSort::default().sort_by_cached_key(&mut tings, |s| {
+            calls += 1;
+            s.parse::<u32>().unwrap()
+        });
+        assert_eq!(tings, &["1", "9", "10", "100"]);
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    fn is_stable_with_unstable_sorter() {
+        let mut tings = vec![(1, 'a'), (0, 'b'), (1, 'c'), (0, 'd'), (1, 'e')];
+        sort_by_expensive_key(&mut tings, |t| t.0, HeapSort::default());
+        assert_eq!(tings, &[(0, 'b'), (0, 'd'), (1, 'a'), (1, 'c'), (1, 'e')]);
+    }
+
+    #[test]
+    fn computes_each_key_once() {
+        let mut calls = 0;
+        let mut tings = vec![5, 1, 4, 2, 3];
+        sort_by_expensive_key(
+            &mut tings,
+            |&x| {
+                calls += 1;
+                x
+            },
+            BubbleSort,
+        );
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        assert_eq!(calls, 5);
+    }
+
+    #[test]
+    fn arena_is_reusable() {
+        let mut arena = KeyArena::new();
+        let mut a = vec![3, 1, 2];
+        let mut b = vec![9, 7, 8, 6];
+        arena.sort_by_expensive_key(&mut a, |x| x.to_string(), MergeSort::default());
+        arena.sort_by_expensive_key(&mut b, |x| x.to_string(), MergeSort::default());
+        assert_eq!(a, &[1, 2, 3]);
+        assert_eq!(b, &[6, 7, 8, 9]);
+    }
+}
@@ -0,0 +1,456 @@
+//! Merging many sorted inputs at once with a tournament tree of losers.
+
+use crate::cmp::{ByFn, ByOrd, Compare};
+use crate::Error;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem;
+#[cfg(feature = "std")]
+use std::sync::mpsc;
+#[cfg(feature = "std")]
+use std::thread;
+
+/// Elements a merge worker hands over at a time in [`KWayMerge::par_merge`].
+#[cfg(feature = "std")]
+const BLOCK: usize = 1024;
+
+/// A k-way merge of sorted iterators, yielding their elements in sorted order.
+///
+/// The inputs are the leaves of a tournament tree whose inner nodes remember the loser
+/// of the match played there, with the overall winner kept on top. Taking the winner
+/// and replaying its path from the leaf costs ⌈log2 k⌉ comparisons per element, about
+/// half of what a binary heap pays. Equal elements come out in the order of their
+/// inputs, so the merge is stable.
+///
+/// ```
+/// use pangua::LoserTree;
+///
+/// let merged: Vec<_> = LoserTree::new(vec![vec![1, 4, 7], vec![2, 5], vec![3, 6]]).collect();
+/// assert_eq!(merged, &[1, 2, 3, 4, 5, 6, 7]);
+/// ```
+#[derive(Debug)]
+pub struct LoserTree<I: Iterator, C = ByOrd> {
+    inputs: Vec<I>,
+    /// The current element of each input, `None` once it is exhausted.
+    heads: Vec<Option<I::Item>>,
+    /// `tree[0]` is the winner, `tree[n]` the loser of the match at inner node `n`.
+    tree: Vec<usize>,
+    cmp: C,
+}
+
+impl<I: Iterator> LoserTree<I, ByOrd>
+where
+    I::Item: Ord,
+{
+    /// Merges the inputs, which must each be sorted.
+    pub fn new<J>(inputs: impl IntoIterator<Item = J>) -> Self
+    where
+        J: IntoIterator<IntoIter = I>,
+    {
+        LoserTree::with_compare(inputs, ByOrd)
+    }
+}
+
+impl<I: Iterator, F> LoserTree<I, ByFn<F>>
+where
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    /// Merges the inputs, which must each be sorted in the order defined by `compare`.
+    pub fn new_by<J>(inputs: impl IntoIterator<Item = J>, compare: F) -> Self
+    where
+        J: IntoIterator<IntoIter = I>,
+    {
+        LoserTree::with_compare(inputs, ByFn(compare))
+    }
+}
+
+impl<I: Iterator, C> LoserTree<I, C> {
+    /// Bytes held per input: the input itself, its current element and its node in
+    /// the tree.
+    pub fn memory_per_input() -> usize {
+        mem::size_of::<I>() + mem::size_of::<Option<I::Item>>() + mem::size_of::<usize>()
+    }
+
+    /// Returns the number of inputs being merged, exhausted or not.
+    pub fn fan_in(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// The comparator ordering the merge, to compare the elements coming out of it
+    /// with one another.
+    pub fn compare_mut(&mut self) -> &mut C {
+        &mut self.cmp
+    }
+}
+
+impl<I: Iterator, C: Compare<I::Item>> LoserTree<I, C> {
+    /// Merges the inputs, which must each be sorted in the order defined by `cmp`.
+    pub fn with_compare<J>(inputs: impl IntoIterator<Item = J>, cmp: C) -> Self
+    where
+        J: IntoIterator<IntoIter = I>,
+    {
+        let mut inputs: Vec<I> = inputs.into_iter().map(|j| j.into_iter()).collect();
+        let heads = inputs.iter_mut().map(|i| i.next()).collect();
+        let k = inputs.len();
+        let mut merge = LoserTree {
+            inputs,
+            heads,
+            tree: vec![0; k],
+            cmp,
+        };
+        if k > 0 {
+            // play every match bottom-up, leaf `i` sitting at node `k + i`
+            let mut winners = vec![0; 2 * k];
+            for (i, w) in winners[k..].iter_mut().enumerate() {
+                *w = i;
+            }
+            for n in (1..k).rev() {
+                let (a, b) = (winners[2 * n], winners[2 * n + 1]);
+                let (winner, loser) = if merge.beats(a, b) { (a, b) } else { (b, a) };
+                winners[n] = winner;
+                merge.tree[n] = loser;
+            }
+            merge.tree[0] = winners[1];
+        }
+        merge
+    }
+
+    /// Whether input `a` sends its current element out before input `b` does.
+    fn beats(&mut self, a: usize, b: usize) -> bool {
+        match (&self.heads[a], &self.heads[b]) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some(x), Some(y)) => match self.cmp.compare(x, y) {
+                Ordering::Less => true,
+                Ordering::Greater => false,
+                Ordering::Equal => a < b,
+            },
+        }
+    }
+}
+
+impl<I: Iterator, C: Compare<I::Item>> Iterator for LoserTree<I, C> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let k = self.inputs.len();
+        let mut winner = *self.tree.first()?;
+        let item = self.heads[winner].take()?;
+        self.heads[winner] = self.inputs[winner].next();
+        let mut node = (winner + k) / 2;
+        while node > 0 {
+            if self.beats(self.tree[node], winner) {
+                mem::swap(&mut self.tree[node], &mut winner);
+            }
+            node /= 2;
+        }
+        self.tree[0] = winner;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.heads.iter().filter(|h| h.is_some()).count();
+        self.inputs
+            .iter()
+            .fold((buffered, Some(buffered)), |(lo, hi), i| {
+                let (l, h) = i.size_hint();
+                (
+                    lo.saturating_add(l),
+                    hi.and_then(|hi| h.and_then(|h| hi.checked_add(h))),
+                )
+            })
+    }
+}
+
+/// Merges any number of sorted inputs, at most `fan_in` of them at a time.
+///
+/// With more inputs than the fan-in, groups of `fan_in` inputs are merged into
+/// intermediate runs, pass after pass, until a single merge finishes the job. A smaller
+/// fan-in bounds the elements held at once, one per input being merged, at the cost of
+/// extra passes over the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KWayMerge {
+    /// Inputs merged together in one pass, at least 2.
+    pub fan_in: usize,
+}
+
+impl KWayMerge {
+    /// Creates a merger of `fan_in` inputs at a time, which must be at least 2.
+    pub fn new(fan_in: usize) -> Result<Self, Error> {
+        let merge = KWayMerge { fan_in };
+        merge.validate()?;
+        Ok(merge)
+    }
+
+    /// Picks the largest fan-in whose merges over inputs of type `I` fit in `budget`
+    /// bytes, given `buffer_per_input` bytes of buffering in each input.
+    pub fn from_memory_budget<I: Iterator>(
+        budget: usize,
+        buffer_per_input: usize,
+    ) -> Result<Self, Error> {
+        let per_input = LoserTree::<I>::memory_per_input() + buffer_per_input;
+        KWayMerge::new(budget / per_input.max(1))
+    }
+
+    /// Checks the configuration, as the merge itself would before starting.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.fan_in < 2 {
+            return Err(Error::FanInTooSmall {
+                fan_in: self.fan_in,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns how many passes over the data merging `inputs` sorted inputs takes.
+    pub fn passes(&self, inputs: usize) -> usize {
+        let mut runs = inputs;
+        let mut passes = 0;
+        while runs > 1 {
+            runs = runs.div_ceil(self.fan_in);
+            passes += 1;
+        }
+        passes
+    }
+
+    /// Merges the inputs, which must each be sorted.
+    pub fn merge<T, J>(&self, inputs: impl IntoIterator<Item = J>) -> Vec<T>
+    where
+        T: Ord,
+        J: IntoIterator<Item = T>,
+    {
+        self.merge_with(inputs, &mut ByOrd)
+    }
+
+    /// Merges the inputs, which must each be sorted in the order defined by `compare`.
+    pub fn merge_by<T, J, F>(&self, inputs: impl IntoIterator<Item = J>, compare: F) -> Vec<T>
+    where
+        J: IntoIterator<Item = T>,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.merge_with(inputs, &mut ByFn(compare))
+    }
+
+    /// Merges the inputs, which must each be sorted in the order defined by `cmp`.
+    pub fn merge_with<T, J, C>(&self, inputs: impl IntoIterator<Item = J>, cmp: &mut C) -> Vec<T>
+    where
+        J: IntoIterator<Item = T>,
+        C: Compare<T>,
+    {
+        if let Err(e) = self.validate() {
+            panic!("{}", e)
+        }
+        let mut inputs = inputs.into_iter().peekable();
+        let mut runs: Vec<Vec<T>> = Vec::new();
+        // the first pass reads the inputs themselves, the others the runs it left
+        while inputs.peek().is_some() {
+            let group = inputs.by_ref().take(self.fan_in);
+            runs.push(LoserTree::with_compare(group, &mut *cmp).collect());
+        }
+        while runs.len() > 1 {
+            let mut next = Vec::with_capacity(runs.len().div_ceil(self.fan_in));
+            let mut drain = runs.into_iter().peekable();
+            while drain.peek().is_some() {
+                let group = drain.by_ref().take(self.fan_in);
+                next.push(LoserTree::with_compare(group, &mut *cmp).collect());
+            }
+            runs = next;
+        }
+        runs.pop().unwrap_or_default()
+    }
+
+    /// Merges the inputs, which must each be sorted, on up to `workers` threads.
+    ///
+    /// The inputs are split into contiguous groups, one per worker, and each worker
+    /// merges its group in passes of `fan_in` as [`KWayMerge::merge`] does. The calling
+    /// thread merges the workers' outputs as they are produced: each worker sends blocks
+    /// of elements through a channel holding one block, so it fills the next block while
+    /// the last one is being merged. Workers are capped by the fan-in, keeping the final
+    /// merge to a single pass, and equal elements keep the order of their inputs.
+    #[cfg(feature = "std")]
+    pub fn par_merge<T, J>(&self, inputs: Vec<J>, workers: usize) -> Vec<T>
+    where
+        T: Ord + Send,
+        J: IntoIterator<Item = T> + Send,
+    {
+        self.par_merge_by(inputs, workers, T::cmp)
+    }
+
+    /// Like [`KWayMerge::par_merge`], with inputs sorted in the order defined by `compare`.
+    #[cfg(feature = "std")]
+    pub fn par_merge_by<T, J, F>(&self, mut inputs: Vec<J>, workers: usize, compare: F) -> Vec<T>
+    where
+        T: Send,
+        J: IntoIterator<Item = T> + Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        if let Err(e) = self.validate() {
+            panic!("{}", e)
+        }
+        let workers = workers.min(self.fan_in).min(inputs.len());
+        if workers < 2 {
+            return self.merge_by(inputs, &compare);
+        }
+        let per_worker = inputs.len().div_ceil(workers);
+        let compare = &compare;
+        thread::scope(|scope| {
+            let mut outputs = Vec::with_capacity(workers);
+            while !inputs.is_empty() {
+                let rest = inputs.split_off(per_worker.min(inputs.len()));
+                let group = mem::replace(&mut inputs, rest);
+                let (tx, rx) = mpsc::sync_channel::<Vec<T>>(1);
+                scope.spawn(move || {
+                    let send = |merged: &mut dyn Iterator<Item = T>| {
+                        let mut block = Vec::with_capacity(BLOCK);
+                        for x in merged {
+                            block.push(x);
+                            if block.len() == BLOCK {
+                                let full = mem::replace(&mut block, Vec::with_capacity(BLOCK));
+                                // the receiver only hangs up when the merge panicked
+                                if tx.send(full).is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        if !block.is_empty() {
+                            let _ = tx.send(block);
+                        }
+                    };
+                    if group.len() <= self.fan_in {
+                        send(&mut LoserTree::new_by(group, compare))
+                    } else {
+                        send(&mut self.merge_by(group, compare).into_iter())
+                    }
+                });
+                outputs.push(rx.into_iter().flatten());
+            }
+            LoserTree::new_by(outputs, compare).collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counter;
+
+    fn runs(k: usize, len: usize) -> Vec<Vec<usize>> {
+        (0..k)
+            .map(|r| (0..len).map(|i| i * k + (r * 7) % k).collect())
+            .collect()
+    }
+
+    #[test]
+    fn loser_tree_works() {
+        let merged: Vec<_> =
+            LoserTree::new(vec![vec![1, 4, 7], vec![], vec![2, 5], vec![3, 6]]).collect();
+        assert_eq!(merged, &[1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(LoserTree::new(Vec::<Vec<u8>>::new()).next(), None);
+        assert_eq!(
+            LoserTree::new(vec![vec![2, 1]]).collect::<Vec<_>>(),
+            &[2, 1]
+        );
+        for k in 1..20 {
+            let inputs = runs(k, 13);
+            let mut expected: Vec<_> = inputs.iter().flatten().copied().collect();
+            expected.sort();
+            let merge = LoserTree::new(inputs);
+            assert_eq!(merge.size_hint(), (13 * k, Some(13 * k)));
+            assert_eq!(merge.collect::<Vec<_>>(), expected, "k = {}", k);
+        }
+    }
+
+    #[test]
+    fn loser_tree_is_stable() {
+        let inputs = vec![
+            vec![(1, 'a'), (2, 'b')],
+            vec![(1, 'c')],
+            vec![(0, 'd'), (2, 'e')],
+        ];
+        let merged: Vec<_> = LoserTree::new_by(inputs, |a, b| a.0.cmp(&b.0)).collect();
+        assert_eq!(merged, &[(0, 'd'), (1, 'a'), (1, 'c'), (2, 'b'), (2, 'e')]);
+    }
+
+    #[test]
+    fn loser_tree_compares_log_k_times_per_element() {
+        let mut counter = Counter::new(ByOrd);
+        let merged: Vec<_> = LoserTree::with_compare(runs(64, 100), &mut counter).collect();
+        assert_eq!(merged.len(), 6400);
+        // building plays k - 1 matches, and each element replays log2(64) = 6
+        assert!(counter.stats().comparisons <= 63 + 6 * 6400);
+    }
+
+    #[test]
+    fn k_way_merge_works() {
+        let inputs = runs(300, 5);
+        let mut expected: Vec<_> = inputs.iter().flatten().copied().collect();
+        expected.sort();
+        for &fan_in in &[2, 3, 16, 299, 300, 1000] {
+            let merge = KWayMerge::new(fan_in).unwrap();
+            assert_eq!(merge.merge(inputs.clone()), expected, "fan-in {}", fan_in);
+        }
+        expected.reverse();
+        let reversed = inputs.into_iter().map(|r| r.into_iter().rev());
+        assert_eq!(
+            KWayMerge { fan_in: 4 }.merge_by(reversed, |a, b| b.cmp(a)),
+            expected
+        );
+        assert!(KWayMerge { fan_in: 2 }
+            .merge(Vec::<Vec<u8>>::new())
+            .is_empty());
+    }
+
+    #[test]
+    fn passes_depend_on_fan_in() {
+        assert_eq!(KWayMerge { fan_in: 2 }.passes(1), 0);
+        assert_eq!(KWayMerge { fan_in: 2 }.passes(8), 3);
+        assert_eq!(KWayMerge { fan_in: 10 }.passes(100), 2);
+        assert_eq!(KWayMerge { fan_in: 10 }.passes(101), 3);
+    }
+
+    #[test]
+    fn fan_in_follows_the_memory_budget() {
+        type Input = alloc::vec::IntoIter<u64>;
+        let per_input = LoserTree::<Input>::memory_per_input() + 4096;
+        let merge = KWayMerge::from_memory_budget::<Input>(100 * per_input, 4096).unwrap();
+        assert_eq!(merge.fan_in, 100);
+        assert_eq!(
+            KWayMerge::from_memory_budget::<Input>(per_input, 4096),
+            Err(Error::FanInTooSmall { fan_in: 1 })
+        );
+        assert_eq!(KWayMerge::new(0), Err(Error::FanInTooSmall { fan_in: 0 }));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn par_merge_matches_merge() {
+        let inputs = runs(50, 300);
+        let mut expected: Vec<_> = inputs.iter().flatten().copied().collect();
+        expected.sort();
+        for &(fan_in, workers) in &[(2, 4), (4, 4), (8, 3), (64, 8), (64, 100), (16, 1)] {
+            let merge = KWayMerge::new(fan_in).unwrap();
+            assert_eq!(
+                merge.par_merge(inputs.clone(), workers),
+                expected,
+                "fan-in {}, {} workers",
+                fan_in,
+                workers
+            );
+        }
+        assert!(KWayMerge { fan_in: 2 }
+            .par_merge(Vec::<Vec<u8>>::new(), 4)
+            .is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn par_merge_is_stable() {
+        let inputs: Vec<Vec<(usize, usize)>> = (0..12)
+            .map(|r| (0..3000).map(|i| (i / 7, r)).collect())
+            .collect();
+        let merged = KWayMerge { fan_in: 3 }.par_merge_by(inputs, 3, |a, b| a.0.cmp(&b.0));
+        assert_eq!(merged.len(), 12 * 3000);
+        assert!(merged.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
@@ -1,9 +1,506 @@
-use std::cmp::Ord;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(any(feature = "alloc", test))]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::{Ord, Ordering};
+use core::fmt;
+use core::ops::Deref;
+
+#[cfg(feature = "alloc")]
+mod american_flag;
+#[cfg(feature = "alloc")]
+mod argsort;
+pub mod array;
+#[cfg(feature = "alloc")]
+mod auto;
+#[cfg(feature = "std")]
+mod batch;
+#[cfg(feature = "bench")]
+pub mod bench;
+mod bitonic;
+#[cfg(feature = "alloc")]
+mod block;
+#[cfg(feature = "alloc")]
+mod blocked;
+#[cfg(feature = "alloc")]
+mod bottom_up;
+#[cfg(feature = "std")]
+mod bounded;
+#[cfg(feature = "alloc")]
+mod bucket;
+pub mod cmp;
+#[cfg(feature = "alloc")]
+mod container;
+#[cfg(feature = "async")]
+pub mod cooperative;
+#[cfg(feature = "std")]
+mod cost;
+#[cfg(feature = "alloc")]
+mod counting;
+mod cycle;
+#[cfg(feature = "alloc")]
+mod default;
+#[cfg(feature = "alloc")]
+pub mod encode;
+mod error;
+mod exchange;
+#[cfg(feature = "std")]
+pub mod external;
+#[cfg(feature = "alloc")]
+mod flash;
+#[cfg(feature = "alloc")]
+pub mod generators;
+pub mod heap;
+#[cfg(feature = "std")]
+mod heatmap;
+#[cfg(feature = "alloc")]
+mod indirect;
+mod intro;
+#[cfg(feature = "alloc")]
+mod iter;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "alloc")]
+mod keys;
+#[cfg(feature = "alloc")]
+mod kway;
+#[cfg(feature = "alloc")]
+mod library;
+#[cfg(feature = "alloc")]
+pub mod list;
+#[cfg(feature = "alloc")]
+pub mod merge;
+#[cfg(feature = "alloc")]
+pub mod natural;
+pub mod networks;
+pub mod ops;
+#[cfg(feature = "alloc")]
+mod ordinal;
+mod pancake;
+#[cfg(feature = "std")]
+mod parallel;
+pub mod partition;
+#[cfg(feature = "alloc")]
+mod patience;
+mod pdq;
+#[cfg(feature = "alloc")]
+mod permutation;
+#[cfg(feature = "alloc")]
+mod pigeonhole;
+mod quick;
+#[cfg(feature = "alloc")]
+mod radix;
+pub mod rng;
+#[cfg(feature = "alloc")]
+mod rows;
+#[cfg(feature = "std")]
+mod run_length;
+pub mod runs;
+pub mod select;
+#[cfg(feature = "alloc")]
+mod shell;
+#[cfg(feature = "silly")]
+mod silly;
+mod small;
+mod smooth;
+#[cfg(feature = "alloc")]
+pub mod sorted_ops;
+#[cfg(feature = "alloc")]
+mod sorted_vec;
+#[cfg(feature = "std")]
+pub mod spill;
+mod stats;
+#[cfg(feature = "alloc")]
+mod stepper;
+#[cfg(feature = "alloc")]
+mod strand;
+#[cfg(feature = "std")]
+pub mod telemetry;
+#[cfg(feature = "alloc")]
+mod topk;
+mod total;
+mod trace;
+#[cfg(feature = "alloc")]
+pub mod tree;
+#[cfg(feature = "std")]
+pub mod tuning;
+#[cfg(feature = "std")]
+mod validate;
+#[cfg(feature = "alloc")]
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "alloc")]
+mod window;
+#[cfg(feature = "alloc")]
+pub mod worst_case;
+
+#[cfg(feature = "alloc")]
+pub use crate::american_flag::AmericanFlagSort;
+#[cfg(feature = "std")]
+pub use crate::argsort::par_argsort_by_key;
+#[cfg(feature = "alloc")]
+pub use crate::argsort::{
+    apply_permutation, apply_permutation_to_columns, argsort, argsort_by_key, invert_permutation,
+    radix_argsort, radix_argsort_refine, sort_columns, sort_pairs, sort_pairs_by, Column,
+};
+pub use crate::array::{sort_array, sort_array_by, sort_array_by_key};
+#[cfg(feature = "alloc")]
+pub use crate::auto::{AutoChoice, AutoReport, AutoSort};
+#[cfg(feature = "std")]
+pub use crate::batch::sort_batch;
+pub use crate::bitonic::BitonicSort;
+#[cfg(feature = "alloc")]
+pub use crate::block::BlockSort;
+#[cfg(feature = "alloc")]
+pub use crate::blocked::BlockedSort;
+#[cfg(feature = "alloc")]
+pub use crate::bottom_up::BottomUpMergeSort;
+#[cfg(feature = "std")]
+pub use crate::bounded::{BoundedSorter, Guarded};
+#[cfg(feature = "alloc")]
+pub use crate::bucket::{BucketSort, ToOrdinal};
+use crate::cmp::{ByDeref, ByFn, ByOrd, Compare, Reversed};
+#[cfg(feature = "alloc")]
+pub use crate::container::SortableContainer;
+#[cfg(feature = "async")]
+pub use crate::cooperative::{async_sort, AsyncSorter};
+#[cfg(feature = "std")]
+pub use crate::cost::{estimate_cost, CostConstants, CostEstimate, TuningProfile};
+#[cfg(feature = "alloc")]
+pub use crate::counting::{CountKey, CountingSort};
+pub use crate::cycle::CycleSort;
+#[cfg(feature = "alloc")]
+pub use crate::default::{default_sorter, DefaultSorter};
+pub use crate::error::Error;
+pub use crate::exchange::{CocktailShakerSort, CombSort, GnomeSort, OddEvenSort};
+#[cfg(feature = "alloc")]
+pub use crate::flash::FlashSort;
+#[cfg(feature = "std")]
+pub use crate::heatmap::{Access, AccessHeatmap};
+#[cfg(feature = "alloc")]
+pub use crate::indirect::IndirectSort;
+pub use crate::intro::IntroSort;
+#[cfg(feature = "alloc")]
+pub use crate::iter::IteratorSortExt;
+#[cfg(feature = "alloc")]
+pub use crate::keys::{sort_by_expensive_key, KeyArena};
+#[cfg(feature = "alloc")]
+pub use crate::kway::{KWayMerge, LoserTree};
+#[cfg(feature = "alloc")]
+pub use crate::library::LibrarySort;
+#[cfg(feature = "alloc")]
+pub use crate::natural::NaturalMergeSort;
+#[cfg(feature = "alloc")]
+pub use crate::ordinal::{sort_by_ordinal_key, sort_ordinal, OrdinalKey, MAX_ORDINAL_DOMAIN};
+#[cfg(feature = "alloc")]
+pub use crate::pancake::pancake_flips;
+pub use crate::pancake::PancakeSort;
+#[cfg(feature = "rayon")]
+pub use crate::parallel::{ParallelMergeSort, ParallelQuickSort};
+#[cfg(feature = "std")]
+pub use crate::parallel::{ParallelRadixSort, SampleSort};
+#[cfg(feature = "alloc")]
+pub use crate::patience::{
+    longest_increasing_subsequence, longest_increasing_subsequence_by, PatienceSort,
+};
+pub use crate::pdq::PdqSort;
+#[cfg(feature = "alloc")]
+pub use crate::pigeonhole::{Pigeonhole, PigeonholeSort};
+pub use crate::quick::{PivotStrategy, QuickSort, ThreeWayQuickSort};
+#[cfg(feature = "alloc")]
+pub use crate::radix::{RadixKey, RadixSort, RadixSorter};
+pub use crate::rng::shuffle;
+#[cfg(feature = "alloc")]
+pub use crate::rows::sort_rows_by_column;
+#[cfg(feature = "std")]
+pub use crate::run_length::{distinct_ratio, RunLengthSort};
+#[cfg(feature = "alloc")]
+pub use crate::runs::find_runs;
+pub use crate::runs::{is_sorted, is_sorted_by};
+#[cfg(feature = "alloc")]
+pub use crate::shell::{search_gaps, GapSequence, ShellSort};
+#[cfg(feature = "silly")]
+pub use crate::silly::{BogoSort, BozoSort};
+pub use crate::small::SmallSortPolicy;
+pub use crate::smooth::SmoothSort;
+#[cfg(feature = "alloc")]
+pub use crate::sorted_vec::SortedVec;
+pub use crate::stats::{Counter, Counting, SortStats};
+#[cfg(feature = "alloc")]
+pub use crate::stepper::Stepper;
+#[cfg(feature = "alloc")]
+pub use crate::strand::StrandSort;
+#[cfg(feature = "std")]
+pub use crate::topk::top_k_frequent;
+#[cfg(feature = "alloc")]
+pub use crate::topk::TopK;
+pub use crate::total::{Total, TotalF32, TotalF64, TotalOrder};
+pub use crate::trace::{SortEvent, TracedSorter};
+#[cfg(feature = "alloc")]
+pub use crate::tree::TreeSort;
+#[cfg(feature = "std")]
+pub use crate::validate::{validated_sort, validated_sort_by};
+#[cfg(feature = "alloc")]
+pub use crate::window::SlidingSorted;
 
 pub trait Sorter {
+    /// Describes the algorithm implemented by this sorter.
+    fn info(&self) -> SorterInfo;
+
+    /// Sorts the slice in the order defined by `cmp`.
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>;
+
+    /// Like [`Sorter::sort_with`], merging through `scratch` instead of a buffer of its
+    /// own, so that sorting many slices in a row allocates once. Sorters that merge
+    /// through no buffer of indices ignore it.
+    #[cfg(feature = "alloc")]
+    fn sort_with_scratch<T, C>(&self, slice: &mut [T], scratch: &mut Vec<usize>, cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        let _ = scratch;
+        self.sort_with(slice, cmp)
+    }
+
     fn sort<T>(&self, slice: &mut [T])
     where
-        T: Ord;
+        T: Ord,
+    {
+        sort_reported(self, slice, &mut ByOrd)
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        sort_reported(self, slice, &mut ByFn(compare))
+    }
+
+    /// Sorts the slice and returns the work it took: comparisons, swaps, moves and the
+    /// deepest recursion, as counted by a [`Counter`].
+    ///
+    /// ```
+    /// use pangua::{InsertionSort, Sorter};
+    ///
+    /// let stats = InsertionSort { smart: false }.sort_with_stats(&mut [3, 2, 1]);
+    /// assert_eq!((stats.comparisons, stats.swaps), (3, 3));
+    /// ```
+    fn sort_with_stats<T>(&self, slice: &mut [T]) -> SortStats
+    where
+        T: Ord,
+    {
+        let mut counter = Counter::new(ByOrd);
+        sort_reported(self, slice, &mut counter);
+        counter.stats()
+    }
+
+    /// Like [`Sorter::sort_with_stats`], ordering with `compare`.
+    fn sort_by_with_stats<T, F>(&self, slice: &mut [T], compare: F) -> SortStats
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut counter = Counter::new(ByFn(compare));
+        sort_reported(self, slice, &mut counter);
+        counter.stats()
+    }
+
+    fn sort_by_key<T, K, F>(&self, slice: &mut [T], mut key: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(slice, |a, b| key(a).cmp(&key(b)))
+    }
+
+    /// Sorts pointers, such as `Box<T>`, `Rc<T>` or `&T`, by comparing their targets
+    /// with `compare`, through [`ByDeref`](cmp::ByDeref).
+    ///
+    /// ```
+    /// use pangua::{QuickSort, Sorter};
+    ///
+    /// trait Shape {
+    ///     fn area(&self) -> f64;
+    /// }
+    ///
+    /// struct Square(f64);
+    /// struct Circle(f64);
+    ///
+    /// impl Shape for Square {
+    ///     fn area(&self) -> f64 {
+    ///         self.0 * self.0
+    ///     }
+    /// }
+    ///
+    /// impl Shape for Circle {
+    ///     fn area(&self) -> f64 {
+    ///         std::f64::consts::PI * self.0 * self.0
+    ///     }
+    /// }
+    ///
+    /// let mut tings: Vec<Box<dyn Shape>> = vec![Box::new(Square(2.0)), Box::new(Circle(1.0))];
+    /// QuickSort::default().sort_by_deref(&mut tings, |a, b| a.area().total_cmp(&b.area()));
+    /// assert_eq!(tings[1].area(), 4.0);
+    /// ```
+    fn sort_by_deref<P, F>(&self, slice: &mut [P], compare: F)
+    where
+        P: Deref,
+        F: FnMut(&P::Target, &P::Target) -> Ordering,
+    {
+        sort_reported(self, slice, &mut ByDeref(ByFn(compare)))
+    }
+
+    /// Sorts by a key computed once per element rather than on every comparison, as
+    /// [`sort_by_expensive_key`] does. Stable whatever the sorter.
+    #[cfg(feature = "alloc")]
+    fn sort_by_cached_key<T, K, F>(&self, slice: &mut [T], key: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        sort_by_expensive_key(slice, key, self)
+    }
+
+    /// Returns a [`Stepper`] sorting the slice one operation at a time, for the caller to
+    /// look at the slice between steps.
+    ///
+    /// The whole sort runs here, on a clone of the slice, and its steps are recorded for
+    /// replay: hence `T: Clone`, and memory for the clone plus every step, moves taking
+    /// an index per element moved.
+    #[cfg(feature = "alloc")]
+    fn stepper<'a, T>(&self, slice: &'a mut [T]) -> Stepper<'a, T>
+    where
+        T: Ord + Clone,
+    {
+        Stepper::new(self, slice, &mut ByOrd)
+    }
+
+    /// Like [`Sorter::stepper`], ordering with `compare`.
+    #[cfg(feature = "alloc")]
+    fn stepper_by<'a, T, F>(&self, slice: &'a mut [T], compare: F) -> Stepper<'a, T>
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        stepper::stepper_by(self, slice, compare)
+    }
+
+    /// Sorts a container that is not necessarily a slice, such as a `VecDeque`; see
+    /// [`SortableContainer`].
+    #[cfg(feature = "alloc")]
+    fn sort_container<K>(&self, container: &mut K)
+    where
+        K: SortableContainer + ?Sized,
+        K::Item: Ord,
+    {
+        container::sort_container_with(self, container, &mut ByOrd)
+    }
+
+    /// Like [`Sorter::sort_container`], ordering with `compare`.
+    #[cfg(feature = "alloc")]
+    fn sort_container_by<K, F>(&self, container: &mut K, compare: F)
+    where
+        K: SortableContainer + ?Sized,
+        F: FnMut(&K::Item, &K::Item) -> Ordering,
+    {
+        container::sort_container_with(self, container, &mut ByFn(compare))
+    }
+
+    /// Sorts floats by their total order, through the [`Total`] wrapper.
+    ///
+    /// NaNs with the sign bit set come first and the other NaNs last, and -0.0 comes
+    /// before +0.0; see [`Total`] for the whole order.
+    ///
+    /// ```
+    /// use pangua::{IntroSort, Sorter};
+    ///
+    /// let mut tings = [1.5f32, f32::NAN, -0.0, f32::NEG_INFINITY];
+    /// IntroSort::default().sort_floats(&mut tings);
+    /// assert_eq!(tings[..3], [f32::NEG_INFINITY, -0.0, 1.5]);
+    /// assert!(tings[3].is_nan());
+    /// ```
+    fn sort_floats<T>(&self, slice: &mut [T])
+    where
+        T: TotalOrder,
+    {
+        self.sort(Total::cast_slice_mut(slice))
+    }
+}
+
+impl<S: Sorter + ?Sized> Sorter for &S {
+    fn info(&self) -> SorterInfo {
+        (**self).info()
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        (**self).sort_with(slice, cmp)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn sort_with_scratch<T, C>(&self, slice: &mut [T], scratch: &mut Vec<usize>, cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        (**self).sort_with_scratch(slice, scratch, cmp)
+    }
+}
+
+/// A sorter that never reorders elements comparing equal, whatever the input.
+///
+/// Only implemented by sorters stable in every configuration, so that code relying on
+/// stability, such as sorting by one key after another, can ask for it in its bounds
+/// rather than checking [`SorterInfo::stable`] at run time. Sorters stable in only some
+/// configurations, like [`InsertionSort`] whose binary search variant is not, go without
+/// it; wrappers have it when the sorter they wrap does.
+pub trait StableSorter: Sorter {}
+
+impl<S: StableSorter + ?Sized> StableSorter for &S {}
+
+/// Asymptotic complexity class, in terms of the input length `n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Complexity {
+    Linear,
+    Linearithmic,
+    LinearLogSquared,
+    PowerThreeHalves,
+    Quadratic,
+    Factorial,
+}
+
+impl fmt::Display for Complexity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Complexity::Linear => "O(n)",
+            Complexity::Linearithmic => "O(n log n)",
+            Complexity::LinearLogSquared => "O(n log^2 n)",
+            Complexity::PowerThreeHalves => "O(n^1.5)",
+            Complexity::Quadratic => "O(n^2)",
+            Complexity::Factorial => "O(n * n!)",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Metadata describing a sorting algorithm.
+///
+/// A stable sorter never reorders elements that compare equal. An in-place sorter
+/// only needs a constant (or logarithmic, for the recursion stack) amount of extra memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SorterInfo {
+    pub name: &'static str,
+    pub stable: bool,
+    pub in_place: bool,
+    pub best: Complexity,
+    pub average: Complexity,
+    pub worst: Complexity,
 }
 
 // fancy approach: extend slice to have a sort_by_sorter method
@@ -15,6 +512,63 @@ where
     sorter.sort(slice)
 }
 
+/// Sorts the slice with a sorter guaranteed to be stable.
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use pangua::{sort_stable_by_key, MergeSort};
+///
+/// let mut tings = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')];
+/// sort_stable_by_key(&mut tings, |t| t.0, MergeSort::default());
+/// assert_eq!(tings, [(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+/// # }
+/// ```
+pub fn sort_stable<T, S>(slice: &mut [T], sorter: S)
+where
+    T: Ord,
+    S: StableSorter,
+{
+    sorter.sort(slice)
+}
+
+/// Sorts the slice by `compare` with a sorter guaranteed to be stable.
+pub fn sort_stable_by<T, S, F>(slice: &mut [T], compare: F, sorter: S)
+where
+    S: StableSorter,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    sorter.sort_by(slice, compare)
+}
+
+/// Sorts the slice by `key` with a sorter guaranteed to be stable.
+pub fn sort_stable_by_key<T, K, S, F>(slice: &mut [T], key: F, sorter: S)
+where
+    K: Ord,
+    S: StableSorter,
+    F: FnMut(&T) -> K,
+{
+    sorter.sort_by_key(slice, key)
+}
+
+/// Sorts with `sorter`, reporting to the global telemetry hook where there can be one.
+fn sort_reported<S, T, C>(sorter: &S, slice: &mut [T], cmp: &mut C)
+where
+    S: Sorter + ?Sized,
+    C: Compare<T>,
+{
+    #[cfg(feature = "std")]
+    telemetry::sort_reported(sorter, slice, cmp);
+    #[cfg(not(feature = "std"))]
+    sorter.sort_with(slice, cmp);
+}
+
+/// Whether the slice is sorted whatever the order: it has fewer than two elements, or
+/// its elements are zero-sized and so indistinguishable. Sorters return early on such
+/// slices without comparing, or touching, any element.
+pub(crate) fn is_trivially_sorted<T>(slice: &[T]) -> bool {
+    slice.len() < 2 || core::mem::size_of::<T>() == 0
+}
+
 /// Bubble Sort
 ///
 /// Bubble sort, sometimes referred to as sinking sort, is a simple sorting algorithm
@@ -23,16 +577,30 @@ where
 pub struct BubbleSort;
 
 impl Sorter for BubbleSort {
-    fn sort<T>(&self, slice: &mut [T])
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "bubble",
+            stable: true,
+            in_place: true,
+            best: Complexity::Linear,
+            average: Complexity::Quadratic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
     where
-        T: Ord,
+        C: Compare<T>,
     {
+        if is_trivially_sorted(slice) {
+            return;
+        }
         let mut swapped = true;
         while swapped {
             swapped = false;
             for i in 1..slice.len() {
-                if slice[i] < slice[i - 1] {
-                    slice.swap(i, i - 1);
+                if cmp.is_less(&slice[i], &slice[i - 1]) {
+                    cmp::swap(cmp, slice, i, i - 1);
                     swapped = true;
                 }
             }
@@ -40,6 +608,8 @@ impl Sorter for BubbleSort {
     }
 }
 
+impl StableSorter for BubbleSort {}
+
 /// Insertion Sort
 ///
 /// Insertion sort iterates, consuming one input element each repetition, and growing a
@@ -50,24 +620,51 @@ pub struct InsertionSort {
     pub smart: bool,
 }
 impl Sorter for InsertionSort {
-    fn sort<T>(&self, slice: &mut [T])
+    fn info(&self) -> SorterInfo {
+        if self.smart {
+            SorterInfo {
+                name: "insertion-smart",
+                // binary search may land on any of several equal elements
+                stable: false,
+                in_place: true,
+                best: Complexity::Linearithmic,
+                average: Complexity::Quadratic,
+                worst: Complexity::Quadratic,
+            }
+        } else {
+            SorterInfo {
+                name: "insertion-dumb",
+                stable: true,
+                in_place: true,
+                best: Complexity::Linear,
+                average: Complexity::Quadratic,
+                worst: Complexity::Quadratic,
+            }
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
     where
-        T: Ord,
+        C: Compare<T>,
     {
+        if is_trivially_sorted(slice) {
+            return;
+        }
         // [sorted | not sorted]
         for unsorted in 1..slice.len() {
             if !self.smart {
                 let mut i = unsorted;
-                while i > 0 && slice[i - 1] > slice[i] {
-                    slice.swap(i - 1, i);
+                while i > 0 && cmp.is_less(&slice[i], &slice[i - 1]) {
+                    cmp::swap(cmp, slice, i - 1, i);
                     i -= 1;
                 }
             } else {
                 // use binary search to find index
-                let i = match slice[..unsorted].binary_search(&slice[unsorted]) {
+                let (sorted, rest) = slice.split_at(unsorted);
+                let i = match sorted.binary_search_by(|x| cmp.compare(x, &rest[0])) {
                     Ok(i) | Err(i) => i,
                 };
-                slice[i..=unsorted].rotate_right(1)
+                cmp::rotate_right(cmp, &mut slice[i..=unsorted], 1)
             }
         }
     }
@@ -89,85 +686,37 @@ impl Sorter for InsertionSort {
 pub struct SelectionSort;
 
 impl Sorter for SelectionSort {
-    fn sort<T>(&self, slice: &mut [T])
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "selection",
+            stable: false,
+            in_place: true,
+            best: Complexity::Quadratic,
+            average: Complexity::Quadratic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
     where
-        T: Ord,
+        C: Compare<T>,
     {
+        if is_trivially_sorted(slice) {
+            return;
+        }
         // [sorted | not sorted]
         for unsorted in 0..slice.len() {
             let smallest_in_rest = slice[unsorted..]
                 .iter()
                 .enumerate()
-                .min_by_key(|t| t.1) // min value
+                .min_by(|a, b| cmp.compare(a.1, b.1)) // min value
                 .map(|t| unsorted + t.0) // get index
                 .expect("slice is non-empty");
             if unsorted != smallest_in_rest {
-                slice.swap(unsorted, smallest_in_rest)
-            }
-        }
-    }
-}
-
-/// Quick Sort
-///
-/// Quicksort is a divide-and-conquer algorithm. It works by selecting a 'pivot' element
-/// from the array and partitioning the other elements into two sub-arrays, according
-/// to whether they are less than or greater than the pivot. The sub-arrays are then
-/// sorted recursively. This can be done in-place, requiring small additional amounts
-/// of memory to perform the sorting.
-pub struct QuickSort;
-
-fn quicksort<T: Ord>(slice: &mut [T]) {
-    match slice.len() {
-        0 | 1 => return,
-        2 => {
-            if slice[0] > slice[1] {
-                slice.swap(0, 1)
+                cmp::swap(cmp, slice, unsorted, smallest_in_rest)
             }
-            return;
-        }
-        _ => {}
-    }
-    let (pivot, rest) = slice.split_first_mut().expect("slice is non-empty");
-    let mut left = 0;
-    let mut right = rest.len() - 1;
-    while left <= right {
-        if &rest[left] <= pivot {
-            left += 1;
-        } else if &rest[right] > pivot {
-            // avoid unnecessary swaps
-            // we must be done
-            if right == 0 {
-                break;
-            }
-            right -= 1;
-        } else {
-            // move element to the right side
-            rest.swap(left, right);
-            left += 1;
-            // we must be done
-            if right == 0 {
-                break;
-            }
-            right -= 1;
         }
     }
-
-    // place pivot in final position
-    slice.swap(0, left);
-    let (left, right) = slice.split_at_mut(left);
-    assert!(left.last() <= right.first());
-    quicksort(left);
-    quicksort(&mut right[1..])
-}
-impl Sorter for QuickSort {
-    fn sort<T>(&self, slice: &mut [T])
-    where
-        T: Ord,
-    {
-        // [ unsorted | pivot | unsorted ]
-        quicksort(slice)
-    }
 }
 
 /// Heap sort
@@ -177,53 +726,79 @@ impl Sorter for QuickSort {
 /// decreasing the range of values considered in the heap operation by one, and sifting
 /// the new first value into its position in the heap. This repeats until the range of
 /// considered values is one value in length.
-pub struct HeapSort;
+///
+/// The heap is `d`-ary: each node has up to `d` children rather than two. A wider heap is
+/// shallower, so sifting takes fewer swaps for more comparisons per level, and a node's
+/// children sit next to each other in memory; a 4-ary heap is often the fastest. With
+/// `min_heap` set the heap is a min-heap rooted at the end of the slice, and the smallest
+/// elements are moved to the front instead of the largest to the back.
+///
+/// ```
+/// use pangua::{HeapSort, Sorter};
+///
+/// let mut tings = [5, 1, 4, 2, 3];
+/// HeapSort { d: 4, ..HeapSort::default() }.sort(&mut tings);
+/// assert_eq!(tings, [1, 2, 3, 4, 5]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapSort {
+    /// Children per node, at least 2.
+    pub d: usize,
+    pub min_heap: bool,
+}
 
 impl HeapSort {
-    fn heapify<T: Ord>(slice: &mut [T]) {
-        let parent = |i| (i - 1) / 2;
-        let mut start = parent(slice.len() - 1) as i64;
-        while start >= 0 {
-            HeapSort::sift_down(slice, start as usize, slice.len() - 1);
-            start -= 1;
-        }
-    }
-    fn sift_down<T: Ord>(slice: &mut [T], start: usize, end: usize) {
-        let left_child = |i| 2 * i + 1;
-        let mut root = start;
-        while left_child(root) <= end {
-            let child = left_child(root);
-            let mut swap = root;
-            if slice[swap] < slice[child] {
-                swap = child;
-            }
-            if child + 1 <= end && slice[swap] < slice[child + 1] {
-                swap = child + 1;
-            }
-            if swap == root {
-                return;
-            }
-            slice.swap(root, swap);
-            root = swap;
+    /// Creates a heap sorter over a `d`-ary max-heap; `d` must be at least 2.
+    pub fn new(d: usize) -> Result<Self, Error> {
+        let sorter = HeapSort {
+            d,
+            ..HeapSort::default()
+        };
+        sorter.validate()?;
+        Ok(sorter)
+    }
+
+    /// Checks the configuration, as the sort itself would before starting.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.d < 2 {
+            return Err(Error::ArityTooSmall { d: self.d });
+        }
+        Ok(())
+    }
+}
+
+impl Default for HeapSort {
+    fn default() -> Self {
+        HeapSort {
+            d: 2,
+            min_heap: false,
         }
     }
 }
 
 impl Sorter for HeapSort {
-    fn sort<T>(&self, slice: &mut [T])
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "heap",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linearithmic,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
     where
-        T: Ord,
+        C: Compare<T>,
     {
-        if slice.is_empty() || slice.len() == 1 {
-            return;
+        if let Err(e) = self.validate() {
+            panic!("{}", e)
         }
-        HeapSort::heapify(slice);
-        let mut end = slice.len() - 1;
-        while end > 0 {
-            slice.swap(0, end);
-            end -= 1;
-            HeapSort::sift_down(slice, 0, end);
+        if is_trivially_sorted(slice) {
+            return;
         }
+        heap::sort_d_ary(slice, self.d, self.min_heap, cmp);
     }
 }
 
@@ -235,62 +810,282 @@ impl Sorter for HeapSort {
 ///   (a list of one element is considered sorted).
 /// - Repeatedly merge sublists to produce new sorted sublists until there is only one
 ///   sublist remaining. This will be the sorted list.
-pub struct MergeSort;
+///
+/// By default each merge records the merged order in a scratch buffer of indices and
+/// then moves the elements into place, for O(n log n) overall. How large a buffer it
+/// may use is up to its [`MemoryPolicy`]: one index per element, half that, or none at
+/// all, merging by rotation for an extra log factor. Runs of at most `small_threshold`
+/// elements are sorted with a branchless insertion sort rather than split further; at 0,
+/// the default, the [`SmallSortPolicy`] decides.
+/// Buffered merges gallop, as Timsort's do, once one run has won `min_gallop` times in
+/// a row: merging runs that barely overlap then takes a few comparisons rather than one
+/// per element. At 0, the default, they never do; top-down merges of unordered input
+/// rarely gain from it.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeSort {
+    pub memory: MemoryPolicy,
+    pub small_threshold: usize,
+    pub min_gallop: usize,
+}
 
+/// How much scratch memory [`MergeSort`] merges with, trading time for space.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MemoryPolicy {
+    /// A buffer of one index per element, so every merge is a single pass. The fastest.
+    #[default]
+    Full,
+    /// A buffer of half as many indices. A merge too long for it, which only the last
+    /// one is, is first split in two by rotating the elements between the runs, as
+    /// [`InPlace`](MemoryPolicy::InPlace) merges are, at the cost of one more pass.
+    Half,
+    /// No buffer: every merge rotates elements into place, costing an extra log factor.
+    /// For targets where allocating in proportion to the input is not an option.
+    InPlace,
+}
+
+#[cfg(feature = "alloc")]
 impl MergeSort {
-    fn merge_sort<T: Ord>(slice: &mut [T], left: usize, right: usize) {
+    fn merge_sort<T, C: Compare<T>>(
+        &self,
+        slice: &mut [T],
+        left: usize,
+        right: usize,
+        scratch: &mut Vec<usize>,
+        small: SmallSortPolicy,
+        cmp: &mut C,
+    ) {
+        if small.sort_small(&mut slice[left..=right], cmp) {
+            return;
+        }
         if left < right {
             let mid = (left + right) / 2;
-            Self::merge_sort(slice, left, mid);
-            Self::merge_sort(slice, mid + 1, right);
-            Self::merge(slice, left, mid, right)
+            cmp.on_enter();
+            self.merge_sort(slice, left, mid, scratch, small, cmp);
+            self.merge_sort(slice, mid + 1, right, scratch, small, cmp);
+            cmp.on_leave();
+            let run = &mut slice[left..=right];
+            match self.memory {
+                MemoryPolicy::InPlace => ops::merge_rotation_with(run, mid + 1 - left, cmp),
+                _ => Self::merge_bounded(run, mid + 1 - left, scratch, self.min_gallop, cmp),
+            }
         }
     }
-    fn merge<T: Ord>(slice: &mut [T], mut start: usize, mut mid: usize, end: usize) {
-        let mut start2 = mid + 1;
-        if slice[mid] <= slice[start2] {
+
+    /// Merges through `scratch`, first splitting merges longer than its capacity in two
+    /// by rotation.
+    fn merge_bounded<T, C: Compare<T>>(
+        slice: &mut [T],
+        mid: usize,
+        scratch: &mut Vec<usize>,
+        min_gallop: usize,
+        cmp: &mut C,
+    ) {
+        if mid == 0 || mid == slice.len() {
+            return;
+        }
+        if slice.len() <= scratch.capacity() {
+            return Self::merge_galloping(slice, mid, scratch, min_gallop, cmp);
+        }
+        if !cmp.is_less(&slice[mid], &slice[mid - 1]) {
             return;
         }
-        while start <= mid && start2 <= end {
-            if slice[start] <= slice[start2] {
-                start += 1;
+        let (split, left_mid, right_mid) = ops::split_merge(slice, mid, cmp);
+        let (left, right) = slice.split_at_mut(split);
+        Self::merge_bounded(left, left_mid, scratch, min_gallop, cmp);
+        Self::merge_bounded(right, right_mid, scratch, min_gallop, cmp);
+    }
+
+    /// Merges the sorted runs `slice[..mid]` and `slice[mid..]` through `scratch`.
+    pub(crate) fn merge_buffered<T, C: Compare<T>>(
+        slice: &mut [T],
+        mid: usize,
+        scratch: &mut Vec<usize>,
+        cmp: &mut C,
+    ) {
+        Self::merge_galloping(slice, mid, scratch, 0, cmp)
+    }
+
+    /// Like [`merge_buffered`](MergeSort::merge_buffered), switching to galloping once
+    /// one run has won `min_gallop` comparisons in a row; 0 never gallops.
+    ///
+    /// Galloping finds how many elements of one run come before the next of the other
+    /// by exponential search, taking them as a chunk in O(log k) comparisons for k
+    /// elements, and stops once neither run wins `min_gallop` elements at a time. A
+    /// galloping merge also first skips the elements at either end that are in place.
+    pub(crate) fn merge_galloping<T, C: Compare<T>>(
+        slice: &mut [T],
+        mid: usize,
+        scratch: &mut Vec<usize>,
+        min_gallop: usize,
+        cmp: &mut C,
+    ) {
+        // already in order, as on sorted input
+        if !cmp.is_less(&slice[mid], &slice[mid - 1]) {
+            return;
+        }
+        scratch.clear();
+        let (mut i, mut j, mut end) = (0, mid, slice.len());
+        if min_gallop > 0 {
+            // the left run up to the first of the right, and the right run from the
+            // last of the left, are where they belong
+            i = ops::gallop_upper_bound(&slice[..mid], &slice[mid], cmp);
+            end = mid + ops::gallop_lower_bound(&slice[mid..], &slice[mid - 1], cmp);
+            scratch.extend(0..i);
+        }
+        let (mut left_wins, mut right_wins) = (0, 0);
+        while i < mid && j < end {
+            // ties go to the left run, keeping the merge stable
+            if cmp.is_less(&slice[j], &slice[i]) {
+                scratch.push(j);
+                j += 1;
+                right_wins += 1;
+                left_wins = 0;
             } else {
-                // shift elements by 1
-                slice[start..=start2].rotate_right(1);
-                // update markers
-                start += 1;
-                mid += 1;
-                start2 += 1;
+                scratch.push(i);
+                i += 1;
+                left_wins += 1;
+                right_wins = 0;
+            }
+            if min_gallop == 0 || left_wins.max(right_wins) < min_gallop {
+                continue;
             }
+            while i < mid && j < end {
+                let left = ops::gallop_upper_bound(&slice[i..mid], &slice[j], cmp);
+                scratch.extend(i..i + left);
+                i += left;
+                if i == mid {
+                    break;
+                }
+                let right = ops::gallop_lower_bound(&slice[j..end], &slice[i], cmp);
+                scratch.extend(j..j + right);
+                j += right;
+                if left < min_gallop && right < min_gallop {
+                    break;
+                }
+            }
+            left_wins = 0;
+            right_wins = 0;
         }
+        scratch.extend(i..mid);
+        scratch.extend(j..slice.len());
+        permutation::apply_by(scratch, |a, b| cmp::swap(cmp, slice, a, b));
     }
 }
 
+#[cfg(feature = "alloc")]
 impl Sorter for MergeSort {
-    fn sort<T>(&self, slice: &mut [T])
+    fn info(&self) -> SorterInfo {
+        let buffered = SorterInfo {
+            name: "merge",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        };
+        match self.memory {
+            MemoryPolicy::Full => buffered,
+            MemoryPolicy::Half => SorterInfo {
+                name: "merge-half-buffer",
+                ..buffered
+            },
+            MemoryPolicy::InPlace => SorterInfo {
+                name: "merge-in-place",
+                in_place: true,
+                // merging in place costs an extra log factor in rotations
+                average: Complexity::LinearLogSquared,
+                worst: Complexity::LinearLogSquared,
+                ..buffered
+            },
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
     where
-        T: Ord,
+        C: Compare<T>,
     {
-        if slice.is_empty() || slice.len() == 1 {
+        self.sort_with_scratch(slice, &mut Vec::new(), cmp)
+    }
+
+    fn sort_with_scratch<T, C>(&self, slice: &mut [T], scratch: &mut Vec<usize>, cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
             return;
         }
-        Self::merge_sort(slice, 0, slice.len() - 1);
+        scratch.clear();
+        match self.memory {
+            MemoryPolicy::Full => scratch.reserve(slice.len()),
+            MemoryPolicy::Half => scratch.reserve(slice.len().div_ceil(2)),
+            MemoryPolicy::InPlace => {}
+        }
+        let small =
+            SmallSortPolicy::configured(self.small_threshold, SmallSortPolicy::None).stable();
+        self.merge_sort(slice, 0, slice.len() - 1, scratch, small, cmp);
     }
 }
 
+#[cfg(feature = "alloc")]
+impl StableSorter for MergeSort {}
+
+#[cfg(feature = "alloc")]
 pub struct StdSorter;
+#[cfg(feature = "alloc")]
 impl Sorter for StdSorter {
-    fn sort<T>(&self, slice: &mut [T])
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "std",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
     where
-        T: Ord,
+        C: Compare<T>,
     {
-        slice.sort()
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        // the standard library does not report its swaps and moves
+        slice.sort_by(|a, b| cmp.compare(a, b))
     }
 }
 
-#[cfg(test)]
+#[cfg(feature = "alloc")]
+impl StableSorter for StdSorter {}
+
+/// Sorts in descending order using any inner sorter.
+///
+/// Elements are compared with their order reversed, so a stable inner sorter stays stable.
+pub struct Descending<S>(pub S);
+
+impl<S: Sorter> Sorter for Descending<S> {
+    fn info(&self) -> SorterInfo {
+        self.0.info()
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        self.0.sort_with(slice, &mut Reversed(cmp))
+    }
+}
+
+impl<S: StableSorter> StableSorter for Descending<S> {}
+
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
     #[test]
     fn std_works() {
         let mut tings = vec![5, 1, 4, 2, 3];
@@ -329,21 +1124,301 @@ mod tests {
     #[test]
     fn quick_works() {
         let mut tings = vec![5, 1, 4, 2, 3];
-        QuickSort.sort(&mut tings);
+        QuickSort::default().sort(&mut tings);
         assert_eq!(tings, &[1, 2, 3, 4, 5]);
     }
 
     #[test]
     fn heap_works() {
         let mut tings = vec![5, 1, 4, 2, 3];
-        HeapSort.sort(&mut tings);
+        HeapSort::default().sort(&mut tings);
         assert_eq!(tings, &[1, 2, 3, 4, 5]);
     }
 
+    #[test]
+    fn heap_arities_agree_with_binary() {
+        let tings: Vec<(u32, u32)> = (0..500).map(|i| ((i * 7919) % 97, i)).collect();
+        let mut binary = tings.clone();
+        HeapSort::default().sort_by_key(&mut binary, |t| t.0);
+        for &d in &[2, 3, 4, 8, 600] {
+            for &min_heap in &[false, true] {
+                let mut sorted = tings.clone();
+                HeapSort { d, min_heap }.sort_by_key(&mut sorted, |t| t.0);
+                let keys = |v: &[(u32, u32)]| v.iter().map(|t| t.0).collect::<Vec<_>>();
+                assert_eq!(keys(&sorted), keys(&binary), "d = {}", d);
+            }
+        }
+        // a 4-ary heap is half as deep, so sifts take fewer swaps
+        let swaps = |d| {
+            let counting = Counting::new(HeapSort { d, min_heap: false });
+            counting.sort(&mut tings.clone());
+            counting.stats().swaps
+        };
+        assert!(swaps(4) < swaps(2));
+        assert_eq!(HeapSort::new(1), Err(Error::ArityTooSmall { d: 1 }));
+    }
+
     #[test]
     fn merge_works() {
         let mut tings = vec![5, 1, 4, 2, 3];
-        MergeSort.sort(&mut tings);
+        MergeSort::default().sort(&mut tings);
         assert_eq!(tings, &[1, 2, 3, 4, 5]);
     }
+
+    #[test]
+    fn merge_is_stable() {
+        let mut tings: Vec<_> = (0..200).map(|i| ((i * 37) % 11, i)).collect();
+        MergeSort::default().sort_by_key(&mut tings, |t| t.0);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        let mut tings: Vec<_> = (0..200).map(|i| ((i * 37) % 11, i)).collect();
+        MergeSort {
+            memory: MemoryPolicy::InPlace,
+            ..MergeSort::default()
+        }
+        .sort_by_key(&mut tings, |t| t.0);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn half_buffer_merges_split_only_the_longest() {
+        let half = MergeSort {
+            memory: MemoryPolicy::Half,
+            ..MergeSort::default()
+        };
+        assert_eq!(half.info().name, "merge-half-buffer");
+        for n in [0u64, 1, 2, 3, 17, 100, 1001] {
+            let mut tings: Vec<_> = (0..n).map(|i| ((i * 7919) % 13, i)).collect();
+            let mut expected = tings.clone();
+            expected.sort_by_key(|t| t.0);
+            half.sort_by_key(&mut tings, |t| t.0);
+            assert_eq!(tings, expected, "n = {}", n);
+        }
+
+        let n = 1 << 12;
+        let comparisons = |memory| {
+            let sorter = Counting::new(MergeSort {
+                memory,
+                ..MergeSort::default()
+            });
+            let mut tings: Vec<u64> = (0..n).map(|i| (i * 2_654_435_761) % 1_000_003).collect();
+            sorter.sort(&mut tings);
+            sorter.stats().comparisons
+        };
+        let full = comparisons(MemoryPolicy::Full);
+        let half = comparisons(MemoryPolicy::Half);
+        // a few binary searches more than the full buffer, far fewer than rotating
+        assert!(half < full + 64);
+        assert!(comparisons(MemoryPolicy::InPlace) > half);
+    }
+
+    #[test]
+    fn buffered_merge_is_linearithmic() {
+        let n = 1 << 12;
+        let sorter = Counting::new(MergeSort::default());
+        let mut tings: Vec<_> = (0..n).rev().collect();
+        sorter.sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        // at most n log n comparisons, and one swap per element per level
+        assert!(sorter.stats().comparisons <= n * 12);
+        assert!(sorter.stats().swaps <= n * 12);
+        assert_eq!(sorter.stats().moves, 0);
+
+        let sorter = Counting::new(MergeSort::default());
+        sorter.sort(&mut tings);
+        assert_eq!(sorter.stats().comparisons, n - 1);
+    }
+
+    #[test]
+    fn merge_small_threshold_is_stable() {
+        let mut tings: Vec<(usize, usize)> = (0..500).map(|i| ((i * 7919) % 10, i)).collect();
+        let sorter = Counting::new(MergeSort {
+            memory: MemoryPolicy::Full,
+            small_threshold: 16,
+            ..MergeSort::default()
+        });
+        sorter.sort_by_key(&mut tings, |t| t.0);
+        assert!(tings.windows(2).all(|w| w[0] < w[1]));
+        let plain = Counting::new(MergeSort::default());
+        plain.sort_by_key(&mut tings.iter().rev().collect::<Vec<_>>(), |t| t.0);
+        assert!(sorter.stats().max_depth < plain.stats().max_depth);
+    }
+
+    #[test]
+    fn galloping_merges_barely_overlapping_runs() {
+        // sorted blocks of 64 elements, each key twice, the blocks in reverse order
+        let tings: Vec<(usize, usize)> = (0..2048)
+            .map(|i| ((31 - i / 64) * 32 + i % 64 / 2, i))
+            .collect();
+        let comparisons = |sorter: MergeSort| {
+            let mut sorted = tings.clone();
+            let counting = Counting::new(sorter);
+            counting.sort_by_key(&mut sorted, |t| t.0);
+            assert!(sorted
+                .windows(2)
+                .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1)));
+            counting.stats().comparisons
+        };
+        let galloping = comparisons(MergeSort {
+            min_gallop: natural::MIN_GALLOP,
+            ..MergeSort::default()
+        });
+        assert!(galloping < comparisons(MergeSort::default()) / 2);
+        comparisons(MergeSort {
+            min_gallop: 1,
+            ..MergeSort::default()
+        });
+    }
+
+    #[test]
+    fn sort_by_key_works() {
+        let mut tings = vec![(1, 'e'), (2, 'a'), (3, 'd'), (4, 'b'), (5, 'c')];
+        QuickSort::default().sort_by_key(&mut tings, |t| t.1);
+        assert_eq!(tings, &[(2, 'a'), (4, 'b'), (5, 'c'), (3, 'd'), (1, 'e')]);
+        HeapSort::default().sort_by(&mut tings, |a, b| a.0.cmp(&b.0));
+        assert_eq!(tings, &[(1, 'e'), (2, 'a'), (3, 'd'), (4, 'b'), (5, 'c')]);
+    }
+
+    #[test]
+    fn sort_with_stats_matches_counting() {
+        let tings: Vec<u32> = (0..500).map(|i| (i * 7919) % 500).collect();
+        let counting = Counting::new(QuickSort::default());
+        counting.sort(&mut tings.clone());
+        let mut sorted = tings.clone();
+        let stats = QuickSort::default().sort_with_stats(&mut sorted);
+        assert_eq!(stats, counting.stats());
+        assert!(stats.max_depth > 1);
+        assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+        let stats = MergeSort::default().sort_by_with_stats(&mut sorted, |a, b| b.cmp(a));
+        assert!(stats.comparisons >= 499);
+        assert!(sorted.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn descending_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        Descending(MergeSort::default()).sort(&mut tings);
+        assert_eq!(tings, &[5, 4, 3, 2, 1]);
+        Descending(QuickSort::default()).sort(&mut tings);
+        assert_eq!(tings, &[5, 4, 3, 2, 1]);
+    }
+
+    /// Runs `check` with every comparison sorter.
+    macro_rules! for_each_sorter {
+        ($check:ident) => {
+            $check(BubbleSort);
+            $check(CocktailShakerSort);
+            $check(GnomeSort);
+            $check(CombSort::default());
+            $check(OddEvenSort);
+            $check(InsertionSort { smart: true });
+            $check(InsertionSort { smart: false });
+            $check(SelectionSort);
+            $check(CycleSort);
+            $check(PancakeSort);
+            $check(BitonicSort);
+            $check(HeapSort::default());
+            $check(SmoothSort);
+            $check(MergeSort::default());
+            $check(MergeSort {
+                memory: MemoryPolicy::InPlace,
+                ..MergeSort::default()
+            });
+            $check(BottomUpMergeSort);
+            $check(NaturalMergeSort::default());
+            $check(BlockSort);
+            $check(BlockedSort {
+                block_bytes: 64,
+                inner: MergeSort::default(),
+            });
+            $check(IndirectSort {
+                min_bytes: 0,
+                inner: MergeSort::default(),
+            });
+            $check(PatienceSort);
+            $check(StrandSort);
+            $check(LibrarySort);
+            $check(TreeSort { balanced: false });
+            $check(TreeSort { balanced: true });
+            $check(StdSorter);
+            $check(QuickSort::default());
+            $check(ThreeWayQuickSort);
+            $check(IntroSort::default());
+            $check(PdqSort);
+            $check(ShellSort::default());
+            $check(AutoSort::default());
+        };
+    }
+
+    #[test]
+    fn zero_sized_elements_are_not_touched() {
+        fn check<S: Sorter>(sorter: S) {
+            let mut tings = vec![(); 1 << 20];
+            sorter.sort_by(&mut tings, |_, _| {
+                panic!("{} compared a ZST", sorter.info().name)
+            });
+        }
+        for_each_sorter!(check);
+        check(Descending(HeapSort::default()));
+
+        let mut tings = vec![(); 1 << 20];
+        let never = |_: &()| -> usize { panic!("projected a ZST") };
+        CountingSort.sort_by_key(&mut tings, never);
+        RadixSort::default().sort_by_key(&mut tings, |_| -> u32 { panic!("keyed a ZST") });
+        #[cfg(feature = "std")]
+        RunLengthSort::new(IntroSort::default())
+            .sort_by_key(&mut tings, |_| -> u8 { panic!("hashed a ZST") });
+        let bucket = BucketSort::new(4, IntroSort::default()).unwrap();
+        bucket.sort_by(&mut tings, |_| panic!(), |_, _| panic!());
+    }
+
+    #[test]
+    fn large_elements_are_moved_not_cloned() {
+        // 1 KiB and deliberately not Clone, so no sorter can copy elements
+        #[derive(Debug, PartialEq, Eq)]
+        struct Big {
+            key: u64,
+            payload: [u64; 128],
+        }
+
+        fn check<S: Sorter>(sorter: S) {
+            let mut tings: Vec<Big> = (0..300u64)
+                .map(|i| {
+                    let key = (i * 7919) % 61;
+                    Big {
+                        key,
+                        payload: [key; 128],
+                    }
+                })
+                .collect();
+            sorter.sort_by_key(&mut tings, |b| b.key);
+            let keys: Vec<_> = tings.iter().map(|b| b.key).collect();
+            assert!(
+                keys.windows(2).all(|w| w[0] <= w[1]),
+                "{}",
+                sorter.info().name
+            );
+            assert!(tings.iter().all(|b| b.payload == [b.key; 128]));
+        }
+        for_each_sorter!(check);
+    }
+
+    #[test]
+    fn info_reports_properties() {
+        assert_eq!(MergeSort::default().info().name, "merge");
+        assert!(MergeSort::default().info().stable);
+        assert!(!MergeSort::default().info().in_place);
+        assert_eq!(
+            MergeSort {
+                memory: MemoryPolicy::InPlace,
+                ..MergeSort::default()
+            }
+            .info()
+            .name,
+            "merge-in-place"
+        );
+        assert!(!QuickSort::default().info().stable);
+        assert_eq!(QuickSort::default().info().worst, Complexity::Quadratic);
+        assert_eq!(HeapSort::default().info().worst.to_string(), "O(n log n)");
+        assert!(!StdSorter.info().in_place);
+    }
 }
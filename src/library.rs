@@ -0,0 +1,137 @@
+use crate::cmp::{self, Compare};
+use crate::{is_trivially_sorted, permutation, Complexity, Sorter, SorterInfo, StableSorter};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Library Sort
+///
+/// Library sort, or gapped insertion sort, is insertion sort that leaves room on the
+/// shelf, as a librarian leaves gaps between books for new ones. Each element's place
+/// is found by binary search and it is put there, shifting its neighbours along only as
+/// far as the next gap. After 1, 2, 4, ... elements the shelf is respaced, with a gap
+/// after every element, so insertions rarely shift far: O(n log n) with high
+/// probability, against insertion sort's O(n²), at the cost of a shelf twice the size
+/// of the input. Input that keeps landing in the same spot still fills the gaps there
+/// and takes O(n²). Elements are shelved as indices and moved into place at the end,
+/// each after its equals, so the sort is stable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LibrarySort;
+
+impl Sorter for LibrarySort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "library",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linearithmic,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut shelf = Shelf {
+            slots: vec![None; 2 * slice.len()],
+            end: 0,
+        };
+        for i in 0..slice.len() {
+            shelf.insert(i, |j| cmp.is_less(&slice[i], &slice[j]));
+            if (i + 1).is_power_of_two() {
+                shelf.respace();
+            }
+        }
+        let mut order: Vec<usize> = shelf.slots.iter().flatten().copied().collect();
+        permutation::apply_by(&mut order, |a, b| cmp::swap(cmp, slice, a, b));
+    }
+}
+
+impl StableSorter for LibrarySort {}
+
+/// Indices of elements in order, with gaps between them.
+struct Shelf {
+    slots: Vec<Option<usize>>,
+    /// One past the last slot in use.
+    end: usize,
+}
+
+impl Shelf {
+    /// Shelves `index` after every element it is not less than, `is_less(j)` telling
+    /// whether it is less than the element at `j`.
+    fn insert(&mut self, index: usize, mut is_less: impl FnMut(usize) -> bool) {
+        // occupied slots before `lo` hold elements not greater than the new one, and
+        // those from `hi` on greater ones
+        let (mut lo, mut hi) = (0, self.end);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match (mid..hi).find(|&s| self.slots[s].is_some()) {
+                Some(s) if !is_less(self.slots[s].unwrap()) => lo = s + 1,
+                _ => hi = mid,
+            }
+        }
+        if lo > 0 && self.slots[lo - 1].is_none() {
+            self.slots[lo - 1] = Some(index);
+            return;
+        }
+        let gap = (lo..self.end)
+            .find(|&s| self.slots[s].is_none())
+            .unwrap_or(self.end);
+        if gap == self.end {
+            self.end += 1;
+        }
+        self.slots[lo..=gap].rotate_right(1);
+        self.slots[lo] = Some(index);
+    }
+
+    /// Spreads the shelved elements out, one gap after each.
+    fn respace(&mut self) {
+        // packed to the left first, then spread out from the right, so that no element
+        // is written over before it has been moved
+        let mut count = 0;
+        for s in 0..self.end {
+            if let Some(index) = self.slots[s].take() {
+                self.slots[count] = Some(index);
+                count += 1;
+            }
+        }
+        for s in (0..count).rev() {
+            self.slots[2 * s] = self.slots[s].take();
+        }
+        self.end = 2 * count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counting;
+
+    #[test]
+    fn library_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        LibrarySort.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for n in 0..150u64 {
+            let mut tings: Vec<_> = (0..n).map(|i| ((i * 7919) % 23, i)).collect();
+            let mut expected = tings.clone();
+            expected.sort_by_key(|t| t.0);
+            LibrarySort.sort_by_key(&mut tings, |t| t.0);
+            assert_eq!(tings, expected, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn takes_n_log_n_comparisons() {
+        let n = 1 << 12;
+        let sorter = Counting::new(LibrarySort);
+        let mut tings: Vec<u64> = (0..n).map(|i| (i * 2_654_435_761) % 1_000_003).collect();
+        sorter.sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        assert!(sorter.stats().comparisons <= n as usize * 14);
+    }
+}
@@ -0,0 +1,267 @@
+//! A singly linked list, and merge sort over its links.
+//!
+//! Sorting a linked list needs none of the random access the slice sorters rely on,
+//! and merge sort suits it best: merging relinks nodes instead of moving elements, so
+//! [`List::sort`] takes O(n log n) comparisons with O(1) extra space and leaves every
+//! element where it was allocated. It merges bottom-up, runs of 1, 2, 4, ... nodes at
+//! a time, so there is no recursion either.
+//!
+//! ```
+//! use pangua::list::List;
+//!
+//! let mut tings: List<_> = vec![5, 1, 4, 2, 3].into_iter().collect();
+//! tings.push_front(0);
+//! tings.sort();
+//! assert!(tings.iter().copied().eq(0..6));
+//! ```
+
+use crate::cmp::{self, ByFn, ByOrd, Compare};
+use alloc::boxed::Box;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    next: Link<T>,
+}
+
+/// A singly linked list, with its length.
+pub struct List<T> {
+    head: Link<T>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        let next = self.head.take();
+        self.head = Some(Box::new(Node { value, next }));
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let node = self.head.take()?;
+        self.head = node.next;
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    /// Sorts the list by relinking its nodes. Stable.
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_with(&mut ByOrd)
+    }
+
+    /// Like [`sort`](List::sort), ordering with `compare`.
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.sort_with(&mut ByFn(compare))
+    }
+
+    /// Like [`sort`](List::sort), ordering by the keys `f` extracts.
+    pub fn sort_by_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_with(&mut cmp::by_key(f))
+    }
+
+    /// Like [`sort`](List::sort), ordering with `cmp`.
+    pub fn sort_with<C: Compare<T>>(&mut self, cmp: &mut C) {
+        let mut width = 1;
+        while width < self.len {
+            let mut rest = self.head.take();
+            let mut tail = &mut self.head;
+            while rest.is_some() {
+                let mut left = rest;
+                let mut right = split_after(&mut left, width);
+                rest = split_after(&mut right, width);
+                tail = merge(left, right, tail, cmp);
+            }
+            width *= 2;
+        }
+    }
+}
+
+/// Cuts the list after its first `n` nodes, returning the rest.
+fn split_after<T>(mut link: &mut Link<T>, n: usize) -> Link<T> {
+    for _ in 0..n {
+        match link {
+            Some(node) => link = &mut node.next,
+            None => return None,
+        }
+    }
+    link.take()
+}
+
+/// Merges the sorted lists `a` and `b` onto `tail`, taking from `a` first on ties,
+/// and returns the new end of the list.
+fn merge<'a, T, C: Compare<T>>(
+    mut a: Link<T>,
+    mut b: Link<T>,
+    mut tail: &'a mut Link<T>,
+    cmp: &mut C,
+) -> &'a mut Link<T> {
+    loop {
+        let from = match (&a, &b) {
+            (Some(x), Some(y)) if cmp.is_less(&y.value, &x.value) => &mut b,
+            (Some(_), _) => &mut a,
+            (None, Some(_)) => &mut b,
+            (None, None) => return tail,
+        };
+        let mut node = from.take().expect("the list was checked to be non-empty");
+        *from = node.next.take();
+        tail = &mut tail.insert(node).next;
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        List::new()
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        // unlinking one node at a time, as dropping the head would recurse down the list
+        let mut link = self.head.take();
+        while let Some(mut node) = link {
+            link = node.next.take();
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        let mut tail = &mut list.head;
+        for value in iter {
+            tail = &mut tail.insert(Box::new(Node { value, next: None })).next;
+            list.len += 1;
+        }
+        list
+    }
+}
+
+/// An iterator over the elements of a [`List`], front to back.
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.next?;
+        self.next = node.next.as_deref();
+        Some(&node.value)
+    }
+}
+
+/// An iterator moving the elements out of a [`List`], front to back.
+pub struct IntoIter<T>(List<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counter;
+    use alloc::format;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn sorts_stably_by_relinking() {
+        for n in 0..70 {
+            let tings: Vec<(u32, u32)> = (0..n).map(|i| ((i * 7919) % 13, i)).collect();
+            let mut list: List<_> = tings.iter().copied().collect();
+            list.sort_by_key(|t| t.0);
+            let mut expected = tings;
+            expected.sort_by_key(|t| t.0);
+            assert_eq!(list.len(), n as usize);
+            assert!(list.into_iter().eq(expected), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn takes_n_log_n_comparisons() {
+        let n = 1 << 12;
+        let mut list: List<u64> = (0..n).map(|i| (i * 2_654_435_761) % 1_000_003).collect();
+        let mut counter = Counter::new(ByOrd);
+        list.sort_with(&mut counter);
+        assert!(counter.stats().comparisons <= n as usize * 12);
+        let sorted: Vec<u64> = list.iter().copied().collect();
+        assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(list.pop_front(), Some(sorted[0]));
+    }
+
+    #[test]
+    fn long_lists_drop_without_recursing() {
+        let list: List<u8> = core::iter::repeat_n(0, 1 << 20).collect();
+        assert_eq!(format!("{:?}", List::from_iter(1..4)), "[1, 2, 3]");
+        drop(list);
+    }
+}
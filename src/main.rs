@@ -1,81 +1,72 @@
+use pangua::rng::Rng;
 use pangua::*;
-use rand::prelude::*;
-
-use std::cell::Cell;
-use std::cmp::Ordering;
-use std::rc::Rc;
-
-#[derive(Clone)]
-struct SortEvaluator<T> {
-    t: T,
-    cmps: Rc<Cell<usize>>,
-}
-impl<T: PartialEq> PartialEq for SortEvaluator<T> {
-    fn eq(&self, other: &Self) -> bool {
-        self.cmps.set(self.cmps.get() + 1);
-        self.t == other.t
-    }
-}
-
-impl<T: Eq> Eq for SortEvaluator<T> {}
-
-impl<T: PartialOrd> PartialOrd for SortEvaluator<T> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.cmps.set(self.cmps.get() + 1);
-        self.t.partial_cmp(&other.t)
-    }
-}
-
-impl<T: Ord> Ord for SortEvaluator<T> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).expect("T: Ord")
-    }
-}
 
 fn main() {
-    let mut rand = rand::thread_rng();
-    let counter = Rc::new(Cell::new(0));
+    // the seed may be given as the only argument, to reproduce a run
+    let seed = std::env::args()
+        .nth(1)
+        .map_or(0, |arg| arg.parse().expect("the seed must be a number"));
+    let mut rng = Rng::new(seed);
     for &n in &[0, 1, 10, 100, 1000, 10000] {
         let mut values = Vec::with_capacity(n);
         for _ in 0..n {
-            values.push(SortEvaluator {
-                t: rand.gen::<usize>(),
-                cmps: Rc::clone(&counter),
-            });
+            values.push(rng.next_u64() as usize);
         }
         for _ in 0..10 {
-            values.shuffle(&mut rand);
-            let took = bench(BubbleSort, &values, &counter);
-            println!("{} {} {} {}", "bubble", n, took.0, took.1);
-            let took = bench(InsertionSort { smart: true }, &values, &counter);
-            println!("{} {} {} {}", "insertion-smart", n, took.0, took.1);
-            let took = bench(InsertionSort { smart: false }, &values, &counter);
-            println!("{} {} {} {}", "insertion-dump", n, took.0, took.1);
-            let took = bench(SelectionSort, &values, &counter);
-            println!("{} {} {} {}", "selection", n, took.0, took.1);
-            let took = bench(QuickSort, &values, &counter);
-            println!("{} {} {} {}", "quick", n, took.0, took.1);
-            let took = bench(StdSorter, &values, &counter);
-            println!("{} {} {} {}", "std", n, took.0, took.1);
-            let took = bench(HeapSort, &values, &counter);
-            println!("{} {} {} {}", "heap", n, took.0, took.1);
-            let took = bench(MergeSort, &values, &counter);
-            println!("{} {} {} {}", "merge", n, took.0, took.1);
+            rng.shuffle(&mut values);
+            run(BubbleSort, &values);
+            run(CocktailShakerSort, &values);
+            run(GnomeSort, &values);
+            run(CombSort::default(), &values);
+            run(OddEvenSort, &values);
+            run(InsertionSort { smart: true }, &values);
+            run(InsertionSort { smart: false }, &values);
+            run(SelectionSort, &values);
+            run(CycleSort, &values);
+            run(PancakeSort, &values);
+            run(BitonicSort, &values);
+            run(QuickSort::default(), &values);
+            run(ThreeWayQuickSort, &values);
+            run(IntroSort::default(), &values);
+            run(PdqSort, &values);
+            run(AutoSort::default(), &values);
+            run(DefaultSorter, &values);
+            run(StdSorter, &values);
+            run(HeapSort::default(), &values);
+            run(SmoothSort, &values);
+            run(MergeSort::default(), &values);
+            run(
+                MergeSort {
+                    memory: MemoryPolicy::InPlace,
+                    ..MergeSort::default()
+                },
+                &values,
+            );
+            run(BottomUpMergeSort, &values);
+            run(NaturalMergeSort::default(), &values);
+            run(BlockSort, &values);
+            run(BlockedSort::default(), &values);
+            run(PatienceSort, &values);
+            run(StrandSort, &values);
+            run(LibrarySort, &values);
+            run(TreeSort { balanced: true }, &values);
         }
     }
 }
 
-fn bench<T: Ord + Clone, S: Sorter>(
-    sorter: S,
-    values: &[SortEvaluator<T>],
-    counter: &Cell<usize>,
-) -> (usize, f64) {
+fn run<T: Ord + Clone, S: Sorter>(sorter: S, values: &[T]) {
+    let name = sorter.info().name;
+    let took = bench(sorter, values);
+    println!("{} {} {} {}", name, values.len(), took.0, took.1);
+}
+
+fn bench<T: Ord + Clone, S: Sorter>(sorter: S, values: &[T]) -> (usize, f64) {
+    let sorter = Counting::new(sorter);
     let mut values = values.to_vec();
-    counter.set(0);
     let time = std::time::Instant::now();
     sorter.sort(&mut values);
     let took = time.elapsed();
-    let count = counter.get();
+    let count = sorter.stats().comparisons;
     for i in 1..values.len() {
         assert!(values[i] >= values[i - 1])
     }
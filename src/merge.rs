@@ -0,0 +1,223 @@
+//! Merging inputs that are already sorted, such as the shards of a larger sort.
+//!
+//! Sorted inputs merge in linear time, where concatenating and sorting them again
+//! would take O(n log n). Equal elements keep the order of their inputs, and
+//! [`merge_aggregate`] can collapse them into one as they meet.
+//!
+//! ```
+//! use pangua::merge::{kway_merge, merge};
+//!
+//! assert_eq!(merge(&[1, 4, 6], &[2, 3, 7]), &[1, 2, 3, 4, 6, 7]);
+//! let shards = vec![vec![1, 5], vec![2, 3], vec![0, 4]];
+//! assert!(kway_merge(shards).eq(0..6));
+//! ```
+
+use crate::cmp::{ByFn, ByOrd};
+use crate::LoserTree;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::iter::Peekable;
+
+/// Merges two sorted slices into a new vector.
+pub fn merge<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    merge_by(a, b, T::cmp)
+}
+
+/// Merges two slices sorted in the order defined by `compare` into a new vector.
+pub fn merge_by<T, F>(a: &[T], b: &[T], mut compare: F) -> Vec<T>
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut out = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        // ties go to `a`
+        if compare(&b[j], &a[i]) == Ordering::Less {
+            out.push(b[j].clone());
+            j += 1;
+        } else {
+            out.push(a[i].clone());
+            i += 1;
+        }
+    }
+    out.extend_from_slice(&a[i..]);
+    out.extend_from_slice(&b[j..]);
+    out
+}
+
+/// Lazily merges any number of sorted inputs.
+///
+/// The inputs are merged by a [`LoserTree`], which takes ⌈log2 k⌉ comparisons per
+/// element for k inputs, about half of what a binary heap of cursors would.
+pub fn kway_merge<I, J>(inputs: I) -> LoserTree<J::IntoIter, ByOrd>
+where
+    I: IntoIterator<Item = J>,
+    J: IntoIterator,
+    J::Item: Ord,
+{
+    LoserTree::new(inputs)
+}
+
+/// Lazily merges any number of inputs sorted in the order defined by `compare`.
+pub fn kway_merge_by<I, J, F>(inputs: I, compare: F) -> LoserTree<J::IntoIter, ByFn<F>>
+where
+    I: IntoIterator<Item = J>,
+    J: IntoIterator,
+    F: FnMut(&J::Item, &J::Item) -> Ordering,
+{
+    LoserTree::new_by(inputs, compare)
+}
+
+/// Lazily merges sorted inputs, collapsing each run of equal elements into one.
+///
+/// The first of the equal elements absorbs the others through `aggregate`, in the order
+/// of their inputs, as a compaction merges the levels of an LSM tree. Each input should
+/// hold an element at most once, or its duplicates are collapsed too.
+///
+/// ```
+/// use pangua::merge::merge_aggregate;
+///
+/// let a = vec![1, 3, 5];
+/// let b = vec![3, 4, 5];
+/// let merged: Vec<_> = merge_aggregate(vec![a, b], |_, _| ()).collect();
+/// assert_eq!(merged, &[1, 3, 4, 5]);
+/// ```
+pub fn merge_aggregate<I, J, F>(inputs: I, aggregate: F) -> impl Iterator<Item = J::Item>
+where
+    I: IntoIterator<Item = J>,
+    J: IntoIterator,
+    J::Item: Ord,
+    F: FnMut(&mut J::Item, J::Item),
+{
+    Collapse {
+        merge: kway_merge(inputs).peekable(),
+        same: |a: &J::Item, b: &J::Item| a == b,
+        aggregate,
+    }
+}
+
+/// Like [`merge_aggregate`], for inputs sorted by the key `key` extracts, collapsing
+/// elements with equal keys.
+///
+/// ```
+/// use pangua::merge::merge_aggregate_by_key;
+///
+/// let older = vec![("apple", 2), ("pear", 1)];
+/// let newer = vec![("apple", 3), ("fig", 4)];
+/// let counts: Vec<_> =
+///     merge_aggregate_by_key(vec![older, newer], |e| e.0, |x, y| x.1 += y.1).collect();
+/// assert_eq!(counts, &[("apple", 5), ("fig", 4), ("pear", 1)]);
+/// ```
+pub fn merge_aggregate_by_key<I, J, K, G, F>(
+    inputs: I,
+    key: G,
+    aggregate: F,
+) -> impl Iterator<Item = J::Item>
+where
+    I: IntoIterator<Item = J>,
+    J: IntoIterator,
+    K: Ord,
+    G: Fn(&J::Item) -> K + Clone,
+    F: FnMut(&mut J::Item, J::Item),
+{
+    let merge_key = key.clone();
+    Collapse {
+        merge: kway_merge_by(inputs, move |a, b| merge_key(a).cmp(&merge_key(b))).peekable(),
+        same: move |a: &J::Item, b: &J::Item| key(a) == key(b),
+        aggregate,
+    }
+}
+
+/// Folds runs of elements that are the `same` into their first.
+struct Collapse<M: Iterator, E, F> {
+    merge: Peekable<M>,
+    same: E,
+    aggregate: F,
+}
+
+impl<M, E, F> Iterator for Collapse<M, E, F>
+where
+    M: Iterator,
+    E: FnMut(&M::Item, &M::Item) -> bool,
+    F: FnMut(&mut M::Item, M::Item),
+{
+    type Item = M::Item;
+
+    fn next(&mut self) -> Option<M::Item> {
+        let mut first = self.merge.next()?;
+        let same = &mut self.same;
+        while let Some(next) = self.merge.next_if(|next| same(&first, next)) {
+            (self.aggregate)(&mut first, next);
+        }
+        Some(first)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.merge.size_hint();
+        (lo.min(1), hi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn merge_works() {
+        assert_eq!(merge(&[1, 3, 5], &[2, 4]), &[1, 2, 3, 4, 5]);
+        assert_eq!(merge(&[], &[2, 4]), &[2, 4]);
+        assert!(merge::<u8>(&[], &[]).is_empty());
+        let a = [(1, 'a'), (2, 'a')];
+        let b = [(1, 'b'), (2, 'b')];
+        assert_eq!(
+            merge_by(&a, &b, |x, y| x.0.cmp(&y.0)),
+            &[(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]
+        );
+    }
+
+    #[test]
+    fn kway_merge_works() {
+        let shards = vec![vec![2, 8], vec![], vec![1, 5, 9], vec![3]];
+        assert_eq!(kway_merge(shards).collect::<Vec<_>>(), &[1, 2, 3, 5, 8, 9]);
+        let descending = vec![vec![9, 1], vec![5, 4]];
+        let merged: Vec<i32> = kway_merge_by(descending, |a: &i32, b: &i32| b.cmp(a)).collect();
+        assert_eq!(merged, &[9, 5, 4, 1]);
+        assert_eq!(kway_merge(Vec::<Vec<u8>>::new()).next(), None);
+    }
+
+    #[test]
+    fn merge_aggregate_collapses_across_inputs() {
+        #[derive(Debug, PartialEq)]
+        struct Entry {
+            key: u32,
+            count: u32,
+            level: usize,
+        }
+        let level = |level: usize, keys: &[u32]| -> Vec<Entry> {
+            keys.iter()
+                .map(|&key| Entry {
+                    key,
+                    count: 1,
+                    level,
+                })
+                .collect()
+        };
+        let levels = vec![level(0, &[2, 5]), level(1, &[1, 2, 5]), level(2, &[2, 9])];
+        let merged: Vec<Entry> = merge_aggregate_by_key(
+            levels,
+            |e| e.key,
+            |x, y| {
+                x.count += y.count;
+                assert!(x.level < y.level);
+            },
+        )
+        .collect();
+        let summary: Vec<_> = merged.iter().map(|e| (e.key, e.count, e.level)).collect();
+        assert_eq!(summary, &[(1, 1, 1), (2, 3, 0), (5, 2, 0), (9, 1, 2)]);
+
+        let merged: Vec<u8> = merge_aggregate(vec![vec![1, 1, 2], vec![]], |_, _| ()).collect();
+        assert_eq!(merged, &[1, 2]);
+    }
+}
@@ -0,0 +1,391 @@
+//! Natural merge sort, and the policies deciding which of its runs to merge when.
+//!
+//! [`NaturalMergeSort`] splits the input into the runs already present in it and keeps
+//! them on a stack. After each new run is pushed, its [`MergePolicy`] picks adjacent runs
+//! on the stack to merge, until it is content to look for the next run. The policy
+//! decides how balanced the merges are, and so how close the sort gets to the
+//! information-theoretic bound for the runs it was given. Implementing the trait plugs a
+//! new policy into the same run detection and merging.
+//!
+//! ```
+//! use pangua::natural::{NaturalMergeSort, Timsort};
+//! use pangua::Sorter;
+//!
+//! let mut tings = vec![1, 2, 3, 9, 8, 7, 4, 5, 6];
+//! NaturalMergeSort::new(Timsort).sort(&mut tings);
+//! assert_eq!(tings, &[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+//! ```
+
+use crate::cmp::{self, Compare};
+use crate::runs;
+use crate::{is_trivially_sorted, Complexity, MergeSort, Sorter, SorterInfo, StableSorter};
+use alloc::vec::Vec;
+
+/// Wins in a row by one run after which merges gallop: Timsort's `MIN_GALLOP`.
+pub const MIN_GALLOP: usize = 7;
+
+/// A sorted run of the slice, as kept on the run stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    pub start: usize,
+    pub len: usize,
+    /// Runs of the input merged into this one.
+    pub runs: usize,
+    /// The Powersort node power of the boundary at the start of this run, computed
+    /// when the run was found; 0 for the first run.
+    pub power: u32,
+}
+
+/// Decides which runs on the stack of a [`NaturalMergeSort`] to merge next.
+pub trait MergePolicy {
+    /// Returns `i` to merge `stack[i]` with `stack[i + 1]`, or `None` to go find the next
+    /// run. `len` is the length of the whole slice.
+    ///
+    /// Called after every run is pushed and after every merge. Once the input is
+    /// exhausted, whatever remains is merged from the top of the stack down.
+    fn merge_at(&self, stack: &[Run], len: usize) -> Option<usize>;
+}
+
+/// Timsort's rule: keep every run longer than the two above it combined, and each
+/// run longer than the one above it.
+///
+/// Run lengths on the stack then grow at least as fast as the Fibonacci numbers, so the
+/// stack stays O(log n) deep. This checks the invariant four runs deep, the correction
+/// made after the original three-run check was found to let it break.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timsort;
+
+impl MergePolicy for Timsort {
+    fn merge_at(&self, stack: &[Run], _len: usize) -> Option<usize> {
+        let n = stack.len();
+        let len = |i: usize| stack[n - i].len;
+        if (n >= 3 && len(3) <= len(2) + len(1)) || (n >= 4 && len(4) <= len(3) + len(2)) {
+            Some(if len(3) < len(1) { n - 3 } else { n - 2 })
+        } else if n >= 2 && len(2) <= len(1) {
+            Some(n - 2)
+        } else {
+            None
+        }
+    }
+}
+
+/// Powersort's rule, as in CPython since 3.11: merge while the boundary below the top
+/// run is deeper in a virtual balanced merge tree than the boundary above it.
+///
+/// A boundary's depth, its node power, depends only on the midpoints of the runs
+/// beside it, and this makes the merge cost within a constant of optimal for the runs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Powersort;
+
+impl MergePolicy for Powersort {
+    fn merge_at(&self, stack: &[Run], _len: usize) -> Option<usize> {
+        let n = stack.len();
+        if n >= 3 && stack[n - 2].power > stack[n - 1].power {
+            Some(n - 3)
+        } else {
+            None
+        }
+    }
+}
+
+/// Merges every run into the one before it as soon as it is found.
+///
+/// The simplest policy, and the worst: the growing merged prefix is passed over once per
+/// run, for O(n·r) time with r runs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Eager;
+
+impl MergePolicy for Eager {
+    fn merge_at(&self, stack: &[Run], _len: usize) -> Option<usize> {
+        stack.len().checked_sub(2)
+    }
+}
+
+/// Merges runs pairwise in rounds, as classic natural merge sort does: each merge
+/// combines two blocks of the same number of input runs, like a binary counter.
+///
+/// Balanced in the number of runs, but not in their lengths.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BalancedBinary;
+
+impl MergePolicy for BalancedBinary {
+    fn merge_at(&self, stack: &[Run], _len: usize) -> Option<usize> {
+        let n = stack.len();
+        if n >= 2 && stack[n - 2].runs == stack[n - 1].runs {
+            Some(n - 2)
+        } else {
+            None
+        }
+    }
+}
+
+/// Natural Merge Sort
+///
+/// Natural merge sort takes advantage of order already present in the input: it finds
+/// the maximal ascending runs, reversing strictly descending ones, and merges those
+/// instead of starting from single elements. Sorted input takes one pass, and input made
+/// of r runs takes O(n log r). Which runs are merged when is up to its [`MergePolicy`],
+/// Powersort's rule by default. Merges gallop once one run has won `min_gallop` times
+/// in a row, [`MIN_GALLOP`] by default and never at 0, so merging runs that barely
+/// overlap takes a few comparisons rather than one per element. The sort is stable.
+#[derive(Debug, Clone, Copy)]
+pub struct NaturalMergeSort<P = Powersort> {
+    pub policy: P,
+    pub min_gallop: usize,
+}
+
+impl<P: MergePolicy> NaturalMergeSort<P> {
+    /// A sorter merging as `policy` decides, galloping after [`MIN_GALLOP`] wins.
+    pub fn new(policy: P) -> Self {
+        NaturalMergeSort {
+            policy,
+            min_gallop: MIN_GALLOP,
+        }
+    }
+}
+
+impl Default for NaturalMergeSort {
+    fn default() -> Self {
+        NaturalMergeSort::new(Powersort)
+    }
+}
+
+impl<P: MergePolicy> Sorter for NaturalMergeSort<P> {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "merge-natural",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        self.sort_with_scratch(slice, &mut Vec::new(), cmp)
+    }
+
+    fn sort_with_scratch<T, C>(&self, slice: &mut [T], scratch: &mut Vec<usize>, cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        merge_runs(slice, &self.policy, 1, self.min_gallop, scratch, cmp)
+    }
+}
+
+impl<P: MergePolicy> StableSorter for NaturalMergeSort<P> {}
+
+/// Sorts the slice by merging its runs as `policy` decides, first extending runs
+/// shorter than `min_run` to that length by binary insertion, as Timsort does. Merges
+/// gallop after `min_gallop` wins in a row, or never at 0, through `scratch`.
+pub(crate) fn merge_runs<T, C, P>(
+    slice: &mut [T],
+    policy: &P,
+    min_run: usize,
+    min_gallop: usize,
+    scratch: &mut Vec<usize>,
+    cmp: &mut C,
+) where
+    C: Compare<T>,
+    P: MergePolicy,
+{
+    let n = slice.len();
+    let mut stack: Vec<Run> = Vec::new();
+    let mut start = 0;
+    while start < n {
+        let mut len = runs::reverse_run_at(&mut slice[start..], cmp);
+        if len < min_run {
+            let end = n.min(start + min_run);
+            extend_run(&mut slice[start..end], len, cmp);
+            len = end - start;
+        }
+        let power = stack.last().map_or(0, |prev| node_power(n, prev, len));
+        stack.push(Run {
+            start,
+            len,
+            runs: 1,
+            power,
+        });
+        start += len;
+        while let Some(i) = policy.merge_at(&stack, n) {
+            merge(slice, &mut stack, i, scratch, min_gallop, cmp);
+        }
+    }
+    while stack.len() > 1 {
+        let i = stack.len() - 2;
+        merge(slice, &mut stack, i, scratch, min_gallop, cmp);
+    }
+}
+
+/// Inserts the elements after the sorted first `sorted` ones into place, each after the
+/// elements equal to it, finding the place by binary search.
+fn extend_run<T, C: Compare<T>>(slice: &mut [T], sorted: usize, cmp: &mut C) {
+    for next in sorted..slice.len() {
+        let (run, rest) = slice.split_at(next);
+        let at = run.partition_point(|x| !cmp.is_less(&rest[0], x));
+        cmp::rotate_right(cmp, &mut slice[at..=next], 1);
+    }
+}
+
+/// Merges `stack[i]` with `stack[i + 1]`, in the slice and on the stack.
+fn merge<T, C: Compare<T>>(
+    slice: &mut [T],
+    stack: &mut Vec<Run>,
+    i: usize,
+    scratch: &mut Vec<usize>,
+    min_gallop: usize,
+    cmp: &mut C,
+) {
+    assert!(i + 1 < stack.len(), "no runs {} and {} to merge", i, i + 1);
+    let (left, right) = (stack[i], stack.remove(i + 1));
+    assert_eq!(
+        left.start + left.len,
+        right.start,
+        "merged runs must be adjacent"
+    );
+    let run = &mut slice[left.start..right.start + right.len];
+    MergeSort::merge_galloping(run, left.len, scratch, min_gallop, cmp);
+    stack[i] = Run {
+        len: left.len + right.len,
+        runs: left.runs + right.runs,
+        ..left
+    };
+}
+
+/// The Powersort node power of the boundary between run `prev` and the `len` elements
+/// following it, in a slice of `n` elements.
+///
+/// That is the first bit at which the binary fractions of the runs' midpoints, relative
+/// to `n`, differ.
+pub(crate) fn node_power(n: usize, prev: &Run, len: usize) -> u32 {
+    let n2 = 2 * n as u128;
+    // the midpoints doubled, to stay whole
+    let mut a = (2 * prev.start + prev.len) as u128;
+    let mut b = a + (prev.len + len) as u128;
+    let mut power = 0;
+    loop {
+        power += 1;
+        a *= 2;
+        b *= 2;
+        match (a >= n2, b >= n2) {
+            (false, false) => {}
+            (true, true) => {
+                a -= n2;
+                b -= n2;
+            }
+            _ => return power,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counting;
+    use alloc::vec;
+
+    fn check<P: MergePolicy>(policy: P) {
+        let sorter = NaturalMergeSort::new(policy);
+        let mut tings = vec![5, 1, 4, 2, 3];
+        sorter.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for n in 0..200u64 {
+            let mut tings: Vec<_> = (0..n).map(|i| ((i * 7919) % 13, i)).collect();
+            sorter.sort_by_key(&mut tings, |t| t.0);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn every_policy_sorts_stably() {
+        check(Powersort);
+        check(Timsort);
+        check(Eager);
+        check(BalancedBinary);
+    }
+
+    #[test]
+    fn sorted_and_reversed_input_take_one_pass() {
+        let n = 1000;
+        for tings in [(0..n).collect::<Vec<_>>(), (0..n).rev().collect()] {
+            let sorter = Counting::new(NaturalMergeSort::default());
+            let mut tings = tings;
+            sorter.sort(&mut tings);
+            assert!(sorter.stats().comparisons < n);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn descending_runs_keep_equal_elements_in_order() {
+        let mut tings = vec![(3, 0), (2, 1), (2, 2), (1, 3)];
+        NaturalMergeSort::default().sort_by_key(&mut tings, |t| t.0);
+        assert_eq!(tings, &[(1, 3), (2, 1), (2, 2), (3, 0)]);
+    }
+
+    #[test]
+    fn eager_merging_costs_more_on_many_runs() {
+        fn comparisons<P: MergePolicy>(policy: P) -> usize {
+            // 100 runs of 20, each spanning the whole range of values
+            let mut tings: Vec<u32> = (0..2000).map(|i| (i % 20) * 100 + i / 20).collect();
+            // galloping through the long merged prefix hides much of eager's cost
+            let sorter = Counting::new(NaturalMergeSort {
+                min_gallop: 0,
+                ..NaturalMergeSort::new(policy)
+            });
+            sorter.sort(&mut tings);
+            sorter.stats().comparisons
+        }
+        let (powersort, eager) = (comparisons(Powersort), comparisons(Eager));
+        assert!(eager > 3 * powersort, "{} vs {}", eager, powersort);
+        assert!(comparisons(Timsort) < 2 * powersort);
+        assert!(comparisons(BalancedBinary) < 2 * powersort);
+    }
+
+    #[test]
+    fn policies_keep_the_stack_shallow() {
+        let runs = |lens: &[usize]| {
+            let mut start = 0;
+            lens.iter()
+                .map(|&len| {
+                    let run = Run {
+                        start,
+                        len,
+                        runs: 1,
+                        power: 0,
+                    };
+                    start += len;
+                    run
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(Timsort.merge_at(&runs(&[10, 5]), 15), None);
+        assert_eq!(Timsort.merge_at(&runs(&[10, 10]), 20), Some(0));
+        assert_eq!(Timsort.merge_at(&runs(&[10, 6, 5]), 21), Some(1));
+        assert_eq!(Timsort.merge_at(&runs(&[10, 6, 12]), 28), Some(0));
+        assert_eq!(BalancedBinary.merge_at(&runs(&[3, 9]), 12), Some(0));
+        assert_eq!(Eager.merge_at(&runs(&[3]), 3), None);
+    }
+
+    #[test]
+    fn node_powers_follow_the_midpoints() {
+        let run = |start, len| Run {
+            start,
+            len,
+            runs: 1,
+            power: 0,
+        };
+        // halves of the slice meet at the root
+        assert_eq!(node_power(8, &run(0, 4), 4), 1);
+        assert_eq!(node_power(8, &run(0, 2), 2), 2);
+        assert_eq!(node_power(8, &run(4, 2), 2), 2);
+        assert_eq!(node_power(8, &run(2, 1), 1), 3);
+    }
+}
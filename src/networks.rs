@@ -0,0 +1,285 @@
+//! Sorting networks: fixed sequences of compare-exchanges that sort whatever the input.
+//!
+//! A network compares the same pairs in the same order for every input, so it has no
+//! data-dependent control flow beyond the exchanges themselves. This makes networks
+//! good base cases for divide-and-conquer sorts and for sorting tiny arrays.
+//!
+//! ```
+//! use pangua::networks;
+//!
+//! let mut tings = [5, 1, 4, 2, 3];
+//! networks::sorting_network(&mut tings);
+//! assert_eq!(tings, [1, 2, 3, 4, 5]);
+//! ```
+
+use crate::cmp::{self, ByFn, ByOrd, Compare};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// A compare-exchange: the elements at the two positions are put in order, the smaller
+/// at the first.
+pub type Comparator = (u8, u8);
+
+/// The largest size with a network in [`optimal`].
+pub const MAX_OPTIMAL: usize = 16;
+
+#[rustfmt::skip]
+const NETWORKS: [&[Comparator]; MAX_OPTIMAL + 1] = [
+    &[],
+    &[],
+    &[(0, 1)],
+    &[(0, 2), (0, 1), (1, 2)],
+    &[(0, 2), (1, 3), (0, 1), (2, 3), (1, 2)],
+    &[(0, 3), (1, 4), (0, 2), (1, 3), (0, 1), (2, 4), (1, 2), (3, 4), (2, 3)],
+    &[
+        (0, 5), (1, 3), (2, 4), (1, 2), (3, 4), (0, 3), (2, 5), (0, 1), (2, 3), (4, 5),
+        (1, 2), (3, 4),
+    ],
+    &[
+        (0, 6), (2, 3), (4, 5), (0, 2), (1, 4), (3, 6), (0, 1), (2, 5), (3, 4), (1, 2),
+        (4, 6), (2, 3), (4, 5), (1, 2), (3, 4), (5, 6),
+    ],
+    &[
+        (0, 2), (1, 3), (4, 6), (5, 7), (0, 4), (1, 5), (2, 6), (3, 7), (0, 1), (2, 3),
+        (4, 5), (6, 7), (2, 4), (3, 5), (1, 4), (3, 6), (1, 2), (3, 4), (5, 6),
+    ],
+    &[
+        (0, 3), (1, 7), (2, 5), (4, 8), (0, 7), (2, 4), (3, 8), (5, 6), (0, 2), (1, 3),
+        (4, 5), (7, 8), (1, 4), (3, 6), (5, 7), (0, 1), (2, 4), (3, 5), (6, 8), (2, 3),
+        (4, 5), (6, 7), (1, 2), (3, 4), (5, 6),
+    ],
+    &[
+        (0, 8), (1, 9), (2, 7), (3, 5), (4, 6), (0, 2), (1, 4), (5, 8), (7, 9), (0, 3),
+        (2, 4), (5, 7), (6, 9), (0, 1), (3, 6), (8, 9), (1, 5), (2, 3), (4, 8), (6, 7),
+        (1, 2), (3, 5), (4, 6), (7, 8), (2, 3), (4, 5), (6, 7), (3, 4), (5, 6),
+    ],
+    &[
+        (0, 9), (1, 6), (2, 4), (3, 7), (5, 8), (0, 1), (3, 5), (4, 10), (6, 9), (7, 8),
+        (1, 3), (2, 5), (4, 7), (8, 10), (0, 4), (1, 2), (3, 7), (5, 9), (6, 8), (0, 1),
+        (2, 6), (4, 5), (7, 8), (9, 10), (2, 4), (3, 6), (5, 7), (8, 9), (1, 2), (3, 4),
+        (5, 6), (7, 8), (2, 3), (4, 5), (6, 7),
+    ],
+    &[
+        (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9), (0, 1), (2, 5), (3, 4), (6, 9),
+        (7, 8), (10, 11), (0, 2), (1, 6), (5, 10), (9, 11), (0, 3), (1, 2), (4, 6), (5, 7),
+        (8, 11), (9, 10), (1, 4), (3, 5), (6, 8), (7, 10), (1, 3), (2, 5), (6, 9), (8, 10),
+        (2, 3), (4, 5), (6, 7), (8, 9), (4, 6), (5, 7), (3, 4), (5, 6), (7, 8),
+    ],
+    &[
+        (0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8), (1, 6), (2, 3), (4, 11), (7, 9),
+        (8, 10), (0, 4), (1, 2), (3, 6), (7, 8), (9, 10), (11, 12), (4, 6), (5, 9), (8, 11),
+        (10, 12), (0, 5), (3, 8), (4, 7), (6, 11), (9, 10), (0, 1), (2, 5), (6, 9), (7, 8),
+        (10, 11), (1, 3), (2, 4), (5, 6), (9, 10), (1, 2), (3, 4), (5, 7), (6, 8), (2, 3),
+        (4, 5), (6, 7), (8, 9), (3, 4), (5, 6),
+    ],
+    &[
+        (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (0, 2), (1, 3), (4, 8),
+        (5, 9), (10, 12), (11, 13), (0, 4), (1, 2), (3, 7), (5, 8), (6, 10), (9, 13),
+        (11, 12), (0, 6), (1, 5), (3, 9), (4, 10), (7, 13), (8, 12), (2, 10), (3, 11),
+        (4, 6), (7, 9), (1, 3), (2, 8), (5, 11), (6, 7), (10, 12), (1, 4), (2, 6), (3, 5),
+        (7, 11), (8, 10), (9, 12), (2, 4), (3, 6), (5, 8), (7, 10), (9, 11), (3, 4), (5, 6),
+        (7, 8), (9, 10), (6, 7),
+    ],
+    &[
+        (0, 13), (1, 12), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10), (0, 5), (1, 7),
+        (2, 9), (3, 4), (6, 13), (8, 14), (11, 12), (0, 1), (2, 3), (4, 5), (6, 8),
+        (7, 9), (10, 11), (12, 13), (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9),
+        (12, 14), (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14), (1, 4),
+        (2, 6), (5, 8), (7, 10), (9, 13), (11, 14), (2, 4), (3, 6), (9, 12), (11, 13),
+        (3, 5), (6, 8), (7, 9), (10, 12), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12),
+        (6, 7), (8, 9),
+    ],
+    &[
+        (0, 13), (1, 12), (2, 15), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10), (0, 5),
+        (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (10, 15), (11, 12), (0, 1), (2, 3),
+        (4, 5), (6, 8), (7, 9), (10, 11), (12, 13), (14, 15), (0, 2), (1, 3), (4, 10),
+        (5, 11), (6, 7), (8, 9), (12, 14), (13, 15), (1, 2), (3, 12), (4, 6), (5, 7),
+        (8, 10), (9, 11), (13, 14), (1, 4), (2, 6), (5, 8), (7, 10), (9, 13), (11, 14),
+        (2, 4), (3, 6), (9, 12), (11, 13), (3, 5), (6, 8), (7, 9), (10, 12), (3, 4),
+        (5, 6), (7, 8), (9, 10), (11, 12), (6, 7), (8, 9),
+    ],
+];
+
+/// The network with the fewest comparators known for `n` elements, if `n` is at most
+/// [`MAX_OPTIMAL`].
+///
+/// Up to 12 elements these are proven optimal in size; from 13 to 16 they are the
+/// smallest known, such as Green's 60-comparator network for 16.
+pub const fn optimal(n: usize) -> Option<&'static [Comparator]> {
+    if n <= MAX_OPTIMAL {
+        Some(NETWORKS[n])
+    } else {
+        None
+    }
+}
+
+/// Batcher's odd-even merge network for `n` elements, in the merge-exchange form that
+/// works for any `n` (Knuth's Algorithm 5.2.2M), with O(n log² n) comparators.
+#[cfg(feature = "alloc")]
+pub fn batcher(n: usize) -> Vec<(usize, usize)> {
+    let mut network = Vec::new();
+    for_each_batcher(n, |a, b| network.push((a, b)));
+    network
+}
+
+pub(crate) fn for_each_batcher(n: usize, mut f: impl FnMut(usize, usize)) {
+    let mut pairs = Batcher::new(n);
+    while let Some((a, b)) = pairs.next_pair() {
+        f(a, b)
+    }
+}
+
+/// The comparators of Batcher's network for `n` elements, one at a time, so that
+/// `const fn`s can walk the network without a closure or an allocation.
+pub(crate) struct Batcher {
+    n: usize,
+    top: usize,
+    p: usize,
+    q: usize,
+    r: usize,
+    d: usize,
+    i: usize,
+}
+
+impl Batcher {
+    pub(crate) const fn new(n: usize) -> Self {
+        let top = if n < 2 {
+            0
+        } else {
+            1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+        };
+        Batcher {
+            n,
+            top,
+            p: top,
+            q: top,
+            r: 0,
+            d: top,
+            i: 0,
+        }
+    }
+
+    pub(crate) const fn next_pair(&mut self) -> Option<(usize, usize)> {
+        while self.p > 0 {
+            while self.i + self.d < self.n {
+                let i = self.i;
+                self.i += 1;
+                if i & self.p == self.r {
+                    return Some((i, i + self.d));
+                }
+            }
+            self.i = 0;
+            if self.q == self.p {
+                self.p >>= 1;
+                self.q = self.top;
+                self.r = 0;
+                self.d = self.p;
+            } else {
+                self.d = self.q - self.p;
+                self.q >>= 1;
+                self.r = self.p;
+            }
+        }
+        None
+    }
+}
+
+/// Sorts the array with the smallest known network for `N` elements, or Batcher's
+/// network past [`MAX_OPTIMAL`]. Not stable.
+pub fn sorting_network<const N: usize, T: Ord>(array: &mut [T; N]) {
+    network_sort(array, &mut ByOrd)
+}
+
+/// Like [`sorting_network`], ordering with `compare`.
+pub fn sorting_network_by<const N: usize, T, F>(array: &mut [T; N], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    network_sort(array, &mut ByFn(compare))
+}
+
+/// Runs the compare-exchanges of `network` over the slice, in order.
+pub fn apply<T, C: Compare<T>>(slice: &mut [T], network: &[Comparator], cmp: &mut C) {
+    for &(a, b) in network {
+        compare_exchange(slice, a as usize, b as usize, cmp);
+    }
+}
+
+/// Sorts the slice with a network: the smallest known one up to [`MAX_OPTIMAL`]
+/// elements, Batcher's beyond.
+pub(crate) fn network_sort<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) {
+    match optimal(slice.len()) {
+        Some(network) => apply(slice, network, cmp),
+        None => for_each_batcher(slice.len(), |a, b| compare_exchange(slice, a, b, cmp)),
+    }
+}
+
+#[inline]
+fn compare_exchange<T, C: Compare<T>>(slice: &mut [T], a: usize, b: usize, cmp: &mut C) {
+    if cmp.is_less(&slice[b], &slice[a]) {
+        cmp::swap(cmp, slice, a, b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn sorts_binary_inputs(n: usize, network: &[(usize, usize)]) -> bool {
+        // by the 0-1 principle, a network sorting all 0-1 inputs sorts everything
+        (0u32..1 << n).all(|bits| {
+            let mut tings: Vec<_> = (0..n).map(|i| (bits >> i) & 1).collect();
+            for &(a, b) in network {
+                if tings[b] < tings[a] {
+                    tings.swap(a, b);
+                }
+            }
+            tings.windows(2).all(|w| w[0] <= w[1])
+        })
+    }
+
+    #[test]
+    fn optimal_networks_sort() {
+        let sizes = [0, 0, 1, 3, 5, 9, 12, 16, 19, 25, 29, 35, 39, 45, 51, 56, 60];
+        for (n, &size) in sizes.iter().enumerate() {
+            let network = optimal(n).unwrap();
+            assert_eq!(network.len(), size, "n = {}", n);
+            let pairs: Vec<_> = network
+                .iter()
+                .map(|&(a, b)| (a as usize, b as usize))
+                .collect();
+            assert!(pairs.iter().all(|&(a, b)| a < b && b < n));
+            assert!(sorts_binary_inputs(n, &pairs), "n = {}", n);
+        }
+        assert_eq!(optimal(MAX_OPTIMAL + 1), None);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn batcher_networks_sort() {
+        for n in 0..=18 {
+            assert!(sorts_binary_inputs(n, &batcher(n)), "n = {}", n);
+        }
+        assert_eq!(batcher(16).len(), 63);
+    }
+
+    #[test]
+    fn sorts_arrays() {
+        let mut tings = [9, 3, 7, 1, 8, 2, 6, 4, 5, 0, 11, 10, 15, 13, 12, 14];
+        sorting_network(&mut tings);
+        assert_eq!(
+            tings,
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+        );
+        let mut big: [u32; 40] = [0; 40];
+        for (i, x) in big.iter_mut().enumerate() {
+            *x = (i as u32 * 7919) % 40;
+        }
+        sorting_network_by(&mut big, |a, b| b.cmp(a));
+        assert!(big.windows(2).all(|w| w[0] >= w[1]));
+        let mut empty: [u8; 0] = [];
+        sorting_network(&mut empty);
+    }
+}
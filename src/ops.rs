@@ -0,0 +1,274 @@
+//! In-place rearrangement primitives: block swaps, rotations and rotation-based merging.
+//!
+//! These are the building blocks of the in-place merges. They never allocate.
+
+use crate::cmp::{ByFn, ByOrd, Compare};
+use core::cmp::Ordering;
+
+/// Swaps the non-overlapping blocks `slice[a..a + len]` and `slice[b..b + len]`.
+///
+/// Panics if the blocks overlap or are out of bounds.
+pub fn block_swap<T>(slice: &mut [T], a: usize, b: usize, len: usize) {
+    let (lo, hi) = (a.min(b), a.max(b));
+    assert!(lo + len <= hi, "blocks overlap");
+    let (head, tail) = slice.split_at_mut(hi);
+    head[lo..lo + len].swap_with_slice(&mut tail[..len]);
+}
+
+/// Rotates the slice so that `slice[mid]` becomes the first element.
+///
+/// Uses the Gries-Mills block swap algorithm: the shorter side is swapped into its
+/// final place and the remainder is rotated in turn, for `slice.len()` element writes
+/// at most.
+///
+/// Panics if `mid > slice.len()`.
+pub fn rotate<T>(slice: &mut [T], mid: usize) {
+    assert!(mid <= slice.len(), "rotation point out of range");
+    let (mut lo, mut hi) = (0, slice.len());
+    while lo < mid && mid < hi {
+        let (left, right) = (mid - lo, hi - mid);
+        if left == right {
+            block_swap(slice, lo, mid, left);
+            return;
+        } else if left < right {
+            // [A B1 B2] -> [B2 B1 A], then rotate [B2 B1]
+            block_swap(slice, lo, hi - left, left);
+            hi -= left;
+        } else {
+            // [A1 A2 B] -> [B A2 A1], then rotate [A2 A1]
+            block_swap(slice, lo, mid, right);
+            lo += right;
+        }
+    }
+}
+
+/// Merges the sorted runs `slice[..mid]` and `slice[mid..]` in place.
+///
+/// The runs are cut around a splitting element found by binary search, the middle
+/// blocks are exchanged with a rotation, and both halves are merged recursively. This
+/// needs O(n log n) work and no extra memory, and is stable.
+pub fn merge_rotation<T: Ord>(slice: &mut [T], mid: usize) {
+    merge_rotation_with(slice, mid, &mut ByOrd)
+}
+
+/// Like [`merge_rotation`], ordering elements with `compare`.
+pub fn merge_rotation_by<T, F>(slice: &mut [T], mid: usize, compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge_rotation_with(slice, mid, &mut ByFn(compare))
+}
+
+pub(crate) fn merge_rotation_with<T, C: Compare<T>>(slice: &mut [T], mid: usize, cmp: &mut C) {
+    assert!(mid <= slice.len(), "merge point out of range");
+    if mid == 0 || mid == slice.len() || !cmp.is_less(&slice[mid], &slice[mid - 1]) {
+        return;
+    }
+    if slice.len() == 2 {
+        crate::cmp::swap(cmp, slice, 0, 1);
+        return;
+    }
+    let (split, left_mid, right_mid) = split_merge(slice, mid, cmp);
+    let (left, right) = slice.split_at_mut(split);
+    merge_rotation_with(left, left_mid, cmp);
+    merge_rotation_with(right, right_mid, cmp);
+}
+
+/// One step of a rotation merge of `slice[..mid]` and `slice[mid..]`: cuts the longer
+/// run in half and rotates the elements in between across, leaving two independent
+/// merges. Returns `(split, left_mid, right_mid)`: what is left is merging
+/// `slice[..split]` at `left_mid` and `slice[split..]` at `right_mid`.
+pub(crate) fn split_merge<T, C: Compare<T>>(
+    slice: &mut [T],
+    mid: usize,
+    cmp: &mut C,
+) -> (usize, usize, usize) {
+    let (cut_left, cut_right) = if mid >= slice.len() - mid {
+        // cut the left run in half; right elements strictly smaller go before the cut
+        let i = mid / 2;
+        let j = mid + lower_bound(&slice[mid..], &slice[i], cmp);
+        (i, j)
+    } else {
+        // cut the right run in half; left elements not greater stay before the cut
+        let j = mid + (slice.len() - mid) / 2;
+        let i = upper_bound(&slice[..mid], &slice[j], cmp);
+        (i, j)
+    };
+    let region = &mut slice[cut_left..cut_right];
+    rotate(region, mid - cut_left);
+    cmp.on_move(region);
+    (cut_left + (cut_right - mid), cut_left, mid - cut_left)
+}
+
+/// Number of elements in the sorted `slice` that are less than `x`.
+fn lower_bound<T, C: Compare<T>>(slice: &[T], x: &T, cmp: &mut C) -> usize {
+    let (mut lo, mut hi) = (0, slice.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if cmp.is_less(&slice[mid], x) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Number of elements in the sorted `slice` that are not greater than `x`.
+fn upper_bound<T, C: Compare<T>>(slice: &[T], x: &T, cmp: &mut C) -> usize {
+    let (mut lo, mut hi) = (0, slice.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if cmp.is_less(x, &slice[mid]) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    lo
+}
+
+/// Like [`lower_bound`], searching exponentially from the start of the slice: O(log k)
+/// comparisons for an answer of k, fewer than a binary search takes when k is small.
+#[cfg(feature = "alloc")]
+pub(crate) fn gallop_lower_bound<T, C: Compare<T>>(slice: &[T], x: &T, cmp: &mut C) -> usize {
+    gallop(slice, |y| cmp.is_less(y, x))
+}
+
+/// Like [`upper_bound`], searching exponentially from the start of the slice.
+#[cfg(feature = "alloc")]
+pub(crate) fn gallop_upper_bound<T, C: Compare<T>>(slice: &[T], x: &T, cmp: &mut C) -> usize {
+    gallop(slice, |y| !cmp.is_less(x, y))
+}
+
+/// The number of leading elements of the slice for which `before` holds, when it holds
+/// for a prefix: probes the 1st, 2nd, 4th, 8th, ... elements until it fails, then
+/// binary searches the last stride.
+#[cfg(feature = "alloc")]
+fn gallop<T, P: FnMut(&T) -> bool>(slice: &[T], mut before: P) -> usize {
+    let mut bound = 1;
+    while bound <= slice.len() && before(&slice[bound - 1]) {
+        bound *= 2;
+    }
+    let known = bound / 2;
+    known + slice[known..bound.min(slice.len())].partition_point(before)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn block_swap_works() {
+        let mut tings = vec![0, 1, 2, 3, 4, 5, 6];
+        block_swap(&mut tings, 0, 4, 3);
+        assert_eq!(tings, &[4, 5, 6, 3, 0, 1, 2]);
+        block_swap(&mut tings, 5, 1, 2);
+        assert_eq!(tings, &[4, 1, 2, 3, 0, 5, 6]);
+        block_swap(&mut tings, 2, 3, 0);
+        assert_eq!(tings, &[4, 1, 2, 3, 0, 5, 6]);
+    }
+
+    #[test]
+    fn block_swap_adjacent() {
+        let mut tings = vec![0, 1, 2, 3];
+        block_swap(&mut tings, 0, 2, 2);
+        assert_eq!(tings, &[2, 3, 0, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn block_swap_rejects_overlap() {
+        block_swap(&mut [0, 1, 2, 3], 0, 1, 2);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn galloping_finds_the_bounds() {
+        for n in 0..40 {
+            let tings: Vec<usize> = (0..n).map(|i| i / 3).collect();
+            for x in 0..n / 3 + 2 {
+                assert_eq!(
+                    gallop_lower_bound(&tings, &x, &mut ByOrd),
+                    lower_bound(&tings, &x, &mut ByOrd)
+                );
+                assert_eq!(
+                    gallop_upper_bound(&tings, &x, &mut ByOrd),
+                    upper_bound(&tings, &x, &mut ByOrd)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rotate_matches_std() {
+        for n in 0..12 {
+            for mid in 0..=n {
+                let mut tings: Vec<_> = (0..n).collect();
+                let mut expected = tings.clone();
+                rotate(&mut tings, mid);
+                expected.rotate_left(mid);
+                assert_eq!(tings, expected, "n = {}, mid = {}", n, mid);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn rotate_rejects_out_of_range() {
+        rotate(&mut [0, 1, 2], 4);
+    }
+
+    #[test]
+    fn merge_rotation_merges_every_split() {
+        for n in 0..14 {
+            for mid in 0..=n {
+                let mut tings: Vec<_> = (0..n).map(|i| (i * 7) % 5).collect();
+                tings[..mid].sort();
+                tings[mid..].sort();
+                let mut expected = tings.clone();
+                expected.sort();
+                merge_rotation(&mut tings, mid);
+                assert_eq!(tings, expected, "n = {}, mid = {}", n, mid);
+            }
+        }
+    }
+
+    #[test]
+    fn merge_rotation_is_stable() {
+        let mut tings = vec![
+            (0, 'a'),
+            (1, 'b'),
+            (1, 'c'),
+            (2, 'd'),
+            (0, 'e'),
+            (1, 'f'),
+            (2, 'g'),
+        ];
+        merge_rotation_by(&mut tings, 4, |a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            tings,
+            &[
+                (0, 'a'),
+                (0, 'e'),
+                (1, 'b'),
+                (1, 'c'),
+                (1, 'f'),
+                (2, 'd'),
+                (2, 'g')
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_rotation_uneven_runs() {
+        let mut tings = vec![9, 1, 2, 3, 4, 5, 6, 7, 8];
+        merge_rotation(&mut tings, 1);
+        assert_eq!(tings, &[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        let mut tings = vec![2, 3, 4, 5, 6, 7, 8, 9, 1];
+        merge_rotation(&mut tings, 8);
+        assert_eq!(tings, &[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+}
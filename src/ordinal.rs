@@ -0,0 +1,199 @@
+use crate::CountingSort;
+use core::cmp::Ordering;
+
+/// The largest domain [`sort_ordinal`] accepts: as many keys as a `u16` has.
+pub const MAX_ORDINAL_DOMAIN: usize = 1 << 16;
+
+/// Values numbered densely from 0, such as the variants of a fieldless enum, so they
+/// can be sorted by counting instead of comparing.
+///
+/// Every value has an ordinal below `DOMAIN`, and ordinals should follow the order of
+/// the values, as the declaration order of an enum's variants does for a derived `Ord`.
+/// Enums get an implementation from [`ordinal_enum!`]; newtypes write one by hand:
+///
+/// ```
+/// use pangua::{sort_ordinal, OrdinalKey};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Hour(u8);
+///
+/// impl OrdinalKey for Hour {
+///     const DOMAIN: usize = 24;
+///
+///     fn ordinal(&self) -> usize {
+///         self.0 as usize
+///     }
+/// }
+///
+/// let mut tings = vec![Hour(23), Hour(0), Hour(9)];
+/// sort_ordinal(&mut tings);
+/// assert_eq!(tings, [Hour(0), Hour(9), Hour(23)]);
+/// ```
+pub trait OrdinalKey {
+    /// The number of distinct ordinals.
+    const DOMAIN: usize;
+
+    fn ordinal(&self) -> usize;
+}
+
+/// Declares a fieldless enum numbering its variants in declaration order.
+///
+/// The variants can't be given explicit discriminants, which keeps the ordinals dense.
+///
+/// ```
+/// use pangua::{ordinal_enum, sort_ordinal, OrdinalKey};
+///
+/// ordinal_enum! {
+///     #[derive(Debug, Clone, Copy, PartialEq)]
+///     pub enum Suit { Clubs, Diamonds, Hearts, Spades }
+/// }
+///
+/// assert_eq!(Suit::DOMAIN, 4);
+/// let mut hand = vec![Suit::Spades, Suit::Clubs, Suit::Hearts];
+/// sort_ordinal(&mut hand);
+/// assert_eq!(hand, [Suit::Clubs, Suit::Hearts, Suit::Spades]);
+/// ```
+#[macro_export]
+macro_rules! ordinal_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$variant_meta])* $variant),*
+        }
+
+        impl $crate::OrdinalKey for $name {
+            const DOMAIN: usize = [$(stringify!($variant)),*].len();
+
+            fn ordinal(&self) -> usize {
+                match self {
+                    $($name::$variant => $name::$variant as usize),*
+                }
+            }
+        }
+    };
+}
+
+impl OrdinalKey for bool {
+    const DOMAIN: usize = 2;
+
+    fn ordinal(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl OrdinalKey for u8 {
+    const DOMAIN: usize = 1 << 8;
+
+    fn ordinal(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl OrdinalKey for u16 {
+    const DOMAIN: usize = 1 << 16;
+
+    fn ordinal(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl OrdinalKey for Ordering {
+    const DOMAIN: usize = 3;
+
+    fn ordinal(&self) -> usize {
+        (*self as i8 + 1) as usize
+    }
+}
+
+/// `None` first, as `Option`'s own order has it.
+impl<T: OrdinalKey> OrdinalKey for Option<T> {
+    const DOMAIN: usize = T::DOMAIN + 1;
+
+    fn ordinal(&self) -> usize {
+        self.as_ref().map_or(0, |x| x.ordinal() + 1)
+    }
+}
+
+/// Sorts values by their ordinals with a counting sort, in O(n + DOMAIN). Stable.
+///
+/// A `DOMAIN` larger than [`MAX_ORDINAL_DOMAIN`] fails to compile:
+///
+/// ```compile_fail
+/// struct Wide(u32);
+///
+/// impl pangua::OrdinalKey for Wide {
+///     const DOMAIN: usize = 1 << 32;
+///
+///     fn ordinal(&self) -> usize {
+///         self.0 as usize
+///     }
+/// }
+///
+/// pangua::sort_ordinal(&mut [Wide(7), Wide(3)]);
+/// ```
+pub fn sort_ordinal<T: OrdinalKey>(slice: &mut [T]) {
+    const { assert!(T::DOMAIN <= MAX_ORDINAL_DOMAIN, "ordinal domain too large") };
+    CountingSort.sort_by_key(slice, |x| in_domain(x.ordinal(), T::DOMAIN))
+}
+
+/// Sorts elements by the ordinal of the key `key` extracts, as [`sort_ordinal`] does.
+pub fn sort_by_ordinal_key<T, K, F>(slice: &mut [T], key: F)
+where
+    K: OrdinalKey,
+    F: Fn(&T) -> K,
+{
+    const { assert!(K::DOMAIN <= MAX_ORDINAL_DOMAIN, "ordinal domain too large") };
+    CountingSort.sort_by_key(slice, |x| in_domain(key(x).ordinal(), K::DOMAIN))
+}
+
+#[inline]
+fn in_domain(ordinal: usize, domain: usize) -> usize {
+    debug_assert!(ordinal < domain, "ordinal {} out of domain", ordinal);
+    ordinal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    ordinal_enum! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+        enum Level {
+            Low,
+            /// Not too much.
+            Medium,
+            High,
+        }
+    }
+
+    #[test]
+    fn ordinal_sort_works() {
+        assert_eq!(Level::DOMAIN, 3);
+        assert_eq!(Level::High.ordinal(), 2);
+        let mut tings = vec![Level::High, Level::Low, Level::Medium, Level::Low];
+        let mut expected = tings.clone();
+        expected.sort();
+        sort_ordinal(&mut tings);
+        assert_eq!(tings, expected);
+    }
+
+    #[test]
+    fn ordinals_follow_ord() {
+        let mut tings = vec![Some(Ordering::Greater), None, Some(Ordering::Less)];
+        assert_eq!(<Option<Ordering>>::DOMAIN, 4);
+        sort_ordinal(&mut tings);
+        assert_eq!(tings, [None, Some(Ordering::Less), Some(Ordering::Greater)]);
+        let mut tings = vec![(true, 'a'), (false, 'b'), (true, 'c'), (false, 'd')];
+        sort_by_ordinal_key(&mut tings, |t| t.0);
+        assert_eq!(
+            tings,
+            [(false, 'b'), (false, 'd'), (true, 'a'), (true, 'c')]
+        );
+    }
+}
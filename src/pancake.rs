@@ -0,0 +1,127 @@
+#[cfg(feature = "alloc")]
+use crate::cmp::ByOrd;
+use crate::cmp::Compare;
+use crate::{is_trivially_sorted, Complexity, Sorter, SorterInfo};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Pancake Sort
+///
+/// Pancake sorting rearranges the slice using nothing but prefix reversals, like a cook
+/// ordering a stack of pancakes by size with a spatula. Each round flips the largest
+/// unsorted element to the front, then flips the whole unsorted prefix to send it to
+/// the back, for at most 2n - 3 flips overall. Use [`pancake_flips`] to get the flips.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PancakeSort;
+
+impl Sorter for PancakeSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "pancake",
+            stable: false,
+            in_place: true,
+            best: Complexity::Quadratic,
+            average: Complexity::Quadratic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        pancake(slice, cmp, |_| {})
+    }
+}
+
+/// Sorts the slice with [`PancakeSort`], returning the flips performed in order.
+///
+/// A flip `k` reverses `slice[..=k]`; replaying the flips on the original slice sorts it.
+///
+/// ```
+/// use pangua::pancake_flips;
+///
+/// let mut stack = [2, 3, 1];
+/// assert_eq!(pancake_flips(&mut stack), &[1, 2]);
+/// assert_eq!(stack, [1, 2, 3]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn pancake_flips<T: Ord>(slice: &mut [T]) -> Vec<usize> {
+    let mut flips = Vec::new();
+    if !is_trivially_sorted(slice) {
+        pancake(slice, &mut ByOrd, |k| flips.push(k));
+    }
+    flips
+}
+
+fn pancake<T, C, F>(slice: &mut [T], cmp: &mut C, mut on_flip: F)
+where
+    C: Compare<T>,
+    F: FnMut(usize),
+{
+    let mut flip = |slice: &mut [T], cmp: &mut C, k: usize| {
+        let prefix = &mut slice[..=k];
+        prefix.reverse();
+        cmp.on_move(prefix);
+        on_flip(k);
+    };
+    for len in (2..=slice.len()).rev() {
+        let mut max = 0;
+        for i in 1..len {
+            if !cmp.is_less(&slice[i], &slice[max]) {
+                max = i;
+            }
+        }
+        if max == len - 1 {
+            continue;
+        }
+        if max > 0 {
+            flip(slice, cmp, max);
+        }
+        flip(slice, cmp, len - 1);
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn pancake_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        PancakeSort.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for n in 0..60u64 {
+            let mut tings: Vec<_> = (0..n).map(|i| (i * 7919) % 13).collect();
+            PancakeSort.sort(&mut tings);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn flips_replay_to_sorted() {
+        for n in 0..40u64 {
+            let original: Vec<_> = (0..n).map(|i| (i * 7919) % 17).collect();
+            let mut tings = original.clone();
+            let flips = pancake_flips(&mut tings);
+            assert!(flips.len() <= (2 * n as usize).saturating_sub(3));
+            let mut replay = original;
+            for k in flips {
+                replay[..=k].reverse();
+            }
+            assert_eq!(replay, tings);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn sorted_stack_needs_no_flips() {
+        assert!(pancake_flips(&mut [1, 2, 2, 3]).is_empty());
+        assert!(pancake_flips::<u8>(&mut []).is_empty());
+        assert_eq!(pancake_flips(&mut [3, 2, 1]), &[2]);
+    }
+}
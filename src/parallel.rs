@@ -0,0 +1,700 @@
+//! Sorts splitting their work across threads.
+
+#[cfg(feature = "rayon")]
+use crate::cmp::ByFn;
+use crate::counting::counting_order;
+#[cfg(feature = "rayon")]
+use crate::partition::{Hoare, Partitioner};
+use crate::permutation;
+#[cfg(feature = "rayon")]
+use crate::quick;
+use crate::rng::Rng;
+use crate::{
+    is_trivially_sorted, Complexity, IntroSort, RadixKey, RadixSort, Sorter, SorterInfo,
+    TuningProfile,
+};
+#[cfg(feature = "rayon")]
+use crate::{MergeSort, QuickSort};
+use std::cmp::Ordering;
+use std::mem;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+/// Below this many elements, a part is sorted on the thread that has it.
+pub(crate) const SEQUENTIAL_CUTOFF: usize = 1 << 13;
+
+/// Sample elements drawn per bucket by default in [`SampleSort`].
+const OVERSAMPLING: usize = 32;
+
+/// Bytes staged per bucket by default in [`ParallelRadixSort`], four cache lines.
+pub(crate) const WRITE_BUFFER_BYTES: usize = 256;
+
+pub(crate) fn threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Levels of splitting that may still spawn a thread, enough to give every available
+/// core a part plus some slack for uneven splits.
+#[cfg(feature = "rayon")]
+fn spawn_depth() -> usize {
+    let threads = threads();
+    if threads == 1 {
+        return 0;
+    }
+    (usize::BITS - (threads - 1).leading_zeros()) as usize + 2
+}
+
+/// Parallel Merge Sort
+///
+/// Top-down merge sort whose two halves are sorted at the same time, one on a new thread
+/// and one on the current thread, down to parts of `sequential_cutoff` elements or until
+/// every core is busy, where [`MergeSort`] takes over. Merges run on the thread that
+/// sorted the halves, so the last one is sequential. Stable.
+///
+/// Comparators must be shareable across threads, so this sorter is used through its own
+/// methods rather than [`Sorter`]. It needs the `rayon` feature, although it forks on
+/// scoped threads of its own rather than on rayon's pool.
+#[cfg(feature = "rayon")]
+#[derive(Debug, Clone, Copy)]
+pub struct ParallelMergeSort {
+    pub sequential_cutoff: usize,
+}
+
+#[cfg(feature = "rayon")]
+impl Default for ParallelMergeSort {
+    fn default() -> Self {
+        ParallelMergeSort {
+            sequential_cutoff: SEQUENTIAL_CUTOFF,
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl ParallelMergeSort {
+    pub fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "merge-parallel",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    pub fn sort<T: Ord + Send>(&self, slice: &mut [T]) {
+        self.sort_by(slice, T::cmp)
+    }
+
+    pub fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        self.merge_sort(slice, spawn_depth(), &compare)
+    }
+
+    pub fn sort_by_key<T, K, F>(&self, slice: &mut [T], key: F)
+    where
+        T: Send,
+        K: Ord,
+        F: Fn(&T) -> K + Sync,
+    {
+        self.sort_by(slice, |a, b| key(a).cmp(&key(b)))
+    }
+
+    fn merge_sort<T, F>(&self, slice: &mut [T], depth: usize, compare: &F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        if depth == 0 || slice.len() <= self.sequential_cutoff.max(1) {
+            MergeSort::default().sort_by(slice, compare);
+            return;
+        }
+        let mid = slice.len() / 2;
+        let (left, right) = slice.split_at_mut(mid);
+        thread::scope(|scope| {
+            scope.spawn(|| self.merge_sort(left, depth - 1, compare));
+            self.merge_sort(right, depth - 1, compare);
+        });
+        let mut scratch = Vec::with_capacity(slice.len());
+        MergeSort::merge_buffered(slice, mid, &mut scratch, &mut ByFn(compare));
+    }
+}
+
+/// Parallel Quick Sort
+///
+/// Quicksort whose two partitions are sorted at the same time, one on a new thread and
+/// one on the current thread, down to partitions of `sequential_cutoff` elements or
+/// until every core is busy, where [`QuickSort`] takes over. Pivots are medians of three
+/// and partitioning follows Hoare's scheme, on the thread that owns the partition, so
+/// the first one is sequential. Not stable.
+///
+/// Comparators must be shareable across threads, so this sorter is used through its own
+/// methods rather than [`Sorter`]. It needs the `rayon` feature, although it forks on
+/// scoped threads of its own rather than on rayon's pool.
+#[cfg(feature = "rayon")]
+#[derive(Debug, Clone, Copy)]
+pub struct ParallelQuickSort {
+    pub sequential_cutoff: usize,
+}
+
+#[cfg(feature = "rayon")]
+impl Default for ParallelQuickSort {
+    fn default() -> Self {
+        ParallelQuickSort {
+            sequential_cutoff: SEQUENTIAL_CUTOFF,
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl ParallelQuickSort {
+    pub fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "quick-parallel",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linearithmic,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    pub fn sort<T: Ord + Send>(&self, slice: &mut [T]) {
+        self.sort_by(slice, T::cmp)
+    }
+
+    pub fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        self.quicksort(slice, spawn_depth(), &compare)
+    }
+
+    pub fn sort_by_key<T, K, F>(&self, slice: &mut [T], key: F)
+    where
+        T: Send,
+        K: Ord,
+        F: Fn(&T) -> K + Sync,
+    {
+        self.sort_by(slice, |a, b| key(a).cmp(&key(b)))
+    }
+
+    fn quicksort<T, F>(&self, slice: &mut [T], depth: usize, compare: &F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        if depth == 0 || slice.len() <= self.sequential_cutoff.max(2) {
+            QuickSort::default().sort_by(slice, compare);
+            return;
+        }
+        let mut cmp = ByFn(compare);
+        quick::median_of_three(slice, &mut cmp);
+        let equal = Hoare.partition(slice, &mut cmp);
+        let (left, right) = slice.split_at_mut(equal.start);
+        let right = &mut right[equal.len()..];
+        thread::scope(|scope| {
+            scope.spawn(|| self.quicksort(left, depth - 1, compare));
+            self.quicksort(right, depth - 1, compare);
+        });
+    }
+}
+
+/// Sample Sort
+///
+/// Sample sort draws a random sample of `oversampling` elements per bucket, sorts it, and
+/// picks evenly spaced splitters from it. Every element is then classified against the
+/// splitters, in parallel, and the slice is rearranged bucket by bucket. The buckets lie
+/// between splitters, except that elements equal to a splitter get a bucket of their
+/// own, which needs no sorting. Finally the buckets are sorted with [`IntroSort`] by a
+/// pool of threads, largest first.
+///
+/// Only the rearrangement is sequential, and unlike quicksort's pivots the splitters
+/// come from a large sample, so the buckets stay balanced on skewed and duplicate-heavy
+/// data. `buckets` defaults to four per core, and slices of at most `sequential_cutoff`
+/// elements are sorted on the calling thread. Not stable.
+///
+/// Comparators must be shareable across threads, so this sorter is used through its own
+/// methods rather than [`Sorter`].
+#[derive(Debug, Clone, Copy)]
+pub struct SampleSort {
+    /// Buckets between splitters, or 0 for four per core.
+    pub buckets: usize,
+    pub oversampling: usize,
+    pub sequential_cutoff: usize,
+}
+
+impl Default for SampleSort {
+    fn default() -> Self {
+        SampleSort {
+            buckets: 0,
+            oversampling: OVERSAMPLING,
+            sequential_cutoff: SEQUENTIAL_CUTOFF,
+        }
+    }
+}
+
+impl SampleSort {
+    pub fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "sample",
+            stable: false,
+            in_place: false,
+            best: Complexity::Linearithmic,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    pub fn sort<T: Ord + Send + Sync>(&self, slice: &mut [T]) {
+        self.sort_by(slice, T::cmp)
+    }
+
+    pub fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        T: Send + Sync,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let threads = threads();
+        let buckets = match self.buckets {
+            0 => 4 * threads,
+            buckets => buckets,
+        };
+        let oversampling = self.oversampling.max(1);
+        if buckets < 2 || slice.len() <= self.sequential_cutoff.max(buckets * oversampling) {
+            IntroSort::default().sort_by(slice, compare);
+            return;
+        }
+        let ids = classify(slice, buckets, oversampling, threads, &compare);
+        let mut counts = vec![0; 2 * buckets - 1];
+        for &id in &ids {
+            counts[id] += 1;
+        }
+        permutation::apply(slice, &mut counting_order(&ids));
+
+        // buckets of elements equal to a splitter have odd ids and are already sorted
+        let mut work = Vec::new();
+        let mut rest = slice;
+        for (id, &count) in counts.iter().enumerate() {
+            let (bucket, tail) = mem::take(&mut rest).split_at_mut(count);
+            rest = tail;
+            if id % 2 == 0 && count > 1 {
+                work.push(bucket);
+            }
+        }
+        work.sort_by_key(|bucket| bucket.len());
+        let work = Mutex::new(work);
+        let compare = &compare;
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| loop {
+                    let bucket = work.lock().unwrap_or_else(PoisonError::into_inner).pop();
+                    match bucket {
+                        Some(bucket) => IntroSort::default().sort_by(bucket, compare),
+                        None => break,
+                    }
+                });
+            }
+        });
+    }
+
+    pub fn sort_by_key<T, K, F>(&self, slice: &mut [T], key: F)
+    where
+        T: Send + Sync,
+        K: Ord,
+        F: Fn(&T) -> K + Sync,
+    {
+        self.sort_by(slice, |a, b| key(a).cmp(&key(b)))
+    }
+}
+
+/// The bucket of every element: `2 * i` between splitters `i - 1` and `i`, `2 * i + 1`
+/// equal to splitter `i`.
+fn classify<T, F>(
+    slice: &[T],
+    buckets: usize,
+    oversampling: usize,
+    threads: usize,
+    compare: &F,
+) -> Vec<usize>
+where
+    T: Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let mut rng = Rng::new(slice.len() as u64);
+    let mut sample: Vec<&T> = (0..buckets * oversampling)
+        .map(|_| &slice[rng.below(slice.len())])
+        .collect();
+    IntroSort::default().sort_by(&mut sample, |a, b| compare(a, b));
+    let splitters: Vec<&T> = (1..buckets).map(|i| sample[i * oversampling]).collect();
+    let bucket_of = |x: &T| {
+        let i = splitters.partition_point(|s| compare(s, x) == Ordering::Less);
+        match splitters.get(i) {
+            Some(s) if compare(x, s) == Ordering::Equal => 2 * i + 1,
+            _ => 2 * i,
+        }
+    };
+
+    let mut ids = vec![0; slice.len()];
+    let chunk = slice.len().div_ceil(threads);
+    let bucket_of = &bucket_of;
+    thread::scope(|scope| {
+        for (part, ids) in slice.chunks(chunk).zip(ids.chunks_mut(chunk)) {
+            scope.spawn(move || {
+                for (x, id) in part.iter().zip(ids) {
+                    *id = bucket_of(x);
+                }
+            });
+        }
+    });
+    ids
+}
+
+/// Parallel Radix Sort
+///
+/// LSD radix sort, as [`RadixSort`], with every pass spread over the cores. The slice is
+/// cut into a part per thread and each thread counts the digits of its part; from the
+/// counts, every thread gets a region of each bucket of the output, after the regions
+/// of the threads before it so that the sort stays stable, and scatters its part into
+/// its regions.
+///
+/// Scattering writes to as many places as there are buckets at once, which defeats the
+/// cache, and where the regions of two threads meet, both cores keep taking the cache
+/// line from each other. So each thread stages its writes in a buffer of
+/// `write_buffer_bytes` per bucket and copies a buffer out only once it is full, writing
+/// the output whole cache lines at a time. The best size depends on the machine:
+/// [`ParallelRadixSort::tuned`] takes it from a [`TuningProfile`].
+///
+/// Slices of at most `sequential_cutoff` elements are sorted by [`RadixSort`] on the
+/// calling thread.
+///
+/// Keys must be sent across threads, so this sorter is used through its own methods
+/// rather than [`RadixSorter`](crate::RadixSorter).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParallelRadixSort {
+    /// Width of the digit handled in each pass, capped at 16 bits.
+    pub radix_bits: u32,
+    /// Size of the staging buffer of each bucket, at least one element.
+    pub write_buffer_bytes: usize,
+    pub sequential_cutoff: usize,
+}
+
+impl Default for ParallelRadixSort {
+    fn default() -> Self {
+        ParallelRadixSort {
+            radix_bits: 8,
+            write_buffer_bytes: WRITE_BUFFER_BYTES,
+            sequential_cutoff: SEQUENTIAL_CUTOFF,
+        }
+    }
+}
+
+impl ParallelRadixSort {
+    /// The default sorter, with its write buffers sized by `profile`.
+    pub fn tuned(profile: &TuningProfile) -> Self {
+        ParallelRadixSort {
+            write_buffer_bytes: profile.radix_write_buffer_bytes,
+            ..ParallelRadixSort::default()
+        }
+    }
+
+    pub fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "radix-parallel",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linear,
+            worst: Complexity::Linear,
+        }
+    }
+
+    pub fn sort<T: RadixKey + Send + Sync>(&self, slice: &mut [T]) {
+        if slice.len() < 2 {
+            return;
+        }
+        self.lsd(slice, |&x| x)
+    }
+
+    /// Sorts arbitrary elements by a radix key extracted once per element.
+    pub fn sort_by_key<T, K, F>(&self, slice: &mut [T], key: F)
+    where
+        K: RadixKey + Send + Sync,
+        F: FnMut(&T) -> K,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut pairs: Vec<(K, usize)> = slice.iter().map(key).zip(0..).collect();
+        self.lsd(&mut pairs, |p| p.0);
+        let mut order: Vec<usize> = pairs.into_iter().map(|p| p.1).collect();
+        permutation::apply(slice, &mut order);
+    }
+
+    fn lsd<E, K, F>(&self, items: &mut [E], key: F)
+    where
+        E: Copy + Send + Sync,
+        K: RadixKey,
+        F: Fn(&E) -> K + Sync,
+    {
+        let sequential = RadixSort {
+            radix_bits: self.radix_bits,
+        };
+        let threads = threads();
+        if threads == 1 || items.len() <= self.sequential_cutoff.max(1) {
+            return sequential.lsd(items, key);
+        }
+        if let Err(e) = sequential.validate() {
+            panic!("{}", e)
+        }
+        let bits = self.radix_bits.min(16);
+        let buckets = 1usize << bits;
+        let mask = (buckets - 1) as u128;
+        let part = items.len().div_ceil(threads);
+        let stage_len = (self.write_buffer_bytes / mem::size_of::<E>().max(1)).max(1);
+        let key = &key;
+        let mut scratch = items.to_vec();
+        let (mut src, mut dst) = (items, &mut scratch[..]);
+        let mut in_scratch = false;
+
+        let mut shift = 0;
+        while shift < K::BITS {
+            let digit = move |e: &E| ((key(e).to_radix() >> shift) & mask) as usize;
+            shift += bits;
+            let counts: Vec<Vec<usize>> = thread::scope(|scope| {
+                let counting: Vec<_> = src
+                    .chunks(part)
+                    .map(|chunk| {
+                        scope.spawn(move || {
+                            let mut counts = vec![0; buckets];
+                            chunk.iter().for_each(|e| counts[digit(e)] += 1);
+                            counts
+                        })
+                    })
+                    .collect();
+                counting.into_iter().map(|t| t.join().unwrap()).collect()
+            });
+            // every key shares this digit, nothing to reorder
+            if (0..buckets).any(|b| counts.iter().map(|c| c[b]).sum::<usize>() == src.len()) {
+                continue;
+            }
+            // bucket by bucket, a region for each part in turn
+            let mut regions: Vec<Vec<&mut [E]>> =
+                counts.iter().map(|_| Vec::with_capacity(buckets)).collect();
+            let mut rest = &mut *dst;
+            for b in 0..buckets {
+                for (regions, counts) in regions.iter_mut().zip(&counts) {
+                    let (region, tail) = mem::take(&mut rest).split_at_mut(counts[b]);
+                    regions.push(region);
+                    rest = tail;
+                }
+            }
+            thread::scope(|scope| {
+                for (chunk, regions) in src.chunks(part).zip(regions) {
+                    scope.spawn(move || scatter(chunk, regions, stage_len, digit));
+                }
+            });
+            mem::swap(&mut src, &mut dst);
+            in_scratch = !in_scratch;
+        }
+        if in_scratch {
+            dst.copy_from_slice(src);
+        }
+    }
+}
+
+/// Moves every element of `part` to the next free slot of the region of its digit,
+/// staging up to `stage_len` elements per region and writing them out together.
+fn scatter<E: Copy>(
+    part: &[E],
+    mut regions: Vec<&mut [E]>,
+    stage_len: usize,
+    digit: impl Fn(&E) -> usize,
+) {
+    let first = match part.first() {
+        Some(&first) => first,
+        None => return,
+    };
+    let mut stage = vec![first; regions.len() * stage_len];
+    let mut staged = vec![0; regions.len()];
+    let mut written = vec![0; regions.len()];
+    for e in part {
+        let d = digit(e);
+        let buffer = &mut stage[d * stage_len..(d + 1) * stage_len];
+        buffer[staged[d]] = *e;
+        staged[d] += 1;
+        if staged[d] == stage_len {
+            regions[d][written[d]..written[d] + stage_len].copy_from_slice(buffer);
+            written[d] += stage_len;
+            staged[d] = 0;
+        }
+    }
+    for (d, region) in regions.iter_mut().enumerate() {
+        let buffer = &stage[d * stage_len..d * stage_len + staged[d]];
+        region[written[d]..written[d] + staged[d]].copy_from_slice(buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shuffled(n: u64) -> Vec<u64> {
+        (0..n).map(|i| (i * 7919) % n).collect()
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn parallel_merge_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        ParallelMergeSort::default().sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        let sorter = ParallelMergeSort {
+            sequential_cutoff: 64,
+        };
+        let mut tings = shuffled(10_000);
+        sorter.sort(&mut tings);
+        assert_eq!(tings, (0..10_000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn parallel_merge_is_stable() {
+        let sorter = ParallelMergeSort {
+            sequential_cutoff: 16,
+        };
+        let mut tings: Vec<(u64, usize)> =
+            shuffled(5000).into_iter().map(|k| k % 7).zip(0..).collect();
+        sorter.sort_by_key(&mut tings, |t| t.0);
+        assert!(tings.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn parallel_quick_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        ParallelQuickSort::default().sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        let sorter = ParallelQuickSort {
+            sequential_cutoff: 64,
+        };
+        for mut tings in [
+            shuffled(10_000),
+            (0..10_000).collect(),
+            (0..10_000).rev().collect(),
+            vec![3; 10_000],
+        ] {
+            let mut expected = tings.clone();
+            expected.sort();
+            sorter.sort(&mut tings);
+            assert_eq!(tings, expected);
+        }
+        let mut tings = shuffled(1000);
+        sorter.sort_by(&mut tings, |a, b| b.cmp(a));
+        assert!(tings.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn sample_sort_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        SampleSort::default().sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        let sorter = SampleSort {
+            buckets: 8,
+            oversampling: 4,
+            sequential_cutoff: 0,
+        };
+        for mut tings in [
+            shuffled(10_000),
+            (0..10_000).rev().collect(),
+            vec![3; 10_000],
+            // skewed: most elements share a few keys
+            shuffled(10_000)
+                .into_iter()
+                .map(|x| x % 3 * x % 5)
+                .collect(),
+        ] {
+            let mut expected = tings.clone();
+            expected.sort();
+            sorter.sort(&mut tings);
+            assert_eq!(tings, expected);
+        }
+        let mut tings: Vec<(u64, u64)> = shuffled(5000).into_iter().map(|x| (x, x % 10)).collect();
+        sorter.sort_by_key(&mut tings, |t| t.1);
+        assert!(tings.windows(2).all(|w| w[0].1 <= w[1].1));
+    }
+
+    #[test]
+    fn splitters_get_buckets_of_their_own() {
+        let tings = vec![7u64; 1000];
+        let ids = classify(&tings, 4, 8, 2, &u64::cmp);
+        // every element equals the first splitter
+        assert!(ids.iter().all(|&id| id == 1));
+        let tings: Vec<u64> = (0..1000).map(|x| x / 10).collect();
+        let ids = classify(&tings, 4, 8, 2, &u64::cmp);
+        assert!(ids.windows(2).all(|w| w[0] <= w[1]));
+        assert!(ids.iter().all(|&id| id < 7));
+    }
+
+    #[test]
+    fn parallel_radix_works() {
+        let mut tings = vec![5u32, 1, 4, 2, 3];
+        ParallelRadixSort::default().sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        let values: Vec<i64> = shuffled(20_000)
+            .into_iter()
+            .map(|x| (x as i64 - 10_000) * 0x1_0000_0001)
+            .collect();
+        let mut expected = values.clone();
+        expected.sort();
+        for &(radix_bits, write_buffer_bytes) in &[(8, 256), (4, 1), (11, 64), (16, 4096)] {
+            let sorter = ParallelRadixSort {
+                radix_bits,
+                write_buffer_bytes,
+                sequential_cutoff: 0,
+            };
+            let mut tings = values.clone();
+            sorter.sort(&mut tings);
+            assert_eq!(
+                tings, expected,
+                "{} bits, {} bytes",
+                radix_bits, write_buffer_bytes
+            );
+        }
+    }
+
+    #[test]
+    fn parallel_radix_is_stable() {
+        let profile = TuningProfile {
+            radix_write_buffer_bytes: 32,
+            ..TuningProfile::default()
+        };
+        let sorter = ParallelRadixSort {
+            sequential_cutoff: 100,
+            ..ParallelRadixSort::tuned(&profile)
+        };
+        assert_eq!(sorter.write_buffer_bytes, 32);
+        let mut tings: Vec<(u16, usize)> = shuffled(30_000)
+            .into_iter()
+            .map(|k| (k % 300) as u16)
+            .zip(0..)
+            .collect();
+        let mut expected = tings.clone();
+        expected.sort_by_key(|t| t.0);
+        sorter.sort_by_key(&mut tings, |t| t.0);
+        assert_eq!(tings, expected);
+    }
+}
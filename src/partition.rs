@@ -0,0 +1,270 @@
+//! Partitioning: the schemes behind quicksort-style sorts, and partitioning by a
+//! predicate or around a value for use on its own.
+//!
+//! [`QuickSort`](crate::QuickSort) and [`IntroSort`](crate::IntroSort) pick a pivot, move
+//! it to the front of the slice, and hand the slice to a [`Partitioner`]. Implementing
+//! the trait plugs a new scheme into their pivot selection, recursion and instrumentation.
+//!
+//! ```
+//! use pangua::partition::Lomuto;
+//! use pangua::*;
+//!
+//! let mut tings = vec![5, 1, 4, 2, 3];
+//! QuickSort::default().with_partitioner(Lomuto).sort(&mut tings);
+//! assert_eq!(tings, &[1, 2, 3, 4, 5]);
+//! ```
+//!
+//! [`partition`] and [`stable_partition`] move the elements satisfying a predicate to
+//! the front; [`partition3`] splits a slice into the elements less than, equal to and
+//! greater than a value.
+//!
+//! ```
+//! use pangua::partition::{partition3, stable_partition};
+//!
+//! let mut tings = [1, 8, 3, 6, 5, 4];
+//! assert_eq!(stable_partition(&mut tings, |x| x % 2 == 0), 3);
+//! assert_eq!(tings, [8, 6, 4, 1, 3, 5]);
+//! assert_eq!(partition3(&mut tings, &4), 2..3);
+//! assert!(tings[..2].iter().all(|&x| x < 4) && tings[3..].iter().all(|&x| x > 4));
+//! ```
+
+use crate::cmp::{self, Compare};
+use crate::quick;
+use core::cmp::Ordering;
+use core::ops::Range;
+
+/// A scheme for partitioning a slice around a pivot.
+pub trait Partitioner {
+    /// Partitions a slice of at least two elements around its first element, the pivot.
+    ///
+    /// Returns the range holding the pivot and any elements equal to it that the scheme
+    /// gathered alongside. Elements before the range must not be greater than the pivot,
+    /// elements after it must not be less, and elements within it must equal it, so the
+    /// range is already in its sorted place and only the sides are left to sort.
+    ///
+    /// Implementations should report the pivot through [`Compare::on_pivot`] and the
+    /// split through [`Compare::on_partition`], for tracing.
+    fn partition<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) -> Range<usize>;
+}
+
+/// Hoare's scheme: scan inwards from both ends, swapping pairs on the wrong sides.
+///
+/// It swaps only elements that are out of place, so it moves the fewest elements of the
+/// built-in schemes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hoare;
+
+impl Partitioner for Hoare {
+    fn partition<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) -> Range<usize> {
+        let mid = quick::partition(slice, cmp);
+        mid..mid + 1
+    }
+}
+
+/// Lomuto's scheme: a single left-to-right scan growing a prefix of smaller elements.
+///
+/// Simpler than Hoare's, but it swaps every smaller element into the prefix, even those
+/// already there.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lomuto;
+
+impl Partitioner for Lomuto {
+    fn partition<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) -> Range<usize> {
+        cmp.on_pivot(&slice[0]);
+        // slice[1..=last] holds the elements found smaller than the pivot
+        let mut last = 0;
+        for i in 1..slice.len() {
+            if cmp.is_less(&slice[i], &slice[0]) {
+                last += 1;
+                if last != i {
+                    cmp::swap(cmp, slice, last, i);
+                }
+            }
+        }
+        if last != 0 {
+            cmp::swap(cmp, slice, 0, last);
+        }
+        cmp.on_partition(slice, last);
+        last..last + 1
+    }
+}
+
+/// Dijkstra's three-way scheme, solving the Dutch national flag problem: less than,
+/// equal to, and greater than the pivot.
+///
+/// Every element equal to the pivot lands in the returned range, so runs of equal keys
+/// are finished in one pass and sorts with few distinct keys become linear.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreeWay;
+
+impl Partitioner for ThreeWay {
+    fn partition<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) -> Range<usize> {
+        cmp.on_pivot(&slice[0]);
+        // [ less | equal | unknown | greater ]
+        // slice[lt] is always equal to the pivot, so it stands in for it
+        let (mut lt, mut i, mut gt) = (0, 1, slice.len());
+        while i < gt {
+            match cmp.compare(&slice[i], &slice[lt]) {
+                Ordering::Less => {
+                    cmp::swap(cmp, slice, lt, i);
+                    lt += 1;
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    gt -= 1;
+                    cmp::swap(cmp, slice, i, gt);
+                }
+                Ordering::Equal => i += 1,
+            }
+        }
+        cmp.on_partition(slice, lt);
+        lt..gt
+    }
+}
+
+/// Moves the elements satisfying `pred` before those that do not, and returns how many
+/// satisfy it. Neither group keeps its order; `pred` is called once per element.
+pub fn partition<T, F>(slice: &mut [T], mut pred: F) -> usize
+where
+    F: FnMut(&T) -> bool,
+{
+    let (mut left, mut right) = (0, slice.len());
+    loop {
+        while left < right && pred(&slice[left]) {
+            left += 1;
+        }
+        while left < right && !pred(&slice[right - 1]) {
+            right -= 1;
+        }
+        if left == right {
+            return left;
+        }
+        // slice[left] fails and slice[right - 1] satisfies the predicate
+        slice.swap(left, right - 1);
+        left += 1;
+        right -= 1;
+    }
+}
+
+/// Like [`partition`], but both groups keep their order.
+///
+/// It needs no extra memory: the halves are partitioned recursively and put together
+/// with a rotation, taking O(n log n) moves and O(log n) stack.
+pub fn stable_partition<T, F>(slice: &mut [T], mut pred: F) -> usize
+where
+    F: FnMut(&T) -> bool,
+{
+    stable_partition_rec(slice, &mut pred)
+}
+
+fn stable_partition_rec<T, F>(slice: &mut [T], pred: &mut F) -> usize
+where
+    F: FnMut(&T) -> bool,
+{
+    match slice.len() {
+        0 => 0,
+        1 => pred(&slice[0]) as usize,
+        len => {
+            let mid = len / 2;
+            let left = stable_partition_rec(&mut slice[..mid], pred);
+            let right = stable_partition_rec(&mut slice[mid..], pred);
+            // [ left yes | left no | right yes | right no ]
+            slice[left..mid + right].rotate_left(mid - left);
+            left + right
+        }
+    }
+}
+
+/// Partitions the slice into the elements less than `pivot`, those equal to it and
+/// those greater, in that order, and returns the range of the equal ones.
+pub fn partition3<T: Ord>(slice: &mut [T], pivot: &T) -> Range<usize> {
+    partition3_by(slice, |x| x.cmp(pivot))
+}
+
+/// Like [`partition3`], with `f` giving how each element orders against the pivot.
+pub fn partition3_by<T, F>(slice: &mut [T], mut f: F) -> Range<usize>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    // [ less | equal | unknown | greater ]
+    let (mut lt, mut i, mut gt) = (0, 0, slice.len());
+    while i < gt {
+        match f(&slice[i]) {
+            Ordering::Less => {
+                slice.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                slice.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    lt..gt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmp::ByOrd;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn check<P: Partitioner>(partitioner: P) {
+        for n in 2..60u64 {
+            let mut tings: Vec<_> = (0..n).map(|i| (i * 7919) % 7).collect();
+            let pivot = tings[0];
+            let range = partitioner.partition(&mut tings, &mut ByOrd);
+            assert!(!range.is_empty() && range.end <= tings.len());
+            assert!(tings[..range.start].iter().all(|&x| x <= pivot));
+            assert!(tings[range.clone()].iter().all(|&x| x == pivot));
+            assert!(tings[range.end..].iter().all(|&x| x >= pivot));
+        }
+    }
+
+    #[test]
+    fn built_in_schemes_partition() {
+        check(Hoare);
+        check(Lomuto);
+        check(ThreeWay);
+    }
+
+    #[test]
+    fn partitions_by_predicate_and_value() {
+        for n in 0..60u64 {
+            let tings: Vec<_> = (0..n).map(|i| ((i * 7919) % 7, i)).collect();
+            let odd = |t: &(u64, u64)| t.0 % 2 == 1;
+            let expected: Vec<_> = tings
+                .iter()
+                .filter(|t| odd(t))
+                .chain(tings.iter().filter(|t| !odd(t)))
+                .copied()
+                .collect();
+
+            let mut stable = tings.clone();
+            let mid = stable_partition(&mut stable, odd);
+            assert_eq!(stable, expected, "n = {}", n);
+
+            let mut unstable = tings.clone();
+            assert_eq!(partition(&mut unstable, odd), mid);
+            assert!(unstable[..mid].iter().all(odd) && !unstable[mid..].iter().any(odd));
+
+            let mut three = tings;
+            let range = partition3_by(&mut three, |t| t.0.cmp(&3));
+            assert!(three[..range.start].iter().all(|t| t.0 < 3));
+            assert!(three[range.clone()].iter().all(|t| t.0 == 3));
+            assert!(three[range.end..].iter().all(|t| t.0 > 3));
+        }
+        assert_eq!(partition3(&mut [1, 2, 3], &5), 3..3);
+    }
+
+    #[test]
+    fn three_way_gathers_every_equal_element() {
+        let mut tings = vec![2, 3, 2, 1, 2, 3, 1, 2];
+        let range = ThreeWay.partition(&mut tings, &mut ByOrd);
+        assert_eq!(range, 2..6);
+        assert_eq!(&tings[range], &[2, 2, 2, 2]);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::cmp::{self, ByFn, ByOrd, Compare};
+use crate::{
+    is_trivially_sorted, permutation, Complexity, LoserTree, Sorter, SorterInfo, StableSorter,
+};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Patience Sort
+///
+/// Patience sorting deals the elements onto piles like the card game: each element goes
+/// on the leftmost pile whose top is greater than it, or starts a new pile on the right.
+/// Every pile then reads in order from top to bottom, and the piles are merged with a
+/// [`LoserTree`]. Descending input lands on a single pile, for linear time, and an
+/// element only ever joins a pile to the right of its equals, so the sort is stable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PatienceSort;
+
+impl Sorter for PatienceSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "patience",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        // piles of indices, each pile's top being its last element
+        let mut piles: Vec<Vec<usize>> = Vec::new();
+        for i in 0..slice.len() {
+            let x = &slice[i];
+            let p = piles.partition_point(|pile| !cmp.is_less(x, &slice[pile[pile.len() - 1]]));
+            match piles.get_mut(p) {
+                Some(pile) => pile.push(i),
+                None => piles.push(vec![i]),
+            }
+        }
+        if piles.len() == slice.len() {
+            // every element started a pile: the input was already sorted
+            return;
+        }
+        let runs = piles.iter().map(|pile| pile.iter().rev().copied());
+        let mut order: Vec<usize> = LoserTree::new_by(runs, |&a: &usize, &b: &usize| {
+            cmp.compare(&slice[a], &slice[b])
+        })
+        .collect();
+        permutation::apply_by(&mut order, |a, b| cmp::swap(cmp, slice, a, b));
+    }
+}
+
+impl StableSorter for PatienceSort {}
+
+/// Finds a longest strictly increasing subsequence of the slice.
+///
+/// Returns the indices of its elements, in increasing order. This deals the elements
+/// onto piles as [`PatienceSort`] does, each element remembering the top of the pile
+/// to its left: there are as many piles as the subsequence is long, and following those
+/// links back from the last pile spells it out. Takes O(n log n) time.
+///
+/// ```
+/// use pangua::longest_increasing_subsequence;
+///
+/// let xs = [3, 1, 4, 1, 5, 9, 2, 6];
+/// assert_eq!(longest_increasing_subsequence(&xs), &[1, 2, 4, 7]);
+/// ```
+pub fn longest_increasing_subsequence<T: Ord>(slice: &[T]) -> Vec<usize> {
+    longest_increasing_subsequence_with(slice, &mut ByOrd)
+}
+
+/// Like [`longest_increasing_subsequence`], ordering elements with `compare`.
+pub fn longest_increasing_subsequence_by<T, F>(slice: &[T], compare: F) -> Vec<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    longest_increasing_subsequence_with(slice, &mut ByFn(compare))
+}
+
+fn longest_increasing_subsequence_with<T, C: Compare<T>>(slice: &[T], cmp: &mut C) -> Vec<usize> {
+    // the index on top of each pile, and for each index the top to its left when placed
+    let mut tops: Vec<usize> = Vec::new();
+    let mut prev: Vec<Option<usize>> = Vec::with_capacity(slice.len());
+    for (i, x) in slice.iter().enumerate() {
+        // an equal element does not extend a strictly increasing run, so it replaces
+        let p = tops.partition_point(|&t| cmp.is_less(&slice[t], x));
+        prev.push(p.checked_sub(1).map(|left| tops[left]));
+        match tops.get_mut(p) {
+            Some(top) => *top = i,
+            None => tops.push(i),
+        }
+    }
+    let mut lis = Vec::with_capacity(tops.len());
+    let mut cur = tops.last().copied();
+    while let Some(i) = cur {
+        lis.push(i);
+        cur = prev[i];
+    }
+    lis.reverse();
+    lis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counting;
+
+    #[test]
+    fn patience_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        PatienceSort.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for n in 0..100u64 {
+            let mut tings: Vec<_> = (0..n).map(|i| (i * 7919) % 23).collect();
+            PatienceSort.sort(&mut tings);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn patience_is_stable() {
+        let mut tings: Vec<_> = (0..200).map(|i| ((i * 37) % 11, i)).collect();
+        PatienceSort.sort_by_key(&mut tings, |t| t.0);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn descending_input_is_one_pile() {
+        let n = 1000;
+        let sorter = Counting::new(PatienceSort);
+        let mut tings: Vec<_> = (0..n).rev().collect();
+        sorter.sort(&mut tings);
+        assert_eq!(tings, (0..n).collect::<Vec<_>>());
+        // one comparison to deal each element, none to merge a single pile
+        assert!(sorter.stats().comparisons < n);
+
+        let sorter = Counting::new(PatienceSort);
+        sorter.sort(&mut tings);
+        assert_eq!(sorter.stats().swaps, 0);
+    }
+
+    #[test]
+    fn lis_works() {
+        assert!(longest_increasing_subsequence::<u8>(&[]).is_empty());
+        assert_eq!(longest_increasing_subsequence(&[7]), &[0]);
+        assert_eq!(longest_increasing_subsequence(&[2, 2, 2]).len(), 1);
+        assert_eq!(longest_increasing_subsequence(&[5, 4, 3]), &[2]);
+        assert_eq!(longest_increasing_subsequence(&[1, 2, 3]), &[0, 1, 2]);
+        for n in 0..60u64 {
+            let xs: Vec<_> = (0..n).map(|i| (i * 7919) % 17).collect();
+            let lis = longest_increasing_subsequence(&xs);
+            assert!(lis.windows(2).all(|w| w[0] < w[1] && xs[w[0]] < xs[w[1]]));
+            assert_eq!(lis.len(), lis_len_quadratic(&xs), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn lis_by_uses_the_given_order() {
+        let xs = [1, 5, 2, 4, 3];
+        assert_eq!(
+            longest_increasing_subsequence_by(&xs, |a, b| b.cmp(a)),
+            &[1, 3, 4]
+        );
+    }
+
+    fn lis_len_quadratic(xs: &[u64]) -> usize {
+        let mut best = vec![1; xs.len()];
+        for i in 0..xs.len() {
+            for j in 0..i {
+                if xs[j] < xs[i] {
+                    best[i] = best[i].max(best[j] + 1);
+                }
+            }
+        }
+        best.into_iter().max().unwrap_or(0)
+    }
+}
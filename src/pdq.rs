@@ -0,0 +1,489 @@
+use crate::cmp::{self, Compare};
+use crate::rng::Rng;
+use crate::{is_trivially_sorted, Complexity, HeapSort, SmallSortPolicy, Sorter, SorterInfo};
+use core::cmp::min;
+
+/// Partitions this small are finished with branchless insertion sort, unless overridden
+/// globally.
+const SMALL_SORT: SmallSortPolicy = SmallSortPolicy::Branchless { threshold: 20 };
+
+/// Slices at least this long pick their pivot as the median of three medians.
+const SHORTEST_NINTHER: usize = 50;
+
+/// Pivot selection taking this many swaps suggests the slice is descending.
+const MAX_PIVOT_SWAPS: usize = 4 * 3;
+
+/// Partial insertion sort gives up after fixing this many adjacent pairs.
+const MAX_INSERTION_STEPS: usize = 5;
+
+/// Partial insertion sort only fixes pairs in slices at least this long.
+const SHORTEST_SHIFTING: usize = 50;
+
+/// Elements classified at a time by the block partition; offsets within a block fit a
+/// `u8`.
+const BLOCK: usize = 128;
+
+/// Pattern-Defeating Quick Sort
+///
+/// Pdqsort, Orson Peters' refinement of introsort and the algorithm behind the
+/// standard library's unstable sort, keeps quicksort's speed on random input while
+/// defeating the patterns that hurt it:
+///
+/// - Pivots are the median of three, or of three medians of three on longer slices.
+///   Choosing one that needed no swaps hints the slice is sorted, which a partial
+///   insertion sort then checks and finishes in linear time; one that needed many hints
+///   it is descending, and the slice is reversed first.
+/// - Partitioning classifies a block of elements at a time before swapping, so the
+///   comparisons leave no branches to mispredict (BlockQuicksort).
+/// - A partition whose pivot is no greater than the previous one's is made of elements
+///   equal to it, which are gathered and never touched again, so few distinct values
+///   take linear time.
+/// - A badly unbalanced partition shuffles a few elements to break up the pattern that
+///   caused it, and after log n of them the slice is handed to heapsort, bounding the
+///   worst case at O(n log n).
+///
+/// Small partitions are finished with a branchless insertion sort.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PdqSort;
+
+impl Sorter for PdqSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "pdq",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let limit = (usize::BITS - slice.len().leading_zeros()) as usize;
+        pdqsort(
+            slice,
+            None,
+            limit,
+            SmallSortPolicy::resolve(SMALL_SORT),
+            cmp,
+        )
+    }
+}
+
+/// Sorts `slice`, whose elements all order no earlier than `pred`, the pivot of an
+/// enclosing partition, if there is one. Recurses into the smaller side of each
+/// partition and loops on the larger, as [`QuickSort`](crate::QuickSort) does.
+fn pdqsort<'a, T, C: Compare<T>>(
+    mut slice: &'a mut [T],
+    mut pred: Option<&'a T>,
+    mut limit: usize,
+    small: SmallSortPolicy,
+    cmp: &mut C,
+) {
+    let mut was_balanced = true;
+    let mut was_partitioned = true;
+    // levels descended by looping rather than recursing, left once the loop ends
+    let mut levels = 0;
+    loop {
+        let len = slice.len();
+        if small.sort_small(slice, cmp) || len < 2 {
+            break;
+        }
+        if limit == 0 {
+            cmp.on_fallback(HeapSort::default().info().name);
+            HeapSort::default().sort_with(slice, cmp);
+            break;
+        }
+        if !was_balanced {
+            break_patterns(slice, cmp);
+            limit -= 1;
+        }
+
+        let (pivot, likely_sorted) = choose_pivot(slice, cmp);
+        if was_balanced && was_partitioned && likely_sorted && partial_insertion_sort(slice, cmp) {
+            break;
+        }
+
+        // the pivot equals `pred`, the least any element can be: put everything equal
+        // to it in place, and carry on with the greater ones
+        if let Some(pred) = pred {
+            if !cmp.is_less(pred, &slice[pivot]) {
+                let mid = partition_equal(slice, pivot, cmp);
+                slice = &mut slice[mid..];
+                continue;
+            }
+        }
+
+        let (mid, partitioned) = partition(slice, pivot, cmp);
+        was_balanced = min(mid, len - mid) >= len / 8;
+        was_partitioned = partitioned;
+
+        let (left, right) = slice.split_at_mut(mid);
+        let (pivot, right) = right.split_first_mut().expect("the pivot is in the slice");
+        cmp.on_enter();
+        levels += 1;
+        if left.len() < right.len() {
+            pdqsort(left, pred, limit, small, cmp);
+            slice = right;
+            pred = Some(pivot);
+        } else {
+            pdqsort(right, Some(pivot), limit, small, cmp);
+            slice = left;
+        }
+    }
+    for _ in 0..levels {
+        cmp.on_leave();
+    }
+}
+
+/// Picks a pivot, returning its index and whether the slice is likely already sorted.
+/// A slice that looks descending is reversed first.
+fn choose_pivot<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) -> (usize, bool) {
+    let len = slice.len();
+    let (mut a, mut b, mut c) = (len / 4, len / 4 * 2, len / 4 * 3);
+    let mut swaps = 0;
+    if len >= 8 {
+        if len >= SHORTEST_NINTHER {
+            for i in [&mut a, &mut b, &mut c] {
+                let (mut lo, mut hi) = (*i - 1, *i + 1);
+                sort3(slice, &mut lo, i, &mut hi, &mut swaps, cmp);
+            }
+        }
+        sort3(slice, &mut a, &mut b, &mut c, &mut swaps, cmp);
+    }
+    if swaps < MAX_PIVOT_SWAPS {
+        (b, swaps == 0)
+    } else {
+        slice.reverse();
+        cmp.on_move(slice);
+        (len - 1 - b, true)
+    }
+}
+
+/// Orders the indices `a`, `b` and `c` by the elements they point at, counting the
+/// exchanges of indices in `swaps`. The elements stay where they are.
+fn sort3<T, C: Compare<T>>(
+    slice: &[T],
+    a: &mut usize,
+    b: &mut usize,
+    c: &mut usize,
+    swaps: &mut usize,
+    cmp: &mut C,
+) {
+    let mut sort2 = |x: &mut usize, y: &mut usize| {
+        if cmp.is_less(&slice[*y], &slice[*x]) {
+            core::mem::swap(x, y);
+            *swaps += 1;
+        }
+    };
+    sort2(a, b);
+    sort2(b, c);
+    sort2(a, b);
+}
+
+/// Sorts the slice if only a few adjacent pairs are out of order, returning whether it
+/// did. Gives up after [`MAX_INSERTION_STEPS`] of them, having moved a few elements
+/// closer to their places.
+fn partial_insertion_sort<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) -> bool {
+    let len = slice.len();
+    let mut i = 1;
+    for _ in 0..MAX_INSERTION_STEPS {
+        while i < len && !cmp.is_less(&slice[i], &slice[i - 1]) {
+            i += 1;
+        }
+        if i == len {
+            return true;
+        }
+        // shifting elements around on a short slice costs more than it saves
+        if len < SHORTEST_SHIFTING {
+            return false;
+        }
+        cmp::swap(cmp, slice, i - 1, i);
+        shift_tail(&mut slice[..i], cmp);
+        shift_head(&mut slice[i..], cmp);
+    }
+    false
+}
+
+/// Moves the last element back into place among the sorted ones before it.
+fn shift_tail<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) {
+    let last = slice.len() - 1;
+    let mut at = last;
+    while at > 0 && cmp.is_less(&slice[last], &slice[at - 1]) {
+        at -= 1;
+    }
+    if at < last {
+        cmp::rotate_right(cmp, &mut slice[at..], 1);
+    }
+}
+
+/// Moves the first element forward into place among the sorted ones after it.
+fn shift_head<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) {
+    let mut at = 0;
+    while at + 1 < slice.len() && cmp.is_less(&slice[at + 1], &slice[0]) {
+        at += 1;
+    }
+    if at > 0 {
+        let moved = &mut slice[..=at];
+        moved.rotate_left(1);
+        cmp.on_move(moved);
+    }
+}
+
+/// Swaps three elements around the middle with random others, to break up whatever
+/// pattern made the last partition unbalanced.
+fn break_patterns<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) {
+    let len = slice.len();
+    if len < 8 {
+        return;
+    }
+    let mut rng = Rng::new(len as u64);
+    let pos = len / 4 * 2;
+    for i in 0..3 {
+        let other = rng.below(len);
+        if other != pos - 1 + i {
+            cmp::swap(cmp, slice, pos - 1 + i, other);
+        }
+    }
+}
+
+/// Partitions the slice around the element at `pivot` into the elements less than it,
+/// the pivot, and the others, returning the pivot's new index and whether the slice
+/// was already partitioned.
+fn partition<T, C: Compare<T>>(slice: &mut [T], pivot: usize, cmp: &mut C) -> (usize, bool) {
+    if pivot != 0 {
+        cmp::swap(cmp, slice, 0, pivot);
+    }
+    cmp.on_pivot(&slice[0]);
+    let (mid, partitioned) = {
+        let (pivot, rest) = slice.split_first_mut().expect("the pivot is in the slice");
+        let pivot = &*pivot;
+        // skip the elements already on the correct sides
+        let (mut l, mut r) = (0, rest.len());
+        while l < r && cmp.is_less(&rest[l], pivot) {
+            l += 1;
+        }
+        while l < r && !cmp.is_less(&rest[r - 1], pivot) {
+            r -= 1;
+        }
+        (l + partition_in_blocks(&mut rest[l..r], pivot, cmp), l >= r)
+    };
+    if mid != 0 {
+        cmp::swap(cmp, slice, 0, mid);
+    }
+    cmp.on_partition(slice, mid);
+    (mid, partitioned)
+}
+
+/// BlockQuicksort's partition: moves the elements less than `pivot` to the front and
+/// returns how many there are.
+///
+/// A block of elements is classified at a time from each end, recording the offsets
+/// of those on the wrong side: the comparisons only decide whether an offset counts,
+/// never which code runs next. Then the misplaced elements of the two blocks are
+/// swapped pairwise.
+fn partition_in_blocks<T, C: Compare<T>>(slice: &mut [T], pivot: &T, cmp: &mut C) -> usize {
+    let (mut l, mut r) = (0, slice.len());
+    let (mut block_l, mut block_r) = (BLOCK, BLOCK);
+    // offsets from `l` of elements not less than the pivot, and from `r - 1` of
+    // elements less than it, of which start..end are yet to be swapped
+    let mut offsets_l = [0u8; BLOCK];
+    let mut offsets_r = [0u8; BLOCK];
+    let (mut start_l, mut end_l) = (0, 0);
+    let (mut start_r, mut end_r) = (0, 0);
+    loop {
+        let is_done = r - l <= 2 * BLOCK;
+        if is_done {
+            // the last blocks share what is left between them
+            let mut rem = r - l;
+            if start_l < end_l || start_r < end_r {
+                rem -= BLOCK;
+            }
+            if start_l < end_l {
+                block_r = rem;
+            } else if start_r < end_r {
+                block_l = rem;
+            } else {
+                block_l = rem / 2;
+                block_r = rem - block_l;
+            }
+        }
+        if start_l == end_l {
+            start_l = 0;
+            end_l = 0;
+            for i in 0..block_l {
+                offsets_l[end_l] = i as u8;
+                end_l += !cmp.is_less(&slice[l + i], pivot) as usize;
+            }
+        }
+        if start_r == end_r {
+            start_r = 0;
+            end_r = 0;
+            for i in 0..block_r {
+                offsets_r[end_r] = i as u8;
+                end_r += cmp.is_less(&slice[r - 1 - i], pivot) as usize;
+            }
+        }
+        let count = min(end_l - start_l, end_r - start_r);
+        for k in 0..count {
+            let a = l + offsets_l[start_l + k] as usize;
+            let b = r - 1 - offsets_r[start_r + k] as usize;
+            cmp::swap(cmp, slice, a, b);
+        }
+        start_l += count;
+        start_r += count;
+        if start_l == end_l {
+            l += block_l;
+        }
+        if start_r == end_r {
+            r -= block_r;
+        }
+        if is_done {
+            break;
+        }
+    }
+
+    // one block may have misplaced elements left, with only correctly placed ones
+    // between it and the other end of the unknown region: move them to that end
+    if start_l < end_l {
+        while start_l < end_l {
+            end_l -= 1;
+            let a = l + offsets_l[end_l] as usize;
+            if a != r - 1 {
+                cmp::swap(cmp, slice, a, r - 1);
+            }
+            r -= 1;
+        }
+        r
+    } else {
+        while start_r < end_r {
+            end_r -= 1;
+            let b = r - 1 - offsets_r[end_r] as usize;
+            if b != l {
+                cmp::swap(cmp, slice, l, b);
+            }
+            l += 1;
+        }
+        l
+    }
+}
+
+/// Partitions the slice into the elements equal to the one at `pivot`, which must be
+/// no greater than any of them, and the greater ones; returns how many are equal.
+fn partition_equal<T, C: Compare<T>>(slice: &mut [T], pivot: usize, cmp: &mut C) -> usize {
+    if pivot != 0 {
+        cmp::swap(cmp, slice, 0, pivot);
+    }
+    cmp.on_pivot(&slice[0]);
+    let equal = {
+        let (pivot, rest) = slice.split_first_mut().expect("the pivot is in the slice");
+        let pivot = &*pivot;
+        let (mut l, mut r) = (0, rest.len());
+        loop {
+            while l < r && !cmp.is_less(pivot, &rest[l]) {
+                l += 1;
+            }
+            while l < r && cmp.is_less(pivot, &rest[r - 1]) {
+                r -= 1;
+            }
+            if l >= r {
+                break l + 1;
+            }
+            r -= 1;
+            cmp::swap(cmp, rest, l, r);
+            l += 1;
+        }
+    };
+    cmp.on_partition(slice, equal - 1);
+    equal
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::cmp::ByOrd;
+    use crate::Counting;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn is_sorted<T: Ord>(slice: &[T]) -> bool {
+        slice.windows(2).all(|w| w[0] <= w[1])
+    }
+
+    #[test]
+    fn sorts_patterns() {
+        for &n in &[0, 1, 2, 7, 20, 21, 50, 300, 1000, 5000] {
+            let mut sorted: Vec<_> = (0..n).collect();
+            let mut reversed: Vec<_> = (0..n).rev().collect();
+            let mut equal = vec![7; n];
+            let mut sawtooth: Vec<_> = (0..n).map(|i| (i * 7919) % 97).collect();
+            let mut pipe: Vec<_> = (0..n).map(|i| i.min(n - 1 - i)).collect();
+            let mut random = crate::generators::shuffled(n, 3);
+            for v in [
+                &mut sorted,
+                &mut reversed,
+                &mut equal,
+                &mut sawtooth,
+                &mut pipe,
+                &mut random,
+            ] {
+                PdqSort.sort(v);
+                assert!(is_sorted(v), "n = {}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn sorted_and_descending_input_take_linear_time() {
+        let n = 10_000;
+        for tings in [(0..n).collect::<Vec<_>>(), (0..n).rev().collect()] {
+            let sorter = Counting::new(PdqSort);
+            let mut tings = tings;
+            sorter.sort(&mut tings);
+            assert!(is_sorted(&tings));
+            assert!(sorter.stats().comparisons < 2 * n, "{:?}", sorter.stats());
+        }
+    }
+
+    #[test]
+    fn few_distinct_values_take_linear_time() {
+        let n = 1 << 14;
+        let mut tings: Vec<u32> = (0..n).map(|i| (i * 7919 % 4) as u32).collect();
+        let sorter = Counting::new(PdqSort);
+        sorter.sort(&mut tings);
+        assert!(is_sorted(&tings));
+        assert!(sorter.stats().comparisons < 8 * n, "{:?}", sorter.stats());
+    }
+
+    #[test]
+    fn adversarial_input_stays_linearithmic() {
+        let n = 1 << 12;
+        let mut tings = crate::worst_case::adversary(&PdqSort, n);
+        let sorter = Counting::new(PdqSort);
+        sorter.sort(&mut tings);
+        assert!(is_sorted(&tings));
+        assert!(
+            sorter.stats().comparisons < 4 * n * 12,
+            "{:?}",
+            sorter.stats()
+        );
+    }
+
+    #[test]
+    fn block_partition_splits_around_the_pivot() {
+        for n in [0, 1, 2, 100, 255, 256, 257, 1000] {
+            let mut tings = crate::generators::shuffled(n, n as u64);
+            let pivot = n / 3;
+            let less = partition_in_blocks(&mut tings, &pivot, &mut ByOrd);
+            assert_eq!(less, pivot, "n = {}", n);
+            assert!(tings[..less].iter().all(|&x| x < pivot));
+            assert!(tings[less..].iter().all(|&x| x >= pivot));
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Applying permutations in place.
+
+/// Reorders `slice` so that `slice[i]` becomes the element at `order[i]`.
+pub(crate) fn apply<T>(slice: &mut [T], order: &mut [usize]) {
+    apply_by(order, |a, b| slice.swap(a, b))
+}
+
+/// Applies the permutation `order` through `swap`, following its cycles.
+///
+/// After the call, position `i` holds what was at `order[i]`, using at most
+/// `order.len() - 1` swaps. `order` is left as the identity.
+pub(crate) fn apply_by<F: FnMut(usize, usize)>(order: &mut [usize], mut swap: F) {
+    for start in 0..order.len() {
+        let mut cur = start;
+        loop {
+            let next = order[cur];
+            order[cur] = cur;
+            if next == start {
+                break;
+            }
+            swap(cur, next);
+            cur = next;
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::permutation;
+use crate::{is_trivially_sorted, Complexity, Error, Sorter, SorterInfo};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Holes allowed per element by default before [`PigeonholeSort`] falls back.
+const HOLES_PER_ELEMENT: usize = 4;
+
+/// Elements located by an integer key, ordered like the elements themselves.
+pub trait Pigeonhole {
+    fn pigeonhole(&self) -> i128;
+}
+
+macro_rules! impl_pigeonhole {
+    ($($t:ty),*) => {
+        $(
+            impl Pigeonhole for $t {
+                #[inline]
+                fn pigeonhole(&self) -> i128 {
+                    *self as i128
+                }
+            }
+        )*
+    };
+}
+
+impl_pigeonhole!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, bool, char);
+
+/// Pigeonhole Sort
+///
+/// Pigeonhole sort finds the smallest and largest keys, sets up one hole per key in
+/// between, drops each element into the hole of its key, and then reads the holes out
+/// in order. It takes O(n + r) time and space for a key range of r, and is stable.
+///
+/// The range is detected from the input. When it exceeds `max_holes_per_element` holes
+/// per element, the holes would cost more than they save, so [`PigeonholeSort::sort`]
+/// sorts with the `fallback` sorter instead and [`PigeonholeSort::try_sort`] reports
+/// [`Error::RangeTooLarge`].
+pub struct PigeonholeSort<S> {
+    pub fallback: S,
+    pub max_holes_per_element: usize,
+}
+
+impl<S: Sorter> PigeonholeSort<S> {
+    /// Creates a pigeonhole sorter allowing 4 holes per element before using `fallback`.
+    pub fn new(fallback: S) -> Self {
+        PigeonholeSort {
+            fallback,
+            max_holes_per_element: HOLES_PER_ELEMENT,
+        }
+    }
+
+    pub fn info(&self) -> SorterInfo {
+        let fallback = self.fallback.info();
+        SorterInfo {
+            name: "pigeonhole",
+            stable: fallback.stable,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linear,
+            worst: fallback.worst,
+        }
+    }
+
+    pub fn sort<T: Pigeonhole>(&self, slice: &mut [T]) {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let keys = slice.iter().map(T::pigeonhole).collect();
+        self.sort_keyed(slice, keys)
+    }
+
+    /// Sorts by a key computed once per element, falling back on a wide key range.
+    pub fn sort_by_key<T, K, F>(&self, slice: &mut [T], key: F)
+    where
+        K: Pigeonhole,
+        F: FnMut(&T) -> K,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let keys = slice.iter().map(key).map(|k| k.pigeonhole()).collect();
+        self.sort_keyed(slice, keys)
+    }
+
+    fn sort_keyed<T>(&self, slice: &mut [T], keys: Vec<i128>) {
+        let mut order = self.hole_order(&keys).unwrap_or_else(|_| {
+            let mut order: Vec<usize> = (0..keys.len()).collect();
+            self.fallback.sort_by_key(&mut order, |&i| keys[i]);
+            order
+        });
+        permutation::apply(slice, &mut order);
+    }
+
+    /// Sorts by pigeonholes, or fails without touching the slice if the key range is
+    /// too wide.
+    pub fn try_sort<T: Pigeonhole>(&self, slice: &mut [T]) -> Result<(), Error> {
+        if is_trivially_sorted(slice) {
+            return Ok(());
+        }
+        let keys: Vec<i128> = slice.iter().map(T::pigeonhole).collect();
+        self.try_sort_keyed(slice, &keys)
+    }
+
+    /// Like [`PigeonholeSort::try_sort`], with a key computed once per element.
+    pub fn try_sort_by_key<T, K, F>(&self, slice: &mut [T], key: F) -> Result<(), Error>
+    where
+        K: Pigeonhole,
+        F: FnMut(&T) -> K,
+    {
+        if is_trivially_sorted(slice) {
+            return Ok(());
+        }
+        let keys: Vec<i128> = slice.iter().map(key).map(|k| k.pigeonhole()).collect();
+        self.try_sort_keyed(slice, &keys)
+    }
+
+    fn try_sort_keyed<T>(&self, slice: &mut [T], keys: &[i128]) -> Result<(), Error> {
+        let mut order = self.hole_order(keys)?;
+        permutation::apply(slice, &mut order);
+        Ok(())
+    }
+
+    /// The stable order that sorts `keys`: `order[destination] = source`.
+    fn hole_order(&self, keys: &[i128]) -> Result<Vec<usize>, Error> {
+        let min = keys.iter().copied().min().unwrap_or(0);
+        let max = keys.iter().copied().max().unwrap_or(0);
+        // keys come from at most 64-bit integers, so the span fits
+        let range = (max - min) as u128 + 1;
+        if range > self.max_holes_per_element.saturating_mul(keys.len()) as u128 {
+            return Err(Error::RangeTooLarge {
+                range,
+                len: keys.len(),
+            });
+        }
+        let mut holes = vec![Vec::new(); range as usize];
+        for (i, &k) in keys.iter().enumerate() {
+            holes[(k - min) as usize].push(i);
+        }
+        Ok(holes.concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Counting, MergeSort};
+
+    #[test]
+    fn pigeonhole_works() {
+        let mut tings = vec![5, -1, 4, 2, 3, -1, 0];
+        PigeonholeSort::new(MergeSort::default()).sort(&mut tings);
+        assert_eq!(tings, &[-1, -1, 0, 2, 3, 4, 5]);
+        let mut tings = vec!['c', 'a', 'b'];
+        PigeonholeSort::new(MergeSort::default()).sort(&mut tings);
+        assert_eq!(tings, &['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn sort_by_key_is_stable() {
+        let mut tings = vec!["bb", "a", "cc", "d", "eee", ""];
+        PigeonholeSort::new(MergeSort::default()).sort_by_key(&mut tings, |s| s.len());
+        assert_eq!(tings, &["", "a", "d", "bb", "cc", "eee"]);
+    }
+
+    #[test]
+    fn wide_ranges_fall_back() {
+        let sorter = PigeonholeSort::new(Counting::new(MergeSort::default()));
+        let mut tings = vec![u64::MAX, 0, 7, u64::MAX / 2];
+        assert_eq!(
+            sorter.try_sort(&mut tings),
+            Err(Error::RangeTooLarge {
+                range: u64::MAX as u128 + 1,
+                len: 4
+            })
+        );
+        assert_eq!(tings, &[u64::MAX, 0, 7, u64::MAX / 2]);
+        sorter.sort(&mut tings);
+        assert_eq!(tings, &[0, 7, u64::MAX / 2, u64::MAX]);
+        assert!(sorter.fallback.stats().comparisons > 0);
+
+        // a dense range never touches the fallback
+        let sorter = PigeonholeSort::new(Counting::new(MergeSort::default()));
+        let mut tings: Vec<i64> = (0..400).map(|i| 1_000_000 - (i * 7919) % 1000).collect();
+        sorter.sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(sorter.fallback.stats().comparisons, 0);
+    }
+
+    #[test]
+    fn hole_budget_is_per_element() {
+        let mut sorter = PigeonholeSort::new(MergeSort::default());
+        sorter.max_holes_per_element = 1;
+        assert!(sorter.try_sort(&mut [3, 1, 2]).is_ok());
+        assert!(sorter.try_sort(&mut [3, 1, 4]).is_err());
+    }
+}
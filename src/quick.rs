@@ -0,0 +1,558 @@
+use crate::cmp::{self, Compare};
+use crate::partition::{Hoare, Partitioner, ThreeWay};
+use crate::rng::{self, Rng};
+use crate::select;
+use crate::{is_trivially_sorted, Complexity, SmallSortPolicy, Sorter, SorterInfo};
+
+/// Quick Sort
+///
+/// Quicksort is a divide-and-conquer algorithm. It works by selecting a 'pivot' element
+/// from the array and partitioning the other elements into two sub-arrays, according
+/// to whether they are less than or greater than the pivot. The sub-arrays are then
+/// sorted recursively. This can be done in-place, requiring small additional amounts
+/// of memory to perform the sorting.
+///
+/// How well quicksort performs depends on the pivots splitting partitions evenly, see
+/// [`PivotStrategy`]. How each partition is split is up to a [`Partitioner`], Hoare's
+/// scheme by default. Partitions of at most `small_threshold` elements are finished with
+/// a branchless insertion sort, which beats partitioning them further; at 0, the
+/// default, the [`SmallSortPolicy`] decides.
+#[derive(Debug, Clone, Copy)]
+pub struct QuickSort<P = Hoare> {
+    pub pivot: PivotStrategy,
+    pub partitioner: P,
+    pub small_threshold: usize,
+}
+
+impl Default for QuickSort {
+    fn default() -> Self {
+        QuickSort {
+            pivot: PivotStrategy::default(),
+            partitioner: Hoare,
+            small_threshold: 0,
+        }
+    }
+}
+
+impl<P> QuickSort<P> {
+    /// The same sort, splitting partitions with `partitioner` instead.
+    pub fn with_partitioner<Q: Partitioner>(self, partitioner: Q) -> QuickSort<Q> {
+        QuickSort {
+            pivot: self.pivot,
+            partitioner,
+            small_threshold: self.small_threshold,
+        }
+    }
+}
+
+/// How [`QuickSort`] picks the element each partition is split around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PivotStrategy {
+    /// The first element. Quadratic on already sorted input.
+    First,
+    /// The last element. Quadratic on already sorted input.
+    Last,
+    /// The middle element.
+    Middle,
+    /// The median of the first, middle and last elements.
+    #[default]
+    MedianOfThree,
+    /// A uniformly random element, with a generator seeded by `seed` so sorts are
+    /// reproducible.
+    Random(u64),
+    /// Tukey's ninther: the median of the medians of three evenly spaced triples. Falls
+    /// back to the median of three on short partitions.
+    Ninther,
+    /// The median of `sample_size` elements picked at random, with a generator seeded
+    /// by `seed` so sorts are reproducible. Larger samples give more balanced partitions
+    /// on skewed data at the cost of selecting their median.
+    SampledMedian { sample_size: usize, seed: u64 },
+}
+
+/// Partitions shorter than this use the median of three instead of the ninther.
+const NINTHER_THRESHOLD: usize = 40;
+
+/// Pivot selection state carried through the recursion.
+struct Pivots {
+    strategy: PivotStrategy,
+    rng: Rng,
+}
+
+impl Pivots {
+    fn new(strategy: PivotStrategy) -> Self {
+        let seed = match strategy {
+            PivotStrategy::Random(seed) | PivotStrategy::SampledMedian { seed, .. } => seed,
+            _ => 0,
+        };
+        Pivots {
+            strategy,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Moves the chosen pivot to the front of the slice.
+    fn choose<T, C: Compare<T>>(&mut self, slice: &mut [T], cmp: &mut C) {
+        let len = slice.len();
+        let pivot = match self.strategy {
+            PivotStrategy::First => 0,
+            PivotStrategy::Last => len - 1,
+            PivotStrategy::Middle => len / 2,
+            PivotStrategy::MedianOfThree => sort3(slice, 0, len / 2, len - 1, cmp),
+            PivotStrategy::Random(_) => self.rng.below(len),
+            PivotStrategy::Ninther if len < NINTHER_THRESHOLD => {
+                sort3(slice, 0, len / 2, len - 1, cmp)
+            }
+            PivotStrategy::Ninther => {
+                let (step, mid) = (len / 8, len / 2);
+                let a = median3(slice, 0, step, 2 * step, cmp);
+                let b = median3(slice, mid - step, mid, mid + step, cmp);
+                let c = median3(slice, len - 1 - 2 * step, len - 1 - step, len - 1, cmp);
+                median3(slice, a, b, c, cmp)
+            }
+            PivotStrategy::SampledMedian { sample_size, .. } => {
+                let k = sample_size.clamp(1, slice.len());
+                rng::sample(slice, k, &mut self.rng, cmp);
+                select::quickselect_with(&mut slice[..k], k / 2, cmp);
+                k / 2
+            }
+        };
+        if pivot != 0 {
+            cmp::swap(cmp, slice, 0, pivot);
+        }
+    }
+}
+
+/// Recurses only into the smaller partition and loops on the larger one, so the call
+/// stack is at most O(log n) deep whatever the pivots.
+fn quicksort<T, C: Compare<T>, P: Partitioner>(
+    mut slice: &mut [T],
+    pivots: &mut Pivots,
+    partitioner: &P,
+    small: SmallSortPolicy,
+    cmp: &mut C,
+) {
+    // levels descended by looping rather than recursing, left once the loop ends
+    let mut levels = 0;
+    loop {
+        if small.sort_small(slice, cmp) {
+            break;
+        }
+        match slice.len() {
+            0 | 1 => break,
+            2 => {
+                if cmp.is_less(&slice[1], &slice[0]) {
+                    cmp::swap(cmp, slice, 0, 1)
+                }
+                break;
+            }
+            _ => {}
+        }
+        pivots.choose(slice, cmp);
+        let equal = partitioner.partition(slice, cmp);
+        let (left, right) = slice.split_at_mut(equal.start);
+        let right = &mut right[equal.len()..];
+        cmp.on_enter();
+        levels += 1;
+        if left.len() < right.len() {
+            quicksort(left, pivots, partitioner, small, cmp);
+            slice = right;
+        } else {
+            quicksort(right, pivots, partitioner, small, cmp);
+            slice = left;
+        }
+    }
+    for _ in 0..levels {
+        cmp.on_leave();
+    }
+}
+
+/// Partitions the slice around its first element, returning the pivot's final index.
+///
+/// Elements before the pivot are not greater than it; elements after it are greater.
+/// The slice must hold at least two elements.
+pub(crate) fn partition<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) -> usize {
+    cmp.on_pivot(&slice[0]);
+    let (pivot, rest) = slice.split_first_mut().expect("slice is non-empty");
+    let mut left = 0;
+    let mut right = rest.len() - 1;
+    while left <= right {
+        if !cmp.is_less(pivot, &rest[left]) {
+            left += 1;
+        } else if cmp.is_less(pivot, &rest[right]) {
+            // avoid unnecessary swaps
+            // we must be done
+            if right == 0 {
+                break;
+            }
+            right -= 1;
+        } else {
+            // move element to the right side
+            cmp::swap(cmp, rest, left, right);
+            left += 1;
+            // we must be done
+            if right == 0 {
+                break;
+            }
+            right -= 1;
+        }
+    }
+
+    // place pivot in final position
+    cmp::swap(cmp, slice, 0, left);
+    cmp.on_partition(slice, left);
+    left
+}
+
+/// Moves the median of the first, middle and last elements to the front.
+pub(crate) fn median_of_three<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) {
+    let len = slice.len();
+    let median = median3(slice, 0, len / 2, len - 1, cmp);
+    if median != 0 {
+        cmp::swap(cmp, slice, 0, median);
+    }
+}
+
+/// Sorts the elements at `a`, `b` and `c` among themselves, returning `b`.
+///
+/// Unlike only finding the median, this also moves the smallest and largest of the three
+/// towards the ends, which keeps partitions of reversed input balanced.
+fn sort3<T, C: Compare<T>>(slice: &mut [T], a: usize, b: usize, c: usize, cmp: &mut C) -> usize {
+    if cmp.is_less(&slice[b], &slice[a]) {
+        cmp::swap(cmp, slice, a, b);
+    }
+    if cmp.is_less(&slice[c], &slice[b]) {
+        cmp::swap(cmp, slice, b, c);
+        if cmp.is_less(&slice[b], &slice[a]) {
+            cmp::swap(cmp, slice, a, b);
+        }
+    }
+    b
+}
+
+/// Returns whichever of the indices `a`, `b` and `c` holds the median of the three.
+fn median3<T, C: Compare<T>>(slice: &[T], a: usize, b: usize, c: usize, cmp: &mut C) -> usize {
+    if cmp.is_less(&slice[a], &slice[b]) {
+        if cmp.is_less(&slice[b], &slice[c]) {
+            b
+        } else if cmp.is_less(&slice[a], &slice[c]) {
+            c
+        } else {
+            a
+        }
+    } else if cmp.is_less(&slice[a], &slice[c]) {
+        a
+    } else if cmp.is_less(&slice[b], &slice[c]) {
+        c
+    } else {
+        b
+    }
+}
+
+impl<P: Partitioner> Sorter for QuickSort<P> {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "quick",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linearithmic,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        // [ unsorted | pivot | unsorted ]
+        let small = SmallSortPolicy::configured(self.small_threshold, SmallSortPolicy::None);
+        let mut pivots = Pivots::new(self.pivot);
+        quicksort(slice, &mut pivots, &self.partitioner, small, cmp)
+    }
+}
+
+/// Three-Way Quick Sort
+///
+/// Three-way quicksort partitions each slice into elements less than, equal to, and
+/// greater than the pivot (the Dutch national flag problem), then recurses only into
+/// the outer two parts. Runs of equal keys are therefore handled once, which makes
+/// the sort linear on inputs with only a few distinct values.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreeWayQuickSort;
+
+impl ThreeWayQuickSort {
+    fn quicksort<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) {
+        if slice.len() < 2 {
+            return;
+        }
+        if slice.len() >= 3 {
+            median_of_three(slice, cmp);
+        }
+        let equal = ThreeWay.partition(slice, cmp);
+        let (left, rest) = slice.split_at_mut(equal.start);
+        cmp.on_enter();
+        Self::quicksort(left, cmp);
+        Self::quicksort(&mut rest[equal.len()..], cmp);
+        cmp.on_leave();
+    }
+}
+
+impl Sorter for ThreeWayQuickSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "quick-3way",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        Self::quicksort(slice, cmp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::partition::Lomuto;
+    use crate::Counting;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    const SAMPLED: PivotStrategy = PivotStrategy::SampledMedian {
+        sample_size: 9,
+        seed: 17,
+    };
+
+    #[test]
+    fn sampled_median_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        QuickSort {
+            pivot: SAMPLED,
+            partitioner: Hoare,
+            small_threshold: 0,
+        }
+        .sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn sampled_median_sorts_patterns() {
+        let mut sorted: Vec<_> = (0..500).collect();
+        let mut reversed: Vec<_> = (0..500).rev().collect();
+        let mut skewed: Vec<_> = (0..500u64).map(|i| i * i % 37).collect();
+        for v in [&mut sorted, &mut reversed, &mut skewed] {
+            QuickSort {
+                pivot: SAMPLED,
+                partitioner: Hoare,
+                small_threshold: 0,
+            }
+            .sort(v);
+            assert!(v.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn sampled_median_balances_sorted_input() {
+        let n = 2000;
+        let first = Counting::new(QuickSort {
+            pivot: PivotStrategy::First,
+            partitioner: Hoare,
+            small_threshold: 0,
+        });
+        first.sort(&mut (0..n).collect::<Vec<_>>());
+        let sampled = Counting::new(QuickSort {
+            pivot: SAMPLED,
+            partitioner: Hoare,
+            small_threshold: 0,
+        });
+        sampled.sort(&mut (0..n).collect::<Vec<_>>());
+        assert!(sampled.stats().comparisons * 10 < first.stats().comparisons);
+        assert!(sampled.stats().max_depth < 64);
+    }
+
+    const STRATEGIES: [PivotStrategy; 7] = [
+        PivotStrategy::First,
+        PivotStrategy::Last,
+        PivotStrategy::Middle,
+        PivotStrategy::MedianOfThree,
+        PivotStrategy::Random(3),
+        PivotStrategy::Ninther,
+        SAMPLED,
+    ];
+
+    #[test]
+    fn every_strategy_sorts() {
+        for &pivot in &STRATEGIES {
+            let mut tings = vec![5, 1, 4, 2, 3];
+            QuickSort {
+                pivot,
+                partitioner: Hoare,
+                small_threshold: 0,
+            }
+            .sort(&mut tings);
+            assert_eq!(tings, &[1, 2, 3, 4, 5]);
+
+            let mut organ: Vec<_> = (0..300).chain((0..300).rev()).collect();
+            let mut dups: Vec<_> = (0..300).map(|i| (i * 7919) % 11).collect();
+            for v in [&mut organ, &mut dups] {
+                QuickSort {
+                    pivot,
+                    partitioner: Hoare,
+                    small_threshold: 0,
+                }
+                .sort(v);
+                assert!(v.windows(2).all(|w| w[0] <= w[1]), "{:?}", pivot);
+            }
+        }
+    }
+
+    #[test]
+    fn default_is_fast_on_sorted_input() {
+        let n = 2000;
+        for &pivot in &[
+            PivotStrategy::default(),
+            PivotStrategy::Middle,
+            PivotStrategy::Random(7),
+            PivotStrategy::Ninther,
+        ] {
+            let sorter = Counting::new(QuickSort {
+                pivot,
+                partitioner: Hoare,
+                small_threshold: 0,
+            });
+            sorter.sort(&mut (0..n).collect::<Vec<_>>());
+            assert!(sorter.stats().comparisons < n * 32, "{:?}", pivot);
+            let sorter = Counting::new(QuickSort {
+                pivot,
+                partitioner: Hoare,
+                small_threshold: 0,
+            });
+            sorter.sort(&mut (0..n).rev().collect::<Vec<_>>());
+            assert!(sorter.stats().comparisons < n * 32, "{:?}", pivot);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn degenerate_pivots_do_not_overflow_the_stack() {
+        let sorted = std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(|| {
+                let mut tings: Vec<_> = (0..5000).collect();
+                let sorter = Counting::new(QuickSort {
+                    pivot: PivotStrategy::First,
+                    partitioner: Hoare,
+                    small_threshold: 0,
+                });
+                sorter.sort(&mut tings);
+                // the reported depth is still that of the partition tree
+                assert_eq!(sorter.stats().max_depth, 4998);
+                tings.windows(2).all(|w| w[0] <= w[1])
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert!(sorted);
+    }
+
+    #[test]
+    fn every_partitioner_sorts() {
+        fn check<P: Partitioner + Copy>(partitioner: P) {
+            for &pivot in &STRATEGIES {
+                let sorter = QuickSort::default().with_partitioner(partitioner);
+                let sorter = QuickSort { pivot, ..sorter };
+                let mut organ: Vec<_> = (0..300).chain((0..300).rev()).collect();
+                let mut dups: Vec<_> = (0..300).map(|i| (i * 7919) % 11).collect();
+                for v in [&mut organ, &mut dups] {
+                    sorter.sort(v);
+                    assert!(v.windows(2).all(|w| w[0] <= w[1]), "{:?}", pivot);
+                }
+            }
+            let mut tings: Vec<_> = (0..500).map(|i| (i * 7919) % 13).collect();
+            crate::IntroSort { partitioner }.sort(&mut tings);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        }
+        check(Hoare);
+        check(Lomuto);
+        check(ThreeWay);
+    }
+
+    #[test]
+    fn three_way_partitioner_is_linear_on_equal_keys() {
+        let n = 10_000;
+        let sorter = Counting::new(QuickSort::default().with_partitioner(ThreeWay));
+        sorter.sort(&mut vec![3; n]);
+        assert!(sorter.stats().comparisons < 2 * n);
+    }
+
+    #[test]
+    fn three_way_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        ThreeWayQuickSort.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        let mut tings: Vec<_> = (0..1000).map(|i| (i * 7919) % 13).collect();
+        ThreeWayQuickSort.sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn three_way_is_linear_on_equal_keys() {
+        let n = 10_000;
+        let sorter = Counting::new(ThreeWayQuickSort);
+        sorter.sort(&mut vec![3; n]);
+        assert!(sorter.stats().comparisons < 2 * n);
+        assert_eq!(sorter.stats().max_depth, 1);
+    }
+
+    #[test]
+    fn sampling_is_reproducible() {
+        let run = || {
+            let sorter = Counting::new(QuickSort {
+                pivot: SAMPLED,
+                partitioner: Hoare,
+                small_threshold: 0,
+            });
+            sorter.sort(&mut (0..300).rev().collect::<Vec<_>>());
+            sorter.stats()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn small_threshold_finishes_with_insertion_sort() {
+        let tings: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        let plain = Counting::new(QuickSort::default());
+        plain.sort(&mut tings.clone());
+        let cut = Counting::new(QuickSort {
+            small_threshold: 16,
+            ..QuickSort::default()
+        });
+        let mut sorted = tings.clone();
+        cut.sort(&mut sorted);
+        assert_eq!(sorted, (0..1000).collect::<Vec<_>>());
+        assert!(cut.stats().max_depth < plain.stats().max_depth);
+
+        // a threshold covering the whole slice is branchless insertion sort alone
+        let all = Counting::new(QuickSort {
+            small_threshold: 50,
+            ..QuickSort::default()
+        });
+        let mut insertion = crate::Counter::new(crate::cmp::ByOrd);
+        all.sort(&mut tings[..50].to_vec());
+        crate::small::branchless_insertion_sort(&mut tings[..50].to_vec(), &mut insertion);
+        assert_eq!(all.stats(), insertion.stats());
+    }
+}
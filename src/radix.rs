@@ -0,0 +1,318 @@
+use crate::cmp::ByFn;
+use crate::permutation;
+use crate::{is_trivially_sorted, Complexity, Error, SmallSortPolicy, SorterInfo};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Inputs this small are sorted by insertion rather than by digits, unless overridden
+/// globally.
+const SMALL_SORT: SmallSortPolicy = SmallSortPolicy::Branchless { threshold: 32 };
+
+/// Low bits of a packed base-case key holding the element's position, see
+/// [`sort_small_packed`].
+const POSITION_BITS: u32 = 8;
+
+/// Keys that can be sorted digit by digit.
+///
+/// `to_radix` must map keys to unsigned integers with the same ordering; only the low
+/// `BITS` bits may be set.
+pub trait RadixKey: Copy {
+    const BITS: u32;
+
+    fn to_radix(self) -> u128;
+}
+
+macro_rules! impl_radix_key_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BITS: u32 = <$t>::BITS;
+
+                #[inline]
+                fn to_radix(self) -> u128 {
+                    self as u128
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_radix_key_signed {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BITS: u32 = <$t>::BITS;
+
+                #[inline]
+                fn to_radix(self) -> u128 {
+                    // flipping the sign bit orders negatives before positives
+                    ((self as $u) ^ (1 << (<$t>::BITS - 1))) as u128
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_key_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_radix_key_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+impl RadixKey for bool {
+    const BITS: u32 = 1;
+
+    fn to_radix(self) -> u128 {
+        self as u128
+    }
+}
+
+impl RadixKey for char {
+    const BITS: u32 = 21;
+
+    fn to_radix(self) -> u128 {
+        self as u128
+    }
+}
+
+/// Sorters that order elements by the digits of a [`RadixKey`] instead of comparing them.
+pub trait RadixSorter {
+    fn info(&self) -> SorterInfo;
+
+    fn sort<T: RadixKey>(&self, slice: &mut [T]);
+
+    /// Sorts arbitrary elements by a radix key extracted once per element.
+    fn sort_by_key<T, K, F>(&self, slice: &mut [T], key: F)
+    where
+        K: RadixKey,
+        F: FnMut(&T) -> K;
+}
+
+/// Radix Sort
+///
+/// Least significant digit (LSD) radix sort processes the keys one digit at a time,
+/// starting from the least significant one. Each pass is a stable counting sort on the
+/// current digit, so after the last pass the elements are ordered by the whole key.
+/// It runs in O(w/d · n) time for w-bit keys and d-bit digits, without comparing elements.
+/// Inputs of a few dozen elements, too few to pay for the passes, are insertion sorted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RadixSort {
+    /// Width of the digit handled in each pass, capped at 16 bits.
+    pub radix_bits: u32,
+}
+
+impl RadixSort {
+    /// Creates a radix sorter handling `radix_bits` bits per pass, which must be positive.
+    pub fn new(radix_bits: u32) -> Result<Self, Error> {
+        let sorter = RadixSort { radix_bits };
+        sorter.validate()?;
+        Ok(sorter)
+    }
+
+    /// Checks the configuration, as the sort itself would before starting.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.radix_bits == 0 {
+            return Err(Error::ZeroRadixBits);
+        }
+        Ok(())
+    }
+}
+
+impl Default for RadixSort {
+    fn default() -> Self {
+        RadixSort { radix_bits: 8 }
+    }
+}
+
+impl RadixSort {
+    /// Stable LSD sort of `items` by `key`, ping-ponging with a scratch copy.
+    pub(crate) fn lsd<E: Copy, K: RadixKey>(&self, items: &mut [E], key: impl Fn(&E) -> K) {
+        if let Err(e) = self.validate() {
+            panic!("{}", e)
+        }
+        let small = SmallSortPolicy::resolve(SMALL_SORT).stable();
+        if sort_small_packed(items, &key, small) {
+            return;
+        }
+        let mut by_key = ByFn(|a: &E, b: &E| key(a).to_radix().cmp(&key(b).to_radix()));
+        if small.sort_small(items, &mut by_key) {
+            return;
+        }
+        let bits = self.radix_bits.min(16);
+        let buckets = 1usize << bits;
+        let mask = (buckets - 1) as u128;
+        let mut counts = vec![0usize; buckets];
+        let mut scratch = items.to_vec();
+        let (mut src, mut dst) = (items, &mut scratch[..]);
+        let mut in_scratch = false;
+
+        let mut shift = 0;
+        while shift < K::BITS {
+            counts.iter_mut().for_each(|c| *c = 0);
+            for e in src.iter() {
+                counts[((key(e).to_radix() >> shift) & mask) as usize] += 1;
+            }
+            shift += bits;
+            // every key shares this digit, nothing to reorder
+            if counts.contains(&src.len()) {
+                continue;
+            }
+            let mut total = 0;
+            for c in counts.iter_mut() {
+                let n = *c;
+                *c = total;
+                total += n;
+            }
+            for e in src.iter() {
+                let digit = ((key(e).to_radix() >> (shift - bits)) & mask) as usize;
+                dst[counts[digit]] = *e;
+                counts[digit] += 1;
+            }
+            core::mem::swap(&mut src, &mut dst);
+            in_scratch = !in_scratch;
+        }
+        if in_scratch {
+            dst.copy_from_slice(src);
+        }
+    }
+}
+
+/// Sorts `items` as a base case if `small` takes them and their keys leave room for their
+/// positions, returning whether it did. Each key is packed above its element's position
+/// into a `u128`, so the packed keys are distinct primitive integers, sorted without
+/// branches by [`SmallSortPolicy::sort_small_integers`] and stably whatever the policy.
+fn sort_small_packed<E: Copy, K: RadixKey>(
+    items: &mut [E],
+    key: impl Fn(&E) -> K,
+    small: SmallSortPolicy,
+) -> bool {
+    if items.len() > small.threshold()
+        || items.len() > 1 << POSITION_BITS
+        || K::BITS > u128::BITS - POSITION_BITS
+    {
+        return false;
+    }
+    let mut packed: Vec<u128> = items
+        .iter()
+        .zip(0..)
+        .map(|(e, i)| key(e).to_radix() << POSITION_BITS | i)
+        .collect();
+    small.sort_small_integers(&mut packed);
+    let original = items.to_vec();
+    let mask = (1 << POSITION_BITS) - 1;
+    for (item, p) in items.iter_mut().zip(packed) {
+        *item = original[(p & mask) as usize];
+    }
+    true
+}
+
+impl RadixSorter for RadixSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "radix",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linear,
+            worst: Complexity::Linear,
+        }
+    }
+
+    fn sort<T: RadixKey>(&self, slice: &mut [T]) {
+        if slice.len() < 2 {
+            return;
+        }
+        self.lsd(slice, |&x| x)
+    }
+
+    fn sort_by_key<T, K, F>(&self, slice: &mut [T], mut key: F)
+    where
+        K: RadixKey,
+        F: FnMut(&T) -> K,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut pairs: Vec<(K, usize)> = slice.iter().map(&mut key).zip(0..).collect();
+        self.lsd(&mut pairs, |p| p.0);
+        let mut order: Vec<usize> = pairs.into_iter().map(|p| p.1).collect();
+        permutation::apply(slice, &mut order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    fn pseudo_random(n: usize) -> Vec<u64> {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        (0..n)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x
+            })
+            .collect()
+    }
+
+    #[test]
+    fn radix_works() {
+        let mut tings = vec![5u32, 1, 4, 2, 3];
+        RadixSort::default().sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn sorts_signed() {
+        let mut tings = vec![3i32, -1, i32::MIN, 0, i32::MAX, -7, 2];
+        RadixSort::default().sort(&mut tings);
+        assert_eq!(tings, &[i32::MIN, -7, -1, 0, 2, 3, i32::MAX]);
+    }
+
+    #[test]
+    fn sorts_with_any_radix_width() {
+        let values = pseudo_random(2000);
+        let mut expected = values.clone();
+        expected.sort();
+        for &radix_bits in &[1, 4, 8, 11, 16, 64] {
+            let mut tings = values.clone();
+            RadixSort { radix_bits }.sort(&mut tings);
+            assert_eq!(tings, expected);
+        }
+    }
+
+    #[test]
+    fn sort_by_key_is_stable() {
+        let mut tings = vec![(2u8, 'a'), (1, 'b'), (2, 'c'), (0, 'd'), (1, 'e')];
+        RadixSort::default().sort_by_key(&mut tings, |t| t.0);
+        assert_eq!(tings, &[(0, 'd'), (1, 'b'), (1, 'e'), (2, 'a'), (2, 'c')]);
+    }
+
+    #[test]
+    fn sort_by_key_moves_non_copy_values() {
+        let mut tings = vec![String::from("ccc"), String::from("a"), String::from("bb")];
+        RadixSort::default().sort_by_key(&mut tings, |s| s.len());
+        assert_eq!(tings, &["a", "bb", "ccc"]);
+    }
+
+    #[test]
+    fn small_inputs_sort_on_packed_integers() {
+        let small = SmallSortPolicy::Network { threshold: 32 };
+        let mut tings: Vec<(u8, usize)> = (0..32).map(|i| ((i * 7919 % 5) as u8, i)).collect();
+        let mut expected = tings.clone();
+        expected.sort_by_key(|t| t.0);
+        assert!(sort_small_packed(&mut tings, |t| t.0, small));
+        assert_eq!(tings, expected);
+
+        // no room for the positions beside 128-bit keys, nor in a threshold past 256
+        assert!(!sort_small_packed(&mut [3u128, 1], |&x| x, small));
+        assert!(!sort_small_packed(&mut [0u8; 33], |&x| x, small));
+        let large = SmallSortPolicy::Insertion { threshold: 1000 };
+        assert!(!sort_small_packed(&mut [0u8; 300], |&x| x, large));
+    }
+
+    #[test]
+    fn new_rejects_zero_width() {
+        assert_eq!(RadixSort::new(0).unwrap_err(), Error::ZeroRadixBits);
+        assert_eq!(RadixSort::new(4).unwrap().radix_bits, 4);
+    }
+}
@@ -0,0 +1,113 @@
+//! A small seedable pseudo-random number generator for the randomized algorithms.
+//!
+//! Everything random in the crate, from random pivots and bogosort's shuffles to the
+//! [`generators`](crate::generators), draws from an [`Rng`] seeded explicitly, so every
+//! run can be reproduced from its seed.
+//!
+//! ```
+//! use pangua::rng::{shuffle, Rng};
+//!
+//! let mut tings: Vec<u32> = (0..10).collect();
+//! shuffle(&mut tings, 42);
+//! let mut again: Vec<u32> = (0..10).collect();
+//! Rng::new(42).shuffle(&mut again);
+//! assert_eq!(tings, again);
+//! ```
+
+use crate::cmp::{self, Compare};
+
+/// SplitMix64: fast, tiny state, and good enough statistical quality for pivot picking.
+///
+/// Not suitable for anything that needs unpredictability.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly distributed number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        debug_assert!(bound > 0);
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+
+    /// Shuffles the slice uniformly, with a Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            slice.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Shuffles the slice uniformly, the same way for the same seed.
+pub fn shuffle<T>(slice: &mut [T], seed: u64) {
+    Rng::new(seed).shuffle(slice)
+}
+
+/// Moves `k` elements picked uniformly at random to the front of the slice.
+///
+/// This is a partial Fisher-Yates shuffle; the rest of the slice is left in some order.
+pub(crate) fn sample<T, C: Compare<T>>(slice: &mut [T], k: usize, rng: &mut Rng, cmp: &mut C) {
+    for i in 0..k.min(slice.len()) {
+        let j = i + rng.below(slice.len() - i);
+        if i != j {
+            cmp::swap(cmp, slice, i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmp::ByOrd;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn is_reproducible() {
+        let a: Vec<_> = (0..5)
+            .map({
+                let mut rng = Rng::new(42);
+                move |_| rng.next_u64()
+            })
+            .collect();
+        let mut rng = Rng::new(42);
+        let b: Vec<_> = (0..5).map(|_| rng.next_u64()).collect();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn below_stays_in_range() {
+        let mut rng = Rng::new(7);
+        assert!((0..1000).all(|_| rng.below(10) < 10));
+    }
+
+    #[test]
+    fn shuffles_reproducibly() {
+        let mut tings: Vec<_> = (0..100).collect();
+        shuffle(&mut tings, 3);
+        assert_ne!(tings, (0..100).collect::<Vec<_>>());
+        let mut again: Vec<_> = (0..100).collect();
+        shuffle(&mut again, 3);
+        assert_eq!(tings, again);
+        tings.sort();
+        assert_eq!(tings, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sample_keeps_elements() {
+        let mut tings: Vec<_> = (0..20).collect();
+        sample(&mut tings, 5, &mut Rng::new(1), &mut ByOrd);
+        tings.sort();
+        assert_eq!(tings, (0..20).collect::<Vec<_>>());
+    }
+}
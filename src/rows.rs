@@ -0,0 +1,74 @@
+//! Sorting rows of packed, row-major 2-D data.
+
+use crate::permutation;
+use crate::Sorter;
+use alloc::vec::Vec;
+
+/// Sorts the rows of a row-major buffer by the value in column `key_col`.
+///
+/// `flat` holds `flat.len() / row_len` rows of `row_len` elements each. The row order is
+/// computed by sorting row indices with `sorter`, then applied by swapping whole rows in
+/// place, so no per-row allocation happens. Rows with equal keys keep their order.
+///
+/// Panics if `row_len` is zero, doesn't divide `flat.len()`, or `key_col >= row_len`.
+///
+/// ```
+/// use pangua::*;
+///
+/// let mut pixels = [3, 30, 300, 1, 10, 100, 2, 20, 200];
+/// sort_rows_by_column(&mut pixels, 3, 0, QuickSort::default());
+/// assert_eq!(pixels, [1, 10, 100, 2, 20, 200, 3, 30, 300]);
+/// ```
+pub fn sort_rows_by_column<T, S>(flat: &mut [T], row_len: usize, key_col: usize, sorter: S)
+where
+    T: Ord,
+    S: Sorter,
+{
+    assert!(row_len > 0, "rows must not be empty");
+    assert_eq!(flat.len() % row_len, 0, "buffer holds a partial row");
+    assert!(key_col < row_len, "key column {} out of range", key_col);
+
+    let rows = flat.len() / row_len;
+    let mut order: Vec<usize> = (0..rows).collect();
+    {
+        let key = |row: usize| &flat[row * row_len + key_col];
+        sorter.sort_by(&mut order, |&a, &b| key(a).cmp(key(b)).then(a.cmp(&b)));
+    }
+    permutation::apply_by(&mut order, |a, b| {
+        let (lo, hi) = (a.min(b), a.max(b));
+        let (head, tail) = flat.split_at_mut(hi * row_len);
+        head[lo * row_len..(lo + 1) * row_len].swap_with_slice(&mut tail[..row_len]);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use alloc::vec;
+
+    #[test]
+    fn sorts_by_key_column() {
+        let mut m = vec![
+            0, 5, 9, //
+            1, 3, 8, //
+            2, 4, 7, //
+            3, 1, 6, //
+        ];
+        sort_rows_by_column(&mut m, 3, 1, HeapSort::default());
+        assert_eq!(m, &[3, 1, 6, 1, 3, 8, 2, 4, 7, 0, 5, 9]);
+    }
+
+    #[test]
+    fn keeps_equal_rows_in_order() {
+        let mut m = vec![1, 0, 0, 1, 1, 2, 0, 3];
+        sort_rows_by_column(&mut m, 2, 0, SelectionSort);
+        assert_eq!(m, &[0, 1, 0, 3, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_partial_rows() {
+        sort_rows_by_column(&mut [1, 2, 3], 2, 0, QuickSort::default());
+    }
+}
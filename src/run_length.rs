@@ -0,0 +1,144 @@
+use crate::counting::counting_order;
+use crate::permutation;
+use crate::{is_trivially_sorted, Complexity, Sorter, SorterInfo};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Elements sampled by [`distinct_ratio`].
+const SAMPLE: usize = 1024;
+
+/// Run-Length Sort
+///
+/// Run-length sorting compresses the input into its distinct values and their counts
+/// with a hash map, sorts only the distinct values with another sorter, and lays the
+/// elements out again run by run. With d distinct values among n elements it takes
+/// O(n + d log d) time, which beats any comparison sort by far when d is much smaller
+/// than n. Elements are moved rather than cloned, and equal elements keep their order.
+pub struct RunLengthSort<S> {
+    pub inner: S,
+}
+
+impl<S: Sorter> RunLengthSort<S> {
+    pub fn new(inner: S) -> Self {
+        RunLengthSort { inner }
+    }
+
+    pub fn info(&self) -> SorterInfo {
+        let inner = self.inner.info();
+        SorterInfo {
+            name: "run-length",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: inner.average,
+            worst: inner.worst,
+        }
+    }
+
+    pub fn sort<T: Hash + Ord>(&self, slice: &mut [T]) {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut order = self.run_order(&slice.iter().collect::<Vec<_>>());
+        permutation::apply(slice, &mut order);
+    }
+
+    /// Sorts by a key computed once per element; only distinct keys are compared.
+    pub fn sort_by_key<T, K, F>(&self, slice: &mut [T], key: F)
+    where
+        K: Hash + Ord,
+        F: FnMut(&T) -> K,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let keys: Vec<K> = slice.iter().map(key).collect();
+        let mut order = self.run_order(&keys);
+        permutation::apply(slice, &mut order);
+    }
+
+    /// The stable order that sorts `keys`: `order[destination] = source`.
+    fn run_order<K: Hash + Ord>(&self, keys: &[K]) -> Vec<usize> {
+        // number the distinct keys by first appearance
+        let mut ids: HashMap<&K, usize> = HashMap::new();
+        let mut distinct: Vec<&K> = Vec::new();
+        let numbered: Vec<usize> = keys
+            .iter()
+            .map(|k| {
+                *ids.entry(k).or_insert_with(|| {
+                    distinct.push(k);
+                    distinct.len() - 1
+                })
+            })
+            .collect();
+        let mut by_value: Vec<usize> = (0..distinct.len()).collect();
+        self.inner
+            .sort_by(&mut by_value, |&a, &b| distinct[a].cmp(distinct[b]));
+        let mut rank = vec![0; distinct.len()];
+        for (r, &id) in by_value.iter().enumerate() {
+            rank[id] = r;
+        }
+        let ranked: Vec<usize> = numbered.into_iter().map(|id| rank[id]).collect();
+        counting_order(&ranked)
+    }
+}
+
+/// Estimates the fraction of the slice's elements that are distinct, from a sample of
+/// evenly spaced elements.
+///
+/// The lower the ratio, the more [`RunLengthSort`] gains over a comparison sort; below
+/// about 0.1 it is usually the faster choice.
+pub fn distinct_ratio<T: Hash + Eq>(slice: &[T]) -> f64 {
+    if slice.is_empty() {
+        return 0.0;
+    }
+    let step = slice.len().div_ceil(SAMPLE);
+    let sample: HashSet<&T> = slice.iter().step_by(step).collect();
+    let sampled = slice.len().div_ceil(step);
+    sample.len() as f64 / sampled as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn run_length_works() {
+        let mut tings = vec![5, 1, 4, 2, 3, 1, 5, 5];
+        RunLengthSort::new(IntroSort::default()).sort(&mut tings);
+        assert_eq!(tings, &[1, 1, 2, 3, 4, 5, 5, 5]);
+        let mut tings = vec!["b", "a", "b", "c", "a"];
+        RunLengthSort::new(HeapSort::default()).sort(&mut tings);
+        assert_eq!(tings, &["a", "a", "b", "b", "c"]);
+    }
+
+    #[test]
+    fn compares_distinct_values_only() {
+        let n = 100_000;
+        let mut tings: Vec<u64> = (0..n).map(|i| (i * 7919) % 16).collect();
+        let sorter = RunLengthSort::new(Counting::new(MergeSort::default()));
+        sorter.sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        assert!(sorter.inner.stats().comparisons < 16 * 4);
+    }
+
+    #[test]
+    fn sort_by_key_is_stable() {
+        let mut tings = vec![(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e')];
+        let sorter = RunLengthSort::new(QuickSort::default());
+        assert!(sorter.info().stable);
+        sorter.sort_by_key(&mut tings, |t| t.0);
+        assert_eq!(tings, &[(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c')]);
+    }
+
+    #[test]
+    fn distinct_ratio_estimates_duplication() {
+        assert_eq!(distinct_ratio::<u8>(&[]), 0.0);
+        assert_eq!(distinct_ratio(&[7; 10]), 0.1);
+        let few: Vec<_> = (0..100_000).map(|i| i % 10).collect();
+        assert!(distinct_ratio(&few) < 0.02);
+        let all: Vec<_> = (0..100_000).collect();
+        assert_eq!(distinct_ratio(&all), 1.0);
+    }
+}
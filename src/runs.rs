@@ -0,0 +1,174 @@
+//! Sortedness: checking whether a slice is sorted, and splitting it into the runs of
+//! order already present in it.
+//!
+//! A run is a maximal stretch of the slice that is either non-descending or strictly
+//! descending, found greedily from the left, as natural merge sort and Timsort find
+//! them. Descending runs are strict so that reversing one, which
+//! [`reverse_descending_runs`] does, never reorders equal elements.
+//!
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! use pangua::runs;
+//!
+//! let mut tings = [1, 2, 2, 9, 5, 3, 4];
+//! assert!(!runs::is_sorted(&tings));
+//! assert_eq!(runs::find_runs(&tings), [0..4, 4..6, 6..7]);
+//! assert_eq!(runs::reverse_descending_runs(&mut tings), [0..4, 4..6, 6..7]);
+//! assert_eq!(tings, [1, 2, 2, 9, 3, 5, 4]);
+//! # }
+//! ```
+
+use crate::cmp::{ByFn, ByOrd, Compare};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+#[cfg(feature = "alloc")]
+use core::ops::Range;
+
+/// Whether the slice is in non-descending order, comparing each adjacent pair at most
+/// once.
+pub fn is_sorted<T: Ord>(slice: &[T]) -> bool {
+    is_sorted_with(slice, &mut ByOrd)
+}
+
+/// Like [`is_sorted`], ordering with `compare`.
+pub fn is_sorted_by<T, F>(slice: &[T], compare: F) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    is_sorted_with(slice, &mut ByFn(compare))
+}
+
+/// Like [`is_sorted`], ordering with `cmp`.
+pub fn is_sorted_with<T, C: Compare<T>>(slice: &[T], cmp: &mut C) -> bool {
+    slice.windows(2).all(|w| !cmp.is_less(&w[1], &w[0]))
+}
+
+/// The length of the run at the start of the slice, and whether it is strictly
+/// descending. A single element is a non-descending run.
+#[cfg(feature = "alloc")]
+pub(crate) fn run_at<T, C: Compare<T>>(slice: &[T], cmp: &mut C) -> (usize, bool) {
+    if slice.len() < 2 {
+        return (slice.len(), false);
+    }
+    let mut end = 2;
+    let descending = cmp.is_less(&slice[1], &slice[0]);
+    if descending {
+        while end < slice.len() && cmp.is_less(&slice[end], &slice[end - 1]) {
+            end += 1;
+        }
+    } else {
+        while end < slice.len() && !cmp.is_less(&slice[end], &slice[end - 1]) {
+            end += 1;
+        }
+    }
+    (end, descending)
+}
+
+/// The slice's runs, in order; together they cover the slice.
+///
+/// A run of two or more elements is strictly descending exactly when its first element
+/// orders after its second.
+#[cfg(feature = "alloc")]
+pub fn find_runs<T: Ord>(slice: &[T]) -> Vec<Range<usize>> {
+    find_runs_with(slice, &mut ByOrd)
+}
+
+/// Like [`find_runs`], ordering with `compare`.
+#[cfg(feature = "alloc")]
+pub fn find_runs_by<T, F>(slice: &[T], compare: F) -> Vec<Range<usize>>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    find_runs_with(slice, &mut ByFn(compare))
+}
+
+/// Like [`find_runs`], ordering with `cmp`.
+#[cfg(feature = "alloc")]
+pub fn find_runs_with<T, C: Compare<T>>(slice: &[T], cmp: &mut C) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    while start < slice.len() {
+        let (len, _) = run_at(&slice[start..], cmp);
+        runs.push(start..start + len);
+        start += len;
+    }
+    runs
+}
+
+/// Finds the slice's runs as [`find_runs`] does and reverses the descending ones, so
+/// that every returned range is sorted.
+#[cfg(feature = "alloc")]
+pub fn reverse_descending_runs<T: Ord>(slice: &mut [T]) -> Vec<Range<usize>> {
+    reverse_descending_runs_with(slice, &mut ByOrd)
+}
+
+/// Like [`reverse_descending_runs`], ordering with `compare`.
+#[cfg(feature = "alloc")]
+pub fn reverse_descending_runs_by<T, F>(slice: &mut [T], compare: F) -> Vec<Range<usize>>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    reverse_descending_runs_with(slice, &mut ByFn(compare))
+}
+
+/// Like [`reverse_descending_runs`], ordering with `cmp`, to which the reversals are
+/// reported as moves.
+#[cfg(feature = "alloc")]
+pub fn reverse_descending_runs_with<T, C: Compare<T>>(
+    slice: &mut [T],
+    cmp: &mut C,
+) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    while start < slice.len() {
+        let len = reverse_run_at(&mut slice[start..], cmp);
+        runs.push(start..start + len);
+        start += len;
+    }
+    runs
+}
+
+/// Finds the run at the start of the slice, reversing it if strictly descending, and
+/// returns its length.
+#[cfg(feature = "alloc")]
+pub(crate) fn reverse_run_at<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) -> usize {
+    let (len, descending) = run_at(slice, cmp);
+    if descending {
+        let run = &mut slice[..len];
+        run.reverse();
+        cmp.on_move(run);
+    }
+    len
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sortedness() {
+        assert!(is_sorted::<u8>(&[]));
+        assert!(is_sorted(&[1]));
+        assert!(is_sorted(&[1, 1, 2, 3, 3]));
+        assert!(!is_sorted(&[1, 3, 2]));
+        assert!(is_sorted_by(&[3, 2, 2, 1], |a, b| b.cmp(a)));
+        assert!(!is_sorted_by(&[3, 2, 2, 1], |a: &i32, b| a.cmp(b)));
+    }
+
+    #[test]
+    fn runs_cover_the_slice() {
+        assert_eq!(find_runs::<u8>(&[]), []);
+        assert_eq!(find_runs(&[7, 1, 2]), [0..2, 2..3]);
+        // equal elements end a descending run, and start an ascending one
+        let tings = [5, 4, 4, 3, 1, 2, 8, 8, 0];
+        assert_eq!(find_runs(&tings), [0..2, 2..5, 5..8, 8..9]);
+        assert_eq!(find_runs_by(&tings, |a, b| b.cmp(a)), [0..5, 5..7, 7..9]);
+
+        let mut tings = [(3, 'a'), (2, 'b'), (2, 'c'), (1, 'd'), (0, 'e')];
+        let runs = reverse_descending_runs_by(&mut tings, |a, b| a.0.cmp(&b.0));
+        assert_eq!(runs, [0..2, 2..5]);
+        assert_eq!(tings, [(2, 'b'), (3, 'a'), (0, 'e'), (1, 'd'), (2, 'c')]);
+        assert!(runs.iter().all(|r| is_sorted(&tings[r.clone()])));
+    }
+}
@@ -0,0 +1,725 @@
+//! Order statistics: finding the element of a given rank without a full sort.
+
+use crate::cmp::{self, ByFn, ByOrd, Compare};
+use crate::partition::{Partitioner, ThreeWay};
+use crate::{heap, Sorter};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem;
+
+/// Reorders the slice so that the element at `n` is the one that would be there if the
+/// slice were sorted, with no greater elements before it and no smaller ones after, and
+/// returns it.
+///
+/// This is quickselect: partition as in quicksort, but only keep going into the side
+/// holding `n`, for linear time on average. Elements equal to the pivot are gathered
+/// together and never looked at again, so duplicate keys stay linear too. It does the
+/// job of the standard library's `select_nth_unstable`.
+///
+/// Panics if `n >= slice.len()`.
+///
+/// ```
+/// let mut tings = [9, 4, 7, 1, 3];
+/// assert_eq!(*pangua::select::quickselect(&mut tings, 3), 7);
+/// assert_eq!(tings[4], 9);
+/// ```
+pub fn quickselect<T: Ord>(slice: &mut [T], n: usize) -> &mut T {
+    quickselect_with(slice, n, &mut ByOrd);
+    &mut slice[n]
+}
+
+/// Like [`quickselect`], ordering elements with `compare`.
+pub fn quickselect_by<T, F>(slice: &mut [T], n: usize, compare: F) -> &mut T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    quickselect_with(slice, n, &mut ByFn(compare));
+    &mut slice[n]
+}
+
+/// Moves the median of the slice to the middle and returns it, or `None` if the slice
+/// is empty.
+///
+/// For even lengths this is the upper median, at index `slice.len() / 2`.
+///
+/// ```
+/// assert_eq!(pangua::select::median(&mut [5, 1, 4, 2, 3]), Some(&mut 3));
+/// assert_eq!(pangua::select::median::<u8>(&mut []), None);
+/// ```
+pub fn median<T: Ord>(slice: &mut [T]) -> Option<&mut T> {
+    if slice.is_empty() {
+        return None;
+    }
+    let mid = slice.len() / 2;
+    Some(quickselect(slice, mid))
+}
+
+/// Like [`quickselect`], ordering elements with `cmp`, and leaving the element in place.
+pub fn quickselect_with<T, C: Compare<T>>(mut slice: &mut [T], mut n: usize, cmp: &mut C) {
+    assert!(n < slice.len(), "rank {} out of range", n);
+    while slice.len() > 1 {
+        // a middle pivot avoids quadratic behaviour on presorted input
+        let mid = slice.len() / 2;
+        cmp::swap(cmp, slice, 0, mid);
+        // gathering the pivot's equals keeps runs of equal keys from going quadratic
+        let equal = ThreeWay.partition(slice, cmp);
+        let rest = core::mem::take(&mut slice);
+        if n < equal.start {
+            slice = &mut rest[..equal.start];
+        } else if n >= equal.end {
+            n -= equal.end;
+            slice = &mut rest[equal.end..];
+        } else {
+            return;
+        }
+    }
+}
+
+/// Splits the slice around the element of rank `k` (its index once sorted).
+///
+/// Returns the elements not greater than it, the element itself, and the elements not
+/// smaller than it, in no particular order within each side. This makes recursing on
+/// both halves, as when building KD-trees, straightforward.
+///
+/// Panics if `k >= slice.len()`.
+///
+/// ```
+/// let mut v = [9, 4, 7, 1, 3];
+/// let (lo, mid, hi) = pangua::select::split_by_rank(&mut v, 1);
+/// assert_eq!(*mid, 3);
+/// assert_eq!(lo, &[1]);
+/// assert_eq!(hi.len(), 3);
+/// ```
+pub fn split_by_rank<T: Ord>(slice: &mut [T], k: usize) -> (&mut [T], &mut T, &mut [T]) {
+    split_by_rank_with(slice, k, &mut ByOrd)
+}
+
+/// Like [`split_by_rank`], ordering elements with `compare`.
+pub fn split_by_rank_by<T, F>(slice: &mut [T], k: usize, compare: F) -> (&mut [T], &mut T, &mut [T])
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    split_by_rank_with(slice, k, &mut ByFn(compare))
+}
+
+/// Splits the slice around its median.
+///
+/// For even lengths this is the upper median, at index `slice.len() / 2`.
+/// Panics if the slice is empty.
+pub fn split_by_median<T: Ord>(slice: &mut [T]) -> (&mut [T], &mut T, &mut [T]) {
+    let k = slice.len() / 2;
+    split_by_rank(slice, k)
+}
+
+/// Finds the elements of ranks `k1` and `k2` together, as for the quartiles of an
+/// interquartile range.
+///
+/// Reorders the slice so that both elements are where they would be if it were sorted,
+/// with no greater elements before `k1`, none outside their bounds between the two, and
+/// no smaller ones after `k2`. Partitioning around two pivots at once, as dual-pivot
+/// quicksort does, splits the two ranks apart in a single pass where two quickselects
+/// would each scan the slice.
+///
+/// Panics unless `k1 <= k2 < slice.len()`.
+///
+/// ```
+/// let mut tings: Vec<u32> = (0..100).rev().collect();
+/// let (q1, q3) = pangua::select::select_two(&mut tings, 25, 75);
+/// assert_eq!((*q1, *q3), (25, 75));
+/// assert!(tings[26..75].iter().all(|x| (25..=75).contains(x)));
+/// ```
+pub fn select_two<T: Ord>(slice: &mut [T], k1: usize, k2: usize) -> (&T, &T) {
+    select_two_with(slice, k1, k2, &mut ByOrd)
+}
+
+/// Like [`select_two`], ordering elements with `compare`.
+pub fn select_two_by<T, F>(slice: &mut [T], k1: usize, k2: usize, compare: F) -> (&T, &T)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    select_two_with(slice, k1, k2, &mut ByFn(compare))
+}
+
+fn select_two_with<'a, T, C: Compare<T>>(
+    slice: &'a mut [T],
+    k1: usize,
+    k2: usize,
+    cmp: &mut C,
+) -> (&'a T, &'a T) {
+    assert!(
+        k1 <= k2 && k2 < slice.len(),
+        "ranks {} and {} out of range",
+        k1,
+        k2
+    );
+    let (mut rest, mut i, mut j) = (&mut *slice, k1, k2);
+    loop {
+        if i == j {
+            quickselect_with(rest, i, cmp);
+            break;
+        }
+        let (lt, gt) = dual_partition(rest, cmp);
+        let part = mem::take(&mut rest);
+        // p at `lt` and q at `gt` are in place: smaller than p before, greater than q
+        // after, and part[lt..a] and part[b..=gt] hold the elements equal to p and q
+        let (a, b) = squeeze_equal(part, lt, gt, cmp);
+        let region = |k: usize| match k {
+            _ if k < lt => Some(0),
+            _ if k >= a && k < b => Some(1),
+            _ if k > gt => Some(2),
+            _ => None,
+        };
+        match (region(i), region(j)) {
+            (Some(0), Some(0)) => rest = &mut part[..lt],
+            (Some(1), Some(1)) => {
+                rest = &mut part[a..b];
+                i -= a;
+                j -= a;
+            }
+            (Some(2), Some(2)) => {
+                rest = &mut part[gt + 1..];
+                i -= gt + 1;
+                j -= gt + 1;
+            }
+            _ => {
+                // the ranks went separate ways: finish each with a plain quickselect
+                for k in [i, j] {
+                    let (start, end) = match region(k) {
+                        None => continue,
+                        Some(0) => (0, lt),
+                        Some(1) => (a, b),
+                        Some(_) => (gt + 1, part.len()),
+                    };
+                    quickselect_with(&mut part[start..end], k - start, cmp);
+                }
+                break;
+            }
+        }
+    }
+    (&slice[k1], &slice[k2])
+}
+
+/// Partitions around pivots p ≤ q taken from a third and two thirds of the way along:
+/// elements less than p, then p, then those between, then q, then those greater than q.
+/// Returns the final positions of p and q.
+fn dual_partition<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) -> (usize, usize) {
+    let n = slice.len();
+    cmp::swap(cmp, slice, 0, n / 3);
+    cmp::swap(cmp, slice, n - 1, 2 * n / 3);
+    if cmp.is_less(&slice[n - 1], &slice[0]) {
+        cmp::swap(cmp, slice, 0, n - 1);
+    }
+    // [p | < p | p..=q | unseen | > q | q]
+    let (mut lt, mut k, mut gt) = (1, 1, n - 1);
+    while k < gt {
+        if cmp.is_less(&slice[k], &slice[0]) {
+            cmp::swap(cmp, slice, k, lt);
+            lt += 1;
+            k += 1;
+        } else if cmp.is_less(&slice[n - 1], &slice[k]) {
+            gt -= 1;
+            cmp::swap(cmp, slice, k, gt);
+        } else {
+            k += 1;
+        }
+    }
+    cmp::swap(cmp, slice, 0, lt - 1);
+    cmp::swap(cmp, slice, n - 1, gt);
+    (lt - 1, gt)
+}
+
+/// Narrows the elements between the pivots p at `lt` and q at `gt` of a
+/// [`dual_partition`] to those strictly between them, returning their range.
+///
+/// When p = q everything between is equal and the range is empty. Otherwise, if the
+/// middle holds more than half the slice, as it does when most keys equal a pivot, the
+/// elements equal to p are moved to its front and those equal to q to its back.
+fn squeeze_equal<T, C: Compare<T>>(
+    part: &mut [T],
+    lt: usize,
+    gt: usize,
+    cmp: &mut C,
+) -> (usize, usize) {
+    if !cmp.is_less(&part[lt], &part[gt]) {
+        return (gt + 1, gt + 1);
+    }
+    let (mut a, mut k, mut b) = (lt + 1, lt + 1, gt);
+    if b - a <= part.len() / 2 {
+        return (a, b);
+    }
+    // [p | = p | p < x < q | unseen | = q | q]
+    while k < b {
+        if !cmp.is_less(&part[lt], &part[k]) {
+            cmp::swap(cmp, part, k, a);
+            a += 1;
+            k += 1;
+        } else if !cmp.is_less(&part[k], &part[gt]) {
+            b -= 1;
+            cmp::swap(cmp, part, k, b);
+        } else {
+            k += 1;
+        }
+    }
+    (a, b)
+}
+
+/// Like [`split_by_rank`], ordering elements by the key `f` extracts.
+///
+/// The key is recomputed on every comparison, as `sort_by_key` does.
+pub fn select_nth_by_key<T, K, F>(
+    slice: &mut [T],
+    n: usize,
+    mut f: F,
+) -> (&mut [T], &mut T, &mut [T])
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    split_by_rank_by(slice, n, |a, b| f(a).cmp(&f(b)))
+}
+
+/// Arranges the slice as an implicit, balanced KD-tree.
+///
+/// The median along axis 0 is placed in the middle of the slice, then each half is
+/// recursively split at its own median along the next axis, cycling through `axes`
+/// axes. `key(x, axis)` returns the coordinate of `x` along `axis`.
+///
+/// ```
+/// let mut points = [(1, 9), (2, 3), (7, 1), (4, 6), (8, 8)];
+/// pangua::select::kd_arrange(&mut points, 2, |p, axis| if axis == 0 { p.0 } else { p.1 });
+/// // the root splits on x, its children on y
+/// assert_eq!(points[2], (4, 6));
+/// assert!(points[..2].iter().all(|p| p.0 <= 4));
+/// ```
+pub fn kd_arrange<T, K, F>(slice: &mut [T], axes: usize, mut key: F)
+where
+    K: Ord,
+    F: FnMut(&T, usize) -> K,
+{
+    assert!(axes > 0, "at least one axis is required");
+    kd_arrange_level(slice, 0, axes, &mut key)
+}
+
+fn kd_arrange_level<T, K, F>(slice: &mut [T], depth: usize, axes: usize, key: &mut F)
+where
+    K: Ord,
+    F: FnMut(&T, usize) -> K,
+{
+    if slice.len() <= 1 {
+        return;
+    }
+    let axis = depth % axes;
+    let mid = slice.len() / 2;
+    quickselect_with(
+        slice,
+        mid,
+        &mut ByFn(|a: &T, b: &T| key(a, axis).cmp(&key(b, axis))),
+    );
+    let (left, right) = slice.split_at_mut(mid);
+    kd_arrange_level(left, depth + 1, axes, key);
+    kd_arrange_level(&mut right[1..], depth + 1, axes, key);
+}
+
+/// How [`partial_sort`] gathers the smallest elements at the front.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartialStrategy {
+    /// Keeps a max-heap of the k smallest elements seen so far while scanning the rest,
+    /// in O(n log k). Best when k is a small fraction of the slice, since most elements
+    /// take a single comparison against the heap's root.
+    Heap,
+    /// Quickselects the element of rank k - 1, in O(n) on average.
+    Select,
+}
+
+impl PartialStrategy {
+    /// Picks the heap for at most one element in 64, quickselect otherwise.
+    pub fn choose(len: usize, k: usize) -> Self {
+        if k <= len / 64 {
+            PartialStrategy::Heap
+        } else {
+            PartialStrategy::Select
+        }
+    }
+}
+
+/// Places the `k` smallest elements at the front of the slice, sorted by `sorter`,
+/// leaving the rest in no particular order.
+///
+/// The strategy is picked by [`PartialStrategy::choose`]. A `k` beyond the length
+/// sorts the whole slice.
+///
+/// ```
+/// use pangua::select::partial_sort;
+/// use pangua::InsertionSort;
+///
+/// let mut tings = [9, 4, 7, 1, 8, 3, 5];
+/// partial_sort(&mut tings, 3, InsertionSort { smart: true });
+/// assert_eq!(tings[..3], [1, 3, 4]);
+/// ```
+pub fn partial_sort<T: Ord, S: Sorter>(slice: &mut [T], k: usize, sorter: S) {
+    let strategy = PartialStrategy::choose(slice.len(), k);
+    partial_sort_with(slice, k, sorter, strategy, &mut ByOrd)
+}
+
+/// Like [`partial_sort`], ordering elements with `compare`.
+pub fn partial_sort_by<T, S, F>(slice: &mut [T], k: usize, sorter: S, compare: F)
+where
+    S: Sorter,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let strategy = PartialStrategy::choose(slice.len(), k);
+    partial_sort_with(slice, k, sorter, strategy, &mut ByFn(compare))
+}
+
+/// Like [`partial_sort`], with the given strategy and comparator.
+pub fn partial_sort_with<T, S, C>(
+    slice: &mut [T],
+    k: usize,
+    sorter: S,
+    strategy: PartialStrategy,
+    cmp: &mut C,
+) where
+    S: Sorter,
+    C: Compare<T>,
+{
+    let k = k.min(slice.len());
+    if k == 0 {
+        return;
+    }
+    if k < slice.len() {
+        match strategy {
+            PartialStrategy::Heap => heap_select(slice, k, cmp),
+            PartialStrategy::Select => quickselect_with(slice, k - 1, cmp),
+        }
+    }
+    sorter.sort_with(&mut slice[..k], cmp);
+}
+
+/// Moves the `k` smallest elements to the front, `0 < k < slice.len()`.
+fn heap_select<T, C: Compare<T>>(slice: &mut [T], k: usize, cmp: &mut C) {
+    if k > 1 {
+        heap::heapify(&mut slice[..k], cmp);
+    }
+    for i in k..slice.len() {
+        if cmp.is_less(&slice[i], &slice[0]) {
+            cmp::swap(cmp, slice, 0, i);
+            if k > 1 {
+                heap::sift_down(&mut slice[..k], 0, cmp);
+            }
+        }
+    }
+}
+
+/// Iterates over the elements of the slice in sorted order, sorting only as far as the
+/// iteration goes.
+///
+/// References to the elements are heapified once, in O(n), and each element yielded
+/// costs O(log n), so stopping after k elements costs O(n + k log n) and the slice is
+/// left untouched. Prefer this when the number of elements needed is not known up
+/// front. When it is, [`partial_sort`] costs O(n + k log k), and once more than about a
+/// quarter of the slice is consumed, sorting it outright is usually faster than the
+/// heap's scattered accesses. Equal elements come out in no particular order.
+///
+/// ```
+/// let tings = [5, 1, 4, 2, 3];
+/// let mut sorted = pangua::select::iter_sorted_lazy(&tings);
+/// assert_eq!(sorted.next(), Some(&1));
+/// assert_eq!(sorted.next(), Some(&2));
+/// assert_eq!(sorted.len(), 3);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn iter_sorted_lazy<T: Ord>(slice: &[T]) -> LazySorted<'_, T, ByOrd> {
+    LazySorted::new(slice, ByOrd)
+}
+
+/// Like [`iter_sorted_lazy`], ordering elements with `compare`.
+#[cfg(feature = "alloc")]
+pub fn iter_sorted_lazy_by<T, F>(slice: &[T], compare: F) -> LazySorted<'_, T, ByFn<F>>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    LazySorted::new(slice, ByFn(compare))
+}
+
+/// The elements of a slice in sorted order, from [`iter_sorted_lazy`].
+#[cfg(feature = "alloc")]
+pub struct LazySorted<'a, T, C> {
+    /// A heap whose root is the smallest element not yet yielded.
+    heap: Vec<&'a T>,
+    cmp: SmallestFirst<C>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, C: Compare<T>> LazySorted<'a, T, C> {
+    fn new(slice: &'a [T], cmp: C) -> Self {
+        let mut heap: Vec<&T> = slice.iter().collect();
+        let mut cmp = SmallestFirst(cmp);
+        if heap.len() > 1 {
+            heap::heapify(&mut heap, &mut cmp);
+        }
+        LazySorted { heap, cmp }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, C: Compare<T>> Iterator for LazySorted<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let last = self.heap.len().checked_sub(1)?;
+        self.heap.swap(0, last);
+        let smallest = self.heap.pop();
+        if last > 1 {
+            heap::sift_down(&mut self.heap, 0, &mut self.cmp);
+        }
+        smallest
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, C: Compare<T>> ExactSizeIterator for LazySorted<'_, T, C> {}
+
+/// Orders references in reverse of their targets, turning heap sort's max-heap into a
+/// min-heap.
+#[cfg(feature = "alloc")]
+struct SmallestFirst<C>(C);
+
+#[cfg(feature = "alloc")]
+impl<T, C: Compare<T>> Compare<&T> for SmallestFirst<C> {
+    fn compare(&mut self, a: &&T, b: &&T) -> Ordering {
+        self.0.compare(b, a)
+    }
+}
+
+fn split_by_rank_with<'a, T, C: Compare<T>>(
+    slice: &'a mut [T],
+    k: usize,
+    cmp: &mut C,
+) -> (&'a mut [T], &'a mut T, &'a mut [T]) {
+    quickselect_with(slice, k, cmp);
+    let (left, rest) = slice.split_at_mut(k);
+    let (mid, right) = rest.split_first_mut().expect("rank is in range");
+    (left, mid, right)
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::{Counter, HeapSort};
+    use alloc::vec;
+
+    #[test]
+    fn quickselect_works() {
+        let tings: Vec<u32> = (0..500).map(|i| (i * 7919) % 500 % 97).collect();
+        let mut sorted = tings.clone();
+        sorted.sort();
+        for n in [0, 1, 250, 498, 499] {
+            let mut tings = tings.clone();
+            let nth = *quickselect(&mut tings, n);
+            assert_eq!(nth, sorted[n]);
+            assert!(tings[..n].iter().all(|x| *x <= nth));
+            assert!(tings[n..].iter().all(|x| *x >= nth));
+        }
+        let mut tings = vec![1, 5, 2, 4, 3];
+        assert_eq!(*quickselect_by(&mut tings, 0, |a, b| b.cmp(a)), 5);
+        assert_eq!(median(&mut [4, 1, 3, 2]), Some(&mut 3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn quickselect_rejects_ranks_out_of_range() {
+        quickselect(&mut [1, 2, 3], 3);
+    }
+
+    #[test]
+    fn split_by_rank_works() {
+        for k in 0..8 {
+            let mut tings = vec![5, 1, 4, 2, 3, 0, 7, 6];
+            let (left, mid, right) = split_by_rank(&mut tings, k);
+            assert_eq!(*mid, k);
+            assert!(left.iter().all(|x| *x < k));
+            assert!(right.iter().all(|x| *x > k));
+        }
+    }
+
+    #[test]
+    fn partial_sort_works() {
+        let tings: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        for &strategy in &[PartialStrategy::Heap, PartialStrategy::Select] {
+            for &k in &[0, 1, 2, 10, 999, 1000, 5000] {
+                let mut partial = tings.clone();
+                partial_sort_with(&mut partial, k, HeapSort::default(), strategy, &mut ByOrd);
+                let k = k.min(1000);
+                assert!(partial[..k].iter().copied().eq(0..k as u32), "k = {}", k);
+                let mut rest = partial[k..].to_vec();
+                rest.sort();
+                assert!(rest.into_iter().eq(k as u32..1000));
+            }
+        }
+        let mut tings = vec![(1, 'a'), (3, 'b'), (2, 'c'), (4, 'd')];
+        partial_sort_by(&mut tings, 2, crate::MergeSort::default(), |a, b| {
+            b.0.cmp(&a.0)
+        });
+        assert_eq!(tings[..2], [(4, 'd'), (3, 'b')]);
+        assert_eq!(
+            PartialStrategy::choose(10_000_000, 100),
+            PartialStrategy::Heap
+        );
+        assert_eq!(PartialStrategy::choose(100, 50), PartialStrategy::Select);
+    }
+
+    #[test]
+    fn iter_sorted_lazy_works() {
+        let tings: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        assert!(iter_sorted_lazy(&tings).copied().eq(0..1000));
+        let top: Vec<_> = iter_sorted_lazy_by(&tings, |a, b| b.cmp(a))
+            .take(3)
+            .collect();
+        assert_eq!(top, &[&999, &998, &997]);
+        assert_eq!(iter_sorted_lazy::<u8>(&[]).next(), None);
+        assert!(iter_sorted_lazy(&[7]).eq(&[7]));
+        assert!(iter_sorted_lazy(&[2, 1, 2, 1]).eq(&[1, 1, 2, 2]));
+    }
+
+    fn check_two(tings: &[u32], k1: usize, k2: usize) {
+        let mut sorted = tings.to_vec();
+        sorted.sort();
+        let mut tings = tings.to_vec();
+        let (a, b) = select_two(&mut tings, k1, k2);
+        assert_eq!(
+            (*a, *b),
+            (sorted[k1], sorted[k2]),
+            "ranks {} and {}",
+            k1,
+            k2
+        );
+        assert!(tings[..k1].iter().all(|x| *x <= sorted[k1]));
+        assert!(tings[k1..=k2]
+            .iter()
+            .all(|x| (sorted[k1]..=sorted[k2]).contains(x)));
+        assert!(tings[k2..].iter().all(|x| *x >= sorted[k2]));
+    }
+
+    #[test]
+    fn select_two_works() {
+        let distinct: Vec<u32> = (0..200).map(|i| (i * 7919) % 200).collect();
+        let skewed: Vec<u32> = (0..200).map(|i| (i * 7919) % 200 % 3).collect();
+        for tings in [distinct, skewed, vec![4; 50], (0..50).collect()] {
+            let n = tings.len();
+            for &(k1, k2) in &[
+                (0, 0),
+                (0, n - 1),
+                (n / 4, 3 * n / 4),
+                (3, 4),
+                (n - 2, n - 1),
+            ] {
+                check_two(&tings, k1, k2);
+            }
+        }
+        let small: Vec<u32> = (0..30).map(|i| (i * 17) % 30 % 11).collect();
+        for k2 in 0..30 {
+            for k1 in 0..=k2 {
+                check_two(&small, k1, k2);
+            }
+        }
+        let mut tings = vec![1, 5, 2, 4, 3];
+        assert_eq!(select_two_by(&mut tings, 0, 4, |a, b| b.cmp(a)), (&5, &1));
+    }
+
+    #[test]
+    fn split_by_median_works() {
+        let mut tings: Vec<_> = (0..101).rev().collect();
+        let (left, mid, right) = split_by_median(&mut tings);
+        assert_eq!(*mid, 50);
+        assert_eq!(left.len(), 50);
+        assert_eq!(right.len(), 50);
+    }
+
+    #[test]
+    fn handles_duplicates() {
+        let mut tings = vec![2, 2, 1, 2, 2, 3, 2];
+        let (left, mid, right) = split_by_median(&mut tings);
+        assert_eq!(*mid, 2);
+        assert!(left.iter().all(|x| *x <= 2));
+        assert!(right.iter().all(|x| *x >= 2));
+    }
+
+    #[test]
+    fn duplicate_keys_stay_linear() {
+        let n = 10_000;
+        let all_equal = vec![7u32; n];
+        let two_valued: Vec<u32> = (0..n as u32).map(|i| i % 2).collect();
+        for tings in [all_equal, two_valued] {
+            for k in [0, n / 4, n / 2, n - 1] {
+                let mut tings = tings.clone();
+                let mut counter = Counter::new(ByOrd);
+                quickselect_with(&mut tings, k, &mut counter);
+                assert!(counter.stats().comparisons < 4 * n);
+                assert!(tings[..k].iter().all(|x| *x <= tings[k]));
+                assert!(tings[k..].iter().all(|x| *x >= tings[k]));
+
+                let mut counter = Counter::new(ByOrd);
+                select_two_with(&mut tings, k / 2, k, &mut counter);
+                assert!(counter.stats().comparisons < 4 * n);
+
+                let mut counter = Counter::new(ByOrd);
+                partial_sort_with(
+                    &mut tings,
+                    k + 1,
+                    HeapSort::default(),
+                    PartialStrategy::Select,
+                    &mut counter,
+                );
+                assert!(tings[..=k].windows(2).all(|w| w[0] <= w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn split_by_rank_by_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        let (_, mid, right) = split_by_rank_by(&mut tings, 0, |a, b| b.cmp(a));
+        assert_eq!(*mid, 5);
+        assert_eq!(right.len(), 4);
+    }
+
+    #[test]
+    fn select_nth_by_key_works() {
+        let mut tings = vec![(1, 'e'), (2, 'a'), (3, 'd'), (4, 'b'), (5, 'c')];
+        let (_, mid, _) = select_nth_by_key(&mut tings, 1, |t| t.1);
+        assert_eq!(*mid, (4, 'b'));
+    }
+
+    fn check_kd(points: &[[i32; 3]], depth: usize) {
+        if points.len() <= 1 {
+            return;
+        }
+        let axis = depth % 3;
+        let mid = points.len() / 2;
+        let root = points[mid][axis];
+        assert!(points[..mid].iter().all(|p| p[axis] <= root));
+        assert!(points[mid + 1..].iter().all(|p| p[axis] >= root));
+        check_kd(&points[..mid], depth + 1);
+        check_kd(&points[mid + 1..], depth + 1);
+    }
+
+    #[test]
+    fn kd_arrange_works() {
+        let mut points: Vec<[i32; 3]> = (0..50)
+            .map(|i| [(i * 7) % 13, (i * 11) % 17, (i * 5) % 19])
+            .collect();
+        kd_arrange(&mut points, 3, |p, axis| p[axis]);
+        check_kd(&points, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rank_out_of_range_panics() {
+        split_by_rank(&mut [1, 2, 3], 3);
+    }
+}
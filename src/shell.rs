@@ -0,0 +1,302 @@
+use crate::cmp::{self, Compare};
+use crate::{is_trivially_sorted, Complexity, Counting, Sorter, SorterInfo};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Gaps used by [`ShellSort`], from which the passes are derived for a given length.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum GapSequence {
+    /// Shell's original sequence: n/2, n/4, ..., 1. Quadratic in the worst case.
+    #[default]
+    Shell,
+    /// Knuth's (3^k - 1) / 2: 1, 4, 13, 40, ..., up to n/3. O(n^1.5) in the worst case.
+    Knuth,
+    /// Ciura's empirically found 1, 4, 10, 23, 57, 132, 301, 701, 1750, extended by
+    /// a factor of 2.25 for longer slices. Among the best known in practice.
+    Ciura,
+    /// Tokuda's ceil((9 (9/4)^k - 4) / 5): 1, 4, 9, 20, 46, 103, ...
+    Tokuda,
+    /// Any set of gaps. Gaps not smaller than the slice are skipped, and a final pass
+    /// with gap 1 is always made so the result is sorted.
+    Custom(Vec<usize>),
+}
+
+impl GapSequence {
+    /// The gaps to use for a slice of length `n`, largest first, ending with 1.
+    pub fn gaps(&self, n: usize) -> Vec<usize> {
+        let mut gaps = match self {
+            GapSequence::Shell => {
+                let mut gaps = Vec::new();
+                let mut gap = n / 2;
+                while gap > 0 {
+                    gaps.push(gap);
+                    gap /= 2;
+                }
+                gaps
+            }
+            GapSequence::Knuth => {
+                let mut gaps = vec![1];
+                let mut gap = 4;
+                while gap <= n / 3 {
+                    gaps.push(gap);
+                    gap = 3 * gap + 1;
+                }
+                gaps
+            }
+            GapSequence::Ciura => {
+                let mut gaps: Vec<usize> = vec![1, 4, 10, 23, 57, 132, 301, 701, 1750];
+                while *gaps.last().expect("sequence is non-empty") < n {
+                    let next =
+                        (*gaps.last().expect("sequence is non-empty") as f64 * 2.25) as usize;
+                    gaps.push(next);
+                }
+                gaps.retain(|&g| g < n);
+                gaps
+            }
+            GapSequence::Tokuda => {
+                let mut gaps = Vec::new();
+                let mut term = 1.0f64;
+                loop {
+                    // the ceiling, without `f64::ceil`, which needs std
+                    let exact = (9.0 * term - 4.0) / 5.0;
+                    let gap = exact as usize + usize::from((exact as usize as f64) < exact);
+                    if gap >= n && !gaps.is_empty() {
+                        break gaps;
+                    }
+                    gaps.push(gap);
+                    term *= 2.25;
+                }
+            }
+            GapSequence::Custom(gaps) => gaps.iter().copied().filter(|&g| g > 0 && g < n).collect(),
+        };
+        gaps.sort_unstable_by(|a, b| b.cmp(a));
+        gaps.dedup();
+        if gaps.last() != Some(&1) {
+            gaps.push(1);
+        }
+        gaps
+    }
+}
+
+/// Shell Sort
+///
+/// Shellsort is a generalization of insertion sort that allows the exchange of items
+/// that are far apart. It sorts pairs of elements far apart from each other, then
+/// progressively reduces the gap between elements to be compared. Its running time
+/// depends heavily on the gap sequence used.
+#[derive(Debug, Clone, Default)]
+pub struct ShellSort {
+    pub gaps: GapSequence,
+}
+
+impl Sorter for ShellSort {
+    fn info(&self) -> SorterInfo {
+        let worst = match self.gaps {
+            GapSequence::Knuth => Complexity::PowerThreeHalves,
+            // no better bound is proven for the others
+            _ => Complexity::Quadratic,
+        };
+        SorterInfo {
+            name: "shell",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linearithmic,
+            average: worst,
+            worst,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        for gap in self.gaps.gaps(slice.len()) {
+            // gapped insertion sort
+            for unsorted in gap..slice.len() {
+                let mut i = unsorted;
+                while i >= gap && cmp.is_less(&slice[i], &slice[i - gap]) {
+                    cmp::swap(cmp, slice, i - gap, i);
+                    i -= gap;
+                }
+            }
+        }
+    }
+}
+
+/// Searches for a gap sequence that sorts data like `samples` with few comparisons.
+///
+/// Starts from the best geometric sequence `1, 1 + r, 1 + r + r^2, ...` over a range of
+/// ratios `r`, then hill-climbs: each gap is nudged up and down, keeping changes that
+/// lower the total number of comparisons needed to sort all samples, for at most
+/// `rounds` passes over the gaps. Comparisons are counted rather than timed, so the
+/// search is deterministic.
+///
+/// The samples should be representative of the data to sort, including its length.
+pub fn search_gaps<T: Ord + Clone>(samples: &[&[T]], rounds: usize) -> GapSequence {
+    let n = samples.iter().map(|s| s.len()).max().unwrap_or(0);
+    let cost = |gaps: &[usize]| -> usize {
+        let sorter = Counting::new(ShellSort {
+            gaps: GapSequence::Custom(gaps.to_vec()),
+        });
+        samples
+            .iter()
+            .map(|sample| {
+                sorter.sort(&mut sample.to_vec());
+                sorter.stats().comparisons
+            })
+            .sum()
+    };
+
+    let mut best = geometric(2.0, n);
+    let mut best_cost = cost(&best);
+    for step in 1..=16 {
+        let candidate = geometric(2.0 + step as f64 / 8.0, n);
+        let candidate_cost = cost(&candidate);
+        if candidate_cost < best_cost {
+            best = candidate;
+            best_cost = candidate_cost;
+        }
+    }
+
+    for _ in 0..rounds {
+        let mut improved = false;
+        // the final gap of 1 is required
+        for i in 1..best.len() {
+            let step = (best[i] / 8).max(1);
+            for candidate in [best[i] + step, best[i] - step] {
+                let lower = best[i - 1];
+                let upper = best.get(i + 1).copied().unwrap_or(usize::MAX);
+                if candidate <= lower || candidate >= upper {
+                    continue;
+                }
+                let mut gaps = best.clone();
+                gaps[i] = candidate;
+                let gaps_cost = cost(&gaps);
+                if gaps_cost < best_cost {
+                    best = gaps;
+                    best_cost = gaps_cost;
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    GapSequence::Custom(best)
+}
+
+/// Increasing gaps `1, 1 + r, 1 + r + r^2, ...` below `n`.
+fn geometric(ratio: f64, n: usize) -> Vec<usize> {
+    let mut gaps = vec![1];
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    loop {
+        term *= ratio;
+        sum += term;
+        let gap = (sum + 0.5) as usize;
+        if gap >= n {
+            return gaps;
+        }
+        if gap > *gaps.last().expect("gaps start with 1") {
+            gaps.push(gap);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudo_random(n: usize, seed: u64) -> Vec<u32> {
+        let mut x = seed;
+        (0..n)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x % 1000) as u32
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shell_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        ShellSort::default().sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn every_sequence_sorts() {
+        let sequences = vec![
+            GapSequence::Shell,
+            GapSequence::Knuth,
+            GapSequence::Ciura,
+            GapSequence::Tokuda,
+            GapSequence::Custom(vec![9, 3]),
+        ];
+        for gaps in sequences {
+            for &n in &[0, 1, 2, 10, 100, 5000] {
+                let mut tings = pseudo_random(n, n as u64 + 1);
+                ShellSort { gaps: gaps.clone() }.sort(&mut tings);
+                assert!(
+                    tings.windows(2).all(|w| w[0] <= w[1]),
+                    "{:?}, n = {}",
+                    gaps,
+                    n
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sequences_match_definitions() {
+        assert_eq!(GapSequence::Knuth.gaps(200), &[40, 13, 4, 1]);
+        assert_eq!(GapSequence::Ciura.gaps(100), &[57, 23, 10, 4, 1]);
+        assert_eq!(
+            GapSequence::Ciura.gaps(5000),
+            &[3937, 1750, 701, 301, 132, 57, 23, 10, 4, 1]
+        );
+        assert_eq!(GapSequence::Tokuda.gaps(250), &[233, 103, 46, 20, 9, 4, 1]);
+    }
+
+    #[test]
+    fn custom_gaps_end_with_one() {
+        let gaps = GapSequence::Custom(vec![4, 100, 0, 10]);
+        assert_eq!(gaps.gaps(50), &[10, 4, 1]);
+        assert_eq!(GapSequence::Shell.gaps(9), &[4, 2, 1]);
+    }
+
+    #[test]
+    fn custom_gaps_sort() {
+        let mut tings = pseudo_random(300, 3);
+        ShellSort {
+            gaps: GapSequence::Custom(vec![57, 23, 10, 4]),
+        }
+        .sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn search_improves_on_shell() {
+        let a = pseudo_random(200, 1);
+        let b = pseudo_random(200, 2);
+        let samples: Vec<&[u32]> = vec![&a, &b];
+        let found = search_gaps(&samples, 4);
+
+        let comparisons = |gaps: GapSequence| {
+            let sorter = Counting::new(ShellSort { gaps });
+            sorter.sort(&mut a.clone());
+            sorter.stats().comparisons
+        };
+        assert!(comparisons(found.clone()) <= comparisons(GapSequence::Shell));
+
+        let mut tings = pseudo_random(200, 9);
+        ShellSort { gaps: found }.sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
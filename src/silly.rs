@@ -0,0 +1,232 @@
+//! Sorts that are famously bad on purpose, for demonstrations and worst-case benchmarks.
+//!
+//! Both rely on luck, so each gives up after a configurable number of attempts instead
+//! of possibly running forever.
+
+use crate::cmp::{self, ByOrd, Compare};
+use crate::rng::{self, Rng};
+use crate::runs::is_sorted_with;
+use crate::{is_trivially_sorted, Complexity, Error, Sorter, SorterInfo};
+
+/// Bogo Sort
+///
+/// Bogosort shuffles the slice until it happens to be sorted, taking n! shuffles on
+/// average for distinct elements. After `max_shuffles` unlucky shuffles it gives up,
+/// leaving the slice in some order; sorting through the [`Sorter`] trait then panics,
+/// while [`BogoSort::try_sort`] returns [`Error::GaveUp`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BogoSort {
+    pub max_shuffles: usize,
+    /// Seeds the shuffles, so sorts are reproducible.
+    pub seed: u64,
+}
+
+impl Default for BogoSort {
+    fn default() -> Self {
+        BogoSort {
+            max_shuffles: 1_000_000,
+            seed: 0,
+        }
+    }
+}
+
+impl BogoSort {
+    /// Sorts the slice, or fails if it is still unsorted after `max_shuffles` shuffles.
+    pub fn try_sort<T: Ord>(&self, slice: &mut [T]) -> Result<(), Error> {
+        self.try_sort_with(slice, &mut ByOrd)
+    }
+
+    fn try_sort_with<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) -> Result<(), Error> {
+        if is_trivially_sorted(slice) {
+            return Ok(());
+        }
+        let mut rng = Rng::new(self.seed);
+        for _ in 0..self.max_shuffles {
+            if is_sorted_with(slice, cmp) {
+                return Ok(());
+            }
+            let len = slice.len();
+            rng::sample(slice, len, &mut rng, cmp);
+        }
+        if is_sorted_with(slice, cmp) {
+            return Ok(());
+        }
+        Err(Error::GaveUp {
+            attempts: self.max_shuffles,
+        })
+    }
+}
+
+impl Sorter for BogoSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "bogo",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linear,
+            average: Complexity::Factorial,
+            worst: Complexity::Factorial,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if let Err(e) = self.try_sort_with(slice, cmp) {
+            panic!("{}", e)
+        }
+    }
+}
+
+/// Bozo Sort
+///
+/// Bozosort swaps two random elements at a time until the slice happens to be sorted.
+/// After `max_swaps` unlucky swaps it gives up, leaving the slice in some order; sorting
+/// through the [`Sorter`] trait then panics, while [`BozoSort::try_sort`] returns
+/// [`Error::GaveUp`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BozoSort {
+    pub max_swaps: usize,
+    /// Seeds the swaps, so sorts are reproducible.
+    pub seed: u64,
+}
+
+impl Default for BozoSort {
+    fn default() -> Self {
+        BozoSort {
+            max_swaps: 1_000_000,
+            seed: 0,
+        }
+    }
+}
+
+impl BozoSort {
+    /// Sorts the slice, or fails if it is still unsorted after `max_swaps` swaps.
+    pub fn try_sort<T: Ord>(&self, slice: &mut [T]) -> Result<(), Error> {
+        self.try_sort_with(slice, &mut ByOrd)
+    }
+
+    fn try_sort_with<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) -> Result<(), Error> {
+        if is_trivially_sorted(slice) {
+            return Ok(());
+        }
+        let mut rng = Rng::new(self.seed);
+        for _ in 0..self.max_swaps {
+            if is_sorted_with(slice, cmp) {
+                return Ok(());
+            }
+            let (a, b) = (rng.below(slice.len()), rng.below(slice.len()));
+            if a != b {
+                cmp::swap(cmp, slice, a, b);
+            }
+        }
+        if is_sorted_with(slice, cmp) {
+            return Ok(());
+        }
+        Err(Error::GaveUp {
+            attempts: self.max_swaps,
+        })
+    }
+}
+
+impl Sorter for BozoSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "bozo",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linear,
+            average: Complexity::Factorial,
+            worst: Complexity::Factorial,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if let Err(e) = self.try_sort_with(slice, cmp) {
+            panic!("{}", e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counting;
+
+    #[test]
+    fn bogo_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        BogoSort::default().sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for seed in 0..20 {
+            let mut tings = vec![3, 1, 2, 1];
+            let sorter = BogoSort {
+                max_shuffles: 10_000,
+                seed,
+            };
+            assert_eq!(sorter.try_sort(&mut tings), Ok(()));
+            assert_eq!(tings, &[1, 1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn bozo_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        BozoSort::default().sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for seed in 0..20 {
+            let mut tings = vec![3, 1, 2, 1];
+            let sorter = BozoSort {
+                max_swaps: 10_000,
+                seed,
+            };
+            assert_eq!(sorter.try_sort(&mut tings), Ok(()));
+            assert_eq!(tings, &[1, 1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn sorted_input_is_checked_once() {
+        let sorter = Counting::new(BogoSort::default());
+        sorter.sort(&mut [1, 2, 3, 4]);
+        assert_eq!(sorter.stats().comparisons, 3);
+        assert_eq!(sorter.stats().swaps, 0);
+    }
+
+    #[test]
+    fn give_up_after_the_cap() {
+        let mut tings: Vec<u32> = (0..20).rev().collect();
+        let bogo = BogoSort {
+            max_shuffles: 100,
+            seed: 1,
+        };
+        assert_eq!(
+            bogo.try_sort(&mut tings),
+            Err(Error::GaveUp { attempts: 100 })
+        );
+        let bozo = BozoSort {
+            max_swaps: 0,
+            seed: 1,
+        };
+        assert_eq!(
+            bozo.try_sort(&mut tings),
+            Err(Error::GaveUp { attempts: 0 })
+        );
+        tings.sort();
+        assert_eq!(bozo.try_sort(&mut tings), Ok(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "gave up after 3 attempts")]
+    fn sorter_panics_on_giving_up() {
+        let bozo = BozoSort {
+            max_swaps: 3,
+            seed: 1,
+        };
+        bozo.sort(&mut (0..20).rev().collect::<Vec<_>>());
+    }
+}
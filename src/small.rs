@@ -0,0 +1,267 @@
+//! The base case shared by divide-and-conquer sorts: what to do once a slice is small.
+
+use crate::cmp::branchless;
+#[cfg(feature = "alloc")]
+use crate::cmp::branchless::Branchless;
+use crate::cmp::{self, Compare};
+use crate::networks::network_sort;
+use crate::{InsertionSort, Sorter};
+#[cfg(feature = "std")]
+use std::sync::{PoisonError, RwLock};
+
+/// How a divide-and-conquer sort finishes slices of at most `threshold` elements.
+///
+/// Quicksort, merge sort, introsort and radix sort each start from a built-in policy,
+/// which [`SmallSortPolicy::set_global`] overrides for every sort started afterwards, so
+/// the base case can be tuned once for all of them. A sorter configured with its own
+/// `small_threshold`, as [`QuickSort`](crate::QuickSort) and
+/// [`MergeSort`](crate::MergeSort) can be, uses [`Branchless`](SmallSortPolicy::Branchless)
+/// up to it regardless. Stable sorts run [`Network`](SmallSortPolicy::Network) as
+/// insertion sort, since a sorting network may reorder equal elements.
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use pangua::*;
+///
+/// SmallSortPolicy::set_global(Some(SmallSortPolicy::Insertion { threshold: 24 }));
+/// let mut tings = vec![5, 1, 4, 2, 3];
+/// QuickSort::default().sort(&mut tings);
+/// assert_eq!(tings, &[1, 2, 3, 4, 5]);
+/// SmallSortPolicy::set_global(None);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmallSortPolicy {
+    /// Keep dividing all the way down.
+    None,
+    /// Insertion sort, which is fast on short and nearly sorted slices.
+    Insertion { threshold: usize },
+    /// Binary insertion sort, as pdqsort finishes its partitions but searching without
+    /// branches: each element's place is found in the same number of comparisons
+    /// whatever their outcomes, so random data causes no mispredictions, and the
+    /// elements after it are shifted along in one move. Stable, and the fastest base
+    /// case for cheap comparisons.
+    Branchless { threshold: usize },
+    /// A sorting network: a fixed sequence of compare-exchanges whatever the data, the
+    /// smallest known up to 16 elements and Batcher's beyond.
+    Network { threshold: usize },
+}
+
+#[cfg(feature = "std")]
+static GLOBAL: RwLock<Option<SmallSortPolicy>> = RwLock::new(None);
+
+impl SmallSortPolicy {
+    /// Overrides the built-in policy of every algorithm, or restores them with `None`.
+    ///
+    /// Sorts already running keep the policy they started with. Only available with the
+    /// `std` feature, as is the override itself.
+    #[cfg(feature = "std")]
+    pub fn set_global(policy: Option<SmallSortPolicy>) {
+        *GLOBAL.write().unwrap_or_else(PoisonError::into_inner) = policy;
+    }
+
+    /// The override set with [`SmallSortPolicy::set_global`], if any.
+    #[cfg(feature = "std")]
+    pub fn global() -> Option<SmallSortPolicy> {
+        *GLOBAL.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The largest slice length handled as a base case; 0 for `None`.
+    pub fn threshold(&self) -> usize {
+        match *self {
+            SmallSortPolicy::None => 0,
+            SmallSortPolicy::Insertion { threshold }
+            | SmallSortPolicy::Branchless { threshold }
+            | SmallSortPolicy::Network { threshold } => threshold,
+        }
+    }
+
+    /// The global override if one is set, otherwise `built_in`.
+    pub(crate) fn resolve(built_in: SmallSortPolicy) -> SmallSortPolicy {
+        #[cfg(feature = "std")]
+        if let Some(policy) = Self::global() {
+            return policy;
+        }
+        built_in
+    }
+
+    /// Branchless insertion sort up to a sorter's own `small_threshold`, which takes
+    /// precedence over the global override; a threshold of 0 leaves the choice to
+    /// [`SmallSortPolicy::resolve`].
+    pub(crate) fn configured(small_threshold: usize, built_in: SmallSortPolicy) -> SmallSortPolicy {
+        match small_threshold {
+            0 => Self::resolve(built_in),
+            threshold => SmallSortPolicy::Branchless { threshold },
+        }
+    }
+
+    /// This policy, with a network replaced by insertion sort to keep equal elements in order.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn stable(self) -> SmallSortPolicy {
+        match self {
+            SmallSortPolicy::Network { threshold } => SmallSortPolicy::Insertion { threshold },
+            policy => policy,
+        }
+    }
+
+    /// Sorts the slice if it is small enough to be a base case, returning whether it did.
+    pub(crate) fn sort_small<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) -> bool {
+        if slice.len() > self.threshold() {
+            return false;
+        }
+        match self {
+            SmallSortPolicy::None => {}
+            SmallSortPolicy::Insertion { .. } => {
+                InsertionSort { smart: false }.sort_with(slice, cmp)
+            }
+            SmallSortPolicy::Branchless { .. } => branchless_insertion_sort(slice, cmp),
+            SmallSortPolicy::Network { .. } => network_sort(slice, cmp),
+        }
+        true
+    }
+
+    /// Like [`sort_small`](SmallSortPolicy::sort_small), for primitive integers in their
+    /// natural order: both insertion sorts and networks are built from the compare-exchange
+    /// of [`branchless`], which does not branch at all. Equal integers cannot be told
+    /// apart, so a network here is as good as stable.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn sort_small_integers<T: Branchless>(&self, slice: &mut [T]) -> bool {
+        if slice.len() > self.threshold() {
+            return false;
+        }
+        match self {
+            SmallSortPolicy::None => {}
+            SmallSortPolicy::Insertion { .. } | SmallSortPolicy::Branchless { .. } => {
+                branchless::insertion_sort(slice)
+            }
+            SmallSortPolicy::Network { .. } => branchless::network_sort(slice),
+        }
+        true
+    }
+}
+
+/// Binary insertion sort with a branch-free search, see
+/// [`SmallSortPolicy::Branchless`].
+pub(crate) fn branchless_insertion_sort<T, C: Compare<T>>(slice: &mut [T], cmp: &mut C) {
+    for end in 1..slice.len() {
+        let (sorted, rest) = slice.split_at(end);
+        let x = &rest[0];
+        // already in place: sorted input takes a single comparison per element
+        if !cmp.is_less(x, &sorted[end - 1]) {
+            continue;
+        }
+        // the first element ordering after x is in base..base + size; halving the
+        // range only moves `base`, selected by a mask rather than a branch
+        let (mut base, mut size) = (0, end);
+        while size > 1 {
+            let half = size / 2;
+            let mid = base + half;
+            base = branchless::select(cmp.is_less(x, &sorted[mid]), base, mid);
+            size -= half;
+        }
+        let at = base + !cmp.is_less(x, &sorted[base]) as usize;
+        cmp::rotate_right(cmp, &mut slice[at..=end], 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmp::ByOrd;
+    use crate::Counter;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn network_sorts_every_binary_input() {
+        // by the 0-1 principle, a network sorting all 0-1 inputs sorts everything
+        for n in 0..=12 {
+            for bits in 0u32..1 << n {
+                let mut tings: Vec<_> = (0..n).map(|i| (bits >> i) & 1).collect();
+                network_sort(&mut tings, &mut ByOrd);
+                assert!(tings.windows(2).all(|w| w[0] <= w[1]), "n = {}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn network_comparisons_ignore_the_input() {
+        let count = |tings: &mut Vec<u32>| {
+            let mut counter = Counter::new(ByOrd);
+            network_sort(tings, &mut counter);
+            counter.stats().comparisons
+        };
+        let mut sorted: Vec<u32> = (0..100).collect();
+        let mut reversed: Vec<u32> = (0..100).rev().collect();
+        assert_eq!(count(&mut sorted), count(&mut reversed));
+        assert_eq!(reversed, sorted);
+    }
+
+    #[test]
+    fn branchless_insertion_sort_is_stable() {
+        for n in 0..40 {
+            let tings: Vec<(u32, usize)> = (0..n).map(|i| ((i * 7919 % 5) as u32, i)).collect();
+            let mut sorted = tings.clone();
+            branchless_insertion_sort(&mut sorted, &mut crate::cmp::by_key(|t: &(u32, usize)| t.0));
+            let mut expected = tings;
+            expected.sort_by_key(|t| t.0);
+            assert_eq!(sorted, expected, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn branchless_search_takes_log_comparisons() {
+        let count = |tings: &mut Vec<u32>| {
+            let mut counter = Counter::new(ByOrd);
+            branchless_insertion_sort(tings, &mut counter);
+            counter.stats()
+        };
+        let mut sorted: Vec<u32> = (0..64).collect();
+        assert_eq!(count(&mut sorted).comparisons, 63);
+        // each element past the first: one check, then log2 of the sorted prefix
+        let mut reversed: Vec<u32> = (0..64).rev().collect();
+        let stats = count(&mut reversed);
+        assert_eq!(reversed, sorted);
+        assert!(stats.comparisons <= 63 * 8, "{}", stats.comparisons);
+        assert_eq!(stats.swaps, 0);
+    }
+
+    #[test]
+    fn sorts_only_small_slices() {
+        let policy = SmallSortPolicy::Network { threshold: 4 };
+        let mut tings = vec![3, 2, 1, 0, 4];
+        assert!(!policy.sort_small(&mut tings, &mut ByOrd));
+        assert_eq!(tings, &[3, 2, 1, 0, 4]);
+        assert!(policy.sort_small(&mut tings[..4], &mut ByOrd));
+        assert_eq!(tings, &[0, 1, 2, 3, 4]);
+        assert!(!SmallSortPolicy::None.sort_small(&mut [2, 1], &mut ByOrd));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn sorts_small_integers_without_a_comparator() {
+        for policy in [
+            SmallSortPolicy::Insertion { threshold: 20 },
+            SmallSortPolicy::Branchless { threshold: 20 },
+            SmallSortPolicy::Network { threshold: 20 },
+        ] {
+            let mut tings: Vec<i32> = (0..20).map(|i| (i * 7919) % 11 - 5).collect();
+            let mut expected = tings.clone();
+            expected.sort();
+            assert!(policy.sort_small_integers(&mut tings));
+            assert_eq!(tings, expected, "{:?}", policy);
+            assert!(!policy.sort_small_integers(&mut [0u8; 21]));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn stable_policy_avoids_networks() {
+        assert_eq!(
+            SmallSortPolicy::Network { threshold: 8 }.stable(),
+            SmallSortPolicy::Insertion { threshold: 8 }
+        );
+        assert_eq!(SmallSortPolicy::None.stable(), SmallSortPolicy::None);
+        assert_eq!(SmallSortPolicy::Insertion { threshold: 3 }.threshold(), 3);
+    }
+}
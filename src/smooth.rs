@@ -0,0 +1,168 @@
+use crate::cmp::{self, Compare};
+use crate::{is_trivially_sorted, Complexity, Sorter, SorterInfo};
+
+/// Leonardo numbers: `L(0) = L(1) = 1` and `L(k) = L(k - 1) + L(k - 2) + 1`, the sizes
+/// of the trees in a Leonardo heap. Saturates beyond what `usize` can count.
+const LEONARDO: [usize; 96] = leonardo_numbers();
+
+const fn leonardo_numbers() -> [usize; 96] {
+    let mut table = [1usize; 96];
+    let mut k = 2;
+    while k < table.len() {
+        table[k] = table[k - 1].saturating_add(table[k - 2]).saturating_add(1);
+        k += 1;
+    }
+    table
+}
+
+/// Smooth Sort
+///
+/// Smoothsort is a comparison-based sorting algorithm invented by Edsger Dijkstra. Like
+/// heapsort, it builds a heap and repeatedly removes the maximum, but the heap is a
+/// forest of Leonardo trees laid out left to right. On already sorted input the forest
+/// is built and torn down without moving anything, so it approaches O(n) time, while
+/// the worst case stays O(n log n) with O(1) extra memory.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SmoothSort;
+
+/// The orders of the trees in the forest, one bit per order. Orders strictly decrease
+/// from left to right, so the lowest set bit is the rightmost tree.
+type Forest = u128;
+
+impl Sorter for SmoothSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "smooth",
+            stable: false,
+            in_place: true,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut forest: Forest = 0;
+        for root in 0..slice.len() {
+            let last = forest.trailing_zeros();
+            forest = if forest != 0 && forest & (1 << (last + 1)) != 0 {
+                // the two rightmost trees become the children of the new root
+                (forest & !(0b11 << last)) | (1 << (last + 2))
+            } else if last == 1 {
+                forest | 1
+            } else {
+                forest | 0b10
+            };
+            rectify(slice, root, forest, cmp);
+        }
+
+        for root in (1..slice.len()).rev() {
+            // the rightmost root is the maximum and stays where it is
+            let order = forest.trailing_zeros() as usize;
+            forest &= !(1 << order);
+            if order >= 2 {
+                // its children become the two rightmost trees
+                forest |= 0b11 << (order - 2);
+                let right = root - 1;
+                let left = right - LEONARDO[order - 2];
+                rectify(slice, left, forest & !(1 << (order - 2)), cmp);
+                rectify(slice, right, forest, cmp);
+            }
+        }
+    }
+}
+
+/// Restores the ordering of the roots after the rightmost tree of `forest`, rooted at
+/// `root`, changed: roots increase from left to right and every tree is a max-heap.
+fn rectify<T, C: Compare<T>>(slice: &mut [T], mut root: usize, forest: Forest, cmp: &mut C) {
+    let mut order = forest.trailing_zeros() as usize;
+    let mut left_trees = forest & !(1 << order);
+    while left_trees != 0 {
+        let prev = root - LEONARDO[order];
+        if !cmp.is_less(&slice[root], &slice[prev]) {
+            break;
+        }
+        if order >= 2 {
+            let biggest = larger_child(slice, root, order, cmp);
+            if !cmp.is_less(&slice[biggest], &slice[prev]) {
+                break;
+            }
+        }
+        cmp::swap(cmp, slice, root, prev);
+        root = prev;
+        order = left_trees.trailing_zeros() as usize;
+        left_trees &= !(1 << order);
+    }
+    sift_down(slice, root, order, cmp);
+}
+
+/// Restores the heap property of the tree of the given order rooted at `root`.
+fn sift_down<T, C: Compare<T>>(slice: &mut [T], mut root: usize, mut order: usize, cmp: &mut C) {
+    while order >= 2 {
+        let child = larger_child(slice, root, order, cmp);
+        if !cmp.is_less(&slice[root], &slice[child]) {
+            return;
+        }
+        cmp::swap(cmp, slice, root, child);
+        order -= if child == root - 1 { 2 } else { 1 };
+        root = child;
+    }
+}
+
+/// The larger of the two children of the tree of the given order rooted at `root`.
+fn larger_child<T, C: Compare<T>>(slice: &[T], root: usize, order: usize, cmp: &mut C) -> usize {
+    let right = root - 1;
+    let left = right - LEONARDO[order - 2];
+    if cmp.is_less(&slice[left], &slice[right]) {
+        right
+    } else {
+        left
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counting;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn smooth_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        SmoothSort.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for n in 0..200u64 {
+            let mut tings: Vec<_> = (0..n).map(|i| (i * 7919) % 31).collect();
+            SmoothSort.sort(&mut tings);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn leonardo_numbers_are_right() {
+        assert_eq!(LEONARDO[..8], [1, 1, 3, 5, 9, 15, 25, 41]);
+        assert_eq!(LEONARDO[95], usize::MAX);
+    }
+
+    #[test]
+    fn adapts_to_sorted_input() {
+        let n = 10_000;
+        let sorter = Counting::new(SmoothSort);
+        sorter.sort(&mut (0..n).collect::<Vec<_>>());
+        assert!(sorter.stats().comparisons < 5 * n);
+        assert_eq!(sorter.stats().swaps, 0);
+
+        let sorter = Counting::new(SmoothSort);
+        let mut tings: Vec<_> = (0..n).rev().collect();
+        sorter.sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        assert!(sorter.stats().comparisons < 3 * n * 14);
+    }
+}
@@ -0,0 +1,140 @@
+//! Algorithms answered by sorting first: duplicate detection, uniqueness and counting.
+
+use crate::{IntroSort, Sorter};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Returns whether any two elements of the slice are equal.
+///
+/// Sorts references to the elements, leaving the slice itself untouched.
+pub fn has_duplicates<T: Ord>(slice: &[T]) -> bool {
+    let mut refs: Vec<&T> = slice.iter().collect();
+    IntroSort::default().sort(&mut refs);
+    refs.windows(2).any(|w| w[0] == w[1])
+}
+
+/// Finds the earliest element equal to one before it.
+///
+/// Returns `(first, duplicate)`, the indices of the earlier occurrence and of the
+/// repeat, where `duplicate` is as small as possible.
+///
+/// ```
+/// use pangua::sorted_ops::find_first_duplicate;
+///
+/// assert_eq!(find_first_duplicate(&[3, 1, 4, 1, 5, 3]), Some((1, 3)));
+/// assert_eq!(find_first_duplicate(&[2, 7, 1, 8]), None);
+/// ```
+pub fn find_first_duplicate<T: Ord>(slice: &[T]) -> Option<(usize, usize)> {
+    let mut indices: Vec<usize> = (0..slice.len()).collect();
+    IntroSort::default().sort_by(&mut indices, |&a, &b| {
+        slice[a].cmp(&slice[b]).then(a.cmp(&b))
+    });
+    // within a run of equal elements the indices are ascending, so the first two
+    // are the run's earliest occurrence and earliest repeat
+    indices
+        .windows(2)
+        .filter(|w| slice[w[0]] == slice[w[1]])
+        .min_by_key(|w| w[1])
+        .map(|w| (w[0], w[1]))
+}
+
+/// Sorts the elements and removes duplicates.
+pub fn unique_sorted<T: Ord>(mut vec: Vec<T>) -> Vec<T> {
+    sort_dedup(&mut vec, IntroSort::default());
+    vec
+}
+
+/// Sorts the vector with `sorter` and removes all but the first of every run of equal
+/// elements, in one pass over the sorted elements.
+///
+/// With a stable sorter the element kept is the earliest of its equals in the input.
+///
+/// ```
+/// use pangua::sorted_ops::sort_dedup;
+/// use pangua::MergeSort;
+///
+/// let mut tings = vec![3, 1, 3, 2, 1];
+/// sort_dedup(&mut tings, MergeSort::default());
+/// assert_eq!(tings, [1, 2, 3]);
+/// ```
+pub fn sort_dedup<T: Ord, S: Sorter>(vec: &mut Vec<T>, sorter: S) {
+    sorter.sort(vec);
+    vec.dedup();
+}
+
+/// Like [`sort_dedup`], ordering with `compare` and removing the elements it finds
+/// equal.
+pub fn sort_dedup_by<T, S, F>(vec: &mut Vec<T>, sorter: S, mut compare: F)
+where
+    S: Sorter,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    sorter.sort_by(vec, &mut compare);
+    vec.dedup_by(|b, a| compare(a, b) == Ordering::Equal);
+}
+
+/// Sorts the vector with `sorter` and collapses every run of equal elements into the
+/// first of them and the length of the run, moving the elements out of the vector.
+///
+/// The vector is left empty, with its capacity.
+///
+/// ```
+/// use pangua::sorted_ops::sort_unique_counts;
+/// use pangua::IntroSort;
+///
+/// let mut words = vec!["b", "a", "b", "c", "b"];
+/// let counts = sort_unique_counts(&mut words, IntroSort::default());
+/// assert_eq!(counts, [("a", 1), ("b", 3), ("c", 1)]);
+/// ```
+pub fn sort_unique_counts<T: Ord, S: Sorter>(vec: &mut Vec<T>, sorter: S) -> Vec<(T, usize)> {
+    sorter.sort(vec);
+    let mut counts: Vec<(T, usize)> = Vec::new();
+    for x in vec.drain(..) {
+        match counts.last_mut() {
+            Some((last, count)) if *last == x => *count += 1,
+            _ => counts.push((x, 1)),
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HeapSort, MergeSort};
+    use alloc::vec;
+
+    #[test]
+    fn has_duplicates_works() {
+        assert!(has_duplicates(&[5, 1, 4, 1]));
+        assert!(!has_duplicates(&[5, 1, 4, 2]));
+        assert!(!has_duplicates::<i32>(&[]));
+    }
+
+    #[test]
+    fn find_first_duplicate_picks_earliest_repeat() {
+        assert_eq!(find_first_duplicate(&[1, 2, 2, 1]), Some((1, 2)));
+        assert_eq!(find_first_duplicate(&[1, 2, 3, 1, 2]), Some((0, 3)));
+        assert_eq!(find_first_duplicate(&[9, 9, 9]), Some((0, 1)));
+        assert_eq!(find_first_duplicate::<u8>(&[]), None);
+    }
+
+    #[test]
+    fn unique_sorted_works() {
+        assert_eq!(unique_sorted(vec![3, 1, 3, 2, 1, 3]), &[1, 2, 3]);
+        assert_eq!(unique_sorted(vec!["b", "a"]), &["a", "b"]);
+    }
+
+    #[test]
+    fn sort_dedup_keeps_one_of_each() {
+        let mut tings = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (0, 'e')];
+        sort_dedup_by(&mut tings, MergeSort::default(), |a, b| a.0.cmp(&b.0));
+        assert_eq!(tings, [(0, 'e'), (1, 'b'), (2, 'a')]);
+
+        let mut tings = vec![5, 5, 5];
+        let counts = sort_unique_counts(&mut tings, HeapSort::default());
+        assert_eq!(counts, [(5, 3)]);
+        assert!(tings.is_empty());
+        assert!(sort_unique_counts(&mut Vec::<u8>::new(), HeapSort::default()).is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+use crate::cmp::ByOrd;
+use crate::{MergeSort, Sorter};
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::ops::{Bound, Deref, RangeBounds};
+
+/// A vector kept in sorted order by a sorter of type `S`.
+///
+/// Single elements are placed by binary search, after any equal ones. Batches go
+/// through [`SortedVec::insert_many`], which sorts the batch with the sorter and merges
+/// it in with one pass over the vector, rather than shifting the tail once per element.
+/// The contents can be read as a sorted slice, searched with
+/// [`contains`](SortedVec::contains) and sliced by value with
+/// [`range`](SortedVec::range).
+///
+/// The sorter is [`MergeSort`] unless chosen with [`SortedVec::with_sorter`]. Equal
+/// elements of a batch keep their order only if it is stable.
+///
+/// ```
+/// use pangua::SortedVec;
+///
+/// let mut tings: SortedVec<_> = vec![5, 1, 4].into();
+/// tings.insert(3);
+/// tings.insert_many(vec![9, 0, 2]);
+/// assert_eq!(tings.as_slice(), &[0, 1, 2, 3, 4, 5, 9]);
+/// assert_eq!(tings.range(2..5), &[2, 3, 4]);
+/// assert!(tings.contains(&9));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SortedVec<T, S = MergeSort> {
+    vec: Vec<T>,
+    sorter: S,
+}
+
+impl<T, S: Default> Default for SortedVec<T, S> {
+    fn default() -> Self {
+        SortedVec::with_sorter(S::default())
+    }
+}
+
+impl<T: Ord> SortedVec<T> {
+    pub fn new() -> Self {
+        SortedVec::default()
+    }
+}
+
+impl<T, S> SortedVec<T, S> {
+    /// An empty vector sorting its batches with `sorter`.
+    pub fn with_sorter(sorter: S) -> Self {
+        SortedVec {
+            vec: Vec::new(),
+            sorter,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.vec
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.vec
+    }
+
+    pub fn sorter(&self) -> &S {
+        &self.sorter
+    }
+}
+
+impl<T: Ord, S: Sorter> SortedVec<T, S> {
+    /// The elements of `vec`, sorted with `sorter`.
+    pub fn from_vec_with(mut vec: Vec<T>, sorter: S) -> Self {
+        sorter.sort(&mut vec);
+        SortedVec { vec, sorter }
+    }
+
+    /// Inserts `value` after any equal elements, returning its position.
+    pub fn insert(&mut self, value: T) -> usize {
+        let at = self.vec.partition_point(|x| x <= &value);
+        self.vec.insert(at, value);
+        at
+    }
+
+    /// Inserts a batch of elements, each after the equal elements already present.
+    ///
+    /// The batch is sorted on its own and merged in, which takes O(n + k log k) for a
+    /// batch of k elements instead of the O(n·k) of inserting them one by one.
+    pub fn insert_many<I: IntoIterator<Item = T>>(&mut self, values: I) {
+        let mid = self.vec.len();
+        self.vec.extend(values);
+        self.sorter.sort(&mut self.vec[mid..]);
+        self.merge_tail(mid);
+    }
+
+    /// Merges all the elements of `other` in, after the equal elements of `self`.
+    pub fn merge_with(&mut self, other: SortedVec<T, S>) {
+        let mid = self.vec.len();
+        self.vec.extend(other.vec);
+        self.merge_tail(mid);
+    }
+
+    /// Whether an element equal to `x` is present, by binary search.
+    pub fn contains(&self, x: &T) -> bool {
+        self.vec.binary_search(x).is_ok()
+    }
+
+    /// The elements within `range`, found by binary search.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> &[T] {
+        let start = match range.start_bound() {
+            Bound::Included(x) => self.vec.partition_point(|y| y < x),
+            Bound::Excluded(x) => self.vec.partition_point(|y| y <= x),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(x) => self.vec.partition_point(|y| y <= x),
+            Bound::Excluded(x) => self.vec.partition_point(|y| y < x),
+            Bound::Unbounded => self.vec.len(),
+        };
+        &self.vec[start..end.max(start)]
+    }
+
+    /// Merges the sorted elements from `mid` on into the ones before.
+    fn merge_tail(&mut self, mid: usize) {
+        if mid > 0 && mid < self.vec.len() {
+            MergeSort::merge_buffered(&mut self.vec, mid, &mut Vec::new(), &mut ByOrd);
+        }
+    }
+}
+
+impl<T: PartialEq, S> PartialEq for SortedVec<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.vec == other.vec
+    }
+}
+
+impl<T: Eq, S> Eq for SortedVec<T, S> {}
+
+impl<T: Hash, S> Hash for SortedVec<T, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.vec.hash(state)
+    }
+}
+
+impl<T, S> Deref for SortedVec<T, S> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.vec
+    }
+}
+
+impl<T: Ord, S: Sorter + Default> From<Vec<T>> for SortedVec<T, S> {
+    fn from(vec: Vec<T>) -> Self {
+        SortedVec::from_vec_with(vec, S::default())
+    }
+}
+
+impl<T, S> From<SortedVec<T, S>> for Vec<T> {
+    fn from(sorted: SortedVec<T, S>) -> Self {
+        sorted.vec
+    }
+}
+
+impl<T: Ord, S: Sorter + Default> FromIterator<T> for SortedVec<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        SortedVec::from(Vec::from_iter(iter))
+    }
+}
+
+impl<T: Ord, S: Sorter> Extend<T> for SortedVec<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_many(iter)
+    }
+}
+
+impl<T, S> IntoIterator for SortedVec<T, S> {
+    type Item = T;
+    type IntoIter = alloc::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HeapSort;
+    use alloc::string::String;
+    use alloc::vec;
+    use core::cmp::Ordering;
+
+    /// Equal by key, told apart by tag.
+    #[derive(Debug, Clone, Copy)]
+    struct Tagged(u8, char);
+
+    impl PartialEq for Tagged {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Tagged {}
+
+    impl PartialOrd for Tagged {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Tagged {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    #[test]
+    fn sorted_vec_works() {
+        let mut tings = SortedVec::new();
+        assert_eq!(tings.insert(3), 0);
+        assert_eq!(tings.insert(1), 0);
+        assert_eq!(tings.insert(3), 2);
+        tings.insert_many(vec![7, 0, 3, 2]);
+        assert_eq!(tings.as_slice(), &[0, 1, 2, 3, 3, 3, 7]);
+        tings.insert_many(Vec::new());
+        tings.extend(vec![8, 9]);
+        assert_eq!(tings.len(), 9);
+        assert!(tings.binary_search(&7).is_ok());
+        let collected: SortedVec<_> = vec![2, 1].into_iter().collect();
+        assert_eq!(collected.into_vec(), &[1, 2]);
+    }
+
+    #[test]
+    fn batches_go_after_equal_elements() {
+        let mut tings: SortedVec<_> = vec![Tagged(2, 'a'), Tagged(1, 'a')].into();
+        tings.insert_many(vec![Tagged(2, 'b'), Tagged(1, 'b'), Tagged(2, 'c')]);
+        let mut other = SortedVec::new();
+        other.insert(Tagged(1, 'd'));
+        tings.merge_with(other);
+        let tags: String = tings.iter().map(|t| t.1).collect();
+        assert_eq!(tags, "abdabc");
+    }
+
+    #[test]
+    fn merges_with_empty_vectors() {
+        let mut tings: SortedVec<i32> = SortedVec::new();
+        tings.merge_with(vec![3, 1, 2].into());
+        assert_eq!(tings.as_slice(), &[1, 2, 3]);
+        tings.merge_with(SortedVec::new());
+        assert_eq!(Vec::from(tings), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn ranges_and_other_sorters() {
+        let mut tings = SortedVec::with_sorter(HeapSort::default());
+        tings.extend(vec![5, 1, 4, 4, 2, 8]);
+        assert_eq!(tings.as_slice(), &[1, 2, 4, 4, 5, 8]);
+        assert_eq!(tings.range(4..), &[4, 4, 5, 8]);
+        assert_eq!(tings.range(..=4), &[1, 2, 4, 4]);
+        assert_eq!(tings.range((Bound::Excluded(4), Bound::Excluded(8))), &[5]);
+        assert!(tings
+            .range((Bound::Included(6), Bound::Excluded(3)))
+            .is_empty());
+        assert!(tings.contains(&5) && !tings.contains(&3));
+        let other = SortedVec::from_vec_with(vec![3, 0], HeapSort::default());
+        tings.merge_with(other);
+        assert_eq!(tings.into_vec(), &[0, 1, 2, 3, 4, 4, 5, 8]);
+    }
+}
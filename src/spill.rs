@@ -0,0 +1,490 @@
+//! Where an external sort spills its sorted runs, and tools to test it.
+//!
+//! A [`SpillStore`] holds runs as opaque bytes, each starting with a [`RunHeader`] that
+//! lets a reader check the run is one of ours and how much it holds. A store's list of
+//! committed runs is the checkpoint a resumed sort starts from, see
+//! [`ExternalSorter::resume_in`](crate::external::ExternalSorter::resume_in), so a sort
+//! lends its store with `&mut` to keep it past a failure. [`FileSpillStore`] keeps
+//! runs in files of a temporary directory, [`MemorySpillStore`] keeps everything in
+//! memory, and [`FaultyStore`] wraps any store to fail writes or corrupt headers on
+//! demand, so error paths can be tested without a filesystem.
+//!
+//! ```
+//! use pangua::spill::*;
+//!
+//! let mut store = MemorySpillStore::default();
+//! let id = write_run(&mut store, 3, b"abc").unwrap();
+//! let mut run = store.open(id).unwrap();
+//! assert_eq!(read_header(&mut run).unwrap().elements, 3);
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, Cursor, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Identifies a committed run within its store.
+pub type RunId = usize;
+
+/// What a run holds, written at its start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunHeader {
+    /// Elements in the run.
+    pub elements: u64,
+    /// Bytes of data following the header.
+    pub bytes: u64,
+}
+
+impl RunHeader {
+    const MAGIC: [u8; 8] = *b"pangrun1";
+    /// Encoded size: the magic, then both counts in little-endian.
+    pub const SIZE: usize = 24;
+
+    pub fn encode(&self) -> [u8; RunHeader::SIZE] {
+        let mut out = [0; RunHeader::SIZE];
+        out[..8].copy_from_slice(&RunHeader::MAGIC);
+        out[8..16].copy_from_slice(&self.elements.to_le_bytes());
+        out[16..].copy_from_slice(&self.bytes.to_le_bytes());
+        out
+    }
+
+    /// Decodes a header, failing with [`ErrorKind::InvalidData`] if it is not one.
+    pub fn decode(bytes: &[u8; RunHeader::SIZE]) -> io::Result<RunHeader> {
+        if bytes[..8] != RunHeader::MAGIC {
+            return Err(io::Error::new(ErrorKind::InvalidData, "corrupt run header"));
+        }
+        let mut count = [0; 8];
+        count.copy_from_slice(&bytes[8..16]);
+        let elements = u64::from_le_bytes(count);
+        count.copy_from_slice(&bytes[16..]);
+        Ok(RunHeader {
+            elements,
+            bytes: u64::from_le_bytes(count),
+        })
+    }
+}
+
+/// Storage for the sorted runs of an external sort.
+pub trait SpillStore {
+    type Writer: Write;
+    type Reader: Read;
+
+    /// Starts a new run, not visible until committed.
+    fn create(&mut self) -> io::Result<Self::Writer>;
+
+    /// Makes a fully written run readable, returning its id.
+    fn commit(&mut self, run: Self::Writer) -> io::Result<RunId>;
+
+    /// Reads a committed run from its start, header included.
+    fn open(&self, id: RunId) -> io::Result<Self::Reader>;
+
+    /// Deletes a committed run once it has been merged.
+    fn remove(&mut self, id: RunId) -> io::Result<()>;
+
+    /// The committed runs not yet removed, in commit order.
+    fn runs(&self) -> Vec<RunId>;
+}
+
+impl<S: SpillStore + ?Sized> SpillStore for &mut S {
+    type Writer = S::Writer;
+    type Reader = S::Reader;
+
+    fn create(&mut self) -> io::Result<S::Writer> {
+        (**self).create()
+    }
+
+    fn commit(&mut self, run: S::Writer) -> io::Result<RunId> {
+        (**self).commit(run)
+    }
+
+    fn open(&self, id: RunId) -> io::Result<S::Reader> {
+        (**self).open(id)
+    }
+
+    fn remove(&mut self, id: RunId) -> io::Result<()> {
+        (**self).remove(id)
+    }
+
+    fn runs(&self) -> Vec<RunId> {
+        (**self).runs()
+    }
+}
+
+/// Writes and commits a run of `elements` elements encoded as `data`.
+pub fn write_run<S: SpillStore>(store: &mut S, elements: u64, data: &[u8]) -> io::Result<RunId> {
+    let mut run = store.create()?;
+    let header = RunHeader {
+        elements,
+        bytes: data.len() as u64,
+    };
+    run.write_all(&header.encode())?;
+    run.write_all(data)?;
+    run.flush()?;
+    store.commit(run)
+}
+
+/// Reads the header at the start of a run, leaving the reader at its data.
+pub fn read_header<R: Read>(run: &mut R) -> io::Result<RunHeader> {
+    let mut bytes = [0; RunHeader::SIZE];
+    run.read_exact(&mut bytes)?;
+    RunHeader::decode(&bytes)
+}
+
+fn no_such_run(id: RunId) -> io::Error {
+    io::Error::new(ErrorKind::NotFound, format!("no run {} in the store", id))
+}
+
+/// A spill store keeping each run in a file of its own.
+///
+/// The files live in a directory created for the store, which is deleted with
+/// everything in it when the store is dropped.
+#[derive(Debug)]
+pub struct FileSpillStore {
+    dir: PathBuf,
+    /// Whether each run created so far is committed and not yet removed.
+    committed: Vec<bool>,
+}
+
+impl FileSpillStore {
+    /// Creates a store in a new directory under `parent`.
+    pub fn new(parent: impl AsRef<Path>) -> io::Result<Self> {
+        static STORES: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "pangua-{}-{}",
+            process::id(),
+            STORES.fetch_add(1, Ordering::Relaxed)
+        );
+        let dir = parent.as_ref().join(name);
+        fs::create_dir_all(parent)?;
+        fs::create_dir(&dir)?;
+        Ok(FileSpillStore {
+            dir,
+            committed: Vec::new(),
+        })
+    }
+
+    /// The directory holding the runs.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, id: RunId) -> PathBuf {
+        self.dir.join(format!("run-{}", id))
+    }
+}
+
+impl SpillStore for FileSpillStore {
+    type Writer = FileRun;
+    type Reader = File;
+
+    fn create(&mut self) -> io::Result<FileRun> {
+        let id = self.committed.len();
+        let file = File::create(self.path(id))?;
+        self.committed.push(false);
+        Ok(FileRun { id, file })
+    }
+
+    fn commit(&mut self, mut run: FileRun) -> io::Result<RunId> {
+        run.file.flush()?;
+        self.committed[run.id] = true;
+        Ok(run.id)
+    }
+
+    fn open(&self, id: RunId) -> io::Result<File> {
+        match self.committed.get(id) {
+            Some(true) => File::open(self.path(id)),
+            _ => Err(no_such_run(id)),
+        }
+    }
+
+    fn remove(&mut self, id: RunId) -> io::Result<()> {
+        match self.committed.get(id) {
+            Some(true) => {
+                fs::remove_file(self.path(id))?;
+                self.committed[id] = false;
+                Ok(())
+            }
+            _ => Err(no_such_run(id)),
+        }
+    }
+
+    fn runs(&self) -> Vec<RunId> {
+        (0..self.committed.len())
+            .filter(|&id| self.committed[id])
+            .collect()
+    }
+}
+
+impl Drop for FileSpillStore {
+    fn drop(&mut self) {
+        // nothing useful can be done about a failure here
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// A run being written to a [`FileSpillStore`].
+#[derive(Debug)]
+pub struct FileRun {
+    id: RunId,
+    file: File,
+}
+
+impl Write for FileRun {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// A spill store keeping its runs in memory.
+#[derive(Debug, Clone, Default)]
+pub struct MemorySpillStore {
+    runs: Vec<Option<Arc<[u8]>>>,
+}
+
+impl MemorySpillStore {
+    /// Bytes held by the committed runs.
+    pub fn bytes(&self) -> usize {
+        self.runs.iter().flatten().map(|run| run.len()).sum()
+    }
+}
+
+impl SpillStore for MemorySpillStore {
+    type Writer = Vec<u8>;
+    type Reader = Cursor<Arc<[u8]>>;
+
+    fn create(&mut self) -> io::Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn commit(&mut self, run: Vec<u8>) -> io::Result<RunId> {
+        self.runs.push(Some(run.into()));
+        Ok(self.runs.len() - 1)
+    }
+
+    fn open(&self, id: RunId) -> io::Result<Self::Reader> {
+        match self.runs.get(id) {
+            Some(Some(run)) => Ok(Cursor::new(Arc::clone(run))),
+            _ => Err(no_such_run(id)),
+        }
+    }
+
+    fn remove(&mut self, id: RunId) -> io::Result<()> {
+        match self.runs.get_mut(id).and_then(Option::take) {
+            Some(_) => Ok(()),
+            None => Err(no_such_run(id)),
+        }
+    }
+
+    fn runs(&self) -> Vec<RunId> {
+        (0..self.runs.len())
+            .filter(|&id| self.runs[id].is_some())
+            .collect()
+    }
+}
+
+/// A spill store failing on demand, for testing how a sort copes.
+///
+/// Writes are counted across all the runs of the store, from 1, and the chosen one fails
+/// with [`ErrorKind::Other`] (the others go through). Runs marked corrupt read back with
+/// a damaged header.
+#[derive(Debug, Default)]
+pub struct FaultyStore<S> {
+    pub inner: S,
+    fail_write: Option<usize>,
+    writes: Arc<AtomicUsize>,
+    corrupt: Vec<RunId>,
+}
+
+impl<S: SpillStore> FaultyStore<S> {
+    pub fn new(inner: S) -> Self {
+        FaultyStore {
+            inner,
+            fail_write: None,
+            writes: Arc::new(AtomicUsize::new(0)),
+            corrupt: Vec::new(),
+        }
+    }
+
+    /// Fails the `n`th write to any run, counting from 1, writes already made included.
+    pub fn fail_write(&mut self, n: usize) {
+        self.fail_write = Some(n);
+    }
+
+    /// Damages the header of run `id` whenever it is opened.
+    pub fn corrupt_header(&mut self, id: RunId) {
+        self.corrupt.push(id);
+    }
+
+    /// Writes attempted so far, the failed one included.
+    pub fn writes(&self) -> usize {
+        self.writes.load(Ordering::Relaxed)
+    }
+}
+
+impl<S: SpillStore> SpillStore for FaultyStore<S> {
+    type Writer = FaultyWriter<S::Writer>;
+    type Reader = FaultyReader<S::Reader>;
+
+    fn create(&mut self) -> io::Result<Self::Writer> {
+        Ok(FaultyWriter {
+            inner: self.inner.create()?,
+            fail_write: self.fail_write,
+            writes: Arc::clone(&self.writes),
+        })
+    }
+
+    fn commit(&mut self, run: Self::Writer) -> io::Result<RunId> {
+        self.inner.commit(run.inner)
+    }
+
+    fn open(&self, id: RunId) -> io::Result<Self::Reader> {
+        Ok(FaultyReader {
+            inner: self.inner.open(id)?,
+            corrupt: self.corrupt.contains(&id),
+        })
+    }
+
+    fn remove(&mut self, id: RunId) -> io::Result<()> {
+        self.inner.remove(id)
+    }
+
+    fn runs(&self) -> Vec<RunId> {
+        self.inner.runs()
+    }
+}
+
+/// A run being written to a [`FaultyStore`].
+#[derive(Debug)]
+pub struct FaultyWriter<W> {
+    inner: W,
+    fail_write: Option<usize>,
+    writes: Arc<AtomicUsize>,
+}
+
+impl<W: Write> Write for FaultyWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.writes.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.fail_write == Some(n) {
+            return Err(io::Error::other("injected write failure"));
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A run being read from a [`FaultyStore`].
+#[derive(Debug)]
+pub struct FaultyReader<R> {
+    inner: R,
+    /// Whether the first byte read still has to be damaged.
+    corrupt: bool,
+}
+
+impl<R: Read> Read for FaultyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if self.corrupt && n > 0 {
+            buf[0] ^= 0xff;
+            self.corrupt = false;
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all<S: SpillStore>(store: &S, id: RunId) -> io::Result<(RunHeader, Vec<u8>)> {
+        let mut run = store.open(id)?;
+        let header = read_header(&mut run)?;
+        let mut data = Vec::new();
+        run.read_to_end(&mut data)?;
+        Ok((header, data))
+    }
+
+    #[test]
+    fn memory_store_round_trips() {
+        let mut store = MemorySpillStore::default();
+        let a = write_run(&mut store, 2, b"hello").unwrap();
+        let b = write_run(&mut store, 0, b"").unwrap();
+        assert_eq!(store.runs(), &[a, b]);
+        let (header, data) = read_all(&store, a).unwrap();
+        assert_eq!(
+            header,
+            RunHeader {
+                elements: 2,
+                bytes: 5
+            }
+        );
+        assert_eq!(data, b"hello");
+        assert_eq!(store.bytes(), 2 * RunHeader::SIZE + 5);
+
+        store.remove(a).unwrap();
+        assert_eq!(store.runs(), &[b]);
+        assert_eq!(store.open(a).unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(store.remove(a).unwrap_err().kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn file_store_round_trips_and_cleans_up() {
+        let mut store = FileSpillStore::new(std::env::temp_dir()).unwrap();
+        let dir = store.dir().to_path_buf();
+        let a = write_run(&mut store, 2, b"hello").unwrap();
+        let b = write_run(&mut store, 1, b"!").unwrap();
+        assert_eq!(store.runs(), &[a, b]);
+        assert_eq!(read_all(&store, a).unwrap().1, b"hello");
+        store.remove(a).unwrap();
+        assert_eq!(store.runs(), &[b]);
+        assert_eq!(store.open(a).unwrap_err().kind(), ErrorKind::NotFound);
+        assert!(dir.is_dir());
+        drop(store);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn uncommitted_runs_are_invisible() {
+        let mut store = MemorySpillStore::default();
+        let mut run = store.create().unwrap();
+        run.write_all(b"partial").unwrap();
+        assert!(store.runs().is_empty());
+        drop(run);
+        assert!(store.runs().is_empty());
+    }
+
+    #[test]
+    fn injected_write_failures() {
+        let mut store = FaultyStore::new(MemorySpillStore::default());
+        // each run takes two writes, its header and its data
+        store.fail_write(4);
+        assert!(write_run(&mut store, 1, b"x").is_ok());
+        let err = write_run(&mut store, 1, b"y").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Other);
+        assert_eq!(store.writes(), 4);
+        assert_eq!(store.runs().len(), 1);
+        assert!(write_run(&mut store, 1, b"z").is_ok());
+        assert_eq!(store.runs().len(), 2);
+    }
+
+    #[test]
+    fn corrupted_headers_are_detected() {
+        let mut store = FaultyStore::new(MemorySpillStore::default());
+        let good = write_run(&mut store, 1, b"a").unwrap();
+        let bad = write_run(&mut store, 1, b"b").unwrap();
+        store.corrupt_header(bad);
+        assert!(read_all(&store, good).is_ok());
+        let err = read_all(&store, bad).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        // the stored run itself is intact
+        assert!(read_all(&store.inner, bad).is_ok());
+    }
+}
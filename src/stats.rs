@@ -0,0 +1,164 @@
+//! Instrumentation: counting the work a sorter performs.
+
+use crate::cmp::Compare;
+use crate::{Sorter, SorterInfo, StableSorter};
+use core::cell::Cell;
+use core::cmp::Ordering;
+
+/// Operation counts collected while sorting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SortStats {
+    pub comparisons: usize,
+    pub swaps: usize,
+    /// Elements moved by anything other than a swap (rotations, buffer copies).
+    pub moves: usize,
+    pub max_depth: usize,
+    /// Times the sort handed over to another algorithm, as introsort does to heapsort.
+    pub fallbacks: usize,
+}
+
+/// A comparator that counts the operations it observes, forwarding them to `inner`.
+#[derive(Debug, Clone)]
+pub struct Counter<C> {
+    inner: C,
+    stats: SortStats,
+    depth: usize,
+}
+
+impl<C> Counter<C> {
+    pub fn new(inner: C) -> Self {
+        Counter {
+            inner,
+            stats: SortStats::default(),
+            depth: 0,
+        }
+    }
+
+    pub fn stats(&self) -> SortStats {
+        self.stats
+    }
+}
+
+impl<T, C: Compare<T>> Compare<T> for Counter<C> {
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        self.stats.comparisons += 1;
+        self.inner.compare(a, b)
+    }
+    fn is_less(&mut self, a: &T, b: &T) -> bool {
+        self.stats.comparisons += 1;
+        self.inner.is_less(a, b)
+    }
+    fn on_swap(&mut self, a: &T, b: &T) {
+        self.stats.swaps += 1;
+        self.inner.on_swap(a, b)
+    }
+    fn on_move(&mut self, region: &[T]) {
+        self.stats.moves += region.len();
+        self.inner.on_move(region)
+    }
+    fn on_pivot(&mut self, pivot: &T) {
+        self.inner.on_pivot(pivot)
+    }
+    fn on_partition(&mut self, region: &[T], mid: usize) {
+        self.inner.on_partition(region, mid)
+    }
+    fn on_enter(&mut self) {
+        self.depth += 1;
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
+        self.inner.on_enter()
+    }
+    fn on_leave(&mut self) {
+        self.depth -= 1;
+        self.inner.on_leave()
+    }
+    fn on_fallback(&mut self, to: &'static str) {
+        self.stats.fallbacks += 1;
+        self.inner.on_fallback(to)
+    }
+}
+
+/// Wraps a sorter, recording the [`SortStats`] of the most recent sort.
+///
+/// ```
+/// use pangua::*;
+///
+/// let sorter = Counting::new(InsertionSort { smart: false });
+/// sorter.sort(&mut [3, 2, 1]);
+/// assert_eq!(sorter.stats().swaps, 3);
+/// ```
+pub struct Counting<S> {
+    inner: S,
+    stats: Cell<SortStats>,
+}
+
+impl<S> Counting<S> {
+    pub fn new(inner: S) -> Self {
+        Counting {
+            inner,
+            stats: Cell::new(SortStats::default()),
+        }
+    }
+
+    /// Statistics of the last sort performed through this wrapper.
+    pub fn stats(&self) -> SortStats {
+        self.stats.get()
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Sorter> Sorter for Counting<S> {
+    fn info(&self) -> SorterInfo {
+        self.inner.info()
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        let mut counter = Counter::new(cmp);
+        self.inner.sort_with(slice, &mut counter);
+        self.stats.set(counter.stats());
+    }
+}
+
+impl<S: StableSorter> StableSorter for Counting<S> {}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn counts_bubble_sort() {
+        let sorter = Counting::new(BubbleSort);
+        sorter.sort(&mut [3, 2, 1]);
+        let stats = sorter.stats();
+        // two passes with swaps, then one clean pass
+        assert_eq!(stats.comparisons, 6);
+        assert_eq!(stats.swaps, 3);
+        assert_eq!(stats.max_depth, 0);
+    }
+
+    #[test]
+    fn tracks_recursion_depth() {
+        let sorter = Counting::new(MergeSort {
+            memory: MemoryPolicy::InPlace,
+            ..MergeSort::default()
+        });
+        let mut tings: Vec<_> = (0..16).rev().collect();
+        sorter.sort(&mut tings);
+        assert_eq!(sorter.stats().max_depth, 4);
+        assert!(sorter.stats().moves > 0);
+    }
+
+    #[test]
+    fn stats_reset_between_sorts() {
+        let sorter = Counting::new(SelectionSort);
+        sorter.sort(&mut [5, 1, 4, 2, 3]);
+        sorter.sort(&mut [1]);
+        assert_eq!(sorter.stats(), SortStats::default());
+    }
+}
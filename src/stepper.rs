@@ -0,0 +1,335 @@
+//! Step-wise sorting: driving a sort one operation at a time, e.g. to animate it.
+
+use crate::cmp::{ByFn, Compare};
+use crate::trace::{Indices, SortEvent};
+use crate::{is_trivially_sorted, Sorter};
+use alloc::vec::{self, Vec};
+use core::cmp::Ordering;
+
+/// A step of a recorded sort: an event and, for a move, the original positions of the
+/// elements the moved region ended up holding.
+struct Step {
+    event: SortEvent,
+    moved: Vec<usize>,
+}
+
+/// A sort run one [`SortEvent`] at a time, created by [`Sorter::stepper`].
+///
+/// Each call to [`step`](Stepper::step) applies one operation of the sort to the slice
+/// and returns it, so the caller can render the slice, through
+/// [`slice`](Stepper::slice), between any two steps. Comparisons and other events that
+/// change nothing are steps too. The sort is run on a copy of the slice when the
+/// stepper is created and its steps recorded; swaps and moves are then replayed on the
+/// slice as they are stepped through. Writes a sorter does not report, such as merging
+/// through a buffer without announcing the moves, are applied along with the last step,
+/// so the slice is always sorted once `step` returns `None`.
+///
+/// The copy costs a clone of every element, kept until the stepper is dropped, and the
+/// recording a few words per step, plus one index per element moved: a sorter
+/// reporting moves of long regions, as merge sorts do, records O(n log n) indices.
+/// Each element of the copy is tagged with its position, so sorters choosing their
+/// strategy by the size of the elements choose as for slightly larger ones.
+///
+/// ```
+/// use pangua::{SelectionSort, SortEvent, Sorter};
+///
+/// let mut tings = [3, 1, 2];
+/// let mut stepper = SelectionSort.stepper(&mut tings);
+/// while let Some(event) = stepper.step() {
+///     if let SortEvent::Swap { .. } = event {
+///         println!("{:?}", stepper.slice());
+///     }
+/// }
+/// assert_eq!(tings, [1, 2, 3]);
+/// ```
+pub struct Stepper<'a, T> {
+    slice: &'a mut [T],
+    recording: Recording<T>,
+}
+
+impl<'a, T: Clone> Stepper<'a, T> {
+    pub(crate) fn new<S, C>(sorter: &S, slice: &'a mut [T], cmp: &mut C) -> Self
+    where
+        S: Sorter + ?Sized,
+        C: Compare<T>,
+    {
+        Stepper {
+            recording: Recording::new(sorter, slice, cmp),
+            slice,
+        }
+    }
+
+    /// Applies the next operation of the sort to the slice and returns it, or `None`
+    /// once the slice is sorted.
+    pub fn step(&mut self) -> Option<SortEvent> {
+        self.recording.replay_next(self.slice)
+    }
+
+    /// Runs the rest of the sort.
+    pub fn finish(mut self) {
+        while self.step().is_some() {}
+    }
+
+    /// The slice as the steps so far have left it.
+    pub fn slice(&self) -> &[T] {
+        self.slice
+    }
+
+    /// The number of steps left.
+    pub fn remaining(&self) -> usize {
+        self.recording.remaining()
+    }
+}
+
+impl<T: Clone> Iterator for Stepper<'_, T> {
+    type Item = SortEvent;
+
+    fn next(&mut self) -> Option<SortEvent> {
+        self.step()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+/// The recorded steps of a sort, replayed one at a time on a copy of the unsorted slice.
+pub(crate) struct Recording<T> {
+    steps: vec::IntoIter<Step>,
+    /// The elements in their original order, which moves are replayed from.
+    original: Vec<T>,
+    /// The original positions of the sorted elements, copied over the replayed slice
+    /// after the last step.
+    sorted: Option<Vec<usize>>,
+}
+
+impl<T: Clone> Recording<T> {
+    /// Sorts a copy of `slice`, recording every step.
+    pub(crate) fn new<S, C>(sorter: &S, slice: &[T], cmp: &mut C) -> Self
+    where
+        S: Sorter + ?Sized,
+        C: Compare<T>,
+    {
+        let original = slice.to_vec();
+        // a slice the sorter would leave alone takes no steps
+        if is_trivially_sorted(slice) {
+            return Recording {
+                steps: Vec::new().into_iter(),
+                original,
+                sorted: None,
+            };
+        }
+        let mut tagged: Vec<Tagged<T>> = slice
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(origin, value)| Tagged { origin, value })
+            .collect();
+        let mut recorder = Recorder {
+            inner: cmp,
+            indices: Indices::new(&tagged),
+            steps: Vec::new(),
+        };
+        sorter.sort_with(&mut tagged, &mut recorder);
+        Recording {
+            steps: recorder.steps.into_iter(),
+            original,
+            sorted: Some(tagged.into_iter().map(|t| t.origin).collect()),
+        }
+    }
+
+    /// Applies the next step to `slice` and returns it, or sorts `slice` and returns
+    /// `None` once there are none left.
+    pub(crate) fn replay_next(&mut self, slice: &mut [T]) -> Option<SortEvent> {
+        let step = match self.steps.next() {
+            Some(step) => step,
+            None => {
+                if let Some(sorted) = self.sorted.take() {
+                    self.place(slice, &sorted);
+                }
+                return None;
+            }
+        };
+        match &step.event {
+            SortEvent::Swap { a, b } => slice.swap(*a, *b),
+            SortEvent::Move { range } => self.place(&mut slice[range.clone()], &step.moved),
+            _ => {}
+        }
+        Some(step.event)
+    }
+
+    /// Fills `region` with the elements originally at `origins`.
+    fn place(&self, region: &mut [T], origins: &[usize]) {
+        for (x, &origin) in region.iter_mut().zip(origins) {
+            x.clone_from(&self.original[origin]);
+        }
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.steps.len()
+    }
+}
+
+/// An element of the recorded copy, with its position in the original slice.
+struct Tagged<T> {
+    origin: usize,
+    value: T,
+}
+
+/// Records the steps of a sort of the tagged slice `indices` was made from.
+///
+/// Hooks on single elements reach `inner`; those on regions, which are of tagged
+/// elements, do not.
+struct Recorder<T, C> {
+    inner: C,
+    indices: Indices<Tagged<T>>,
+    steps: Vec<Step>,
+}
+
+impl<T, C> Recorder<T, C> {
+    fn record(&mut self, event: SortEvent) {
+        self.steps.push(Step {
+            event,
+            moved: Vec::new(),
+        });
+    }
+}
+
+impl<T, C: Compare<T>> Compare<Tagged<T>> for Recorder<T, C> {
+    fn compare(&mut self, a: &Tagged<T>, b: &Tagged<T>) -> Ordering {
+        let (a_index, b_index) = (self.indices.index_of(a), self.indices.index_of(b));
+        self.record(SortEvent::Compare {
+            a: a_index,
+            b: b_index,
+        });
+        self.inner.compare(&a.value, &b.value)
+    }
+    fn is_less(&mut self, a: &Tagged<T>, b: &Tagged<T>) -> bool {
+        let (a_index, b_index) = (self.indices.index_of(a), self.indices.index_of(b));
+        self.record(SortEvent::Compare {
+            a: a_index,
+            b: b_index,
+        });
+        self.inner.is_less(&a.value, &b.value)
+    }
+    fn on_swap(&mut self, a: &Tagged<T>, b: &Tagged<T>) {
+        if let (Some(a), Some(b)) = (self.indices.index_of(a), self.indices.index_of(b)) {
+            self.record(SortEvent::Swap { a, b });
+        }
+        self.inner.on_swap(&a.value, &b.value)
+    }
+    fn on_move(&mut self, region: &[Tagged<T>]) {
+        if let Some(range) = self.indices.range_of(region) {
+            self.steps.push(Step {
+                event: SortEvent::Move { range },
+                moved: region.iter().map(|t| t.origin).collect(),
+            });
+        }
+    }
+    fn on_pivot(&mut self, pivot: &Tagged<T>) {
+        if let Some(index) = self.indices.index_of(pivot) {
+            self.record(SortEvent::Pivot { index });
+        }
+        self.inner.on_pivot(&pivot.value)
+    }
+    fn on_partition(&mut self, region: &[Tagged<T>], mid: usize) {
+        if let Some(range) = self.indices.range_of(region) {
+            let pivot = range.start + mid;
+            self.record(SortEvent::Partition { range, pivot });
+        }
+    }
+    fn on_enter(&mut self) {
+        self.record(SortEvent::Enter);
+        self.inner.on_enter()
+    }
+    fn on_leave(&mut self) {
+        self.record(SortEvent::Leave);
+        self.inner.on_leave()
+    }
+    fn on_fallback(&mut self, to: &'static str) {
+        self.inner.on_fallback(to)
+    }
+}
+
+/// Like [`Sorter::stepper`], ordering with `compare`.
+pub(crate) fn stepper_by<'a, S, T, F>(sorter: &S, slice: &'a mut [T], compare: F) -> Stepper<'a, T>
+where
+    S: Sorter + ?Sized,
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    Stepper::new(sorter, slice, &mut ByFn(compare))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use alloc::vec;
+
+    #[test]
+    fn swaps_are_applied_step_by_step() {
+        let original = [5, 1, 4, 2, 3, 9, 0, 8, 7, 6];
+        let mut tings = original;
+        let mut stepper = QuickSort::default().stepper(&mut tings);
+        let mut swaps = 0;
+        let total = stepper.remaining();
+        while let Some(event) = stepper.step() {
+            if let SortEvent::Swap { .. } = event {
+                swaps += 1;
+            }
+            let mut seen = stepper.slice().to_vec();
+            seen.sort_unstable();
+            assert_eq!(seen, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        }
+        assert_eq!(stepper.remaining(), 0);
+        assert!(swaps > 0 && swaps < total);
+        assert_eq!(tings, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        let counting = Counting::new(QuickSort::default());
+        counting.sort(&mut original.clone());
+        assert_eq!(counting.stats().swaps, swaps);
+    }
+
+    #[test]
+    fn moves_and_unreported_writes_end_sorted() {
+        let mut tings = vec![3, 1, 2, 6, 5, 4];
+        let mut stepper = InsertionSort { smart: true }.stepper(&mut tings);
+        assert_eq!(stepper.nth(1), Some(SortEvent::Move { range: 0..2 }));
+        assert_eq!(stepper.slice(), [1, 3, 2, 6, 5, 4]);
+        stepper.finish();
+        assert_eq!(tings, [1, 2, 3, 4, 5, 6]);
+
+        let mut tings = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')];
+        MergeSort::default()
+            .stepper_by(&mut tings, |a, b| a.0.cmp(&b.0))
+            .finish();
+        assert_eq!(tings, [(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    }
+
+    #[test]
+    fn moves_replay_the_moved_elements() {
+        let original: Vec<(u8, usize)> = (0..40).map(|i| ((i * 7919 % 5) as u8, i)).collect();
+        let mut all = original.clone();
+        all.sort_unstable();
+        let mut tings = original;
+        let mut stepper =
+            InsertionSort { smart: true }.stepper_by(&mut tings, |a, b| a.0.cmp(&b.0));
+        let mut moves = 0;
+        while let Some(event) = stepper.step() {
+            if let SortEvent::Move { .. } = event {
+                moves += 1;
+            }
+            // every element is still there exactly once
+            let mut seen = stepper.slice().to_vec();
+            seen.sort_unstable();
+            assert_eq!(seen, all);
+        }
+        assert!(moves > 0);
+        assert!(tings.windows(2).all(|w| w[0].0 <= w[1].0));
+
+        // nothing to sort, nothing to step through
+        let mut units = [(), (), ()];
+        assert_eq!(MergeSort::default().stepper(&mut units).remaining(), 0);
+    }
+}
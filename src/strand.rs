@@ -0,0 +1,127 @@
+use crate::cmp::{self, Compare};
+use crate::{is_trivially_sorted, permutation, Complexity, Sorter, SorterInfo, StableSorter};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Strand Sort
+///
+/// Strand sort pulls increasing subsequences, strands, out of the input one at a time:
+/// the first element left starts a strand, and every later one not less than the
+/// strand's last element joins it. Each strand is merged into the output as it is
+/// pulled, until no elements are left. Sorted input is a single strand, for linear time,
+/// but every strand costs a pass over what is left and a merge over what is done,
+/// so random input, with about 2√n strands, takes O(n√n), and reversed input, with one
+/// strand per element, O(n²). Elements are tracked by index and moved into place at the
+/// end, with ties broken by index, so the sort is stable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StrandSort;
+
+impl Sorter for StrandSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: "strand",
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::PowerThreeHalves,
+            worst: Complexity::Quadratic,
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut rest: Vec<usize> = (0..slice.len()).collect();
+        let mut left = Vec::with_capacity(slice.len());
+        let mut strand = Vec::with_capacity(slice.len());
+        let mut sorted: Vec<usize> = Vec::with_capacity(slice.len());
+        let mut merged = Vec::with_capacity(slice.len());
+        while !rest.is_empty() {
+            strand.push(rest[0]);
+            for &i in &rest[1..] {
+                if cmp.is_less(&slice[i], &slice[strand[strand.len() - 1]]) {
+                    left.push(i);
+                } else {
+                    strand.push(i);
+                }
+            }
+            if sorted.is_empty() {
+                if left.is_empty() {
+                    // the whole input was one strand: it was already sorted
+                    return;
+                }
+                sorted.append(&mut strand);
+            } else {
+                merge(slice, &sorted, &strand, &mut merged, cmp);
+                strand.clear();
+                core::mem::swap(&mut sorted, &mut merged);
+            }
+            rest.clear();
+            core::mem::swap(&mut rest, &mut left);
+        }
+        permutation::apply_by(&mut sorted, |a, b| cmp::swap(cmp, slice, a, b));
+    }
+}
+
+impl StableSorter for StrandSort {}
+
+/// Merges the sorted runs of indices `a` and `b` into `out`, equal elements in index order.
+fn merge<T, C: Compare<T>>(
+    slice: &[T],
+    a: &[usize],
+    b: &[usize],
+    out: &mut Vec<usize>,
+    cmp: &mut C,
+) {
+    out.clear();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let order = cmp
+            .compare(&slice[b[j]], &slice[a[i]])
+            .then(b[j].cmp(&a[i]));
+        if order == Ordering::Less {
+            out.push(b[j]);
+            j += 1;
+        } else {
+            out.push(a[i]);
+            i += 1;
+        }
+    }
+    out.extend_from_slice(&a[i..]);
+    out.extend_from_slice(&b[j..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counting;
+    use alloc::vec;
+
+    #[test]
+    fn strand_works() {
+        let mut tings = vec![5, 1, 4, 2, 3];
+        StrandSort.sort(&mut tings);
+        assert_eq!(tings, &[1, 2, 3, 4, 5]);
+        for n in 0..100u64 {
+            let mut tings: Vec<_> = (0..n).map(|i| ((i * 7919) % 23, i)).collect();
+            let mut expected = tings.clone();
+            expected.sort_by_key(|t| t.0);
+            StrandSort.sort_by_key(&mut tings, |t| t.0);
+            assert_eq!(tings, expected, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn sorted_input_is_one_strand() {
+        let n = 1000;
+        let sorter = Counting::new(StrandSort);
+        let mut tings: Vec<_> = (0..n).collect();
+        sorter.sort(&mut tings);
+        assert_eq!(sorter.stats().comparisons, n - 1);
+        assert_eq!(sorter.stats().swaps, 0);
+    }
+}
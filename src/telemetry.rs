@@ -0,0 +1,312 @@
+//! Telemetry: a summary of every sort call, for exporting metrics.
+//!
+//! A hook installed with [`set_global_hook`] is handed a [`SortSummary`] after each call
+//! to [`Sorter::sort`], [`Sorter::sort_by`] and the methods built on them, from any
+//! thread, so a service can feed its metrics system without touching the call sites.
+//! [`Instrumented`] reports the sorts of a single sorter to a hook of its own instead.
+//!
+//! Only the outermost sort of a thread is reported: sorters calling other sorters, or
+//! themselves on parts of the slice, add to the summary of the call that started them.
+//! Calls to [`Sorter::sort_with`] are the building block sorters use on one another and
+//! are never reported by the global hook. While no global hook is installed a sort call
+//! pays for a single atomic load.
+//!
+//! ```
+//! use pangua::telemetry::{self, SortSummary};
+//! use pangua::{IntroSort, Sorter};
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! static COMPARISONS: AtomicUsize = AtomicUsize::new(0);
+//!
+//! telemetry::set_global_hook(Some(Box::new(|summary: &SortSummary| {
+//!     COMPARISONS.fetch_add(summary.stats.comparisons, Ordering::Relaxed);
+//! })));
+//! IntroSort::default().sort(&mut [3, 1, 2]);
+//! telemetry::set_global_hook(None);
+//! assert!(COMPARISONS.load(Ordering::Relaxed) >= 2);
+//! ```
+
+use crate::cmp::Compare;
+use crate::{Counter, SortStats, Sorter, SorterInfo, StableSorter};
+use std::cell::Cell;
+use std::cmp::Ordering as CmpOrdering;
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+/// What a single sort call did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortSummary {
+    /// The name of the sorter called, as in its [`SorterInfo`].
+    pub algorithm: &'static str,
+    /// Elements in the slice.
+    pub len: usize,
+    /// Wall-clock time of the call.
+    pub duration: Duration,
+    pub stats: SortStats,
+    /// Scratch memory the algorithm is expected to use: none for an in-place sorter,
+    /// otherwise a buffer the size of the slice. An estimate from [`SorterInfo`], not a
+    /// measurement.
+    pub aux_bytes: usize,
+    /// The algorithm the sorter fell back on, if it gave up on its own strategy.
+    pub fallback: Option<&'static str>,
+}
+
+/// A function receiving a [`SortSummary`] per sort call.
+pub type Hook = Box<dyn Fn(&SortSummary) + Send + Sync>;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static GLOBAL: RwLock<Option<Arc<Hook>>> = RwLock::new(None);
+
+thread_local! {
+    /// Whether this thread is inside a sort being reported.
+    static ACTIVE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Installs the hook receiving the summary of every sort call, or removes it with
+/// `None`.
+///
+/// Sorts already running report to the hook they started with.
+pub fn set_global_hook(hook: Option<Hook>) {
+    let mut global = GLOBAL.write().unwrap_or_else(PoisonError::into_inner);
+    ENABLED.store(hook.is_some(), Ordering::Release);
+    *global = hook.map(Arc::new);
+}
+
+/// Sorts with `sorter`, reporting to the global hook if one is installed.
+pub(crate) fn sort_reported<S, T, C>(sorter: &S, slice: &mut [T], cmp: &mut C)
+where
+    S: Sorter + ?Sized,
+    C: Compare<T>,
+{
+    if !ENABLED.load(Ordering::Acquire) || ACTIVE.with(Cell::get) {
+        return sorter.sort_with(slice, cmp);
+    }
+    let hook = GLOBAL
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    match hook {
+        Some(hook) => observe(sorter, slice, cmp, |summary| hook(summary)),
+        None => sorter.sort_with(slice, cmp),
+    }
+}
+
+/// Sorts with `sorter`, then hands the summary of the sort to `report`.
+fn observe<S, T, C, R>(sorter: &S, slice: &mut [T], cmp: &mut C, report: R)
+where
+    S: Sorter + ?Sized,
+    C: Compare<T>,
+    R: FnOnce(&SortSummary),
+{
+    let info = sorter.info();
+    let mut probe = Probe {
+        counter: Counter::new(cmp),
+        fallback: None,
+    };
+    let start = Instant::now();
+    {
+        let _active = Active::enter();
+        sorter.sort_with(slice, &mut probe);
+    }
+    let duration = start.elapsed();
+    report(&SortSummary {
+        algorithm: info.name,
+        len: slice.len(),
+        duration,
+        stats: probe.counter.stats(),
+        aux_bytes: aux_bytes::<T>(&info, slice.len()),
+        fallback: probe.fallback,
+    });
+}
+
+/// Marks the thread as inside a reported sort until dropped, even by a panic.
+struct Active {
+    outermost: bool,
+}
+
+impl Active {
+    fn enter() -> Self {
+        Active {
+            outermost: !ACTIVE.with(|active| active.replace(true)),
+        }
+    }
+}
+
+impl Drop for Active {
+    fn drop(&mut self) {
+        if self.outermost {
+            ACTIVE.with(|active| active.set(false));
+        }
+    }
+}
+
+fn aux_bytes<T>(info: &SorterInfo, len: usize) -> usize {
+    if info.in_place {
+        0
+    } else {
+        len * mem::size_of::<T>()
+    }
+}
+
+/// Counts a sort's operations and records the first fallback it takes.
+struct Probe<C> {
+    counter: Counter<C>,
+    fallback: Option<&'static str>,
+}
+
+impl<T, C: Compare<T>> Compare<T> for Probe<C> {
+    fn compare(&mut self, a: &T, b: &T) -> CmpOrdering {
+        self.counter.compare(a, b)
+    }
+    fn is_less(&mut self, a: &T, b: &T) -> bool {
+        self.counter.is_less(a, b)
+    }
+    fn on_swap(&mut self, a: &T, b: &T) {
+        self.counter.on_swap(a, b)
+    }
+    fn on_move(&mut self, region: &[T]) {
+        self.counter.on_move(region)
+    }
+    fn on_pivot(&mut self, pivot: &T) {
+        self.counter.on_pivot(pivot)
+    }
+    fn on_partition(&mut self, region: &[T], mid: usize) {
+        self.counter.on_partition(region, mid)
+    }
+    fn on_enter(&mut self) {
+        self.counter.on_enter()
+    }
+    fn on_leave(&mut self) {
+        self.counter.on_leave()
+    }
+    fn on_fallback(&mut self, to: &'static str) {
+        self.fallback.get_or_insert(to);
+        self.counter.on_fallback(to)
+    }
+}
+
+/// Wraps a sorter, handing a [`SortSummary`] of every sort to its own hook.
+///
+/// Unlike the global hook, every sort through the wrapper is reported, including calls
+/// to [`Sorter::sort_with`] and sorts nested in other reported sorts.
+///
+/// ```
+/// use pangua::telemetry::Instrumented;
+/// use pangua::{MergeSort, Sorter};
+/// use std::cell::Cell;
+///
+/// let sorted = Cell::new(0);
+/// let sorter = Instrumented::new(MergeSort::default(), |summary| {
+///     sorted.set(sorted.get() + summary.len)
+/// });
+/// sorter.sort(&mut [2, 1, 3]);
+/// sorter.sort(&mut [5, 4]);
+/// assert_eq!(sorted.get(), 5);
+/// ```
+pub struct Instrumented<S, H> {
+    inner: S,
+    hook: H,
+}
+
+impl<S, H: Fn(&SortSummary)> Instrumented<S, H> {
+    pub fn new(inner: S, hook: H) -> Self {
+        Instrumented { inner, hook }
+    }
+
+    pub fn into_parts(self) -> (S, H) {
+        (self.inner, self.hook)
+    }
+}
+
+impl<S: Sorter, H: Fn(&SortSummary)> Sorter for Instrumented<S, H> {
+    fn info(&self) -> SorterInfo {
+        self.inner.info()
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        observe(&self.inner, slice, cmp, &self.hook)
+    }
+}
+
+impl<S: StableSorter, H: Fn(&SortSummary)> StableSorter for Instrumented<S, H> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Complexity, InsertionSort, MergeSort};
+    use std::sync::Mutex;
+
+    /// Sorts through a nested call to merge sort, after pretending to give up.
+    struct Nested;
+
+    impl Sorter for Nested {
+        fn info(&self) -> SorterInfo {
+            SorterInfo {
+                name: "nested",
+                stable: true,
+                in_place: true,
+                best: Complexity::Linear,
+                average: Complexity::Linearithmic,
+                worst: Complexity::Linearithmic,
+            }
+        }
+
+        fn sort_with<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) {
+            cmp.on_fallback("merge");
+            MergeSort::default().sort_by(slice, |a, b| cmp.compare(a, b))
+        }
+    }
+
+    #[test]
+    fn instrumented_reports_every_sort() {
+        let summaries = Mutex::new(Vec::new());
+        let sorter = Instrumented::new(MergeSort::default(), |summary: &SortSummary| {
+            summaries.lock().unwrap().push(*summary)
+        });
+        sorter.sort(&mut [3u32, 1, 2]);
+        sorter.sort_by_key(&mut [(1, 'a'), (0, 'b')], |t| t.0);
+        let summaries = summaries.into_inner().unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].algorithm, "merge");
+        assert_eq!(summaries[0].len, 3);
+        assert_eq!(summaries[0].aux_bytes, 12);
+        assert!(summaries[0].stats.comparisons >= 2);
+        assert_eq!(summaries[1].fallback, None);
+    }
+
+    #[test]
+    fn fallbacks_are_reported() {
+        let fallback = Cell::new(None);
+        let sorter = Instrumented::new(Nested, |summary| fallback.set(summary.fallback));
+        sorter.sort(&mut [2, 1]);
+        assert_eq!(fallback.get(), Some("merge"));
+        let sorter = Instrumented::new(InsertionSort { smart: false }, |summary| {
+            fallback.set(summary.fallback)
+        });
+        sorter.sort(&mut [2, 1]);
+        assert_eq!(fallback.get(), None);
+    }
+
+    #[test]
+    fn global_hook_reports_outermost_sorts_only() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        set_global_hook(Some(Box::new(move |summary: &SortSummary| {
+            sink.lock()
+                .unwrap()
+                .push((summary.algorithm, summary.len, summary.fallback))
+        })));
+        Nested.sort(&mut [3, 1, 2, 5, 4, 6, 7, 9, 8, 0, 11, 10, 12]);
+        set_global_hook(None);
+        MergeSort::default().sort(&mut [3, 1, 2, 5, 4, 6, 7, 9, 8, 0, 11, 10, 12]);
+        let seen = seen.lock().unwrap();
+        // other tests may sort concurrently, so only sorts of this length are looked at
+        let ours: Vec<_> = seen.iter().filter(|s| s.1 == 13).collect();
+        assert_eq!(ours, [&("nested", 13, Some("merge"))]);
+    }
+}
@@ -0,0 +1,210 @@
+//! Top-k queries over streams of elements.
+
+use crate::cmp::{ByOrd, Compare, Reversed};
+use crate::heap::Heap;
+#[cfg(feature = "std")]
+use crate::{MergeSort, Sorter};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::cmp::Reverse;
+#[cfg(feature = "std")]
+use std::collections::{BinaryHeap, HashMap};
+#[cfg(feature = "std")]
+use std::hash::Hash;
+
+/// An accumulator keeping the `k` largest elements pushed into it, under `C`, in O(k)
+/// memory however many elements it sees.
+///
+/// The kept elements live in a [`Heap`] rooted at the smallest of them, so each push
+/// costs one comparison when the element does not make the cut and O(log k) when it
+/// does. [`TopK::smallest`] keeps the `k` smallest instead.
+///
+/// ```
+/// use pangua::TopK;
+///
+/// let mut top = TopK::largest(3);
+/// top.extend(vec![5, 1, 9, 3, 7, 2]);
+/// assert_eq!(top.threshold(), Some(&5));
+/// assert_eq!(top.into_sorted_vec(), [9, 7, 5]);
+///
+/// let mut smallest = TopK::smallest(2);
+/// for x in (0..1000).rev() {
+///     smallest.push(x);
+/// }
+/// assert_eq!(smallest.into_sorted_vec(), [0, 1]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TopK<T, C = ByOrd> {
+    k: usize,
+    heap: Heap<T, Reversed<C>>,
+}
+
+impl<T: Ord> TopK<T> {
+    /// Keeps the `k` largest elements.
+    pub fn largest(k: usize) -> Self {
+        TopK::with_compare(k, ByOrd)
+    }
+}
+
+impl<T: Ord> TopK<T, Reversed<ByOrd>> {
+    /// Keeps the `k` smallest elements.
+    pub fn smallest(k: usize) -> Self {
+        TopK::with_compare(k, Reversed(ByOrd))
+    }
+}
+
+impl<T, C: Compare<T>> TopK<T, C> {
+    /// Keeps the `k` elements that order last under `cmp`.
+    pub fn with_compare(k: usize, cmp: C) -> Self {
+        TopK {
+            k,
+            heap: Heap::with_compare(Reversed(cmp)),
+        }
+    }
+
+    /// Offers an element, returning the one it displaced, or the element itself when it
+    /// does not make the cut. Of equal elements, the first ones pushed are kept.
+    pub fn push(&mut self, x: T) -> Option<T> {
+        if self.heap.len() < self.k {
+            self.heap.push(x);
+            None
+        } else if self.k == 0 {
+            Some(x)
+        } else {
+            Some(self.heap.push_pop(x))
+        }
+    }
+
+    /// The least of the kept elements once `k` are kept: what an element must beat to
+    /// be kept.
+    pub fn threshold(&self) -> Option<&T> {
+        if self.heap.len() == self.k {
+            self.heap.peek()
+        } else {
+            None
+        }
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// The kept elements, the largest first.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.heap.into_sorted_vec()
+    }
+}
+
+impl<T, C: Compare<T>> Extend<T> for TopK<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+/// Returns the `k` most frequent items with their counts, most frequent first.
+///
+/// Items are counted in a hash map, then a heap bounded to `k` entries keeps the most
+/// frequent, so only O(k) extra work is done per distinct item beyond counting.
+/// Items with equal counts are ordered smallest first.
+///
+/// ```
+/// use pangua::top_k_frequent;
+///
+/// let words = "the cat and the dog and the bird".split(' ');
+/// assert_eq!(top_k_frequent(words, 2), vec![("the", 3), ("and", 2)]);
+/// ```
+#[cfg(feature = "std")]
+pub fn top_k_frequent<T, I>(iter: I, k: usize) -> Vec<(T, usize)>
+where
+    T: Hash + Ord,
+    I: IntoIterator<Item = T>,
+{
+    let mut counts: HashMap<T, usize> = HashMap::new();
+    for item in iter {
+        *counts.entry(item).or_insert(0) += 1;
+    }
+    if k == 0 {
+        return Vec::new();
+    }
+
+    // min-heap on (count, reversed item): the root is the weakest entry kept so far
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for (item, count) in counts {
+        heap.push(Reverse((count, Reverse(item))));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+    let mut top: Vec<(T, usize)> = heap
+        .into_iter()
+        .map(|Reverse((count, Reverse(item)))| (item, count))
+        .collect();
+    MergeSort::default().sort_by(&mut top, |a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn keeps_the_k_largest_of_a_stream() {
+        let tings = (0..10_000u64).map(|i| (i * 2_654_435_761) % 1_000_003);
+        let mut expected: Vec<u64> = tings.clone().collect();
+        expected.sort_unstable_by(|a, b| b.cmp(a));
+        let mut top = TopK::largest(10);
+        top.extend(tings.clone());
+        assert_eq!(top.len(), 10);
+        assert_eq!(top.threshold(), Some(&expected[9]));
+        assert_eq!(top.into_sorted_vec(), expected[..10]);
+
+        let mut bottom = TopK::smallest(3);
+        assert_eq!(bottom.push(u64::MAX), None);
+        assert_eq!(bottom.threshold(), None);
+        bottom.extend(tings);
+        assert_eq!(bottom.push(2_000_000), Some(2_000_000));
+        expected.reverse();
+        assert_eq!(bottom.into_sorted_vec(), expected[..3]);
+        assert_eq!(TopK::largest(0).push(1), Some(1));
+    }
+
+    #[test]
+    fn keeps_the_first_of_equal_elements() {
+        let mut top = TopK::with_compare(2, crate::cmp::by_key(|t: &(u8, char)| t.0));
+        top.extend(vec![(1, 'a'), (3, 'b'), (3, 'c'), (2, 'd'), (3, 'e')]);
+        assert_eq!(top.into_sorted_vec(), [(3, 'b'), (3, 'c')]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn top_k_frequent_works() {
+        let xs = vec![1, 1, 1, 2, 2, 3, 4, 4, 4, 4];
+        assert_eq!(top_k_frequent(xs.clone(), 2), vec![(4, 4), (1, 3)]);
+        assert_eq!(top_k_frequent(xs, 10).len(), 4);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn breaks_ties_by_item() {
+        let xs = vec!['c', 'b', 'a', 'c', 'b', 'a', 'd'];
+        assert_eq!(top_k_frequent(xs, 2), vec![('a', 2), ('b', 2)]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn handles_empty_input_and_zero_k() {
+        assert!(top_k_frequent(Vec::<u8>::new(), 3).is_empty());
+        assert!(top_k_frequent(vec![1, 2], 0).is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+#[cfg(feature = "alloc")]
+use crate::ToOrdinal;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::ops::{Deref, DerefMut};
+
+/// Floating point types ordered by IEEE 754's `totalOrder` predicate.
+pub trait TotalOrder: Copy {
+    fn total_cmp(&self, other: &Self) -> Ordering;
+    /// The raw bits, equal exactly when [`TotalOrder::total_cmp`] finds the values equal.
+    fn to_raw_bits(&self) -> u64;
+}
+
+impl TotalOrder for f32 {
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+
+    #[inline]
+    fn to_raw_bits(&self) -> u64 {
+        self.to_bits() as u64
+    }
+}
+
+impl TotalOrder for f64 {
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+
+    #[inline]
+    fn to_raw_bits(&self) -> u64 {
+        self.to_bits()
+    }
+}
+
+/// A float with a total order, so it can be sorted by any sorter needing `Ord`.
+///
+/// Values are ordered as by `total_cmp`: negative NaNs, -∞, negative numbers, -0.0,
+/// +0.0, positive numbers, +∞, then positive NaNs. The wrapper has the layout of the
+/// float, so [`Total::cast_slice_mut`] views a `&mut [f64]` as `&mut [Total<f64>]` and
+/// sorts it in place without copying.
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use pangua::*;
+///
+/// let mut tings = [2.5, f64::NAN, -0.0, 0.0, -1.0];
+/// MergeSort::default().sort(Total::cast_slice_mut(&mut tings));
+/// assert_eq!(tings[..4], [-1.0, -0.0, 0.0, 2.5]);
+/// assert!(tings[4].is_nan());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(transparent)]
+pub struct Total<T>(pub T);
+
+/// An `f32` ordered by `total_cmp`.
+pub type TotalF32 = Total<f32>;
+
+/// An `f64` ordered by `total_cmp`.
+pub type TotalF64 = Total<f64>;
+
+impl<T> Total<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+
+    /// Views a slice of floats as totally ordered ones.
+    pub fn cast_slice(slice: &[T]) -> &[Total<T>] {
+        // SAFETY: `Total<T>` is `repr(transparent)` over `T`, so both slices have the
+        // same layout, and the lifetime and mutability carry over unchanged.
+        unsafe { &*(slice as *const [T] as *const [Total<T>]) }
+    }
+
+    /// Views a mutable slice of floats as totally ordered ones, for sorting in place.
+    pub fn cast_slice_mut(slice: &mut [T]) -> &mut [Total<T>] {
+        // SAFETY: as for `cast_slice`; the view borrows `slice` exclusively.
+        unsafe { &mut *(slice as *mut [T] as *mut [Total<T>]) }
+    }
+
+    /// Views totally ordered floats as plain ones again.
+    pub fn uncast_slice(slice: &[Total<T>]) -> &[T] {
+        // SAFETY: as for `cast_slice`.
+        unsafe { &*(slice as *const [Total<T>] as *const [T]) }
+    }
+}
+
+impl<T: TotalOrder> Ord for Total<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl<T: TotalOrder> PartialOrd for Total<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: TotalOrder> PartialEq for Total<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: TotalOrder> Eq for Total<T> {}
+
+impl<T: TotalOrder> Hash for Total<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_raw_bits().hash(state)
+    }
+}
+
+impl<T> From<T> for Total<T> {
+    fn from(value: T) -> Self {
+        Total(value)
+    }
+}
+
+impl<T> Deref for Total<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Total<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Copy + Into<f64>> ToOrdinal for Total<T> {
+    fn to_ordinal(&self) -> f64 {
+        self.0.into()
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::*;
+    use alloc::vec;
+
+    #[test]
+    fn total_order_works() {
+        let mut tings: Vec<Total<f64>> = [3.0, f64::INFINITY, -0.0, f64::NAN, 0.0, -7.5]
+            .iter()
+            .copied()
+            .map(Total::from)
+            .collect();
+        HeapSort::default().sort(&mut tings);
+        let bits: Vec<u64> = tings.iter().map(|t| t.to_bits()).collect();
+        let expected: Vec<u64> = [-7.5, -0.0, 0.0, 3.0, f64::INFINITY, f64::NAN]
+            .iter()
+            .map(|x: &f64| x.to_bits())
+            .collect();
+        assert_eq!(bits, expected);
+        assert!(Total(-f64::NAN) < Total(f64::NEG_INFINITY));
+        assert_ne!(Total(0.0f32), Total(-0.0f32));
+    }
+
+    #[test]
+    fn casts_sort_in_place() {
+        let mut tings: Vec<f32> = (0..100).map(|i| ((i * 7919) % 100) as f32 - 50.5).collect();
+        QuickSort::default().sort(Total::cast_slice_mut(&mut tings));
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        let view = Total::cast_slice(&tings);
+        assert_eq!(view.len(), 100);
+        assert_eq!(*view[0], -50.5);
+        assert_eq!(Total::uncast_slice(view), &tings[..]);
+    }
+
+    #[test]
+    fn sorters_sort_floats() {
+        let mut tings = vec![f64::NAN, 2.0, -f64::NAN, -0.0, 0.0, -3.0];
+        MergeSort::default().sort_floats(&mut tings);
+        assert!(tings[0].is_nan() && tings[0].is_sign_negative());
+        assert_eq!(tings[1..5], [-3.0, -0.0, 0.0, 2.0]);
+        assert!(tings[1..5][1].is_sign_negative());
+        assert!(tings[5].is_nan() && tings[5].is_sign_positive());
+        let mut tings: Vec<TotalF32> = vec![Total(1.0), Total(-1.0)];
+        ShellSort::default().sort(&mut tings);
+        assert_eq!(*tings[0], -1.0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn equal_values_hash_alike() {
+        use std::collections::HashSet;
+
+        let set: HashSet<Total<f64>> = [1.0, 1.0, 0.0, -0.0, f64::NAN, f64::NAN]
+            .iter()
+            .copied()
+            .map(Total)
+            .collect();
+        assert_eq!(set.len(), 4);
+        let mut total = Total(1.5);
+        *total += 1.0;
+        assert_eq!(total.into_inner(), 2.5);
+    }
+}
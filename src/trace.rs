@@ -0,0 +1,253 @@
+//! Tracing: reporting each step of a sort to an observer, e.g. to visualize it.
+
+use crate::cmp::Compare;
+use crate::{Sorter, SorterInfo, StableSorter};
+use core::cell::RefCell;
+use core::cmp::Ordering;
+use core::ops::Range;
+
+/// A step performed by a sorter, in terms of indices into the slice being sorted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SortEvent {
+    /// Two elements were compared. An index is `None` when the element lives outside the
+    /// slice, e.g. in a scratch buffer.
+    Compare {
+        a: Option<usize>,
+        b: Option<usize>,
+    },
+    Swap {
+        a: usize,
+        b: usize,
+    },
+    /// The elements in `range` were moved into their new positions.
+    Move {
+        range: Range<usize>,
+    },
+    /// The element at `index` was picked as the pivot.
+    Pivot {
+        index: usize,
+    },
+    /// `range` was partitioned, with the pivot ending up at `pivot`.
+    Partition {
+        range: Range<usize>,
+        pivot: usize,
+    },
+    Enter,
+    Leave,
+}
+
+/// Maps references to elements of a slice back to their indices.
+pub(crate) struct Indices<T> {
+    base: *const T,
+    len: usize,
+}
+
+impl<T> Indices<T> {
+    pub(crate) fn new(slice: &[T]) -> Self {
+        Indices {
+            base: slice.as_ptr(),
+            len: slice.len(),
+        }
+    }
+
+    /// The index of `x`, or `None` if it is not an element of the slice.
+    pub(crate) fn index_of(&self, x: &T) -> Option<usize> {
+        let size = core::mem::size_of::<T>();
+        if size == 0 {
+            return None;
+        }
+        let offset = (x as *const T as usize).wrapping_sub(self.base as usize) / size;
+        if offset < self.len {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn range_of(&self, region: &[T]) -> Option<Range<usize>> {
+        let start = self.index_of(region.first()?)?;
+        Some(start..start + region.len())
+    }
+}
+
+/// Reports the steps of a sort of the slice `indices` was made from to an observer.
+struct Tracer<'f, T, C, F> {
+    inner: C,
+    indices: Indices<T>,
+    observer: &'f mut F,
+}
+
+impl<'f, T, C: Compare<T>, F: FnMut(SortEvent)> Compare<T> for Tracer<'f, T, C, F> {
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        let event = SortEvent::Compare {
+            a: self.indices.index_of(a),
+            b: self.indices.index_of(b),
+        };
+        (self.observer)(event);
+        self.inner.compare(a, b)
+    }
+    fn is_less(&mut self, a: &T, b: &T) -> bool {
+        let event = SortEvent::Compare {
+            a: self.indices.index_of(a),
+            b: self.indices.index_of(b),
+        };
+        (self.observer)(event);
+        self.inner.is_less(a, b)
+    }
+    fn on_swap(&mut self, a: &T, b: &T) {
+        if let (Some(a), Some(b)) = (self.indices.index_of(a), self.indices.index_of(b)) {
+            (self.observer)(SortEvent::Swap { a, b });
+        }
+        self.inner.on_swap(a, b)
+    }
+    fn on_move(&mut self, region: &[T]) {
+        if let Some(range) = self.indices.range_of(region) {
+            (self.observer)(SortEvent::Move { range });
+        }
+        self.inner.on_move(region)
+    }
+    fn on_pivot(&mut self, pivot: &T) {
+        if let Some(index) = self.indices.index_of(pivot) {
+            (self.observer)(SortEvent::Pivot { index });
+        }
+        self.inner.on_pivot(pivot)
+    }
+    fn on_partition(&mut self, region: &[T], mid: usize) {
+        if let Some(range) = self.indices.range_of(region) {
+            let pivot = range.start + mid;
+            (self.observer)(SortEvent::Partition { range, pivot });
+        }
+        self.inner.on_partition(region, mid)
+    }
+    fn on_enter(&mut self) {
+        (self.observer)(SortEvent::Enter);
+        self.inner.on_enter()
+    }
+    fn on_leave(&mut self) {
+        (self.observer)(SortEvent::Leave);
+        self.inner.on_leave()
+    }
+    fn on_fallback(&mut self, to: &'static str) {
+        self.inner.on_fallback(to)
+    }
+}
+
+/// Wraps a sorter, reporting every [`SortEvent`] to an observer as the sort runs.
+///
+/// ```
+/// use pangua::*;
+///
+/// let mut swaps = 0;
+/// let sorter = TracedSorter::new(BubbleSort, |event| {
+///     if let SortEvent::Swap { .. } = event {
+///         swaps += 1;
+///     }
+/// });
+/// sorter.sort(&mut [2, 1, 3]);
+/// drop(sorter);
+/// assert_eq!(swaps, 1);
+/// ```
+pub struct TracedSorter<S, F> {
+    inner: S,
+    observer: RefCell<F>,
+}
+
+impl<S, F: FnMut(SortEvent)> TracedSorter<S, F> {
+    pub fn new(inner: S, observer: F) -> Self {
+        TracedSorter {
+            inner,
+            observer: RefCell::new(observer),
+        }
+    }
+
+    pub fn into_parts(self) -> (S, F) {
+        (self.inner, self.observer.into_inner())
+    }
+}
+
+impl<S: Sorter, F: FnMut(SortEvent)> Sorter for TracedSorter<S, F> {
+    fn info(&self) -> SorterInfo {
+        self.inner.info()
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        let mut observer = self.observer.borrow_mut();
+        let mut tracer = Tracer {
+            inner: cmp,
+            indices: Indices::new(slice),
+            observer: &mut *observer,
+        };
+        self.inner.sort_with(slice, &mut tracer)
+    }
+}
+
+impl<S: StableSorter, F: FnMut(SortEvent)> StableSorter for TracedSorter<S, F> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::partition::Hoare;
+    use crate::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn trace<S: Sorter>(sorter: S, slice: &mut [i32]) -> Vec<SortEvent> {
+        let mut events = Vec::new();
+        TracedSorter::new(sorter, |e| events.push(e)).sort(slice);
+        events
+    }
+
+    #[test]
+    fn swaps_replay_to_sorted() {
+        let original = vec![5, 1, 4, 2, 3];
+        let mut tings = original.clone();
+        let events = trace(SelectionSort, &mut tings);
+        let mut replay = original;
+        for event in events {
+            if let SortEvent::Swap { a, b } = event {
+                replay.swap(a, b);
+            }
+        }
+        assert_eq!(replay, tings);
+    }
+
+    #[test]
+    fn reports_absolute_indices() {
+        let mut tings = vec![3, 1, 2, 6, 5, 4, 9, 8, 7];
+        let first = QuickSort {
+            pivot: PivotStrategy::First,
+            partitioner: Hoare,
+            small_threshold: 0,
+        };
+        let events = trace(first, &mut tings);
+        assert_eq!(events[0], SortEvent::Pivot { index: 0 });
+        let partitions: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                SortEvent::Partition { range, pivot } => Some((range.clone(), *pivot)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(partitions[0], (0..9, 2));
+        assert_eq!(partitions[1], (3..9, 5));
+    }
+
+    #[test]
+    fn reports_moves() {
+        let mut tings = vec![2, 1];
+        let events = trace(InsertionSort { smart: true }, &mut tings);
+        assert_eq!(
+            events,
+            &[
+                SortEvent::Compare {
+                    a: Some(0),
+                    b: Some(1)
+                },
+                SortEvent::Move { range: 0..2 },
+            ]
+        );
+    }
+}
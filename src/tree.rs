@@ -0,0 +1,360 @@
+//! Binary search trees, and the tree sort built on them.
+
+use crate::cmp::{self, ByFn, ByOrd, Compare};
+use crate::{is_trivially_sorted, permutation, Complexity, Sorter, SorterInfo, StableSorter};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    value: T,
+    left: Option<usize>,
+    right: Option<usize>,
+    /// Nodes on the longest path down from here, this one included.
+    height: u32,
+}
+
+/// A binary search tree, optionally kept balanced as an AVL tree.
+///
+/// Values equal to one already in the tree are inserted after it, so iterating visits
+/// equal values in insertion order. Nodes live in a single vector and link to each other
+/// by index, which keeps them close in memory and lets even a degenerate tree be dropped
+/// without recursing.
+///
+/// An unbalanced tree built from sorted input degenerates into a list, making each
+/// insertion linear. A balanced tree rotates nodes after each insertion so that no two
+/// sibling subtrees differ in height by more than one, bounding the height to about
+/// 1.44 log2 n.
+///
+/// ```
+/// use pangua::tree::SearchTree;
+///
+/// let mut tree = SearchTree::balanced();
+/// for x in 0..1000 {
+///     tree.insert(x);
+/// }
+/// assert!(tree.height() <= 15);
+/// assert!(tree.iter().copied().eq(0..1000));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearchTree<T> {
+    nodes: Vec<Node<T>>,
+    root: Option<usize>,
+    balanced: bool,
+}
+
+impl<T> SearchTree<T> {
+    /// Creates an empty tree that is never rebalanced.
+    pub fn new() -> Self {
+        SearchTree {
+            nodes: Vec::new(),
+            root: None,
+            balanced: false,
+        }
+    }
+
+    /// Creates an empty AVL tree.
+    pub fn balanced() -> Self {
+        SearchTree {
+            balanced: true,
+            ..SearchTree::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Nodes on the longest path from the root down to a leaf; 0 for an empty tree.
+    pub fn height(&self) -> usize {
+        self.height_of(self.root) as usize
+    }
+
+    pub fn insert(&mut self, value: T)
+    where
+        T: Ord,
+    {
+        self.insert_with(value, &mut ByOrd)
+    }
+
+    /// Inserts a value into a tree ordered by `compare`.
+    pub fn insert_by<F>(&mut self, value: T, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.insert_with(value, &mut ByFn(compare))
+    }
+
+    pub(crate) fn insert_with<C: Compare<T>>(&mut self, value: T, cmp: &mut C) {
+        let new = self.nodes.len();
+        // the nodes walked through, each with whether the walk went left from it
+        let mut path: Vec<(usize, bool)> = Vec::new();
+        let mut cur = self.root;
+        while let Some(n) = cur {
+            let left = cmp.is_less(&value, &self.nodes[n].value);
+            path.push((n, left));
+            cur = if left {
+                self.nodes[n].left
+            } else {
+                self.nodes[n].right
+            };
+        }
+        self.nodes.push(Node {
+            value,
+            left: None,
+            right: None,
+            height: 1,
+        });
+        // retrace the path upwards, relinking, and rebalancing if asked to, each subtree
+        let mut child = new;
+        while let Some((n, went_left)) = path.pop() {
+            if went_left {
+                self.nodes[n].left = Some(child);
+            } else {
+                self.nodes[n].right = Some(child);
+            }
+            child = if self.balanced {
+                self.rebalance(n)
+            } else {
+                self.update_height(n);
+                n
+            };
+        }
+        self.root = Some(child);
+    }
+
+    /// Restores the AVL property at `n`, whose subtrees are balanced, returning the
+    /// root of the rebalanced subtree.
+    fn rebalance(&mut self, n: usize) -> usize {
+        self.update_height(n);
+        match self.balance(n) {
+            2 => {
+                let left = self.nodes[n]
+                    .left
+                    .expect("left-heavy node has a left child");
+                if self.balance(left) < 0 {
+                    self.nodes[n].left = Some(self.rotate_left(left));
+                }
+                self.rotate_right(n)
+            }
+            -2 => {
+                let right = self.nodes[n]
+                    .right
+                    .expect("right-heavy node has a right child");
+                if self.balance(right) > 0 {
+                    self.nodes[n].right = Some(self.rotate_right(right));
+                }
+                self.rotate_left(n)
+            }
+            _ => n,
+        }
+    }
+
+    /// Lifts the left child of `n` above it, returning the new subtree root.
+    fn rotate_right(&mut self, n: usize) -> usize {
+        let pivot = self.nodes[n].left.expect("rotated node has a left child");
+        self.nodes[n].left = self.nodes[pivot].right;
+        self.nodes[pivot].right = Some(n);
+        self.update_height(n);
+        self.update_height(pivot);
+        pivot
+    }
+
+    /// Lifts the right child of `n` above it, returning the new subtree root.
+    fn rotate_left(&mut self, n: usize) -> usize {
+        let pivot = self.nodes[n].right.expect("rotated node has a right child");
+        self.nodes[n].right = self.nodes[pivot].left;
+        self.nodes[pivot].left = Some(n);
+        self.update_height(n);
+        self.update_height(pivot);
+        pivot
+    }
+
+    fn height_of(&self, n: Option<usize>) -> u32 {
+        n.map_or(0, |n| self.nodes[n].height)
+    }
+
+    fn update_height(&mut self, n: usize) {
+        let node = &self.nodes[n];
+        let height = 1 + self.height_of(node.left).max(self.height_of(node.right));
+        self.nodes[n].height = height;
+    }
+
+    /// Height of the left subtree of `n` minus that of its right subtree.
+    fn balance(&self, n: usize) -> i64 {
+        let node = &self.nodes[n];
+        self.height_of(node.left) as i64 - self.height_of(node.right) as i64
+    }
+
+    /// Node indices, in order.
+    fn in_order(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = Vec::new();
+        let mut cur = self.root;
+        loop {
+            while let Some(n) = cur {
+                stack.push(n);
+                cur = self.nodes[n].left;
+            }
+            match stack.pop() {
+                Some(n) => {
+                    order.push(n);
+                    cur = self.nodes[n].right;
+                }
+                None => return order,
+            }
+        }
+    }
+
+    /// Iterates over the values in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.in_order()
+            .into_iter()
+            .map(move |n| &self.nodes[n].value)
+    }
+
+    /// Consumes the tree, returning its values in order.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let order = self.in_order();
+        let mut values: Vec<Option<T>> = self.nodes.into_iter().map(|n| Some(n.value)).collect();
+        order
+            .into_iter()
+            .map(|n| values[n].take().expect("in-order visits each node once"))
+            .collect()
+    }
+}
+
+impl<T> Default for SearchTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tree Sort
+///
+/// Tree sort inserts every element into a binary search tree, then reads them back with
+/// an in-order traversal. Without balancing, sorted input degenerates the tree into a
+/// list and the sort becomes quadratic; with `balanced` set, the tree is an AVL tree and
+/// the sort is O(n log n) in the worst case. Either way the sort is stable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TreeSort {
+    pub balanced: bool,
+}
+
+impl Sorter for TreeSort {
+    fn info(&self) -> SorterInfo {
+        SorterInfo {
+            name: if self.balanced { "tree-avl" } else { "tree" },
+            stable: true,
+            in_place: false,
+            best: Complexity::Linearithmic,
+            average: Complexity::Linearithmic,
+            worst: if self.balanced {
+                Complexity::Linearithmic
+            } else {
+                Complexity::Quadratic
+            },
+        }
+    }
+
+    fn sort_with<T, C>(&self, slice: &mut [T], cmp: &mut C)
+    where
+        C: Compare<T>,
+    {
+        if is_trivially_sorted(slice) {
+            return;
+        }
+        let mut tree = if self.balanced {
+            SearchTree::balanced()
+        } else {
+            SearchTree::new()
+        };
+        let mut by_element = ByFn(|&a: &usize, &b: &usize| cmp.compare(&slice[a], &slice[b]));
+        for i in 0..slice.len() {
+            tree.insert_with(i, &mut by_element);
+        }
+        let mut order = tree.into_sorted_vec();
+        permutation::apply_by(&mut order, |a, b| cmp::swap(cmp, slice, a, b));
+    }
+}
+
+impl StableSorter for TreeSort {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn tree_sort_works() {
+        for &balanced in &[false, true] {
+            let mut tings = vec![5, 1, 4, 2, 3];
+            TreeSort { balanced }.sort(&mut tings);
+            assert_eq!(tings, &[1, 2, 3, 4, 5]);
+            for n in 0..100u64 {
+                let mut tings: Vec<_> = (0..n).map(|i| (i * 7919) % 23).collect();
+                TreeSort { balanced }.sort(&mut tings);
+                assert!(tings.windows(2).all(|w| w[0] <= w[1]), "n = {}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn tree_sort_is_stable() {
+        for &balanced in &[false, true] {
+            let mut tings: Vec<_> = (0..200).map(|i| ((i * 37) % 11, i)).collect();
+            TreeSort { balanced }.sort_by_key(&mut tings, |t| t.0);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn balanced_tree_stays_shallow() {
+        let mut plain = SearchTree::new();
+        let mut avl = SearchTree::balanced();
+        for x in 0..1024 {
+            plain.insert(x);
+            avl.insert(1023 - x);
+        }
+        assert_eq!(plain.height(), 1024);
+        assert_eq!(avl.height(), 11);
+        assert_eq!(avl.len(), 1024);
+        assert!(avl.iter().copied().eq(0..1024));
+        assert_eq!(plain.into_sorted_vec(), (0..1024).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn every_node_is_balanced() {
+        fn check<T>(tree: &SearchTree<T>, n: Option<usize>) {
+            if let Some(n) = n {
+                assert!(tree.balance(n).abs() <= 1);
+                check(tree, tree.nodes[n].left);
+                check(tree, tree.nodes[n].right);
+            }
+        }
+        let mut tree = SearchTree::balanced();
+        for i in 0..500u64 {
+            tree.insert((i * 7919) % 97);
+            check(&tree, tree.root);
+        }
+        let values: Vec<_> = tree.iter().copied().collect();
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn insert_by_keeps_equal_values_in_order() {
+        let mut tree = SearchTree::balanced();
+        for (i, key) in [3, 1, 3, 2, 1, 3].iter().enumerate() {
+            tree.insert_by((*key, i), |a, b| a.0.cmp(&b.0));
+        }
+        assert_eq!(
+            tree.into_sorted_vec(),
+            &[(1, 1), (1, 4), (2, 3), (3, 0), (3, 2), (3, 5)]
+        );
+        assert!(SearchTree::<u8>::default().is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+//! Thresholds measured on the host rather than hardcoded.
+//!
+//! Where the sorters switch strategy, at the length below which insertion sort beats
+//! dividing further, the part size below which a thread is not worth spawning and the
+//! wins in a row after which a merge gallops, the best value depends on the machine's
+//! caches, branch predictor and core count. [`calibrate`] times a few candidates for
+//! each on this machine and returns the fastest as a [`Tuning`], which
+//! [`Tunable::tuned`] applies to any sorter with those knobs. [`tuning`] calibrates once
+//! per process, on first use, and hands out the same result afterwards.
+//!
+//! ```
+//! use pangua::tuning::{self, Tunable};
+//! use pangua::{QuickSort, Sorter};
+//!
+//! let mut tings = vec![5, 1, 4, 2, 3];
+//! QuickSort::default().tuned(&tuning::tuning()).sort(&mut tings);
+//! assert_eq!(tings, [1, 2, 3, 4, 5]);
+//! ```
+
+use crate::generators;
+use crate::natural::{self, NaturalMergeSort};
+use crate::parallel::{self, SEQUENTIAL_CUTOFF};
+use crate::{MergeSort, ParallelRadixSort, QuickSort, SampleSort, Sorter};
+#[cfg(feature = "rayon")]
+use crate::{ParallelMergeSort, ParallelQuickSort};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Base case lengths tried by [`calibrate`].
+const SMALL_THRESHOLDS: [usize; 6] = [8, 12, 16, 24, 32, 48];
+
+/// Part sizes below which [`calibrate`] tries sorting on one thread.
+const SEQUENTIAL_CUTOFFS: [usize; 5] = [1 << 11, 1 << 12, 1 << 13, 1 << 14, 1 << 15];
+
+/// Wins in a row before galloping tried by [`calibrate`].
+const MIN_GALLOPS: [usize; 5] = [3, 5, 7, 10, 16];
+
+/// Times each candidate is run, keeping its fastest.
+const REPEATS: usize = 3;
+
+/// Elements sorted to time a base case or galloping threshold.
+const SEQUENTIAL_LEN: usize = 1 << 14;
+
+/// Elements sorted to time a parallel cutoff, a few times the largest candidate.
+const PARALLEL_LEN: usize = 1 << 17;
+
+/// The thresholds at which sorters switch strategy, as measured by [`calibrate`].
+///
+/// The default holds the crate's built-in values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tuning {
+    /// Longest slice finished by the base case rather than divided further.
+    pub small_threshold: usize,
+    /// Part size below which parallel sorts stop spawning threads.
+    pub sequential_cutoff: usize,
+    /// Wins in a row by one run after which merges gallop.
+    pub min_gallop: usize,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            small_threshold: 16,
+            sequential_cutoff: SEQUENTIAL_CUTOFF,
+            min_gallop: natural::MIN_GALLOP,
+        }
+    }
+}
+
+/// Times the candidates for every threshold on this machine and returns the fastest.
+///
+/// Takes tens of milliseconds in release builds. Shuffled input is used for the base case
+/// and the parallel cutoff, and nearly sorted runs for galloping, which only pays when
+/// runs barely overlap. On a single core the parallel cutoff is left at its default.
+pub fn calibrate() -> Tuning {
+    let shuffled = generators::shuffled(SEQUENTIAL_LEN, 1);
+    let small_threshold = fastest(&SMALL_THRESHOLDS, |small_threshold| {
+        let sorter = QuickSort {
+            small_threshold,
+            ..QuickSort::default()
+        };
+        time(&shuffled, |tings| sorter.sort(tings))
+    });
+
+    let runs = generators::runs_with_noise(SEQUENTIAL_LEN, 256, 0.01, 2);
+    let min_gallop = fastest(&MIN_GALLOPS, |min_gallop| {
+        let sorter = NaturalMergeSort {
+            min_gallop,
+            ..NaturalMergeSort::default()
+        };
+        time(&runs, |tings| sorter.sort(tings))
+    });
+
+    let sequential_cutoff = if parallel::threads() == 1 {
+        SEQUENTIAL_CUTOFF
+    } else {
+        let shuffled = generators::shuffled(PARALLEL_LEN, 3);
+        fastest(&SEQUENTIAL_CUTOFFS, |sequential_cutoff| {
+            let sorter = SampleSort {
+                sequential_cutoff,
+                ..SampleSort::default()
+            };
+            time(&shuffled, |tings| sorter.sort(tings))
+        })
+    };
+
+    Tuning {
+        small_threshold,
+        sequential_cutoff,
+        min_gallop,
+    }
+}
+
+/// The [`calibrate`]d tuning of this machine, measured on the first call.
+///
+/// Later calls, from any thread, return the same tuning without measuring again.
+pub fn tuning() -> Tuning {
+    static TUNING: OnceLock<Tuning> = OnceLock::new();
+    *TUNING.get_or_init(calibrate)
+}
+
+/// The candidate whose run took least time; the first of them on ties.
+fn fastest(candidates: &[usize], mut run: impl FnMut(usize) -> Duration) -> usize {
+    candidates
+        .iter()
+        .map(|&candidate| (run(candidate), candidate))
+        .min_by_key(|&(elapsed, _)| elapsed)
+        .map(|(_, candidate)| candidate)
+        .expect("there are candidates to try")
+}
+
+/// The fastest of [`REPEATS`] runs of `sort` over a fresh copy of `input`.
+fn time(input: &[usize], mut sort: impl FnMut(&mut [usize])) -> Duration {
+    (0..REPEATS)
+        .map(|_| {
+            let mut tings = input.to_vec();
+            let start = Instant::now();
+            sort(&mut tings);
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// Sorters whose thresholds can be set from a [`Tuning`].
+pub trait Tunable {
+    /// The same sorter, with the thresholds it has taken from `tuning`.
+    fn tuned(self, tuning: &Tuning) -> Self;
+}
+
+impl<P> Tunable for QuickSort<P> {
+    fn tuned(self, tuning: &Tuning) -> Self {
+        QuickSort {
+            small_threshold: tuning.small_threshold,
+            ..self
+        }
+    }
+}
+
+/// Galloping is left off if it was off, as top-down merges rarely gain from it.
+impl Tunable for MergeSort {
+    fn tuned(self, tuning: &Tuning) -> Self {
+        MergeSort {
+            small_threshold: tuning.small_threshold,
+            min_gallop: match self.min_gallop {
+                0 => 0,
+                _ => tuning.min_gallop,
+            },
+            ..self
+        }
+    }
+}
+
+impl<P> Tunable for NaturalMergeSort<P> {
+    fn tuned(self, tuning: &Tuning) -> Self {
+        NaturalMergeSort {
+            min_gallop: tuning.min_gallop,
+            ..self
+        }
+    }
+}
+
+macro_rules! tunable_parallel {
+    ($($sorter:ident),*) => {$(
+        impl Tunable for $sorter {
+            fn tuned(mut self, tuning: &Tuning) -> Self {
+                self.sequential_cutoff = tuning.sequential_cutoff;
+                self
+            }
+        }
+    )*};
+}
+
+tunable_parallel!(SampleSort, ParallelRadixSort);
+#[cfg(feature = "rayon")]
+tunable_parallel!(ParallelMergeSort, ParallelQuickSort);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn calibrates_to_candidates() {
+        let tuning = tuning();
+        assert!(SMALL_THRESHOLDS.contains(&tuning.small_threshold));
+        assert!(MIN_GALLOPS.contains(&tuning.min_gallop));
+        assert!(SEQUENTIAL_CUTOFFS.contains(&tuning.sequential_cutoff));
+        assert_eq!(super::tuning(), tuning);
+    }
+
+    #[test]
+    fn tuned_sorters_still_sort() {
+        let tuning = Tuning {
+            small_threshold: 5,
+            sequential_cutoff: 100,
+            min_gallop: 2,
+        };
+        let tings = generators::shuffled(3000, 7);
+        let mut expected = tings.clone();
+        expected.sort();
+
+        let mut sorted = tings.clone();
+        QuickSort::default().tuned(&tuning).sort(&mut sorted);
+        assert_eq!(sorted, expected);
+        let merge = MergeSort::default().tuned(&tuning);
+        assert_eq!((merge.small_threshold, merge.min_gallop), (5, 0));
+        let mut sorted = tings.clone();
+        NaturalMergeSort::default().tuned(&tuning).sort(&mut sorted);
+        assert_eq!(sorted, expected);
+        let mut sorted = tings;
+        let parallel = SampleSort::default().tuned(&tuning);
+        assert_eq!(parallel.sequential_cutoff, 100);
+        parallel.sort(&mut sorted);
+        assert_eq!(sorted, expected);
+    }
+}
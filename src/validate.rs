@@ -0,0 +1,132 @@
+//! Sorting with end-to-end checks that the output is a sorted permutation of the input.
+
+use crate::{Error, Sorter};
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Sorts the slice, then checks that the result is sorted and holds the same elements.
+///
+/// Fails with [`Error::ChecksumMismatch`] or [`Error::NotSorted`].
+///
+/// The elements are compared through an order-independent checksum, the wrapping sum of
+/// their hashes, taken before and after sorting. This costs two linear passes, and
+/// catches elements lost, duplicated or corrupted by a faulty sorter or comparator.
+///
+/// ```
+/// use pangua::*;
+///
+/// let mut tings = vec![3, 1, 2];
+/// assert_eq!(validated_sort(&mut tings, HeapSort::default()), Ok(()));
+/// assert_eq!(tings, &[1, 2, 3]);
+/// ```
+pub fn validated_sort<T, S>(slice: &mut [T], sorter: S) -> Result<(), Error>
+where
+    T: Ord + Hash,
+    S: Sorter,
+{
+    validated_sort_by(slice, sorter, T::cmp)
+}
+
+/// Like [`validated_sort`], with the order defined by `compare`.
+pub fn validated_sort_by<T, S, F>(slice: &mut [T], sorter: S, mut compare: F) -> Result<(), Error>
+where
+    T: Hash,
+    S: Sorter,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let before = checksum(slice);
+    sorter.sort_by(slice, &mut compare);
+    let after = checksum(slice);
+    if before != after {
+        return Err(Error::ChecksumMismatch { before, after });
+    }
+    match slice
+        .windows(2)
+        .position(|w| compare(&w[1], &w[0]) == Ordering::Less)
+    {
+        Some(i) => Err(Error::NotSorted { index: i + 1 }),
+        None => Ok(()),
+    }
+}
+
+fn checksum<T: Hash>(slice: &[T]) -> u64 {
+    slice.iter().fold(0, |sum: u64, x| {
+        let mut hasher = DefaultHasher::new();
+        x.hash(&mut hasher);
+        sum.wrapping_add(hasher.finish())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmp::Compare;
+    use crate::*;
+
+    /// Leaves the elements out of order.
+    struct Broken;
+
+    impl Sorter for Broken {
+        fn info(&self) -> SorterInfo {
+            StdSorter.info()
+        }
+
+        fn sort_with<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) {
+            StdSorter.sort_with(slice, cmp);
+            let last = slice.len() - 1;
+            slice.swap(0, last);
+            slice[1..].reverse();
+        }
+    }
+
+    #[test]
+    fn validated_sort_works() {
+        let mut tings = vec![5, 1, 4, 2, 3, 3];
+        assert_eq!(validated_sort(&mut tings, IntroSort::default()), Ok(()));
+        assert_eq!(tings, &[1, 2, 3, 3, 4, 5]);
+        let mut tings = vec!["b", "c", "a"];
+        assert_eq!(
+            validated_sort_by(&mut tings, MergeSort::default(), |a, b| b.cmp(a)),
+            Ok(())
+        );
+        assert_eq!(tings, &["c", "b", "a"]);
+    }
+
+    #[test]
+    fn detects_unsorted_output() {
+        let mut tings = vec![1, 2, 3];
+        assert_eq!(
+            validated_sort(&mut tings, Broken),
+            Err(Error::NotSorted { index: 1 })
+        );
+    }
+
+    #[test]
+    fn detects_corrupted_elements() {
+        use std::cell::Cell;
+
+        struct Flaky(Cell<u32>);
+
+        impl Hash for Flaky {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.get().hash(state)
+            }
+        }
+
+        let mut tings: Vec<_> = (0..10).map(|i| Flaky(Cell::new(i))).collect();
+        // a comparator that corrupts the elements as it goes
+        let result = validated_sort_by(&mut tings, HeapSort::default(), |a, b| {
+            a.0.set(a.0.get() | 1);
+            a.0.get().cmp(&b.0.get())
+        });
+        assert!(matches!(result, Err(Error::ChecksumMismatch { .. })));
+    }
+
+    #[test]
+    fn checksum_ignores_order_but_not_contents() {
+        assert_eq!(checksum(&[1, 2, 2, 3]), checksum(&[2, 3, 1, 2]));
+        assert_ne!(checksum(&[1, 2, 2, 3]), checksum(&[1, 2, 3, 3]));
+        assert_ne!(checksum(&[7, 7]), checksum(&[7]));
+    }
+}
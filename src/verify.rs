@@ -0,0 +1,232 @@
+//! A correctness harness for sorters, the checks the built-in sorters are held to.
+//!
+//! [`check_sorter`] sorts a battery of inputs, from the edge cases where sorters tend to
+//! break to large random slices, and panics unless every output is a sorted permutation
+//! of its input. It is meant to be called from the tests of a crate implementing its own
+//! [`Sorter`]:
+//!
+//! ```
+//! use pangua::cmp::Compare;
+//! use pangua::{verify, InsertionSort, Sorter, SorterInfo};
+//!
+//! /// Insertion sort, under another name.
+//! struct MySort;
+//!
+//! impl Sorter for MySort {
+//!     fn info(&self) -> SorterInfo {
+//!         SorterInfo {
+//!             name: "mine",
+//!             ..InsertionSort { smart: false }.info()
+//!         }
+//!     }
+//!
+//!     fn sort_with<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) {
+//!         InsertionSort { smart: false }.sort_with(slice, cmp)
+//!     }
+//! }
+//!
+//! verify::check_sorter(&MySort);
+//! ```
+
+use crate::rng::Rng;
+use crate::{Complexity, Sorter};
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Lengths every case is tried at, besides the large random one.
+const LENGTHS: &[usize] = &[1, 2, 3, 4, 5, 7, 8, 15, 16, 17, 31, 32, 33, 100, 255, 1000];
+
+/// The kinds of input tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    Empty,
+    Single,
+    AllEqual,
+    Sorted,
+    Reversed,
+    /// Ascending runs of a few dozen elements.
+    Sawtooth,
+    /// Random keys from a range a tenth the length of the slice.
+    RandomWithDuplicates,
+    /// Random keys, as many elements as the sorter's average complexity allows.
+    LargeRandom,
+}
+
+impl Case {
+    /// Every case, in the order they are tried.
+    pub const ALL: [Case; 8] = [
+        Case::Empty,
+        Case::Single,
+        Case::AllEqual,
+        Case::Sorted,
+        Case::Reversed,
+        Case::Sawtooth,
+        Case::RandomWithDuplicates,
+        Case::LargeRandom,
+    ];
+
+    /// The keys of the case at about length `n`.
+    pub fn generate(self, n: usize, seed: u64) -> Vec<u32> {
+        let mut rng = Rng::new(seed);
+        match self {
+            Case::Empty => Vec::new(),
+            Case::Single => vec![7],
+            Case::AllEqual => vec![7; n],
+            Case::Sorted => (0..n as u32).collect(),
+            Case::Reversed => (0..n as u32).rev().collect(),
+            Case::Sawtooth => (0..n as u32).map(|i| i % 37).collect(),
+            Case::RandomWithDuplicates => {
+                let range = (n / 10).max(2);
+                (0..n).map(|_| rng.below(range) as u32).collect()
+            }
+            Case::LargeRandom => (0..n).map(|_| rng.next_u64() as u32).collect(),
+        }
+    }
+
+    fn lengths(self, large: usize) -> Vec<usize> {
+        match self {
+            Case::Empty => vec![0],
+            Case::Single => vec![1],
+            Case::LargeRandom => vec![large],
+            _ => LENGTHS.iter().copied().filter(|&n| n <= large).collect(),
+        }
+    }
+}
+
+/// The length of the large random case for a sorter of the given average complexity,
+/// keeping each sort to a fraction of a second. No other case is longer.
+fn large_len(average: Complexity) -> usize {
+    match average {
+        Complexity::Factorial => 7,
+        Complexity::Quadratic => 3000,
+        _ => 100_000,
+    }
+}
+
+/// Sorts every [`Case`] with `sorter` and panics on the first wrong output.
+///
+/// An output is right when it is a permutation of the input in non-descending order,
+/// and, for a sorter whose [`info`](Sorter::info) claims stability, equal elements are
+/// in their original order. Cases are sorted with both [`Sorter::sort`] and
+/// [`Sorter::sort_by_key`]; sorters too slow for large inputs, going by their average
+/// complexity, get smaller ones. The panic message names the case, the length and the
+/// index at fault.
+pub fn check_sorter<S: Sorter + ?Sized>(sorter: &S) {
+    let info = sorter.info();
+    let large = large_len(info.average);
+    for case in Case::ALL.iter().copied() {
+        for n in case.lengths(large) {
+            let keys = case.generate(n, n as u64);
+            let what = format!("{}: {:?} of length {}", info.name, case, keys.len());
+
+            let mut tings = keys.clone();
+            sorter.sort(&mut tings);
+            let mut expected = keys.clone();
+            expected.sort_unstable();
+            if let Some(i) = tings.iter().zip(&expected).position(|(a, b)| a != b) {
+                panic!(
+                    "{}: sort put {} at index {}, where {} belongs",
+                    what, tings[i], i, expected[i]
+                );
+            }
+
+            let mut tagged: Vec<(u32, usize)> = keys.iter().copied().zip(0..).collect();
+            sorter.sort_by_key(&mut tagged, |t| t.0);
+            check_sorted_permutation(&keys, &tagged, info.stable, &what);
+        }
+    }
+}
+
+/// Checks that `sorted` holds each `(keys[i], i)` once, ordered by key, and by `i`
+/// among equal keys if `stable`.
+fn check_sorted_permutation(keys: &[u32], sorted: &[(u32, usize)], stable: bool, what: &str) {
+    assert_eq!(sorted.len(), keys.len(), "{}: length changed", what);
+    let mut seen = vec![false; keys.len()];
+    for (i, &(key, tag)) in sorted.iter().enumerate() {
+        assert!(
+            tag < keys.len() && keys[tag] == key && !seen[tag],
+            "{}: element at index {} is not one of the input's",
+            what,
+            i
+        );
+        seen[tag] = true;
+        if i > 0 {
+            let (prev_key, prev_tag) = sorted[i - 1];
+            assert!(prev_key <= key, "{}: not sorted at index {}", what, i);
+            assert!(
+                !stable || prev_key < key || prev_tag < tag,
+                "{}: equal elements reordered at index {}",
+                what,
+                i
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::cmp::Compare;
+    use crate::*;
+
+    #[cfg(feature = "std")]
+    /// Sorts, then swaps the ends of slices longer than 40 elements.
+    struct Swapped;
+
+    #[cfg(feature = "std")]
+    impl Sorter for Swapped {
+        fn info(&self) -> SorterInfo {
+            HeapSort::default().info()
+        }
+
+        fn sort_with<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) {
+            HeapSort::default().sort_with(slice, cmp);
+            if slice.len() > 40 {
+                slice.swap(0, slice.len() - 1);
+            }
+        }
+    }
+
+    #[test]
+    fn built_in_sorters_pass() {
+        check_sorter(&MergeSort::default());
+        check_sorter(&IntroSort::default());
+        check_sorter(&InsertionSort { smart: true });
+        check_sorter(&DefaultSorter);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn catches_broken_sorters() {
+        use std::panic;
+
+        let result = panic::catch_unwind(|| check_sorter(&Swapped));
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert_eq!(
+            message,
+            "heap: Sorted of length 100: sort put 99 at index 0, where 0 belongs"
+        );
+        // an unstable sorter claiming to be stable
+        let liar = Named(HeapSort::default(), MergeSort::default().info());
+        let result = panic::catch_unwind(|| check_sorter(&liar));
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("equal elements reordered"), "{}", message);
+    }
+
+    #[cfg(feature = "std")]
+    /// Sorts with the first sorter, described as the second.
+    struct Named<S>(S, SorterInfo);
+
+    #[cfg(feature = "std")]
+    impl<S: Sorter> Sorter for Named<S> {
+        fn info(&self) -> SorterInfo {
+            self.1
+        }
+
+        fn sort_with<T, C: Compare<T>>(&self, slice: &mut [T], cmp: &mut C) {
+            self.0.sort_with(slice, cmp)
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Bindings for JavaScript through `wasm_bindgen`, so a sorting visualization in the
+//! browser can run the crate's algorithms rather than reimplementations of them.
+//!
+//! Sorters are chosen by their [`SorterInfo::name`](crate::SorterInfo::name), listed by
+//! [`algorithms`]. Numbers are sorted as `f64` in their total order, which orders `i32`
+//! exactly as integers. [`trace_i32`] and [`trace_f64`] return every step of a sort at
+//! once; an [`Animation`] replays them one at a time along with the values they leave,
+//! as [`Stepper`](crate::Stepper) does.
+//!
+//! ```js
+//! import { Animation } from "pangua";
+//!
+//! const animation = Animation.fromI32("quick", [5, 1, 4, 2, 3]);
+//! for (let step = animation.step(); step; step = animation.step()) {
+//!     draw(animation.values(), step.kind, step.a, step.b);
+//! }
+//! ```
+
+use crate::stepper::Recording;
+use crate::*;
+use std::string::{String, ToString};
+use std::vec::Vec;
+use wasm_bindgen::prelude::*;
+
+/// The names [`algorithms`] lists, one per sorter that can be chosen.
+const ALGORITHMS: [&str; 27] = [
+    "default",
+    "std",
+    "auto",
+    "quick",
+    "quick-3way",
+    "intro",
+    "pdq",
+    "heap",
+    "smooth",
+    "merge",
+    "merge-bottom-up",
+    "merge-natural",
+    "block",
+    "patience",
+    "tree",
+    "shell",
+    "comb",
+    "insertion-smart",
+    "insertion-dumb",
+    "selection",
+    "cycle",
+    "pancake",
+    "bitonic",
+    "bubble",
+    "cocktail-shaker",
+    "gnome",
+    "odd-even",
+];
+
+/// Evaluates `$body` with `$sorter` bound to the sorter named `$name`, or returns an
+/// error naming it if there is none.
+macro_rules! with_sorter {
+    ($name:expr, $sorter:ident => $body:expr) => {
+        with_sorter!(@ $name, $sorter, $body,
+            "default" => DefaultSorter,
+            "std" => StdSorter,
+            "auto" => AutoSort::default(),
+            "quick" => QuickSort::default(),
+            "quick-3way" => ThreeWayQuickSort,
+            "intro" => IntroSort::default(),
+            "pdq" => PdqSort,
+            "heap" => HeapSort::default(),
+            "smooth" => SmoothSort,
+            "merge" => MergeSort::default(),
+            "merge-bottom-up" => BottomUpMergeSort,
+            "merge-natural" => NaturalMergeSort::default(),
+            "block" => BlockSort,
+            "patience" => PatienceSort,
+            "tree" => TreeSort { balanced: false },
+            "shell" => ShellSort::default(),
+            "comb" => CombSort::default(),
+            "insertion-smart" => InsertionSort { smart: true },
+            "insertion-dumb" => InsertionSort { smart: false },
+            "selection" => SelectionSort,
+            "cycle" => CycleSort,
+            "pancake" => PancakeSort,
+            "bitonic" => BitonicSort,
+            "bubble" => BubbleSort,
+            "cocktail-shaker" => CocktailShakerSort,
+            "gnome" => GnomeSort,
+            "odd-even" => OddEvenSort
+        )
+    };
+    (@ $name:expr, $sorter:ident, $body:expr, $($known:literal => $make:expr),*) => {
+        match $name {
+            $($known => Ok({
+                let $sorter = $make;
+                $body
+            }),)*
+            name => Err(format!("unknown algorithm {:?}", name)),
+        }
+    };
+}
+
+/// The names of the sorters the other functions accept.
+#[wasm_bindgen]
+pub fn algorithms() -> Vec<String> {
+    ALGORITHMS.iter().map(ToString::to_string).collect()
+}
+
+/// What a [`Step`] did, after the [`SortEvent`] it was made from.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepKind {
+    Compare,
+    Swap,
+    Move,
+    Pivot,
+    Partition,
+    Enter,
+    Leave,
+}
+
+/// A [`SortEvent`] as a flat object JavaScript can read.
+///
+/// `a` and `b` are the indices compared or swapped, with `a` also the pivot's index;
+/// `start..end` is the range moved or partitioned, empty for other steps.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub kind: StepKind,
+    pub a: Option<usize>,
+    pub b: Option<usize>,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl From<SortEvent> for Step {
+    fn from(event: SortEvent) -> Step {
+        let step = |kind, a, b, start, end| Step {
+            kind,
+            a,
+            b,
+            start,
+            end,
+        };
+        match event {
+            SortEvent::Compare { a, b } => step(StepKind::Compare, a, b, 0, 0),
+            SortEvent::Swap { a, b } => step(StepKind::Swap, Some(a), Some(b), 0, 0),
+            SortEvent::Move { range } => step(StepKind::Move, None, None, range.start, range.end),
+            SortEvent::Pivot { index } => step(StepKind::Pivot, Some(index), None, 0, 0),
+            SortEvent::Partition { range, pivot } => step(
+                StepKind::Partition,
+                Some(pivot),
+                None,
+                range.start,
+                range.end,
+            ),
+            SortEvent::Enter => step(StepKind::Enter, None, None, 0, 0),
+            SortEvent::Leave => step(StepKind::Leave, None, None, 0, 0),
+        }
+    }
+}
+
+/// Sorts `values` with the sorter named `algorithm`.
+#[wasm_bindgen(js_name = sortI32)]
+pub fn sort_i32(algorithm: &str, mut values: Vec<i32>) -> Result<Vec<i32>, String> {
+    with_sorter!(algorithm, sorter => sorter.sort(&mut values))?;
+    Ok(values)
+}
+
+/// Sorts `values` with the sorter named `algorithm`, in their total order: negative NaNs
+/// first, then -∞ up to ∞, then positive NaNs.
+#[wasm_bindgen(js_name = sortF64)]
+pub fn sort_f64(algorithm: &str, mut values: Vec<f64>) -> Result<Vec<f64>, String> {
+    let totals = Total::cast_slice_mut(&mut values);
+    with_sorter!(algorithm, sorter => sorter.sort(totals))?;
+    Ok(values)
+}
+
+/// Every step of sorting `values` with the sorter named `algorithm`.
+#[wasm_bindgen(js_name = traceI32)]
+pub fn trace_i32(algorithm: &str, values: Vec<i32>) -> Result<Vec<Step>, String> {
+    trace_f64(algorithm, values.into_iter().map(f64::from).collect())
+}
+
+/// Every step of sorting `values` with the sorter named `algorithm`, see [`sort_f64`].
+#[wasm_bindgen(js_name = traceF64)]
+pub fn trace_f64(algorithm: &str, mut values: Vec<f64>) -> Result<Vec<Step>, String> {
+    let totals = Total::cast_slice_mut(&mut values);
+    let mut steps = Vec::new();
+    with_sorter!(algorithm, sorter => {
+        TracedSorter::new(sorter, |event| steps.push(Step::from(event))).sort(totals)
+    })?;
+    Ok(steps)
+}
+
+/// A sort of numbers replayed one step at a time, the values changing as it goes.
+#[wasm_bindgen]
+pub struct Animation {
+    values: Vec<Total<f64>>,
+    recording: Recording<Total<f64>>,
+}
+
+#[wasm_bindgen]
+impl Animation {
+    /// Records the sort of `values` with the sorter named `algorithm`, to be replayed.
+    #[wasm_bindgen(constructor)]
+    pub fn new(algorithm: &str, values: Vec<f64>) -> Result<Animation, String> {
+        let values: Vec<Total<f64>> = values.into_iter().map(Total).collect();
+        let recording = with_sorter!(algorithm, sorter => {
+            Recording::new(&sorter, &values, &mut crate::cmp::ByOrd)
+        })?;
+        Ok(Animation { values, recording })
+    }
+
+    /// Like [`Animation::new`], for integers.
+    #[wasm_bindgen(js_name = fromI32)]
+    pub fn from_i32(algorithm: &str, values: Vec<i32>) -> Result<Animation, String> {
+        Animation::new(algorithm, values.into_iter().map(f64::from).collect())
+    }
+
+    /// Applies the next step to the values and returns it, or `undefined` once they are
+    /// sorted.
+    pub fn step(&mut self) -> Option<Step> {
+        self.recording.replay_next(&mut self.values).map(Step::from)
+    }
+
+    /// The values as the steps so far have left them.
+    pub fn values(&self) -> Vec<f64> {
+        self.values.iter().map(|v| v.into_inner()).collect()
+    }
+
+    /// The number of steps left.
+    pub fn remaining(&self) -> usize {
+        self.recording.remaining()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_algorithm_is_found_by_its_name() {
+        for &name in ALGORITHMS.iter() {
+            assert_eq!(with_sorter!(name, sorter => sorter.info().name), Ok(name));
+        }
+        assert_eq!(algorithms().len(), ALGORITHMS.len());
+        assert!(sort_i32("slow", vec![]).is_err());
+    }
+
+    #[test]
+    fn animations_end_sorted() {
+        let tings = vec![5, -1, 4, 2, 3, 9, 0, 8, 7, 6];
+        let mut sorted = tings.clone();
+        sorted.sort_unstable();
+        for &name in ALGORITHMS.iter() {
+            assert_eq!(sort_i32(name, tings.clone()).unwrap(), sorted);
+            let steps = trace_i32(name, tings.clone()).unwrap();
+            let mut animation = Animation::from_i32(name, tings.clone()).unwrap();
+            assert_eq!(animation.remaining(), steps.len());
+            let replayed: Vec<Step> = std::iter::from_fn(|| animation.step()).collect();
+            assert_eq!(replayed, steps, "{}", name);
+            assert!(animation
+                .values()
+                .iter()
+                .copied()
+                .eq(sorted.iter().map(|&v| v as f64)));
+        }
+
+        let floats = sort_f64("pdq", vec![2.5, f64::NEG_INFINITY, -0.0, 0.0]).unwrap();
+        assert_eq!(floats, [f64::NEG_INFINITY, -0.0, 0.0, 2.5]);
+        assert!(floats[1].is_sign_negative());
+    }
+}
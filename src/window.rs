@@ -0,0 +1,324 @@
+//! Order statistics over a sliding window of a stream.
+
+use crate::rng::Rng;
+use crate::Error;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+const NIL: usize = usize::MAX;
+
+struct Node<T> {
+    value: T,
+    /// Arrival number, making every key unique so equal values can be told apart.
+    seq: u64,
+    priority: u64,
+    size: usize,
+    left: usize,
+    right: usize,
+}
+
+/// A moving window over a stream that keeps its elements in sorted order.
+///
+/// The window holds the `capacity` most recently pushed elements. Pushing and evicting
+/// take O(log w) expected time, and so does looking up any rank, which makes moving
+/// medians, quantiles and extremes cheap to maintain.
+///
+/// Elements are stored in a treap (a binary search tree balanced by random priorities)
+/// augmented with subtree sizes, alongside a queue recording their arrival order.
+///
+/// ```
+/// use pangua::SlidingSorted;
+///
+/// let mut window = SlidingSorted::new(3);
+/// let medians: Vec<_> = [5, 1, 4, 2, 8]
+///     .iter()
+///     .map(|&x| {
+///         window.push(x);
+///         *window.median().unwrap()
+///     })
+///     .collect();
+/// assert_eq!(medians, &[5, 5, 4, 2, 4]);
+/// ```
+pub struct SlidingSorted<T> {
+    capacity: usize,
+    nodes: Vec<Option<Node<T>>>,
+    free: Vec<usize>,
+    root: usize,
+    arrivals: VecDeque<usize>,
+    next_seq: u64,
+    rng: Rng,
+}
+
+impl<T: Ord> SlidingSorted<T> {
+    /// Creates an empty window holding at most `capacity` elements.
+    ///
+    /// Panics if `capacity` is zero; see [`SlidingSorted::try_new`].
+    pub fn new(capacity: usize) -> Self {
+        match Self::try_new(capacity) {
+            Ok(window) => window,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Creates an empty window holding at most `capacity` elements, which must be
+    /// positive.
+    pub fn try_new(capacity: usize) -> Result<Self, Error> {
+        if capacity == 0 {
+            return Err(Error::ZeroCapacity);
+        }
+        Ok(SlidingSorted {
+            capacity,
+            nodes: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            root: NIL,
+            arrivals: VecDeque::with_capacity(capacity),
+            next_seq: 0,
+            rng: Rng::new(capacity as u64),
+        })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.arrivals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.arrivals.is_empty()
+    }
+
+    /// Adds `value` to the window, returning the oldest element if it was evicted to
+    /// make room.
+    pub fn push(&mut self, value: T) -> Option<T> {
+        let evicted = if self.len() == self.capacity {
+            self.pop()
+        } else {
+            None
+        };
+        let id = self.alloc(value);
+        let (left, right) = self.split(self.root, id);
+        let left = self.merge(left, id);
+        self.root = self.merge(left, right);
+        self.arrivals.push_back(id);
+        evicted
+    }
+
+    /// Removes and returns the oldest element of the window.
+    pub fn pop(&mut self) -> Option<T> {
+        let id = self.arrivals.pop_front()?;
+        // the node is the first of the nodes not ordered before it
+        let (left, rest) = self.split(self.root, id);
+        let right = self.remove_first(rest);
+        self.root = self.merge(left, right);
+        let node = self.nodes[id].take().expect("live node");
+        self.free.push(id);
+        Some(node.value)
+    }
+
+    /// The element with `rank` smaller elements in the window.
+    pub fn get(&self, rank: usize) -> Option<&T> {
+        if rank >= self.len() {
+            return None;
+        }
+        let (mut t, mut rank) = (self.root, rank);
+        loop {
+            let node = self.node(t);
+            let left = self.size(node.left);
+            match rank.cmp(&left) {
+                Ordering::Less => t = node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => {
+                    rank -= left + 1;
+                    t = node.right;
+                }
+            }
+        }
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    /// The median of the window; for even lengths this is the upper median.
+    pub fn median(&self) -> Option<&T> {
+        self.get(self.len() / 2)
+    }
+
+    /// The `q`-quantile of the window by the nearest-rank method, for `q` in `0.0..=1.0`.
+    pub fn quantile(&self, q: f64) -> Option<&T> {
+        assert!((0.0..=1.0).contains(&q), "quantile must be between 0 and 1");
+        let last = self.len().checked_sub(1)?;
+        self.get((q * last as f64 + 0.5) as usize)
+    }
+
+    /// Iterates over the window in sorted order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        (0..self.len()).map(move |rank| self.get(rank).expect("rank in range"))
+    }
+
+    fn alloc(&mut self, value: T) -> usize {
+        let node = Node {
+            value,
+            seq: self.next_seq,
+            priority: self.rng.next_u64(),
+            size: 1,
+            left: NIL,
+            right: NIL,
+        };
+        self.next_seq += 1;
+        match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = Some(node);
+                id
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn node(&self, id: usize) -> &Node<T> {
+        self.nodes[id].as_ref().expect("live node")
+    }
+
+    fn node_mut(&mut self, id: usize) -> &mut Node<T> {
+        self.nodes[id].as_mut().expect("live node")
+    }
+
+    fn size(&self, id: usize) -> usize {
+        if id == NIL {
+            0
+        } else {
+            self.node(id).size
+        }
+    }
+
+    fn update(&mut self, id: usize) {
+        let node = self.node(id);
+        let size = 1 + self.size(node.left) + self.size(node.right);
+        self.node_mut(id).size = size;
+    }
+
+    /// Whether node `a` orders before node `b`.
+    fn before(&self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.node(a), self.node(b));
+        (&a.value, a.seq) < (&b.value, b.seq)
+    }
+
+    /// Splits the tree `t` into the nodes ordered before `key`, and the rest.
+    fn split(&mut self, t: usize, key: usize) -> (usize, usize) {
+        if t == NIL {
+            return (NIL, NIL);
+        }
+        if self.before(t, key) {
+            let (left, right) = self.split(self.node(t).right, key);
+            self.node_mut(t).right = left;
+            self.update(t);
+            (t, right)
+        } else {
+            let (left, right) = self.split(self.node(t).left, key);
+            self.node_mut(t).left = right;
+            self.update(t);
+            (left, t)
+        }
+    }
+
+    /// Unlinks the first node of the tree `t`, returning the remaining tree.
+    fn remove_first(&mut self, t: usize) -> usize {
+        let node = self.node(t);
+        if node.left == NIL {
+            return node.right;
+        }
+        let left = self.remove_first(node.left);
+        self.node_mut(t).left = left;
+        self.update(t);
+        t
+    }
+
+    /// Joins two trees where every node of `a` orders before every node of `b`.
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        if self.node(a).priority > self.node(b).priority {
+            let right = self.merge(self.node(a).right, b);
+            self.node_mut(a).right = right;
+            self.update(a);
+            a
+        } else {
+            let left = self.merge(a, self.node(b).left);
+            self.node_mut(b).left = left;
+            self.update(b);
+            b
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sliding_sorted_works() {
+        let mut window = SlidingSorted::new(4);
+        for &x in &[7, 3, 9, 1] {
+            assert_eq!(window.push(x), None);
+        }
+        assert_eq!(window.iter().collect::<Vec<_>>(), &[&1, &3, &7, &9]);
+        assert_eq!(window.push(5), Some(7));
+        assert_eq!(window.iter().collect::<Vec<_>>(), &[&1, &3, &5, &9]);
+        assert_eq!((window.min(), window.max()), (Some(&1), Some(&9)));
+        assert_eq!(window.quantile(0.0), Some(&1));
+        assert_eq!(window.quantile(1.0), Some(&9));
+        assert_eq!(window.pop(), Some(3));
+        assert_eq!(window.len(), 3);
+    }
+
+    #[test]
+    fn matches_sorting_each_window() {
+        let w = 16;
+        let stream: Vec<u32> = (0..500u32)
+            .map(|i| i.wrapping_mul(2654435761) % 50)
+            .collect();
+        let mut window = SlidingSorted::new(w);
+        for (i, &x) in stream.iter().enumerate() {
+            window.push(x);
+            let mut expected = stream[i.saturating_sub(w - 1)..=i].to_vec();
+            expected.sort();
+            assert_eq!(window.iter().copied().collect::<Vec<_>>(), expected);
+            assert_eq!(window.median(), Some(&expected[expected.len() / 2]));
+        }
+    }
+
+    #[test]
+    fn empty_window_has_no_statistics() {
+        let mut window = SlidingSorted::<i32>::new(2);
+        assert!(window.is_empty());
+        assert_eq!(
+            (window.min(), window.max(), window.median()),
+            (None, None, None)
+        );
+        assert_eq!(window.quantile(0.5), None);
+        assert_eq!(window.pop(), None);
+    }
+
+    #[test]
+    fn try_new_rejects_zero_capacity() {
+        assert_eq!(
+            SlidingSorted::<u8>::try_new(0).err(),
+            Some(Error::ZeroCapacity)
+        );
+        assert_eq!(SlidingSorted::<u8>::try_new(5).unwrap().capacity(), 5);
+    }
+}
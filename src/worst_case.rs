@@ -0,0 +1,435 @@
+//! Inputs constructed to be as expensive as possible for a particular sorter.
+//!
+//! Each synthesizer is driven by the parameters of the sorter it targets, such as its
+//! gap sequence or merge policy, and returns a permutation of `0..n`. Random input
+//! shows the average case; these show how bad the worst case gets in practice.
+//!
+//! ```
+//! use pangua::{worst_case, Counting, HeapSort, Sorter};
+//!
+//! let n = 1 << 10;
+//! let sorter = Counting::new(HeapSort::default());
+//! sorter.sort(&mut worst_case::heap_sort(n));
+//! // two comparisons a level, for nearly all of the 10 levels of every sift
+//! assert!(sorter.stats().comparisons > 2 * n * 8);
+//! ```
+
+use crate::cmp::Compare;
+use crate::natural::{self, MergePolicy, Run};
+use crate::{Counting, GapSequence, ShellSort, Sorter};
+#[cfg(doc)]
+use crate::{HeapSort, NaturalMergeSort, QuickSort};
+use alloc::collections::BinaryHeap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem;
+
+/// An input on which a [`ShellSort`] with `gaps` makes as many comparisons as this
+/// finds it can.
+///
+/// An input h-sorted for a gap h is ordered along every chain i, i + h, i + 2h, ...,
+/// and the pass with that gap moves nothing. For each gap, this builds the input that
+/// is h-sorted for it and the gaps above, placing small values at high indices
+/// wherever the chains allow, so that the passes below it get as many inversions as
+/// possible. Each candidate is sorted, and the one taking the most comparisons wins.
+/// For Shell's halving gaps that is the classic worst case, the smaller half on the odd
+/// positions and the larger half on the even ones, which takes Θ(n²). For sequences of
+/// coprime gaps, the middle gaps pay the most.
+///
+/// Synthesizing takes as long as sorting each candidate.
+pub fn shell_sort(gaps: &GapSequence, n: usize) -> Vec<usize> {
+    let sorter = ShellSort { gaps: gaps.clone() };
+    let passes: Vec<usize> = gaps.gaps(n).into_iter().filter(|&gap| gap > 1).collect();
+    (0..=passes.len())
+        .map(|k| chain_sorted(&passes[..k], n))
+        .max_by_key(|input| {
+            let counting = Counting::new(&sorter);
+            counting.sort(&mut input.clone());
+            counting.stats().comparisons
+        })
+        .expect("there is a candidate")
+}
+
+/// The permutation of `0..n` that is h-sorted for every gap in `gaps` and has the
+/// smallest values as far right as that allows.
+fn chain_sorted(gaps: &[usize], n: usize) -> Vec<usize> {
+    let gaps: Vec<usize> = gaps.iter().copied().filter(|&gap| gap < n).collect();
+    // values are handed out in increasing order, each to the highest index whose
+    // predecessors on every chain already have theirs
+    let mut waiting: Vec<usize> = (0..n)
+        .map(|i| gaps.iter().filter(|&&gap| gap <= i).count())
+        .collect();
+    let mut ready: BinaryHeap<usize> = (0..n).filter(|&i| waiting[i] == 0).collect();
+    let mut input = vec![0; n];
+    let mut value = 0;
+    while let Some(i) = ready.pop() {
+        input[i] = value;
+        value += 1;
+        for &gap in &gaps {
+            if i + gap < n {
+                waiting[i + gap] -= 1;
+                if waiting[i + gap] == 0 {
+                    ready.push(i + gap);
+                }
+            }
+        }
+    }
+    input
+}
+
+/// An input on which every sift of a [`HeapSort`] goes down to the deepest level of
+/// the heap, or within one level of it.
+///
+/// The input is built by running heap sort backwards from a one-element heap: each
+/// step puts a new maximum at the root and pushes the elements on a path to a deepest
+/// leaf down by one, moving the displaced leaf to the end. Extracting the maximum
+/// undoes the step, sifting that element back down the whole path. The heapify is
+/// undone the same way, so that building the heap sifts every element from the
+/// bottom of its subtree too.
+pub fn heap_sort(n: usize) -> Vec<usize> {
+    let mut heap = Vec::with_capacity(n);
+    if n == 0 {
+        return heap;
+    }
+    heap.push(0);
+    // the minimum is always on a leaf that extractions can end at
+    let mut min_at = 0;
+    for max in 1..n {
+        let size = heap.len();
+        // the element moved to the end must not be larger than its new parent
+        let parent = (size - 1) / 2;
+        let fits = |path: &[usize]| {
+            let above = match path.iter().position(|&i| i == parent) {
+                Some(0) => max,
+                Some(depth) => heap[path[depth - 1]],
+                None => heap[parent],
+            };
+            heap[*path.last().expect("path is non-empty")] < above
+        };
+        let mut path = path_to(size - 1);
+        if !fits(&path) {
+            path = path_to(min_at);
+        }
+        let leaf = *path.last().expect("path is non-empty");
+        let moved = heap[leaf];
+        for depth in (1..path.len()).rev() {
+            heap[path[depth]] = heap[path[depth - 1]];
+        }
+        heap[0] = max;
+        heap.push(moved);
+        if leaf == min_at {
+            min_at = size;
+        }
+    }
+    // undo the heapify the same way, from the root on: each element came from the
+    // bottom of the leftmost path below it, the deepest in its subtree
+    let mut input = heap;
+    for top in 0..n / 2 {
+        let mut leaf = top;
+        while 2 * leaf + 1 < n {
+            leaf = 2 * leaf + 1;
+        }
+        let path = path_to(leaf);
+        let path = &path[path.iter().position(|&i| i == top).expect("leaf is below")..];
+        let moved = input[leaf];
+        for depth in (1..path.len()).rev() {
+            input[path[depth]] = input[path[depth - 1]];
+        }
+        input[top] = moved;
+    }
+    input
+}
+
+/// The heap positions from the root down to `leaf`.
+fn path_to(leaf: usize) -> Vec<usize> {
+    let mut path = vec![leaf];
+    let mut i = leaf;
+    while i > 0 {
+        i = (i - 1) / 2;
+        path.push(i);
+    }
+    path.reverse();
+    path
+}
+
+/// An input on which a [`NaturalMergeSort`] with `policy` merges as many elements as
+/// it can, comparing before every move.
+///
+/// The input holds as many runs as it can, all of length 2, which the policy merges
+/// in a simulation of the run stack. The values are then dealt out down the simulated
+/// merges so that the two sides of every merge alternate to the end. Policies merging
+/// in balance take about n·log2(n/2) comparisons; [`Eager`](natural::Eager) takes
+/// Θ(n²).
+pub fn natural_merge_sort<P: MergePolicy>(policy: &P, n: usize) -> Vec<usize> {
+    if n < 4 {
+        return (0..n).collect();
+    }
+    let mut simulation = Simulation::default();
+    // an odd element goes to the first run
+    simulation.push(policy, n, 2 + n % 2);
+    for _ in 1..n / 2 {
+        simulation.push(policy, n, 2);
+    }
+    simulation.finish();
+    simulation.deal(n)
+}
+
+/// A node of the merge tree: a run of the input, or the merge of two nodes.
+#[derive(Debug, Clone, Copy)]
+enum Node {
+    Run(usize),
+    Merge(usize),
+}
+
+/// A run stack as a [`NaturalMergeSort`] would keep it, tracking the merges made.
+#[derive(Default)]
+struct Simulation {
+    stack: Vec<(Run, Node)>,
+    /// The lengths of the runs pushed.
+    runs: Vec<usize>,
+    /// The nodes merged by each merge made, and the length of the left one.
+    merges: Vec<(Node, Node, usize)>,
+}
+
+impl Simulation {
+    fn push<P: MergePolicy>(&mut self, policy: &P, n: usize, len: usize) {
+        let start = self.stack.last().map_or(0, |(run, _)| run.start + run.len);
+        let power = self
+            .stack
+            .last()
+            .map_or(0, |(prev, _)| natural::node_power(n, prev, len));
+        let run = Run {
+            start,
+            len,
+            runs: 1,
+            power,
+        };
+        self.stack.push((run, Node::Run(self.runs.len())));
+        self.runs.push(len);
+        loop {
+            let runs: Vec<Run> = self.stack.iter().map(|&(run, _)| run).collect();
+            match policy.merge_at(&runs, n) {
+                Some(i) => self.merge(i),
+                None => break,
+            }
+        }
+    }
+
+    fn merge(&mut self, i: usize) {
+        let (right, right_node) = self.stack.remove(i + 1);
+        let (left, left_node) = &mut self.stack[i];
+        self.merges.push((*left_node, right_node, left.len));
+        left.len += right.len;
+        left.runs += right.runs;
+        *left_node = Node::Merge(self.merges.len() - 1);
+    }
+
+    fn finish(&mut self) {
+        while self.stack.len() > 1 {
+            self.merge(self.stack.len() - 2);
+        }
+    }
+
+    /// Deals the values `0..n` out from the last merge down to the runs, and lays the
+    /// runs out in ascending order.
+    ///
+    /// Each side of a merge is spread evenly over the values of both, except that the
+    /// left side gets the smallest and the second largest value, and the right side
+    /// the largest. A merge then takes one comparison per element but the last. The
+    /// largest value of every left side ends up in its last run, above the smallest
+    /// value of the right side, which ends up in its first run, so no two adjacent runs
+    /// run into each other.
+    fn deal(&self, n: usize) -> Vec<usize> {
+        let mut runs: Vec<Vec<usize>> = vec![Vec::new(); self.runs.len()];
+        let mut merges: Vec<Vec<usize>> = vec![Vec::new(); self.merges.len()];
+        match merges.last_mut() {
+            Some(last) => *last = (0..n).collect(),
+            None => return (0..n).collect(),
+        }
+        for (i, &(left, right, left_len)) in self.merges.iter().enumerate().rev() {
+            let all = mem::take(&mut merges[i]);
+            let len = all.len();
+            let mut left_values = vec![all[0]];
+            let mut right_values = Vec::with_capacity(len - left_len);
+            let middle = &all[1..len - 2];
+            for (k, &value) in middle.iter().enumerate() {
+                let (k, m, a) = (k + 1, middle.len(), left_len - 2);
+                if k * a / m > (k - 1) * a / m {
+                    left_values.push(value);
+                } else {
+                    right_values.push(value);
+                }
+            }
+            left_values.push(all[len - 2]);
+            right_values.push(all[len - 1]);
+            for (node, values) in [(left, left_values), (right, right_values)] {
+                match node {
+                    Node::Run(j) => runs[j] = values,
+                    Node::Merge(j) => merges[j] = values,
+                }
+            }
+        }
+        runs.concat()
+    }
+}
+
+/// An input on which `sorter` makes about as many comparisons as it can be made to, by
+/// McIlroy's adversary.
+///
+/// The sorter runs on elements whose values are not decided yet. When it compares two
+/// undecided elements, one of them is fixed at the next smallest value, preferring the
+/// one that was compared most recently, as a pivot would be; undecided elements compare
+/// above decided ones. Once the sort is over, the remaining elements are given the
+/// largest values. Any deterministic sorter then repeats the same comparisons on the
+/// resulting input, so a quicksort picking its pivot from a few elements, such as
+/// [`QuickSort`]'s median of three, is driven to quadratic time.
+///
+/// Synthesizing takes as long as the sort it targets.
+pub fn adversary<S: Sorter + ?Sized>(sorter: &S, n: usize) -> Vec<usize> {
+    let mut gas = Gas {
+        values: vec![None; n],
+        solid: 0,
+        candidate: None,
+    };
+    let mut items: Vec<usize> = (0..n).collect();
+    sorter.sort_with(&mut items, &mut gas);
+    for value in gas.values.iter_mut().filter(|value| value.is_none()) {
+        *value = Some(gas.solid);
+        gas.solid += 1;
+    }
+    gas.values.into_iter().flatten().collect()
+}
+
+/// The adversary's comparator, over the positions of the elements in the input.
+struct Gas {
+    values: Vec<Option<usize>>,
+    solid: usize,
+    candidate: Option<usize>,
+}
+
+impl Gas {
+    fn freeze(&mut self, position: usize) {
+        self.values[position] = Some(self.solid);
+        self.solid += 1;
+    }
+}
+
+impl Compare<usize> for Gas {
+    fn compare(&mut self, &a: &usize, &b: &usize) -> Ordering {
+        if self.values[a].is_none() && self.values[b].is_none() {
+            self.freeze(if self.candidate == Some(a) { a } else { b });
+        }
+        if self.values[a].is_none() {
+            self.candidate = Some(a);
+        } else if self.values[b].is_none() {
+            self.candidate = Some(b);
+        }
+        // undecided elements are above every decided one
+        match (self.values[a], self.values[b]) {
+            (Some(x), Some(y)) => x.cmp(&y),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => unreachable!("one of them was just frozen"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::natural::{BalancedBinary, Eager, Powersort, Timsort};
+    use crate::{HeapSort, IntroSort, NaturalMergeSort, PivotStrategy, QuickSort};
+    use rand::prelude::*;
+
+    fn is_permutation(input: &[usize]) -> bool {
+        let mut sorted = input.to_vec();
+        sorted.sort_unstable();
+        sorted.iter().enumerate().all(|(i, &x)| i == x)
+    }
+
+    fn comparisons<S: Sorter>(sorter: S, input: &[usize]) -> usize {
+        let sorter = Counting::new(sorter);
+        let mut tings = input.to_vec();
+        sorter.sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+        sorter.stats().comparisons
+    }
+
+    fn shuffled(n: usize) -> Vec<usize> {
+        let mut tings: Vec<usize> = (0..n).collect();
+        tings.shuffle(&mut StdRng::seed_from_u64(7));
+        tings
+    }
+
+    #[test]
+    fn synthesizers_make_permutations() {
+        for n in 0..40 {
+            assert!(is_permutation(&shell_sort(&GapSequence::Ciura, n)));
+            assert!(is_permutation(&heap_sort(n)));
+            assert!(is_permutation(&natural_merge_sort(&Timsort, n)));
+            assert!(is_permutation(&adversary(&QuickSort::default(), n)));
+        }
+    }
+
+    #[test]
+    fn shell_sort_input_is_worse_than_random() {
+        let n = 1 << 10;
+        for gaps in [GapSequence::Shell, GapSequence::Knuth, GapSequence::Ciura] {
+            let input = shell_sort(&gaps, n);
+            let sorter = ShellSort { gaps: gaps.clone() };
+            assert!(comparisons(sorter.clone(), &input) > comparisons(sorter, &shuffled(n)));
+        }
+        // the smaller half on the odd positions
+        assert_eq!(
+            shell_sort(&GapSequence::Shell, 8),
+            &[4, 0, 5, 1, 6, 2, 7, 3]
+        );
+    }
+
+    #[test]
+    fn heap_sort_input_sifts_to_the_bottom() {
+        let n = 1 << 12;
+        let worst = comparisons(HeapSort::default(), &heap_sort(n));
+        assert!(worst > comparisons(HeapSort::default(), &shuffled(n)));
+        // two comparisons a level, over about log2(n) - 1 levels an extraction
+        assert!(worst > 2 * n * 10);
+    }
+
+    #[test]
+    fn natural_merge_input_keeps_its_runs_apart() {
+        fn check<P: MergePolicy + Copy>(policy: P, at_least: usize) {
+            let n = 1 << 10;
+            let input = natural_merge_sort(&policy, n);
+            let descents = input.windows(2).filter(|w| w[1] < w[0]).count();
+            assert_eq!(descents, n / 2 - 1);
+            let sorter = NaturalMergeSort {
+                min_gallop: 0,
+                ..NaturalMergeSort::new(policy)
+            };
+            assert!(comparisons(sorter, &input) > at_least);
+        }
+        // every merge but the last level compares once per element
+        let balanced = (1 << 10) * 9;
+        check(Timsort, balanced);
+        check(Powersort, balanced);
+        check(BalancedBinary, balanced);
+        check(Eager, (1 << 20) / 4);
+    }
+
+    #[test]
+    fn adversary_makes_quicksort_quadratic() {
+        let n = 1 << 11;
+        for pivot in [PivotStrategy::MedianOfThree, PivotStrategy::Ninther] {
+            let sorter = QuickSort {
+                pivot,
+                ..QuickSort::default()
+            };
+            let worst = comparisons(sorter, &adversary(&sorter, n));
+            assert!(worst > n * n / 16, "{:?}: {}", pivot, worst);
+        }
+        // introsort bails out to heapsort before it gets that far
+        let intro = IntroSort::default();
+        assert!(comparisons(intro, &adversary(&intro, n)) < 8 * n * 11);
+    }
+}
@@ -0,0 +1,169 @@
+//! Exhaustive checks of every sorter on every small input.
+//!
+//! Random inputs rarely hit the boundaries where sorters go wrong: an empty or
+//! one-element region handed to a helper, the last element of an odd-length merge, a
+//! pivot that is the maximum. Up to 8 elements it is cheap to try every permutation,
+//! and up to 6 every arrangement of keys with duplicates, checking stability too. For
+//! sorts that are comparator networks, whose comparisons never depend on the data, the
+//! 0-1 principle turns trying all 2^n vectors of zeros and ones into a proof that they
+//! sort every input of that length.
+
+use pangua::cmp::ByOrd;
+use pangua::networks;
+use pangua::*;
+
+/// Permutations of this many elements and fewer are tried.
+const MAX_PERMUTATION_LEN: usize = 8;
+/// Inputs of this many keys and fewer, each key below the length, are tried.
+const MAX_MULTISET_LEN: usize = 6;
+
+/// Runs `check` with every comparison sorter.
+macro_rules! for_each_sorter {
+    ($check:ident) => {
+        $check(BubbleSort);
+        $check(CocktailShakerSort);
+        $check(GnomeSort);
+        $check(CombSort::default());
+        $check(OddEvenSort);
+        $check(InsertionSort { smart: true });
+        $check(InsertionSort { smart: false });
+        $check(SelectionSort);
+        $check(CycleSort);
+        $check(PancakeSort);
+        $check(BitonicSort);
+        $check(HeapSort::default());
+        $check(SmoothSort);
+        $check(MergeSort::default());
+        $check(MergeSort {
+            memory: MemoryPolicy::InPlace,
+            ..MergeSort::default()
+        });
+        $check(MergeSort {
+            memory: MemoryPolicy::Half,
+            ..MergeSort::default()
+        });
+        $check(BottomUpMergeSort);
+        $check(NaturalMergeSort::default());
+        $check(NaturalMergeSort::new(natural::Timsort));
+        $check(NaturalMergeSort::new(natural::Eager));
+        $check(NaturalMergeSort::new(natural::BalancedBinary));
+        $check(BlockSort);
+        $check(BlockedSort {
+            block_bytes: 8,
+            inner: MergeSort::default(),
+        });
+        $check(PatienceSort);
+        $check(StrandSort);
+        $check(LibrarySort);
+        $check(TreeSort { balanced: false });
+        $check(TreeSort { balanced: true });
+        $check(QuickSort::default());
+        $check(QuickSort {
+            pivot: PivotStrategy::First,
+            ..QuickSort::default()
+        });
+        $check(QuickSort {
+            pivot: PivotStrategy::Ninther,
+            ..QuickSort::default()
+        });
+        $check(ThreeWayQuickSort);
+        $check(IntroSort::default());
+        $check(ShellSort::default());
+        $check(ShellSort {
+            gaps: GapSequence::Ciura,
+        });
+        $check(AutoSort::default());
+        $check(DefaultSorter);
+        $check(StdSorter);
+    };
+}
+
+/// Calls `f` with every permutation of `0..n`, by Heap's algorithm.
+fn for_each_permutation(n: usize, mut f: impl FnMut(&[usize])) {
+    let mut items: Vec<usize> = (0..n).collect();
+    let mut counters = vec![0; n];
+    f(&items);
+    let mut i = 1;
+    while i < n {
+        if counters[i] < i {
+            let j = if i % 2 == 0 { 0 } else { counters[i] };
+            items.swap(j, i);
+            f(&items);
+            counters[i] += 1;
+            i = 1;
+        } else {
+            counters[i] = 0;
+            i += 1;
+        }
+    }
+}
+
+/// Calls `f` with every sequence of `n` values below `base`, counting in base `base`.
+fn for_each_word(n: usize, base: usize, mut f: impl FnMut(&[usize])) {
+    let mut word = vec![0; n];
+    loop {
+        f(&word);
+        match word.iter().rposition(|&digit| digit + 1 < base) {
+            Some(i) => {
+                word[i] += 1;
+                word[i + 1..].iter_mut().for_each(|digit| *digit = 0);
+            }
+            None => return,
+        }
+    }
+}
+
+fn check_sorter<S: Sorter>(sorter: S) {
+    let info = sorter.info();
+    for n in 0..=MAX_PERMUTATION_LEN {
+        let sorted: Vec<usize> = (0..n).collect();
+        for_each_permutation(n, |permutation| {
+            let mut tings = permutation.to_vec();
+            sorter.sort(&mut tings);
+            assert_eq!(tings, sorted, "{} on {:?}", info.name, permutation);
+        });
+    }
+    for n in 0..=MAX_MULTISET_LEN {
+        for_each_word(n, n, |keys| {
+            let mut tings: Vec<(usize, usize)> = keys.iter().copied().zip(0..).collect();
+            sorter.sort_by_key(&mut tings, |t| t.0);
+            let mut expected: Vec<(usize, usize)> = keys.iter().copied().zip(0..).collect();
+            expected.sort_by_key(|t| t.0);
+            if info.stable {
+                assert_eq!(tings, expected, "{} is unstable on {:?}", info.name, keys);
+            } else {
+                tings.sort();
+                assert_eq!(tings, expected, "{} on {:?}", info.name, keys);
+            }
+        });
+    }
+}
+
+#[test]
+fn every_sorter_sorts_every_small_input() {
+    for_each_sorter!(check_sorter);
+}
+
+#[test]
+fn networks_sort_every_zero_one_vector() {
+    let sorts_all_zero_one = |n: usize, sort: &dyn Fn(&mut [u8])| {
+        for_each_word(n, 2, |word| {
+            let mut tings: Vec<u8> = word.iter().map(|&bit| bit as u8).collect();
+            sort(&mut tings);
+            assert!(tings.windows(2).all(|w| w[0] <= w[1]), "{:?}", word);
+        });
+    };
+    for n in 0..=networks::MAX_OPTIMAL {
+        let network = networks::optimal(n).expect("an optimal network");
+        sorts_all_zero_one(n, &|tings| networks::apply(tings, network, &mut ByOrd));
+        let batcher = networks::batcher(n);
+        sorts_all_zero_one(n, &|tings| {
+            for &(a, b) in &batcher {
+                if tings[b] < tings[a] {
+                    tings.swap(a, b);
+                }
+            }
+        });
+        sorts_all_zero_one(n, &|tings| BitonicSort.sort(tings));
+    }
+}
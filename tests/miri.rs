@@ -0,0 +1,152 @@
+//! Small-input checks of every sorter, kept quick enough to run under Miri.
+//!
+//! `cargo +nightly miri test --test miri` runs this suite with the sizes shrunk, so it
+//! finishes while still walking every code path that moves elements around: small
+//! inputs, duplicates, already sorted runs, and comparators that panic mid-sort.
+
+use pangua::*;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Largest input length tried.
+const MAX_LEN: usize = if cfg!(miri) { 24 } else { 100 };
+
+/// Comparisons allowed before a panicking comparator gives up.
+const PANIC_AFTERS: &[usize] = if cfg!(miri) {
+    &[0, 5, 40]
+} else {
+    &[0, 1, 5, 40, 300, 2000]
+};
+
+/// Runs `check` with every comparison sorter.
+macro_rules! for_each_sorter {
+    ($check:ident) => {
+        $check(BubbleSort);
+        $check(CocktailShakerSort);
+        $check(GnomeSort);
+        $check(CombSort::default());
+        $check(OddEvenSort);
+        $check(InsertionSort { smart: true });
+        $check(InsertionSort { smart: false });
+        $check(SelectionSort);
+        $check(CycleSort);
+        $check(PancakeSort);
+        $check(BitonicSort);
+        $check(HeapSort::default());
+        $check(SmoothSort);
+        $check(MergeSort::default());
+        $check(MergeSort {
+            memory: MemoryPolicy::InPlace,
+            ..MergeSort::default()
+        });
+        $check(MergeSort {
+            memory: MemoryPolicy::Full,
+            small_threshold: 16,
+            ..MergeSort::default()
+        });
+        $check(BottomUpMergeSort);
+        $check(NaturalMergeSort::default());
+        $check(BlockSort);
+        $check(BlockedSort {
+            block_bytes: 64,
+            inner: MergeSort::default(),
+        });
+        $check(PatienceSort);
+        $check(StrandSort);
+        $check(LibrarySort);
+        $check(TreeSort { balanced: false });
+        $check(TreeSort { balanced: true });
+        $check(StdSorter);
+        $check(QuickSort::default());
+        $check(QuickSort {
+            small_threshold: 16,
+            ..QuickSort::default()
+        });
+        $check(ThreeWayQuickSort);
+        $check(IntroSort::default());
+        $check(ShellSort::default());
+        $check(AutoSort::default());
+    };
+}
+
+fn inputs() -> Vec<Vec<u32>> {
+    let mut inputs = Vec::new();
+    for n in 0..=MAX_LEN {
+        let n = n as u32;
+        inputs.push((0..n).map(|i| (i * 7919) % 13).collect());
+        inputs.push((0..n).rev().collect());
+        inputs.push((0..n).map(|i| i % 2).collect());
+        inputs.push((0..n).map(|i| if i < n / 2 { i } else { n - i }).collect());
+    }
+    inputs
+}
+
+fn sorted_copy<T: Ord + Clone>(slice: &[T]) -> Vec<T> {
+    let mut copy = slice.to_vec();
+    copy.sort();
+    copy
+}
+
+#[test]
+fn sorts_small_inputs() {
+    fn check<S: Sorter>(sorter: S) {
+        let name = sorter.info().name;
+        for input in inputs() {
+            // heap-allocated elements catch elements being duplicated or leaked
+            let mut tings: Vec<String> = input.iter().map(|i| i.to_string()).collect();
+            sorter.sort_by_key(&mut tings, |s| s.parse::<u32>().unwrap());
+            let keys: Vec<u32> = tings.iter().map(|s| s.parse().unwrap()).collect();
+            assert_eq!(keys, sorted_copy(&input), "{}", name);
+        }
+    }
+    for_each_sorter!(check);
+}
+
+#[test]
+fn descending_sorts_small_inputs() {
+    for input in inputs() {
+        let mut tings = input.clone();
+        Descending(MergeSort::default()).sort(&mut tings);
+        let mut expected = sorted_copy(&input);
+        expected.reverse();
+        assert_eq!(tings, expected);
+    }
+}
+
+#[test]
+fn float_views_sort_in_place() {
+    for input in inputs() {
+        let mut tings: Vec<f64> = input.iter().map(|&x| x as f64 - 6.5).collect();
+        QuickSort::default().sort(Total::cast_slice_mut(&mut tings));
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
+
+#[test]
+fn panicking_comparators_leave_a_permutation() {
+    fn check<S: Sorter>(sorter: S) {
+        let name = sorter.info().name;
+        for input in inputs() {
+            for &after in PANIC_AFTERS {
+                let mut tings: Vec<Box<u32>> = input.iter().copied().map(Box::new).collect();
+                let mut budget = after;
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    sorter.sort_by(&mut tings, |a, b| {
+                        if budget == 0 {
+                            panic!("comparator gave up");
+                        }
+                        budget -= 1;
+                        a.cmp(b)
+                    })
+                }));
+                if result.is_ok() {
+                    continue;
+                }
+                let values: Vec<u32> = tings.iter().map(|b| **b).collect();
+                assert_eq!(sorted_copy(&values), sorted_copy(&input), "{}", name);
+            }
+        }
+    }
+    panic::set_hook(Box::new(|_| {}));
+    for_each_sorter!(check);
+    let _ = panic::take_hook();
+}
@@ -0,0 +1,65 @@
+//! Checks of the global small-slice policy override.
+//!
+//! The override is process-wide, so everything touching it lives in the one test below,
+//! in its own test binary, where it cannot disturb sorts running in other tests.
+
+use pangua::*;
+
+fn partitions<S: Sorter>(sorter: S, tings: &mut [u32]) -> usize {
+    let mut partitions = 0;
+    TracedSorter::new(sorter, |e| {
+        if let SortEvent::Partition { .. } = e {
+            partitions += 1
+        }
+    })
+    .sort(tings);
+    partitions
+}
+
+fn tagged(n: usize) -> Vec<(u32, usize)> {
+    (0..n).map(|i| (((i * 7919) % 3) as u32, i)).collect()
+}
+
+fn assert_stably_sorted(tings: &[(u32, usize)]) {
+    assert!(tings.windows(2).all(|w| w[0] <= w[1]), "{:?}", tings);
+}
+
+#[test]
+fn global_policy_overrides_every_algorithm() {
+    let input: Vec<u32> = (0..12).map(|i| (i * 7919) % 12).collect();
+    assert_eq!(SmallSortPolicy::global(), None);
+    assert!(partitions(QuickSort::default(), &mut input.clone()) > 0);
+    assert_eq!(partitions(IntroSort::default(), &mut input.clone()), 0);
+
+    SmallSortPolicy::set_global(Some(SmallSortPolicy::Insertion { threshold: 16 }));
+    let mut tings = input.clone();
+    assert_eq!(partitions(QuickSort::default(), &mut tings), 0);
+    assert_eq!(tings, (0..12).collect::<Vec<_>>());
+    let merge = Counting::new(MergeSort::default());
+    merge.sort(&mut input.clone());
+    assert_eq!(merge.stats().max_depth, 0);
+
+    SmallSortPolicy::set_global(Some(SmallSortPolicy::None));
+    let mut tings = input.clone();
+    assert!(partitions(IntroSort::default(), &mut tings) > 0);
+    assert_eq!(tings, (0..12).collect::<Vec<_>>());
+
+    // networks are unstable, so stable sorts fall back to insertion sort
+    SmallSortPolicy::set_global(Some(SmallSortPolicy::Network { threshold: 16 }));
+    for n in [0, 1, 2, 5, 16, 17, 100] {
+        let mut tings: Vec<u32> = (0..n).map(|i| (i * 7919) % 97).collect();
+        QuickSort::default().sort(&mut tings);
+        assert!(tings.windows(2).all(|w| w[0] <= w[1]), "n = {}", n);
+
+        let mut tings = tagged(n as usize);
+        MergeSort::default().sort_by_key(&mut tings, |t| t.0);
+        assert_stably_sorted(&tings);
+        let mut tings = tagged(n as usize);
+        RadixSort::default().sort_by_key(&mut tings, |t| t.0);
+        assert_stably_sorted(&tings);
+    }
+
+    SmallSortPolicy::set_global(None);
+    assert_eq!(SmallSortPolicy::global(), None);
+    assert!(partitions(QuickSort::default(), &mut input.clone()) > 0);
+}
@@ -0,0 +1,282 @@
+//! Stability checks of every sorter claiming to be stable, on duplicate-heavy inputs.
+//!
+//! Elements are `(key, tag)` pairs sorted by key alone, where the tag is the element's
+//! original position. A stable sort leaves the tags ascending within each run of equal
+//! keys. The generators tune how many distinct keys there are, from all equal to all
+//! distinct, and how the keys are laid out, since stability bugs tend to hide in merges
+//! of long equal runs that only some layouts produce.
+
+use pangua::*;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+/// Input lengths tried, covering small-slice cutoffs and several merge levels.
+const LENGTHS: &[usize] = &[0, 1, 2, 3, 7, 16, 33, 100, 257, 1000];
+
+/// Fractions of the elements that have a distinct key.
+const DISTINCT_RATIOS: &[f64] = &[0.0, 0.01, 0.1, 0.5, 1.0];
+
+type Tagged = (u32, usize);
+
+/// Runs `check` with every comparison sorter; unstable ones are skipped by the checks.
+macro_rules! for_each_sorter {
+    ($check:ident) => {
+        $check(BubbleSort);
+        $check(CocktailShakerSort);
+        $check(GnomeSort);
+        $check(CombSort::default());
+        $check(OddEvenSort);
+        $check(InsertionSort { smart: true });
+        $check(InsertionSort { smart: false });
+        $check(SelectionSort);
+        $check(CycleSort);
+        $check(PancakeSort);
+        $check(BitonicSort);
+        $check(HeapSort::default());
+        $check(SmoothSort);
+        $check(MergeSort::default());
+        $check(MergeSort {
+            memory: MemoryPolicy::InPlace,
+            ..MergeSort::default()
+        });
+        $check(MergeSort {
+            memory: MemoryPolicy::Half,
+            ..MergeSort::default()
+        });
+        $check(MergeSort {
+            memory: MemoryPolicy::Full,
+            small_threshold: 16,
+            ..MergeSort::default()
+        });
+        $check(MergeSort {
+            min_gallop: 1,
+            ..MergeSort::default()
+        });
+        $check(BottomUpMergeSort);
+        $check(NaturalMergeSort::default());
+        $check(BlockSort);
+        $check(BlockedSort {
+            block_bytes: 64,
+            inner: MergeSort::default(),
+        });
+        $check(PatienceSort);
+        $check(StrandSort);
+        $check(LibrarySort);
+        $check(TreeSort { balanced: false });
+        $check(TreeSort { balanced: true });
+        $check(StdSorter);
+        $check(QuickSort::default());
+        $check(QuickSort {
+            small_threshold: 16,
+            ..QuickSort::default()
+        });
+        $check(ThreeWayQuickSort);
+        $check(IntroSort::default());
+        $check(ShellSort::default());
+        $check(AutoSort::default());
+    };
+}
+
+/// How keys are laid out in a generated input.
+#[derive(Debug, Clone, Copy)]
+enum Layout {
+    Random,
+    /// Equal keys are grouped in runs, the runs in random order.
+    Runs,
+    /// Keys ascend, then descend, as the halves of a merge often do.
+    Sawtooth,
+    /// Keys descend, so every run of equal keys sits far from its place.
+    Descending,
+}
+
+const LAYOUTS: &[Layout] = &[
+    Layout::Random,
+    Layout::Runs,
+    Layout::Sawtooth,
+    Layout::Descending,
+];
+
+/// Generates `n` tagged elements about `distinct_ratio * n` distinct keys, at least one.
+fn generate(n: usize, distinct_ratio: f64, layout: Layout, rng: &mut StdRng) -> Vec<Tagged> {
+    let distinct = ((n as f64 * distinct_ratio) as u32).max(1);
+    let mut keys: Vec<u32> = (0..n).map(|_| rng.gen_range(0, distinct)).collect();
+    match layout {
+        Layout::Random => {}
+        Layout::Runs => {
+            keys.sort_unstable();
+            let mut runs: Vec<&[u32]> = keys.chunk_by(|a, b| a == b).collect();
+            runs.shuffle(rng);
+            keys = runs.concat();
+        }
+        Layout::Sawtooth => {
+            keys.sort_unstable();
+            let mid = n / 2;
+            keys[mid..].reverse();
+        }
+        Layout::Descending => keys.sort_unstable_by(|a, b| b.cmp(a)),
+    }
+    keys.into_iter().zip(0..).collect()
+}
+
+/// Every generated input, with a description for failure messages.
+fn inputs() -> Vec<(String, Vec<Tagged>)> {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    let mut inputs = Vec::new();
+    for &n in LENGTHS {
+        for &ratio in DISTINCT_RATIOS {
+            for &layout in LAYOUTS {
+                let what = format!("n = {}, distinct = {}, {:?}", n, ratio, layout);
+                inputs.push((what, generate(n, ratio, layout, &mut rng)));
+            }
+        }
+    }
+    inputs
+}
+
+/// Asserts `sorted` is `input` sorted by key, with equal keys in their original order.
+fn assert_stably_sorted(input: &[Tagged], sorted: &[Tagged], what: &str) {
+    let mut expected = input.to_vec();
+    expected.sort_by_key(|t| t.0);
+    assert_eq!(sorted, &expected[..], "{}", what);
+}
+
+#[test]
+fn stable_sorters_are_stable() {
+    fn check<S: Sorter>(sorter: S) {
+        let info = sorter.info();
+        if !info.stable {
+            return;
+        }
+        for (what, input) in inputs() {
+            let mut tings = input.clone();
+            sorter.sort_by_key(&mut tings, |t| t.0);
+            assert_stably_sorted(&input, &tings, &format!("{}: {}", info.name, what));
+        }
+    }
+    for_each_sorter!(check);
+    check(Counting::new(BlockSort));
+}
+
+#[test]
+fn stable_sorters_stay_stable_in_reverse() {
+    fn check<S: Sorter>(sorter: S) {
+        let info = sorter.info();
+        if !info.stable {
+            return;
+        }
+        for (what, input) in inputs() {
+            let mut tings = input.clone();
+            Descending(&sorter).sort_by_key(&mut tings, |t| t.0);
+            let mut expected = input.clone();
+            expected.sort_by_key(|t| std::cmp::Reverse(t.0));
+            assert_eq!(tings, expected, "{}: {}", info.name, what);
+        }
+    }
+    for_each_sorter!(check);
+}
+
+#[test]
+fn stable_sorters_are_marked_and_stable() {
+    // only compiles for sorters implementing StableSorter
+    fn check<S: StableSorter>(sorter: S) {
+        let info = sorter.info();
+        assert!(
+            info.stable,
+            "{} is marked stable but reports otherwise",
+            info.name
+        );
+        for (what, input) in inputs() {
+            let mut tings = input.clone();
+            sort_stable_by_key(&mut tings, |t| t.0, &sorter);
+            assert_stably_sorted(&input, &tings, &format!("{}: {}", info.name, what));
+        }
+    }
+    check(BubbleSort);
+    check(CocktailShakerSort);
+    check(GnomeSort);
+    check(OddEvenSort);
+    check(MergeSort::default());
+    check(MergeSort {
+        memory: MemoryPolicy::InPlace,
+        ..MergeSort::default()
+    });
+    check(BottomUpMergeSort);
+    check(NaturalMergeSort::default());
+    check(NaturalMergeSort::new(natural::Timsort));
+    check(BlockSort);
+    check(BlockedSort {
+        block_bytes: 64,
+        inner: NaturalMergeSort::default(),
+    });
+    check(PatienceSort);
+    check(StrandSort);
+    check(LibrarySort);
+    check(TreeSort { balanced: true });
+    check(DefaultSorter);
+    check(StdSorter);
+    check(Counting::new(Descending(Descending(MergeSort::default()))));
+}
+
+#[test]
+fn non_comparison_sorters_are_stable() {
+    for (what, input) in inputs() {
+        let mut tings = input.clone();
+        CountingSort.sort_by_key(&mut tings, |t| t.0 as usize);
+        assert_stably_sorted(&input, &tings, &format!("counting: {}", what));
+
+        let mut tings = input.clone();
+        PigeonholeSort::new(MergeSort::default()).sort_by_key(&mut tings, |t| t.0);
+        assert_stably_sorted(&input, &tings, &format!("pigeonhole: {}", what));
+
+        let mut tings = input.clone();
+        RadixSort::default().sort_by_key(&mut tings, |t| t.0);
+        assert_stably_sorted(&input, &tings, &format!("radix: {}", what));
+
+        let mut tings = input.clone();
+        let bucket = BucketSort::new(8, MergeSort::default()).unwrap();
+        bucket.sort_by(&mut tings, |t| t.0 as f64, |a, b| a.0.cmp(&b.0));
+        assert_stably_sorted(&input, &tings, &format!("bucket: {}", what));
+
+        #[cfg(feature = "std")]
+        {
+            let mut tings = input.clone();
+            RunLengthSort::new(QuickSort::default()).sort_by_key(&mut tings, |t| t.0);
+            assert_stably_sorted(&input, &tings, &format!("run-length: {}", what));
+        }
+    }
+}
+
+#[test]
+fn merges_are_stable() {
+    for (what, input) in inputs() {
+        // split into sorted chunks, in order, so equal keys must leave earlier inputs first
+        let chunks: Vec<Vec<Tagged>> = input
+            .chunks(7)
+            .map(|c| {
+                let mut c = c.to_vec();
+                c.sort_by_key(|t| t.0);
+                c
+            })
+            .collect();
+        for &fan_in in &[2, 3, 16] {
+            let merged = KWayMerge::new(fan_in)
+                .unwrap()
+                .merge_by(chunks.clone(), |a, b| a.0.cmp(&b.0));
+            assert_stably_sorted(&input, &merged, &format!("fan-in {}: {}", fan_in, what));
+        }
+        let merged: Vec<_> = LoserTree::new_by(chunks, |a, b| a.0.cmp(&b.0)).collect();
+        assert_stably_sorted(&input, &merged, &format!("loser tree: {}", what));
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn generators_honour_the_distinct_ratio() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let all_equal = generate(500, 0.0, Layout::Random, &mut rng);
+    assert!(all_equal.iter().all(|t| t.0 == 0));
+    let few = generate(1000, 0.01, Layout::Runs, &mut rng);
+    assert!(distinct_ratio(&few.iter().map(|t| t.0).collect::<Vec<_>>()) <= 0.01);
+    let tags: Vec<_> = few.iter().map(|t| t.1).collect();
+    assert_eq!(tags, (0..1000).collect::<Vec<_>>());
+}